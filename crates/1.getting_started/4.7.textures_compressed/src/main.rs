@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{mem, process, ptr};
+use gl::types::*;
+use glfw::{Action, Context, Key, OpenGlProfileHint, Window, WindowEvent, WindowHint};
+use learnopengl_shared::{filesystem, util};
+use learnopengl_shared::shader_s::Shader;
+
+const SCR_WIDTH: u32 = 800;
+const SCR_HEIGHT: u32 = 600;
+
+fn main() {
+    // glfw: initialize and configure
+    // ------------------------------
+    let mut glfw = glfw::init(glfw::fail_on_errors)
+        .expect("Failed to initialise GLFW.");
+
+    glfw.window_hint(WindowHint::ContextVersionMajor(3));
+    glfw.window_hint(WindowHint::ContextVersionMinor(3));
+    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+
+    // glfw window creation
+    // --------------------
+    let (mut window, events) = glfw.create_window(
+        SCR_WIDTH, SCR_HEIGHT,
+        "LearnOpenGL", glfw::WindowMode::Windowed)
+        .expect("Failed to create GLFW window.");
+    window.set_framebuffer_size_callback(framebuffer_size_callback);
+
+    window.set_key_polling(true);
+    window.make_current();
+
+    // load all OpenGL function pointers
+    // ---------------------------------
+    gl::load_with(|s| window.get_proc_address(s) as *const _);
+
+    unsafe {
+        // build and compile our shader program
+        // ------------------------------------
+        let our_shader = Shader::new("4.7.texture.vs".to_string(), "4.7.texture.fs".to_string());
+
+        // set up vertex data (and buffer(s)) and configure vertex attributes
+        // ------------------------------------------------------------------
+        // compressed textures can't be flipped on load like regular images, the DDS file stores its top
+        // row first, so the texture coordinates are flipped vertically instead
+        let vertices = [
+            // positions          // colors           // texture coords
+            0.5f32,  0.5, 0.0,   1.0, 0.0, 0.0,   1.0, 0.0, // top right
+            0.5, -0.5, 0.0,   0.0, 1.0, 0.0,   1.0, 1.0, // bottom right
+            -0.5, -0.5, 0.0,   0.0, 0.0, 1.0,   0.0, 1.0, // bottom left
+            -0.5,  0.5, 0.0,   1.0, 1.0, 0.0,   0.0, 0.0  // top left
+        ];
+        let indices = [
+            0u32, 1, 3, // first triangle
+            1, 2, 3  // second triangle
+        ];
+        let (mut vbo, mut vao, mut ebo) = (0u32, 0u32, 0u32);
+        gl::GenVertexArrays(1, &mut vao);
+        gl::GenBuffers(1, &mut vbo);
+        gl::GenBuffers(1, &mut ebo);
+
+        gl::BindVertexArray(vao);
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        gl::BufferData(
+            gl::ARRAY_BUFFER,
+            (vertices.len() * mem::size_of::<f32>()) as GLsizeiptr,
+            ptr::addr_of!(vertices) as *const _,
+            gl::STATIC_DRAW
+        );
+
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+        gl::BufferData(
+            gl::ELEMENT_ARRAY_BUFFER,
+            (indices.len() * mem::size_of::<u32>()) as GLsizeiptr,
+            ptr::addr_of!(indices) as *const _,
+            gl::STATIC_DRAW
+        );
+
+        // position attribute
+        gl::VertexAttribPointer(
+            0,
+            3,
+            gl::FLOAT,
+            gl::FALSE,
+            (8 * mem::size_of::<f32>()) as GLsizei,
+            ptr::null()
+        );
+        gl::EnableVertexAttribArray(0);
+        // color attribute
+        gl::VertexAttribPointer(
+            1,
+            3,
+            gl::FLOAT,
+            gl::FALSE,
+            (8 * mem::size_of::<f32>()) as GLsizei,
+            (3 * mem::size_of::<f32>()) as *const _
+        );
+        gl::EnableVertexAttribArray(1);
+        // texture coord attribute
+        gl::VertexAttribPointer(
+            2,
+            2,
+            gl::FLOAT,
+            gl::FALSE,
+            (8 * mem::size_of::<f32>()) as GLsizei,
+            (6 * mem::size_of::<f32>()) as *const _
+        );
+        gl::EnableVertexAttribArray(2);
+
+        // load the BC3 (DXT5) compressed texture: it stays compressed in video memory and brings its
+        // own mipmaps, so there's nothing to decode or generate at load time
+        // -------------------------------------------------------------------------------------------
+        let texture = util::image::load_compressed(filesystem::get_path(
+            "resources/textures/dds/container2_bc3.dds".to_string())).unwrap_or_else(|err| {
+            eprintln!("Failed to load compressed texture: {}", err);
+            process::exit(1);
+        });
+
+        // render loop
+        // -----------
+        while !window.should_close() {
+            // input
+            // -----
+            for (_, event) in glfw::flush_messages(&events) {
+                process_input(&mut window, event);
+            }
+
+            // render
+            // ------
+            gl::ClearColor(0.2, 0.3, 0.3, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+
+            // bind Texture
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+
+            // render container
+            our_shader.use_shader();
+            gl::BindVertexArray(vao);
+            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, ptr::null());
+
+            // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
+            // -------------------------------------------------------------------------------
+            window.swap_buffers();
+            glfw.poll_events();
+        }
+
+        // optional: de-allocate all resources once they've outlived their purpose:
+        // ------------------------------------------------------------------------
+        gl::DeleteVertexArrays(1, &vao);
+        gl::DeleteBuffers(1, &vbo);
+        gl::DeleteBuffers(1, &ebo);
+    }
+}
+
+fn process_input(
+    window: &mut Window,
+    event: WindowEvent
+) {
+    match event {
+        WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
+            window.set_should_close(true)
+        }
+        _ => {}
+    }
+}
+
+fn framebuffer_size_callback(
+    _: &mut Window,
+    width: i32,
+    height: i32
+) {
+    unsafe {
+        gl::Viewport(0, 0, width, height);
+    }
+}
\ No newline at end of file
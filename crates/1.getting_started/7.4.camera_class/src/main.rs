@@ -19,11 +19,12 @@ extern crate nalgebra_glm as glm;
 use std::{mem, ptr};
 use std::sync::Mutex;
 use gl::types::*;
-use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
+use glfw::{Action, Key};
 use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader_m::Shader;
 use lazy_static::lazy_static;
-use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::camera::Camera;
+use learnopengl_shared::app::App;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
@@ -32,44 +33,22 @@ const SCR_HEIGHT: u32 = 600;
 lazy_static! {
     static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
 }
-static mut LAST_X: f32 = 800.0 / 2.0;
-static mut LAST_Y: f32 = 600.0 / 2.0;
-static mut FIRST_MOUSE: bool = false;
-
-// timing
-static mut DELTA_TIME: f32 = 0.0;
-static mut LAST_FRAME: f32 = 0.0;
 
 fn main() {
-    // glfw: initialize and configure
-    // ------------------------------
-    let mut glfw = glfw::init(glfw::fail_on_errors)
-        .expect("Failed to initialise GLFW.");
-
-    glfw.window_hint(WindowHint::ContextVersionMajor(3));
-    glfw.window_hint(WindowHint::ContextVersionMinor(3));
-    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
-    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
-
-    // glfw window creation
-    // --------------------
-    let (mut window, _) = glfw.create_window(
-        SCR_WIDTH, SCR_HEIGHT,
-        "LearnOpenGL", glfw::WindowMode::Windowed)
-        .expect("Failed to create GLFW window.");
-    window.set_framebuffer_size_callback(framebuffer_size_callback);
-    window.set_cursor_pos_callback(mouse_callback);
-    window.set_scroll_callback(scroll_callback);
-
-    window.set_key_polling(true);
-    window.make_current();
-
-    // tell GLFW to capture our mouse
-    window.set_cursor_mode(CursorMode::Disabled);
-
-    // load all OpenGL function pointers
-    // ---------------------------------
-    gl::load_with(|s| window.get_proc_address(s) as *const _);
+    // glfw: initialize, create the window and load all OpenGL function pointers
+    // -------------------------------------------------------------------------
+    let mut app = App::new(SCR_WIDTH, SCR_HEIGHT, "LearnOpenGL");
+    app.set_camera(&CAMERA);
+
+    // C toggles smoothed (eased) camera movement, e.g. for recording fly-throughs
+    app.window.set_key_callback(|_, key, _, action, _| {
+        if key == Key::C && action == Action::Press {
+            let mut camera = CAMERA.lock().unwrap();
+            let smoothing = !camera.smoothing();
+            camera.enable_smoothing(smoothing);
+            println!("Camera smoothing: {}", if smoothing { "on" } else { "off" });
+        }
+    });
 
     unsafe {
         // configure global opengl state
@@ -245,17 +224,7 @@ fn main() {
 
         // render loop
         // -----------
-        while !window.should_close() {
-            // per-frame time logic
-            // --------------------
-            let current_frame = glfw.get_time() as f32;
-            DELTA_TIME = current_frame - LAST_FRAME;
-            LAST_FRAME = current_frame;
-
-            // input
-            // -----
-            process_input(&mut window);
-
+        app.run_with(our_shader, |our_shader, _| {
             // render
             // ------
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
@@ -291,12 +260,7 @@ fn main() {
 
                 gl::DrawArrays(gl::TRIANGLES, 0, 36);
             }
-
-            // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
-            // -------------------------------------------------------------------------------
-            window.swap_buffers();
-            glfw.poll_events();
-        }
+        });
 
         // optional: de-allocate all resources once they've outlived their purpose:
         // ------------------------------------------------------------------------
@@ -304,72 +268,3 @@ fn main() {
         gl::DeleteBuffers(1, &vbo);
     }
 }
-
-fn process_input(window: &mut Window) {
-    if window.get_key(Key::Escape) == Action::Press {
-        window.set_should_close(true)
-    }
-
-    if window.get_key(Key::W) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::FORWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::S) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::BACKWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::A) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::LEFT, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::D) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
-        }
-    }
-}
-
-fn framebuffer_size_callback(
-    _: &mut Window,
-    width: i32,
-    height: i32
-) {
-    unsafe {
-        gl::Viewport(0, 0, width, height);
-    }
-}
-
-fn mouse_callback(
-    _: &mut Window,
-    x_pos_in: f64,
-    y_pos_in: f64
-) {
-    let x_pos = x_pos_in as f32;
-    let y_pos = y_pos_in as f32;
-
-    unsafe {
-        if FIRST_MOUSE {
-            LAST_X = x_pos;
-            LAST_Y = y_pos;
-            FIRST_MOUSE = false;
-        }
-
-        let x_offset = x_pos - LAST_X;
-        let y_offset = LAST_Y - y_pos; // reversed since y-coordinates go from bottom to top
-        LAST_X = x_pos;
-        LAST_Y = y_pos;
-
-        CAMERA.lock().unwrap().process_mouse_movement(x_offset, y_offset);
-    }
-}
-
-fn scroll_callback(
-    _: &mut Window,
-    _x_offset: f64,
-    y_offset: f64
-) {
-    CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
-}
\ No newline at end of file
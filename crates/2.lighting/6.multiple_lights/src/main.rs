@@ -22,6 +22,7 @@ use gl::types::*;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader_m::Shader;
+use learnopengl_shared::util::render::DepthPrepass;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
 
@@ -36,6 +37,11 @@ static mut LAST_X: f32 = 800.0 / 2.0;
 static mut LAST_Y: f32 = 600.0 / 2.0;
 static mut FIRST_MOUSE: bool = false;
 
+// Z toggles the depth prepass of the containers, the window title shows how many samples the lighting
+// shader ran for with and without it
+static mut DEPTH_PREPASS: bool = true;
+static mut DEPTH_PREPASS_KEY_PRESSED: bool = false;
+
 // timing
 static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
@@ -80,6 +86,7 @@ fn main() {
         // ------------------------------------
         let lighting_shader = Shader::new("6.multiple_lights.vs".to_string(), "6.multiple_lights.fs".to_string());
         let lighting_cube_shader = Shader::new("6.light_cube.vs".to_string(), "6.light_cube.fs".to_string());
+        let depth_prepass_shader = Shader::new("6.depth_prepass.vs".to_string(), "6.depth_prepass.fs".to_string());
 
         // set up vertex data (and buffer(s)) and configure vertex attributes
         // ------------------------------------------------------------------
@@ -218,6 +225,12 @@ fn main() {
         lighting_shader.set_int("material.diffuse".to_string(), 0);
         lighting_shader.set_int("material.specular".to_string(), 1);
 
+        // counts the samples shaded by the lighting pass
+        let mut depth_prepass = DepthPrepass::new();
+        let mut samples_query = 0u32;
+        gl::GenQueries(1, &mut samples_query);
+        let mut title_timer = 0f32;
+
         // render loop
         // -----------
         while !window.should_close() {
@@ -323,16 +336,44 @@ fn main() {
             // gl::DrawArrays(gl::TRIANGLES, 0, 36);
 
             // render containers
-            gl::BindVertexArray(cube_vao);
-            for (i, position) in cube_positions.iter().enumerate() {
-                // calculate the model matrix for each object and pass it to shader before drawing
-                let mut model = util::glm::diag_mat4(1.0);
-                model = glm::translate(&model, position);
-                let angle = (20 * i) as f32;
-                model = glm::rotate(&model, angle.to_radians(), &glm::vec3(1.0, 0.3, 0.5));
-                lighting_shader.set_mat4("model".to_string(), &model);
-
-                gl::DrawArrays(gl::TRIANGLES, 0, 36);
+            let draw_containers = |shader: &Shader| {
+                gl::BindVertexArray(cube_vao);
+                for (i, position) in cube_positions.iter().enumerate() {
+                    // calculate the model matrix for each object and pass it to shader before drawing
+                    let mut model = util::glm::diag_mat4(1.0);
+                    model = glm::translate(&model, position);
+                    let angle = (20 * i) as f32;
+                    model = glm::rotate(&model, angle.to_radians(), &glm::vec3(1.0, 0.3, 0.5));
+                    shader.set_mat4("model".to_string(), &model);
+
+                    gl::DrawArrays(gl::TRIANGLES, 0, 36);
+                }
+            };
+            depth_prepass.set_enabled(DEPTH_PREPASS);
+            if depth_prepass.begin_depth() {
+                depth_prepass_shader.use_shader();
+                depth_prepass_shader.set_mat4("projection".to_string(), &projection);
+                depth_prepass_shader.set_mat4("view".to_string(), &view);
+                draw_containers(&depth_prepass_shader);
+                lighting_shader.use_shader();
+            }
+            depth_prepass.begin_color();
+            gl::BeginQuery(gl::SAMPLES_PASSED, samples_query);
+            draw_containers(&lighting_shader);
+            gl::EndQuery(gl::SAMPLES_PASSED);
+            depth_prepass.end();
+
+            // waiting for the result stalls the pipeline, fine for a readout twice a second
+            title_timer += DELTA_TIME;
+            if title_timer >= 0.5 {
+                let mut samples = 0u32;
+                gl::GetQueryObjectuiv(samples_query, gl::QUERY_RESULT, &mut samples);
+                window.set_title(format!(
+                    "LearnOpenGL | depth prepass {} | {} samples shaded",
+                    if DEPTH_PREPASS { "on" } else { "off" },
+                    samples
+                ).as_str());
+                title_timer = 0.0;
             }
 
             // also draw the lamp object(s)
@@ -361,6 +402,7 @@ fn main() {
         gl::DeleteVertexArrays(1, &cube_vao);
         gl::DeleteVertexArrays(1, &light_cube_vao);
         gl::DeleteBuffers(1, &vbo);
+        gl::DeleteQueries(1, &samples_query);
     }
 }
 
@@ -389,6 +431,16 @@ fn process_input(window: &mut Window) {
             CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
         }
     }
+
+    unsafe {
+        if window.get_key(Key::Z) == Action::Press && !DEPTH_PREPASS_KEY_PRESSED {
+            DEPTH_PREPASS = !DEPTH_PREPASS;
+            DEPTH_PREPASS_KEY_PRESSED = true;
+        }
+        if window.get_key(Key::Z) == Action::Release {
+            DEPTH_PREPASS_KEY_PRESSED = false;
+        }
+    }
 }
 
 fn framebuffer_size_callback(
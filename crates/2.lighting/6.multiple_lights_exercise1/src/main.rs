@@ -19,11 +19,12 @@ extern crate nalgebra_glm as glm;
 use std::{io, mem, ptr};
 use std::sync::Mutex;
 use gl::types::*;
-use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader_m::Shader;
 use lazy_static::lazy_static;
-use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::app::App;
+use learnopengl_shared::camera::Camera;
+use learnopengl_shared::scene::Environment;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
@@ -32,20 +33,6 @@ const SCR_HEIGHT: u32 = 600;
 lazy_static! {
     static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
 }
-static mut LAST_X: f32 = 800.0 / 2.0;
-static mut LAST_Y: f32 = 600.0 / 2.0;
-static mut FIRST_MOUSE: bool = false;
-
-// timing
-static mut DELTA_TIME: f32 = 0.0;
-static mut LAST_FRAME: f32 = 0.0;
-
-enum Environment {
-    Desert,
-    Factory,
-    Horror,
-    BiochemicalLab
-}
 
 fn main() {
     println!("Please input the type of environment being shown.");
@@ -58,44 +45,20 @@ fn main() {
     io::stdin().read_line(&mut input).unwrap();
     let mut s = input.split_whitespace();
     let a: i32 = s.next().unwrap().parse().unwrap();
-    let environment;
-    match a {
-        0 => environment = Environment::Desert,
-        1 => environment = Environment::Factory,
-        2 => environment = Environment::Horror,
-        3 => environment = Environment::BiochemicalLab,
+    let mut environment = match a {
+        0 => Environment::desert(),
+        1 => Environment::factory(),
+        2 => Environment::horror(),
+        3 => Environment::lab(),
         _ => panic!("Invalid input")
-    }
-
-    // glfw: initialize and configure
-    // ------------------------------
-    let mut glfw = glfw::init(glfw::fail_on_errors)
-        .expect("Failed to initialise GLFW.");
-
-    glfw.window_hint(WindowHint::ContextVersionMajor(3));
-    glfw.window_hint(WindowHint::ContextVersionMinor(3));
-    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
-    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+    };
 
-    // glfw window creation
-    // --------------------
-    let (mut window, _) = glfw.create_window(
-        SCR_WIDTH, SCR_HEIGHT,
-        "LearnOpenGL", glfw::WindowMode::Windowed)
-        .expect("Failed to create GLFW window.");
-    window.set_framebuffer_size_callback(framebuffer_size_callback);
-    window.set_cursor_pos_callback(mouse_callback);
-    window.set_scroll_callback(scroll_callback);
-
-    window.set_key_polling(true);
-    window.make_current();
-
-    // tell GLFW to capture our mouse
-    window.set_cursor_mode(CursorMode::Disabled);
-
-    // load all OpenGL function pointers
-    // ---------------------------------
-    gl::load_with(|s| window.get_proc_address(s) as *const _);
+    // glfw: initialize, create the window and load all OpenGL function pointers
+    // -------------------------------------------------------------------------
+    let mut app = App::new(SCR_WIDTH, SCR_HEIGHT, "LearnOpenGL");
+    app.set_camera(&CAMERA);
+    // the projection is cached by the app and only rebuilt when the zoom or the window size changes
+    let projection_cache = app.projection();
 
     unsafe {
         // configure global opengl state
@@ -246,284 +209,22 @@ fn main() {
 
         // render loop
         // -----------
-        while !window.should_close() {
-            // per-frame time logic
-            // --------------------
-            let current_frame = glfw.get_time() as f32;
-            DELTA_TIME = current_frame - LAST_FRAME;
-            LAST_FRAME = current_frame;
-
-            // input
-            // -----
-            process_input(&mut window);
-
+        app.run_with((lighting_shader, lighting_cube_shader), |(lighting_shader, lighting_cube_shader), _| {
             // render
             // ------
-            match environment {
-                Environment::Desert => gl::ClearColor(0.75, 0.52, 0.3, 1.0),
-                Environment::Factory => gl::ClearColor(0.1, 0.1, 0.1, 1.0),
-                Environment::Horror => gl::ClearColor(0.0, 0.0, 0.0, 1.0),
-                Environment::BiochemicalLab => gl::ClearColor(0.9, 0.9, 0.9, 1.0)
-            }
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-
-            let point_light_colors;
-            match environment {
-                Environment::Desert => {
-                    point_light_colors = [
-                        glm::vec3(1.0, 0.6, 0.0),
-                        glm::vec3(1.0, 0.0, 0.0),
-                        glm::vec3(1.0, 1.0, 0.0),
-                        glm::vec3(0.2, 0.2, 1.0)
-                    ];
-                }
-                Environment::Factory => {
-                    point_light_colors = [
-                        glm::vec3(0.2, 0.2, 0.6),
-                        glm::vec3(0.3, 0.3, 0.7),
-                        glm::vec3(0.0, 0.0, 0.3),
-                        glm::vec3(0.4, 0.4, 0.4)
-                    ];
-                }
-                Environment::Horror => {
-                    point_light_colors = [
-                        glm::vec3(0.1, 0.1, 0.1),
-                        glm::vec3(0.1, 0.1, 0.1),
-                        glm::vec3(0.1, 0.1, 0.1),
-                        glm::vec3(0.3, 0.1, 0.1)
-                    ];
-                }
-                Environment::BiochemicalLab => {
-                    point_light_colors = [
-                        glm::vec3(0.4, 0.7, 0.1),
-                        glm::vec3(0.4, 0.7, 0.1),
-                        glm::vec3(0.4, 0.7, 0.1),
-                        glm::vec3(0.4, 0.7, 0.1)
-                    ];
-                }
-            }
+            environment.clear();
 
             // be sure to activate shader when setting uniforms/drawing objects
             lighting_shader.use_shader();
             lighting_shader.set_vec3("viewPos".to_string(), &CAMERA.lock().unwrap().position());
             lighting_shader.set_float("material.shininess".to_string(), 32.0);
 
-            /*
-            Here we set all the uniforms for the 5/6 types of lights we have. We have to set them manually and index
-            the proper PointLight struct in the array to set each uniform variable. This can be done more code-friendly
-            by defining light types as classes and set their values in there, or by using a more efficient uniform approach
-            by using 'Uniform buffer objects', but that is something we'll discuss in the 'Advanced GLSL' tutorial.
-            */
-            match environment {
-                Environment::Desert => {
-                    // Directional light
-                    lighting_shader.set_vec3_coords("dirLight.direction".to_string(), -0.2, -1.0, -0.3);
-                    lighting_shader.set_vec3_coords("dirLight.ambient".to_string(), 0.3, 0.24, 0.14);
-                    lighting_shader.set_vec3_coords("dirLight.diffuse".to_string(), 0.7, 0.42, 0.26);
-                    lighting_shader.set_vec3_coords("dirLight.specular".to_string(), 0.5, 0.5, 0.5);
-                    // Point light 1
-                    lighting_shader.set_vec3("pointLights[0].position".to_string(), &point_light_positions[0]);
-                    lighting_shader.set_vec3("pointLights[0].ambient".to_string(), &(point_light_colors[0] * 0.1));
-                    lighting_shader.set_vec3("pointLights[0].diffuse".to_string(), &point_light_colors[0]);
-                    lighting_shader.set_vec3("pointLights[0].specular".to_string(), &point_light_colors[0]);
-                    lighting_shader.set_float("pointLights[0].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[0].linear".to_string(), 0.09);
-                    lighting_shader.set_float("pointLights[0].quadratic".to_string(), 0.032);
-                    // Point light 2
-                    lighting_shader.set_vec3("pointLights[1].position".to_string(), &point_light_positions[1]);
-                    lighting_shader.set_vec3("pointLights[1].ambient".to_string(), &(point_light_colors[1] * 0.1));
-                    lighting_shader.set_vec3("pointLights[1].diffuse".to_string(), &point_light_colors[1]);
-                    lighting_shader.set_vec3("pointLights[1].specular".to_string(), &point_light_colors[1]);
-                    lighting_shader.set_float("pointLights[1].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[1].linear".to_string(), 0.09);
-                    lighting_shader.set_float("pointLights[1].quadratic".to_string(), 0.032);
-                    // Point light 3
-                    lighting_shader.set_vec3("pointLights[2].position".to_string(), &point_light_positions[2]);
-                    lighting_shader.set_vec3("pointLights[2].ambient".to_string(), &(point_light_colors[2] * 0.1));
-                    lighting_shader.set_vec3("pointLights[2].diffuse".to_string(), &point_light_colors[2]);
-                    lighting_shader.set_vec3("pointLights[2].specular".to_string(), &point_light_colors[2]);
-                    lighting_shader.set_float("pointLights[2].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[2].linear".to_string(), 0.09);
-                    lighting_shader.set_float("pointLights[2].quadratic".to_string(), 0.032);
-                    // Point light 4
-                    lighting_shader.set_vec3("pointLights[3].position".to_string(), &point_light_positions[3]);
-                    lighting_shader.set_vec3("pointLights[3].ambient".to_string(), &(point_light_colors[3] * 0.1));
-                    lighting_shader.set_vec3("pointLights[3].diffuse".to_string(), &point_light_colors[3]);
-                    lighting_shader.set_vec3("pointLights[3].specular".to_string(), &point_light_colors[3]);
-                    lighting_shader.set_float("pointLights[3].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[3].linear".to_string(), 0.09);
-                    lighting_shader.set_float("pointLights[3].quadratic".to_string(), 0.032);
-                    // SpotLight
-                    lighting_shader.set_vec3("spotLight.position".to_string(), &CAMERA.lock().unwrap().position());
-                    lighting_shader.set_vec3("spotLight.direction".to_string(), &CAMERA.lock().unwrap().front());
-                    lighting_shader.set_vec3_coords("spotLight.ambient".to_string(), 0.0, 0.0, 0.0);
-                    lighting_shader.set_vec3_coords("spotLight.diffuse".to_string(), 0.8, 0.8, 0.0);
-                    lighting_shader.set_vec3_coords("spotLight.specular".to_string(), 0.8, 0.8, 0.0);
-                    lighting_shader.set_float("spotLight.constant".to_string(), 1.0);
-                    lighting_shader.set_float("spotLight.linear".to_string(), 0.09);
-                    lighting_shader.set_float("spotLight.quadratic".to_string(), 0.032);
-                    lighting_shader.set_float("spotLight.cutOff".to_string(), 12.5f32.to_radians().cos());
-                    lighting_shader.set_float("spotLight.outerCutOff".to_string(), 13f32.to_radians().cos());
-                }
-                Environment::Factory => {
-                    // Directional light
-                    lighting_shader.set_vec3_coords("dirLight.direction".to_string(), -0.2, -1.0, -0.3);
-                    lighting_shader.set_vec3_coords("dirLight.ambient".to_string(), 0.05, 0.05, 0.1);
-                    lighting_shader.set_vec3_coords("dirLight.diffuse".to_string(), 0.2, 0.2, 0.7);
-                    lighting_shader.set_vec3_coords("dirLight.specular".to_string(), 0.7, 0.7, 0.7);
-                    // Point light 1
-                    lighting_shader.set_vec3("pointLights[0].position".to_string(), &point_light_positions[0]);
-                    lighting_shader.set_vec3("pointLights[0].ambient".to_string(), &(point_light_colors[0] * 0.1));
-                    lighting_shader.set_vec3("pointLights[0].diffuse".to_string(), &point_light_colors[0]);
-                    lighting_shader.set_vec3("pointLights[0].specular".to_string(), &point_light_colors[0]);
-                    lighting_shader.set_float("pointLights[0].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[0].linear".to_string(), 0.09);
-                    lighting_shader.set_float("pointLights[0].quadratic".to_string(), 0.032);
-                    // Point light 2
-                    lighting_shader.set_vec3("pointLights[1].position".to_string(), &point_light_positions[1]);
-                    lighting_shader.set_vec3("pointLights[1].ambient".to_string(), &(point_light_colors[1] * 0.1));
-                    lighting_shader.set_vec3("pointLights[1].diffuse".to_string(), &point_light_colors[1]);
-                    lighting_shader.set_vec3("pointLights[1].specular".to_string(), &point_light_colors[1]);
-                    lighting_shader.set_float("pointLights[1].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[1].linear".to_string(), 0.09);
-                    lighting_shader.set_float("pointLights[1].quadratic".to_string(), 0.032);
-                    // Point light 3
-                    lighting_shader.set_vec3("pointLights[2].position".to_string(), &point_light_positions[2]);
-                    lighting_shader.set_vec3("pointLights[2].ambient".to_string(), &(point_light_colors[2] * 0.1));
-                    lighting_shader.set_vec3("pointLights[2].diffuse".to_string(), &point_light_colors[2]);
-                    lighting_shader.set_vec3("pointLights[2].specular".to_string(), &point_light_colors[2]);
-                    lighting_shader.set_float("pointLights[2].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[2].linear".to_string(), 0.09);
-                    lighting_shader.set_float("pointLights[2].quadratic".to_string(), 0.032);
-                    // Point light 4
-                    lighting_shader.set_vec3("pointLights[3].position".to_string(), &point_light_positions[3]);
-                    lighting_shader.set_vec3("pointLights[3].ambient".to_string(), &(point_light_colors[3] * 0.1));
-                    lighting_shader.set_vec3("pointLights[3].diffuse".to_string(), &point_light_colors[3]);
-                    lighting_shader.set_vec3("pointLights[3].specular".to_string(), &point_light_colors[3]);
-                    lighting_shader.set_float("pointLights[3].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[3].linear".to_string(), 0.09);
-                    lighting_shader.set_float("pointLights[3].quadratic".to_string(), 0.032);
-                    // SpotLight
-                    lighting_shader.set_vec3("spotLight.position".to_string(), &CAMERA.lock().unwrap().position());
-                    lighting_shader.set_vec3("spotLight.direction".to_string(), &CAMERA.lock().unwrap().front());
-                    lighting_shader.set_vec3_coords("spotLight.ambient".to_string(), 0.0, 0.0, 0.0);
-                    lighting_shader.set_vec3_coords("spotLight.diffuse".to_string(), 1.0, 1.0, 1.0);
-                    lighting_shader.set_vec3_coords("spotLight.specular".to_string(), 1.0, 1.0, 1.0);
-                    lighting_shader.set_float("spotLight.constant".to_string(), 1.0);
-                    lighting_shader.set_float("spotLight.linear".to_string(), 0.009);
-                    lighting_shader.set_float("spotLight.quadratic".to_string(), 0.032);
-                    lighting_shader.set_float("spotLight.cutOff".to_string(), 10f32.to_radians().cos());
-                    lighting_shader.set_float("spotLight.outerCutOff".to_string(), 12.5f32.to_radians().cos());
-                }
-                Environment::Horror => {
-                    // Directional light
-                    lighting_shader.set_vec3_coords("dirLight.direction".to_string(), -0.2, -1.0, -0.3);
-                    lighting_shader.set_vec3_coords("dirLight.ambient".to_string(), 0.0, 0.0, 0.0);
-                    lighting_shader.set_vec3_coords("dirLight.diffuse".to_string(), 0.05, 0.05, 0.05);
-                    lighting_shader.set_vec3_coords("dirLight.specular".to_string(), 0.2, 0.2, 0.2);
-                    // Point light 1
-                    lighting_shader.set_vec3("pointLights[0].position".to_string(), &point_light_positions[0]);
-                    lighting_shader.set_vec3("pointLights[0].ambient".to_string(), &(point_light_colors[0] * 0.1));
-                    lighting_shader.set_vec3("pointLights[0].diffuse".to_string(), &point_light_colors[0]);
-                    lighting_shader.set_vec3("pointLights[0].specular".to_string(), &point_light_colors[0]);
-                    lighting_shader.set_float("pointLights[0].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[0].linear".to_string(), 0.14);
-                    lighting_shader.set_float("pointLights[0].quadratic".to_string(), 0.07);
-                    // Point light 2
-                    lighting_shader.set_vec3("pointLights[1].position".to_string(), &point_light_positions[1]);
-                    lighting_shader.set_vec3("pointLights[1].ambient".to_string(), &(point_light_colors[1] * 0.1));
-                    lighting_shader.set_vec3("pointLights[1].diffuse".to_string(), &point_light_colors[1]);
-                    lighting_shader.set_vec3("pointLights[1].specular".to_string(), &point_light_colors[1]);
-                    lighting_shader.set_float("pointLights[1].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[1].linear".to_string(), 0.14);
-                    lighting_shader.set_float("pointLights[1].quadratic".to_string(), 0.07);
-                    // Point light 3
-                    lighting_shader.set_vec3("pointLights[2].position".to_string(), &point_light_positions[2]);
-                    lighting_shader.set_vec3("pointLights[2].ambient".to_string(), &(point_light_colors[2] * 0.1));
-                    lighting_shader.set_vec3("pointLights[2].diffuse".to_string(), &point_light_colors[2]);
-                    lighting_shader.set_vec3("pointLights[2].specular".to_string(), &point_light_colors[2]);
-                    lighting_shader.set_float("pointLights[2].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[2].linear".to_string(), 0.14);
-                    lighting_shader.set_float("pointLights[2].quadratic".to_string(), 0.07);
-                    // Point light 4
-                    lighting_shader.set_vec3("pointLights[3].position".to_string(), &point_light_positions[3]);
-                    lighting_shader.set_vec3("pointLights[3].ambient".to_string(), &(point_light_colors[3] * 0.1));
-                    lighting_shader.set_vec3("pointLights[3].diffuse".to_string(), &point_light_colors[3]);
-                    lighting_shader.set_vec3("pointLights[3].specular".to_string(), &point_light_colors[3]);
-                    lighting_shader.set_float("pointLights[3].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[3].linear".to_string(), 0.14);
-                    lighting_shader.set_float("pointLights[3].quadratic".to_string(), 0.07);
-                    // SpotLight
-                    lighting_shader.set_vec3("spotLight.position".to_string(), &CAMERA.lock().unwrap().position());
-                    lighting_shader.set_vec3("spotLight.direction".to_string(), &CAMERA.lock().unwrap().front());
-                    lighting_shader.set_vec3_coords("spotLight.ambient".to_string(), 0.0, 0.0, 0.0);
-                    lighting_shader.set_vec3_coords("spotLight.diffuse".to_string(), 1.0, 1.0, 1.0);
-                    lighting_shader.set_vec3_coords("spotLight.specular".to_string(), 1.0, 1.0, 1.0);
-                    lighting_shader.set_float("spotLight.constant".to_string(), 1.0);
-                    lighting_shader.set_float("spotLight.linear".to_string(), 0.09);
-                    lighting_shader.set_float("spotLight.quadratic".to_string(), 0.032);
-                    lighting_shader.set_float("spotLight.cutOff".to_string(), 10f32.to_radians().cos());
-                    lighting_shader.set_float("spotLight.outerCutOff".to_string(), 15f32.to_radians().cos());
-                }
-                Environment::BiochemicalLab => {
-                    // Directional light
-                    lighting_shader.set_vec3_coords("dirLight.direction".to_string(), -0.2, -1.0, -0.3);
-                    lighting_shader.set_vec3_coords("dirLight.ambient".to_string(), 0.5, 0.5, 0.5);
-                    lighting_shader.set_vec3_coords("dirLight.diffuse".to_string(), 1.0, 1.0, 1.0);
-                    lighting_shader.set_vec3_coords("dirLight.specular".to_string(), 1.0, 1.0, 1.0);
-                    // Point light 1
-                    lighting_shader.set_vec3("pointLights[0].position".to_string(), &point_light_positions[0]);
-                    lighting_shader.set_vec3("pointLights[0].ambient".to_string(), &(point_light_colors[0] * 0.1));
-                    lighting_shader.set_vec3("pointLights[0].diffuse".to_string(), &point_light_colors[0]);
-                    lighting_shader.set_vec3("pointLights[0].specular".to_string(), &point_light_colors[0]);
-                    lighting_shader.set_float("pointLights[0].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[0].linear".to_string(), 0.07);
-                    lighting_shader.set_float("pointLights[0].quadratic".to_string(), 0.017);
-                    // Point light 2
-                    lighting_shader.set_vec3("pointLights[1].position".to_string(), &point_light_positions[1]);
-                    lighting_shader.set_vec3("pointLights[1].ambient".to_string(), &(point_light_colors[1] * 0.1));
-                    lighting_shader.set_vec3("pointLights[1].diffuse".to_string(), &point_light_colors[1]);
-                    lighting_shader.set_vec3("pointLights[1].specular".to_string(), &point_light_colors[1]);
-                    lighting_shader.set_float("pointLights[1].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[1].linear".to_string(), 0.07);
-                    lighting_shader.set_float("pointLights[1].quadratic".to_string(), 0.017);
-                    // Point light 3
-                    lighting_shader.set_vec3("pointLights[2].position".to_string(), &point_light_positions[2]);
-                    lighting_shader.set_vec3("pointLights[2].ambient".to_string(), &(point_light_colors[2] * 0.1));
-                    lighting_shader.set_vec3("pointLights[2].diffuse".to_string(), &point_light_colors[2]);
-                    lighting_shader.set_vec3("pointLights[2].specular".to_string(), &point_light_colors[2]);
-                    lighting_shader.set_float("pointLights[2].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[2].linear".to_string(), 0.07);
-                    lighting_shader.set_float("pointLights[2].quadratic".to_string(), 0.017);
-                    // Point light 4
-                    lighting_shader.set_vec3("pointLights[3].position".to_string(), &point_light_positions[3]);
-                    lighting_shader.set_vec3("pointLights[3].ambient".to_string(), &(point_light_colors[3] * 0.1));
-                    lighting_shader.set_vec3("pointLights[3].diffuse".to_string(), &point_light_colors[3]);
-                    lighting_shader.set_vec3("pointLights[3].specular".to_string(), &point_light_colors[3]);
-                    lighting_shader.set_float("pointLights[3].constant".to_string(), 1.0);
-                    lighting_shader.set_float("pointLights[3].linear".to_string(), 0.07);
-                    lighting_shader.set_float("pointLights[3].quadratic".to_string(), 0.017);
-                    // SpotLight
-                    lighting_shader.set_vec3("spotLight.position".to_string(), &CAMERA.lock().unwrap().position());
-                    lighting_shader.set_vec3("spotLight.direction".to_string(), &CAMERA.lock().unwrap().front());
-                    lighting_shader.set_vec3_coords("spotLight.ambient".to_string(), 0.0, 0.0, 0.0);
-                    lighting_shader.set_vec3_coords("spotLight.diffuse".to_string(), 0.0, 1.0, 1.0);
-                    lighting_shader.set_vec3_coords("spotLight.specular".to_string(), 0.0, 1.0, 1.0);
-                    lighting_shader.set_float("spotLight.constant".to_string(), 1.0);
-                    lighting_shader.set_float("spotLight.linear".to_string(), 0.07);
-                    lighting_shader.set_float("spotLight.quadratic".to_string(), 0.017);
-                    lighting_shader.set_float("spotLight.cutOff".to_string(), 7f32.to_radians().cos());
-                    lighting_shader.set_float("spotLight.outerCutOff".to_string(), 10f32.to_radians().cos());
-                }
-            }
+            // directional, point and spot lights of the chosen environment
+            environment.spot_light.follow_camera(&CAMERA.lock().unwrap());
+            environment.upload_lights(&lighting_shader, &point_light_positions);
 
             // view/projection transformations
-            let projection = glm::perspective(
-                CAMERA.lock().unwrap().zoom().to_radians(),
-                (SCR_WIDTH as f32) / (SCR_HEIGHT as f32),
-                0.1,
-                100.0
-            );
+            let projection = projection_cache.matrix(CAMERA.lock().unwrap().zoom());
             let view = CAMERA.lock().unwrap().get_view_matrix();
             lighting_shader.set_mat4("projection".to_string(), &projection);
             lighting_shader.set_mat4("view".to_string(), &view);
@@ -570,12 +271,7 @@ fn main() {
                 lighting_cube_shader.set_mat4("model".to_string(), &model);
                 gl::DrawArrays(gl::TRIANGLES, 0, 36);
             }
-
-            // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
-            // -------------------------------------------------------------------------------
-            window.swap_buffers();
-            glfw.poll_events();
-        }
+        });
 
         // optional: de-allocate all resources once they've outlived their purpose:
         // ------------------------------------------------------------------------
@@ -585,75 +281,6 @@ fn main() {
     }
 }
 
-fn process_input(window: &mut Window) {
-    if window.get_key(Key::Escape) == Action::Press {
-        window.set_should_close(true)
-    }
-
-    if window.get_key(Key::W) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::FORWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::S) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::BACKWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::A) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::LEFT, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::D) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
-        }
-    }
-}
-
-fn framebuffer_size_callback(
-    _: &mut Window,
-    width: i32,
-    height: i32
-) {
-    unsafe {
-        gl::Viewport(0, 0, width, height);
-    }
-}
-
-fn mouse_callback(
-    _: &mut Window,
-    x_pos_in: f64,
-    y_pos_in: f64
-) {
-    let x_pos = x_pos_in as f32;
-    let y_pos = y_pos_in as f32;
-
-    unsafe {
-        if FIRST_MOUSE {
-            LAST_X = x_pos;
-            LAST_Y = y_pos;
-            FIRST_MOUSE = false;
-        }
-
-        let x_offset = x_pos - LAST_X;
-        let y_offset = LAST_Y - y_pos; // reversed since y-coordinates go from bottom to top
-        LAST_X = x_pos;
-        LAST_Y = y_pos;
-
-        CAMERA.lock().unwrap().process_mouse_movement(x_offset, y_offset);
-    }
-}
-
-fn scroll_callback(
-    _: &mut Window,
-    _x_offset: f64,
-    y_offset: f64
-) {
-    CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
-}
-
 // utility function for loading a 2D texture from file
 // ---------------------------------------------------
 fn load_texture(path: String) -> u32 {
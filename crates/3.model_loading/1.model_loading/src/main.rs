@@ -23,9 +23,13 @@ use learnopengl_shared::shader::Shader;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
 use learnopengl_shared_ex::model::Model;
+use learnopengl_shared::debug::BarycentricWireframe;
+use learnopengl_shared::input::InputState;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
+static mut WIREFRAME: bool = false;
+static mut WIREFRAME_KEY_PRESSED: bool = false;
 
 // camera
 lazy_static! {
@@ -81,11 +85,22 @@ fn main() {
 
         // load models
         // -----------
-        let our_model = Model::new_without_gamma(filesystem::get_path("resources/objects/backpack/backpack.obj".to_string()));
+        let backpack = filesystem::get_path("resources/objects/backpack/backpack.obj".to_string());
+        // built with --features tobj the backpack is read by the pure Rust OBJ loader instead of ASSIMP
+        #[cfg(feature = "tobj")]
+        let our_model = Model::from_obj(backpack, false);
+        #[cfg(not(feature = "tobj"))]
+        let our_model = Model::new_without_gamma(backpack);
+
+        // single pass wireframe view of the model (toggled with F)
+        let wireframe = BarycentricWireframe::new(glm::vec3(0.0, 0.0, 0.0));
 
         // draw in wireframe
         gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
 
+        // F12 saves the current frame
+        let mut input = InputState::new();
+
         // render loop
         // -----------
         while !window.should_close() {
@@ -117,8 +132,27 @@ fn main() {
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::translate(&model, &glm::vec3(0.0, 0.0, 0.0)); // translate it down so it's at the center of the scene
             model = glm::scale(&model, &glm::vec3(1.0, 1.0, 1.0)); // it's a bit too big for our scene, so scale it down
-            our_shader.set_mat4("model".to_string(), &model);
-            our_model.draw(&our_shader);
+            if WIREFRAME {
+                // the barycentric wireframe draws its edges on filled surfaces
+                gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+                wireframe.draw(&projection, &view, |wire_shader| {
+                    wire_shader.set_mat4("model".to_string(), &model);
+                    our_model.draw(wire_shader);
+                });
+                gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+            } else {
+                our_shader.set_mat4("model".to_string(), &model);
+                our_model.draw(&our_shader);
+            }
+
+            input.update(&window);
+            if input.just_pressed(Key::F12) {
+                let (width, height) = window.get_framebuffer_size();
+                match util::image::capture_screenshot(width as u32, height as u32, "screenshot.png") {
+                    Ok(()) => println!("Saved screenshot.png"),
+                    Err(err) => println!("Failed to save the screenshot: {}", err)
+                }
+            }
 
             // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
             // -------------------------------------------------------------------------------
@@ -153,6 +187,16 @@ fn process_input(window: &mut Window) {
             CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
         }
     }
+
+    unsafe {
+        if window.get_key(Key::F) == Action::Press && !WIREFRAME_KEY_PRESSED {
+            WIREFRAME = !WIREFRAME;
+            WIREFRAME_KEY_PRESSED = true;
+        }
+        if window.get_key(Key::F) == Action::Release {
+            WIREFRAME_KEY_PRESSED = false;
+        }
+    }
 }
 
 fn framebuffer_size_callback(
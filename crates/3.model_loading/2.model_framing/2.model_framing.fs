@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+// shininess is the Ns of the model's material, uploaded by Model::draw
+struct Material {
+    float shininess;
+};
+
+in vec3 FragPos;
+in vec3 Normal;
+in vec2 TexCoords;
+
+uniform sampler2D texture_diffuse1;
+uniform sampler2D texture_specular1;
+uniform Material material;
+uniform vec3 viewPos;
+
+void main()
+{    
+    vec3 color = texture(texture_diffuse1, TexCoords).rgb;
+    // the light sits at the camera, so the framed model is always lit from the front
+    vec3 norm = normalize(Normal);
+    vec3 lightDir = normalize(viewPos - FragPos);
+    // ambient
+    vec3 ambient = 0.2 * color;
+    // diffuse
+    float diff = max(dot(norm, lightDir), 0.0);
+    vec3 diffuse = diff * color;
+    // specular
+    vec3 reflectDir = reflect(-lightDir, norm);
+    float spec = pow(max(dot(lightDir, reflectDir), 0.0), material.shininess);
+    vec3 specular = spec * texture(texture_specular1, TexCoords).rgb;
+    FragColor = vec4(ambient + diffuse + specular, 1.0);
+}
\ No newline at end of file
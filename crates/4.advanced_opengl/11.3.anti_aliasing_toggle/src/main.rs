@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::{mem, ptr};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Mutex;
+use gl::types::*;
+use glfw::{Action, Key};
+use learnopengl_shared::util;
+use learnopengl_shared::shader::Shader;
+use lazy_static::lazy_static;
+use learnopengl_shared::app::App;
+use learnopengl_shared::camera::Camera;
+use learnopengl_shared::framebuffer::FramebufferBuilder;
+use learnopengl_shared::primitives::render_quad;
+use learnopengl_shared::util::post::Fxaa;
+
+const SCR_WIDTH: u32 = 800;
+const SCR_HEIGHT: u32 = 600;
+
+// camera
+lazy_static! {
+    static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AntiAliasing {
+    Msaa,
+    Fxaa,
+    Off
+}
+
+impl AntiAliasing {
+    fn next(self) -> Self {
+        match self {
+            AntiAliasing::Msaa => AntiAliasing::Fxaa,
+            AntiAliasing::Fxaa => AntiAliasing::Off,
+            AntiAliasing::Off => AntiAliasing::Msaa
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AntiAliasing::Msaa => "4x MSAA",
+            AntiAliasing::Fxaa => "FXAA",
+            AntiAliasing::Off => "off"
+        }
+    }
+}
+
+fn main() {
+    // glfw: initialize, create the window and load all OpenGL function pointers
+    // -------------------------------------------------------------------------
+    let mut app = App::new(SCR_WIDTH, SCR_HEIGHT, "LearnOpenGL");
+    app.set_camera(&CAMERA);
+
+    // M cycles between 4x MSAA, FXAA and no anti-aliasing at all
+    let anti_aliasing = Rc::new(Cell::new(AntiAliasing::Msaa));
+    let anti_aliasing_toggle = Rc::clone(&anti_aliasing);
+    app.window.set_key_callback(move |_, key, _, action, _| {
+        if key == Key::M && action == Action::Press {
+            anti_aliasing_toggle.set(anti_aliasing_toggle.get().next());
+            println!("Anti-aliasing: {}", anti_aliasing_toggle.get().name());
+        }
+    });
+
+    unsafe {
+        // configure global opengl state
+        // -----------------------------
+        gl::Enable(gl::DEPTH_TEST);
+
+        // build and compile shaders
+        // -------------------------
+        let shader = Shader::new("11.3.anti_aliasing.vs".to_string(), "11.3.anti_aliasing.fs".to_string(), None);
+        let screen_shader = Shader::new("11.3.aa_post.vs".to_string(), "11.3.aa_post.fs".to_string(), None);
+        let fxaa = Fxaa::new();
+
+        // set up vertex data (and buffer(s)) and configure vertex attributes
+        // ------------------------------------------------------------------
+        let cube_vertices = [
+            // positions       
+            -0.5f32, -0.5, -0.5,
+            0.5, -0.5, -0.5,
+            0.5,  0.5, -0.5,
+            0.5,  0.5, -0.5,
+            -0.5,  0.5, -0.5,
+            -0.5, -0.5, -0.5,
+
+            -0.5, -0.5,  0.5,
+            0.5, -0.5,  0.5,
+            0.5,  0.5,  0.5,
+            0.5,  0.5,  0.5,
+            -0.5,  0.5,  0.5,
+            -0.5, -0.5,  0.5,
+
+            -0.5,  0.5,  0.5,
+            -0.5,  0.5, -0.5,
+            -0.5, -0.5, -0.5,
+            -0.5, -0.5, -0.5,
+            -0.5, -0.5,  0.5,
+            -0.5,  0.5,  0.5,
+
+            0.5,  0.5,  0.5,
+            0.5,  0.5, -0.5,
+            0.5, -0.5, -0.5,
+            0.5, -0.5, -0.5,
+            0.5, -0.5,  0.5,
+            0.5,  0.5,  0.5,
+
+            -0.5, -0.5, -0.5,
+            0.5, -0.5, -0.5,
+            0.5, -0.5,  0.5,
+            0.5, -0.5,  0.5,
+            -0.5, -0.5,  0.5,
+            -0.5, -0.5, -0.5,
+
+            -0.5,  0.5, -0.5,
+            0.5,  0.5, -0.5,
+            0.5,  0.5,  0.5,
+            0.5,  0.5,  0.5,
+            -0.5,  0.5,  0.5,
+            -0.5,  0.5, -0.5
+        ];
+        // setup cube VAO
+        let (mut cube_vao, mut cube_vbo) = (0u32, 0u32);
+        gl::GenVertexArrays(1, &mut cube_vao);
+        gl::GenBuffers(1, &mut cube_vbo);
+        gl::BindVertexArray(cube_vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, cube_vbo);
+        gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&cube_vertices) as _, ptr::addr_of!(cube_vertices) as _, gl::STATIC_DRAW);
+        gl::EnableVertexAttribArray(0);
+        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (3 * mem::size_of::<f32>()) as _, ptr::null());
+
+        // configure MSAA framebuffer
+        // --------------------------
+        let multisampled = FramebufferBuilder::new(SCR_WIDTH, SCR_HEIGHT).samples(4).build();
+        // configure second post-processing framebuffer, the scene is rendered straight into it when MSAA is off
+        let intermediate = FramebufferBuilder::new(SCR_WIDTH, SCR_HEIGHT).build();
+
+        // shader configuration
+        // --------------------
+        screen_shader.use_shader();
+        screen_shader.set_int("screenTexture".to_string(), 0);
+
+        // render loop
+        // -----------
+        app.run_with((shader, screen_shader), |(shader, screen_shader), _| {
+            // the framebuffers have a fixed size, the window's viewport is restored for the screen quad
+            let mut viewport = [0 as GLint; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+
+            // 1. draw scene as normal, in multisampled buffers if MSAA is on
+            let msaa = anti_aliasing.get() == AntiAliasing::Msaa;
+            let scene_framebuffer = if msaa { &multisampled } else { &intermediate };
+            scene_framebuffer.bind();
+            gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            gl::Enable(gl::DEPTH_TEST);
+
+            // set transformation matrices
+            shader.use_shader();
+            let projection = glm::perspective(CAMERA.lock().unwrap().zoom().to_radians(), (SCR_WIDTH as f32) / (SCR_HEIGHT as f32), 0.1, 100.0);
+            shader.set_mat4("projection".to_string(), &projection);
+            shader.set_mat4("view".to_string(), &CAMERA.lock().unwrap().get_view_matrix());
+            shader.set_mat4("model".to_string(), &util::glm::diag_mat4(1.0));
+
+            gl::BindVertexArray(cube_vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, 36);
+
+            // 2. now blit multisampled buffer(s) to normal colorbuffer of intermediate FBO
+            if msaa {
+                multisampled.resolve_to(&intermediate);
+            }
+
+            // 3. now render quad with scene's visuals as its texture image
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+            gl::ClearColor(1.0, 1.0, 1.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::Disable(gl::DEPTH_TEST);
+
+            // draw Screen quad, FXAA smooths the edges of the single sampled image while drawing it
+            if anti_aliasing.get() == AntiAliasing::Fxaa {
+                fxaa.apply(intermediate.color_texture().unwrap());
+            } else {
+                screen_shader.use_shader();
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, intermediate.color_texture().unwrap()); // use the resolved color attachment as the quad's texture
+                render_quad();
+            }
+        });
+
+        // optional: de-allocate all resources once they've outlived their purpose:
+        // ------------------------------------------------------------------------
+        gl::DeleteVertexArrays(1, &cube_vao);
+        gl::DeleteBuffers(1, &cube_vbo);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+extern crate nalgebra_glm as glm;
+
+use std::{mem, ptr};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Mutex;
+use gl::types::*;
+use glfw::{Action, Key};
+use learnopengl_shared::{filesystem, util};
+use learnopengl_shared::util::image::TextureOptions;
+use learnopengl_shared::shader_m::Shader;
+use lazy_static::lazy_static;
+use learnopengl_shared::app::App;
+use learnopengl_shared::camera::Camera;
+use learnopengl_shared::texture::Texture;
+
+const SCR_WIDTH: u32 = 800;
+const SCR_HEIGHT: u32 = 600;
+
+// camera
+lazy_static! {
+    static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
+}
+
+fn main() {
+    // glfw: initialize, create the window and load all OpenGL function pointers
+    // -------------------------------------------------------------------------
+    let mut app = App::new(SCR_WIDTH, SCR_HEIGHT, "LearnOpenGL");
+    app.set_camera(&CAMERA);
+
+    match util::image::max_anisotropy() {
+        Some(max) => println!("Anisotropic filtering supported up to {}x, press space to toggle it.", max),
+        None => println!("Anisotropic filtering is not supported, the floor is always trilinear filtered.")
+    }
+
+    // space switches the floor between anisotropic and trilinear filtering
+    let anisotropic = Rc::new(Cell::new(true));
+    let anisotropic_toggle = Rc::clone(&anisotropic);
+    app.window.set_key_callback(move |_, key, _, action, _| {
+        if key == Key::Space && action == Action::Press {
+            anisotropic_toggle.set(!anisotropic_toggle.get());
+            println!("anisotropic filtering: {}", if anisotropic_toggle.get() { "on" } else { "off" });
+        }
+    });
+
+    unsafe {
+        // configure global opengl state
+        // -----------------------------
+        gl::Enable(gl::DEPTH_TEST);
+
+        // build and compile shaders
+        // -------------------------
+        let shader = Shader::new("12.anisotropic_filtering.vs".to_string(), "12.anisotropic_filtering.fs".to_string());
+
+        // set up vertex data (and buffer(s)) and configure vertex attributes
+        // ------------------------------------------------------------------
+        let plane_vertices = [
+            // positions            // texture Coords (a large floor with many repeats, looked at from a grazing angle)
+            50.0f32, -0.5,  50.0,  50.0,  0.0,
+            -50.0, -0.5,  50.0,   0.0,  0.0,
+            -50.0, -0.5, -50.0,   0.0, 50.0,
+
+            50.0, -0.5,  50.0,  50.0,  0.0,
+            -50.0, -0.5, -50.0,   0.0, 50.0,
+            50.0, -0.5, -50.0,  50.0, 50.0
+        ];
+        // plane VAO
+        let (mut plane_vao, mut plane_vbo) = (0u32, 0u32);
+        gl::GenVertexArrays(1, &mut plane_vao);
+        gl::GenBuffers(1, &mut plane_vbo);
+        gl::BindVertexArray(plane_vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, plane_vbo);
+        gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&plane_vertices) as GLsizeiptr, ptr::addr_of!(plane_vertices) as *const _, gl::STATIC_DRAW);
+        gl::EnableVertexAttribArray(0);
+        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as GLsizei, ptr::null());
+        gl::EnableVertexAttribArray(1);
+        gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as GLsizei, (3 * mem::size_of::<f32>()) as *const _);
+        gl::BindVertexArray(0);
+
+        // load textures
+        // -------------
+        let floor_texture = Texture::from_file_2d_with_options(
+            filesystem::get_path("resources/textures/wood.png".to_string()),
+            false,
+            &TextureOptions { anisotropic: true, ..Default::default() }
+        );
+        let mut floor_anisotropic = true;
+
+        // shader configuration
+        // --------------------
+        shader.use_shader();
+        shader.set_int("texture1".to_string(), 0);
+
+        // render loop
+        // -----------
+        app.run_with(shader, |shader, _| {
+            // render
+            // ------
+            gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            if floor_anisotropic != anisotropic.get() {
+                floor_anisotropic = anisotropic.get();
+                floor_texture.set_anisotropic(floor_anisotropic);
+            }
+
+            shader.use_shader();
+            let projection = glm::perspective(CAMERA.lock().unwrap().zoom().to_radians(), (SCR_WIDTH as f32) / (SCR_HEIGHT as f32), 0.1, 100.0);
+            shader.set_mat4("model".to_string(), &util::glm::diag_mat4(1.0));
+            shader.set_mat4("view".to_string(), &CAMERA.lock().unwrap().get_view_matrix());
+            shader.set_mat4("projection".to_string(), &projection);
+            // floor
+            gl::BindVertexArray(plane_vao);
+            floor_texture.bind(0);
+            gl::DrawArrays(gl::TRIANGLES, 0, 6);
+            gl::BindVertexArray(0);
+        });
+
+        // optional: de-allocate all resources once they've outlived their purpose:
+        // ------------------------------------------------------------------------
+        gl::DeleteVertexArrays(1, &plane_vao);
+        gl::DeleteBuffers(1, &plane_vbo);
+    }
+}
\ No newline at end of file
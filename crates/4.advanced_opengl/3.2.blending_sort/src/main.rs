@@ -17,7 +17,6 @@
 extern crate nalgebra_glm as glm;
 
 use std::{mem, ptr};
-use std::collections::HashMap;
 use std::sync::Mutex;
 use gl::types::*;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
@@ -41,32 +40,6 @@ static mut FIRST_MOUSE: bool = false;
 static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
 
-#[derive(Hash, Eq, PartialEq)]
-struct F32(u8, u8, u8, u8);
-
-impl F32 {
-    fn from(value: f32) -> Self {
-        let mut result = Self::default();
-        let bytes = value.to_be_bytes();
-        result.0 = bytes[0];
-        result.1 = bytes[1];
-        result.2 = bytes[2];
-        result.3 = bytes[3];
-        result
-    }
-
-    fn value(&self) -> f32 {
-        let bytes = [self.0, self.1, self.2, self.3];
-        f32::from_be_bytes(bytes)
-    }
-}
-
-impl Default for F32 {
-    fn default() -> Self {
-        Self(0, 0, 0, 0)
-    }
-}
-
 fn main() {
     // glfw: initialize and configure
     // ------------------------------
@@ -248,11 +221,7 @@ fn main() {
 
             // sort the transparent windows before rendering
             // ---------------------------------------------
-            let mut sorted: HashMap<F32, glm::TVec3<f32>> = HashMap::new();
-            for window in windows.iter() {
-                let distance = glm::length(&(CAMERA.lock().unwrap().position() - window));
-                sorted.insert(F32::from(distance), *window);
-            }
+            let sorted = util::render::sort_by_distance(&windows, &CAMERA.lock().unwrap().position());
 
             // render
             // ------
@@ -286,11 +255,9 @@ fn main() {
             // windows (from furthest to nearest)
             gl::BindVertexArray(transparent_vao);
             gl::BindTexture(gl::TEXTURE_2D, transparent_texture);
-            let mut sorted_vec: Vec<_> = sorted.iter().collect();
-            sorted_vec.sort_by(|a, b| b.0.value().partial_cmp(&a.0.value()).unwrap());
-            for val in sorted_vec.iter() {
+            for i in sorted.iter() {
                 let mut model = util::glm::diag_mat4(1.0);
-                model = glm::translate(&model, val.1);
+                model = glm::translate(&model, &windows[*i]);
                 shader.set_mat4("model".to_string(), &model);
                 gl::DrawArrays(gl::TRIANGLES, 0, 6);
             }
@@ -16,6 +16,7 @@
 
 extern crate nalgebra_glm as glm;
 
+use std::ffi::{c_void, CStr};
 use std::{mem, ptr};
 use std::sync::Mutex;
 use gl::types::*;
@@ -24,6 +25,8 @@ use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader_m::Shader;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::texture::Texture;
+use learnopengl_shared::util::image::TextureOptions;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
@@ -40,6 +43,22 @@ static mut FIRST_MOUSE: bool = false;
 static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
 
+// reports GL errors raised by the driver, e.g. when deleting a texture that doesn't exist (anymore)
+extern "system" fn gl_debug_output(_source: GLenum,
+                   gltype: GLenum,
+                   id: GLuint,
+                   _severity: GLenum,
+                   _length: GLsizei,
+                   message: *const GLchar,
+                   _user_param: *mut c_void)
+{
+    if gltype != gl::DEBUG_TYPE_ERROR {
+        return;
+    }
+    let message_str = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    println!("Debug message ({}): {}", id, message_str);
+}
+
 fn main() {
     // glfw: initialize and configure
     // ------------------------------
@@ -50,6 +69,7 @@ fn main() {
     glfw.window_hint(WindowHint::ContextVersionMinor(3));
     glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
     glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+    glfw.window_hint(WindowHint::OpenGlDebugContext(true));
 
     // glfw window creation
     // --------------------
@@ -72,6 +92,16 @@ fn main() {
     gl::load_with(|s| window.get_proc_address(s) as *const _);
 
     unsafe {
+        // enable OpenGL debug output if the context allows for it
+        let mut flags = 0i32;
+        gl::GetIntegerv(gl::CONTEXT_FLAGS, &mut flags);
+        if flags & gl::CONTEXT_FLAG_DEBUG_BIT as i32 != 0 && gl::DebugMessageCallback::is_loaded() {
+            gl::Enable(gl::DEBUG_OUTPUT);
+            gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+            gl::DebugMessageCallback(Some(gl_debug_output), ptr::null());
+            gl::DebugMessageControl(gl::DONT_CARE, gl::DONT_CARE, gl::DONT_CARE, 0, ptr::null(), gl::TRUE);
+        }
+
         // configure global opengl state
         // -----------------------------
         gl::Enable(gl::DEPTH_TEST);
@@ -187,8 +217,13 @@ fn main() {
 
         // load textures
         // -------------
-        let cube_texture = load_texture(filesystem::get_path("resources/textures/container.jpg".to_string()));
-        let floor_texture = load_texture(filesystem::get_path("resources/textures/metal.png".to_string()));
+        let cube_texture = Texture::from_file_2d(filesystem::get_path("resources/textures/container.jpg".to_string()), false);
+        // anisotropic filtering keeps the floor from shimmering at grazing angles (if supported)
+        let floor_texture = Texture::from_file_2d_with_options(
+            filesystem::get_path("resources/textures/metal.png".to_string()),
+            false,
+            &TextureOptions { anisotropic: true, ..Default::default() }
+        );
 
         // shader configuration
         // --------------------
@@ -254,19 +289,18 @@ fn main() {
             let mut camera = CAMERA.lock().unwrap();
             let new_yaw = camera.yaw() + 180.0;
             camera.set_yaw(new_yaw); // rotate the camera's yaw 180 degrees around
-            camera.process_mouse_movement_ex(0.0, 0.0, false); // call this to make sure it updates its camera vectors, note that we disable pitch constrains for this specific case (otherwise we can't reverse camera's pitch values)
+            camera.update_camera_vectors(); // call this to make sure it updates its camera vectors
             let view = camera.get_view_matrix();
             let new_yaw = camera.yaw() - 180.0;
             camera.set_yaw(new_yaw); // reset it back to its original orientation
-            camera.process_mouse_movement_ex(0.0, 0.0, true);
+            camera.update_camera_vectors();
             let projection = glm::perspective(camera.zoom().to_radians(), (SCR_WIDTH as f32) / (SCR_HEIGHT as f32), 0.1, 100.0);
             shader.set_mat4("view".to_string(), &view);
             shader.set_mat4("projection".to_string(), &projection);
             drop(camera);
             // cubes
             gl::BindVertexArray(cube_vao);
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, cube_texture);
+            cube_texture.bind(0);
             model = glm::translate(&model, &glm::vec3(-1.0, 0.0, -1.0));
             shader.set_mat4("model".to_string(), &model);
             gl::DrawArrays(gl::TRIANGLES, 0, 36);
@@ -276,7 +310,7 @@ fn main() {
             gl::DrawArrays(gl::TRIANGLES, 0, 36);
             // floor
             gl::BindVertexArray(plane_vao);
-            gl::BindTexture(gl::TEXTURE_2D, floor_texture);
+            floor_texture.bind(0);
             shader.set_mat4("model".to_string(), &util::glm::diag_mat4(1.0));
             gl::DrawArrays(gl::TRIANGLES, 0, 6);
             gl::BindVertexArray(0);
@@ -294,8 +328,7 @@ fn main() {
 
             // cubes
             gl::BindVertexArray(cube_vao);
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, cube_texture);
+            cube_texture.bind(0);
             model = glm::translate(&model, &glm::vec3(-1.0, 0.0, -1.0));
             shader.set_mat4("model".to_string(), &model);
             gl::DrawArrays(gl::TRIANGLES, 0, 36);
@@ -305,7 +338,7 @@ fn main() {
             gl::DrawArrays(gl::TRIANGLES, 0, 36);
             // floor
             gl::BindVertexArray(plane_vao);
-            gl::BindTexture(gl::TEXTURE_2D, floor_texture);
+            floor_texture.bind(0);
             shader.set_mat4("model".to_string(), &util::glm::diag_mat4(1.0));
             gl::DrawArrays(gl::TRIANGLES, 0, 6);
             gl::BindVertexArray(0);
@@ -334,7 +367,18 @@ fn main() {
         gl::DeleteBuffers(1, &plane_vbo);
         gl::DeleteBuffers(1, &quad_vbo);
         gl::DeleteRenderbuffers(1, &rbo);
+        gl::DeleteTextures(1, &texture_colorbuffer);
         gl::DeleteFramebuffers(1, &framebuffer);
+
+        // the loaded textures delete themselves when dropped, make sure they are really gone
+        let texture_ids = [cube_texture.id(), floor_texture.id()];
+        drop(cube_texture);
+        drop(floor_texture);
+        for texture_id in texture_ids {
+            if gl::IsTexture(texture_id) == gl::TRUE {
+                println!("ERROR::TEXTURE:: Texture {} was not deleted!", texture_id);
+            }
+        }
     }
 }
 
@@ -406,34 +450,3 @@ fn scroll_callback(
 ) {
     CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
 }
-
-// utility function for loading a 2D texture from file
-// ---------------------------------------------------
-fn load_texture(path: String) -> u32 {
-    let mut texture_id = 0u32;
-    unsafe {
-        gl::GenTextures(1, &mut texture_id);
-
-        let img = util::image::load_image_data_rgba(path)
-            .expect("Failed to load texture data.");
-        let width = img.width();
-        let height = img.height();
-        let data = img.as_raw();
-
-        gl::BindTexture(gl::TEXTURE_2D, texture_id);
-        gl::TexImage2D(
-            gl::TEXTURE_2D,
-            0,
-            gl::RGBA as GLint,
-            width as GLint,
-            height as GLint,
-            0,
-            gl::RGBA,
-            gl::UNSIGNED_BYTE,
-            data.as_ptr() as *const _
-        );
-        gl::GenerateMipmap(gl::TEXTURE_2D);
-    }
-
-    texture_id
-}
\ No newline at end of file
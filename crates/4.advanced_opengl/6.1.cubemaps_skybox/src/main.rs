@@ -16,60 +16,45 @@
 
 extern crate nalgebra_glm as glm;
 
-use std::{mem, ptr};
+use std::f32::consts::PI;
+use std::process;
 use std::sync::Mutex;
-use gl::types::*;
-use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
-use learnopengl_shared::{filesystem, util};
+use learnopengl_shared::{filesystem, primitives, util};
+use learnopengl_shared::util::image::TextureOptions;
+use learnopengl_shared::util::mesh::VaoBuilder;
+use learnopengl_shared::util::render::DynamicCubemap;
 use learnopengl_shared::shader_m::Shader;
 use lazy_static::lazy_static;
-use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::camera::Camera;
+use learnopengl_shared::app::App;
+use learnopengl_shared::ubo::MatricesUbo;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
+const ORBITING_CUBES: u32 = 4;
 
 // camera
 lazy_static! {
     static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
 }
-static mut LAST_X: f32 = 800.0 / 2.0;
-static mut LAST_Y: f32 = 600.0 / 2.0;
-static mut FIRST_MOUSE: bool = false;
-
-// timing
-static mut DELTA_TIME: f32 = 0.0;
-static mut LAST_FRAME: f32 = 0.0;
 
 fn main() {
-    // glfw: initialize and configure
-    // ------------------------------
-    let mut glfw = glfw::init(glfw::fail_on_errors)
-        .expect("Failed to initialise GLFW.");
-
-    glfw.window_hint(WindowHint::ContextVersionMajor(3));
-    glfw.window_hint(WindowHint::ContextVersionMinor(3));
-    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
-    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
-
-    // glfw window creation
-    // --------------------
-    let (mut window, _) = glfw.create_window(
-        SCR_WIDTH, SCR_HEIGHT,
-        "LearnOpenGL", glfw::WindowMode::Windowed)
-        .expect("Failed to create GLFW window.");
-    window.set_framebuffer_size_callback(framebuffer_size_callback);
-    window.set_cursor_pos_callback(mouse_callback);
-    window.set_scroll_callback(scroll_callback);
-
-    window.set_key_polling(true);
-    window.make_current();
-
-    // tell GLFW to capture our mouse
-    window.set_cursor_mode(CursorMode::Disabled);
-
-    // load all OpenGL function pointers
-    // ---------------------------------
-    gl::load_with(|s| window.get_proc_address(s) as *const _);
+    // with --headless a single frame is rendered off-screen and only a hash of its pixels is printed,
+    // which lets CI compare the output against a known value without a display
+    let headless = std::env::args().any(|arg| arg == "--headless");
+
+    // glfw: initialize, create the window and load all OpenGL function pointers
+    // -------------------------------------------------------------------------
+    let mut app = if headless {
+        App::new_headless(SCR_WIDTH, SCR_HEIGHT).unwrap_or_else(|err| {
+            eprintln!("Failed to set up headless rendering: {}", err);
+            process::exit(1);
+        })
+    } else {
+        let mut app = App::new(SCR_WIDTH, SCR_HEIGHT, "LearnOpenGL");
+        app.set_camera(&CAMERA);
+        app
+    };
 
     unsafe {
         // configure global opengl state
@@ -80,6 +65,7 @@ fn main() {
         // -------------------------
         let shader = Shader::new("6.1.cubemaps.vs".to_string(), "6.1.cubemaps.fs".to_string());
         let skybox_shader = Shader::new("6.1.skybox.vs".to_string(), "6.1.skybox.fs".to_string());
+        let reflection_shader = Shader::new("6.1.reflection.vs".to_string(), "6.1.reflection.fs".to_string());
 
         // set up vertex data (and buffer(s)) and configure vertex attributes
         // ------------------------------------------------------------------
@@ -173,29 +159,22 @@ fn main() {
         ];
         
         // cube VAO
-        let (mut cube_vao, mut cube_vbo) = (0u32, 0u32);
-        gl::GenVertexArrays(1, &mut cube_vao);
-        gl::GenBuffers(1, &mut cube_vbo);
-        gl::BindVertexArray(cube_vao);
-        gl::BindBuffer(gl::ARRAY_BUFFER, cube_vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&cube_vertices) as GLsizeiptr, ptr::addr_of!(cube_vertices) as *const _, gl::STATIC_DRAW);
-        gl::EnableVertexAttribArray(0);
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as GLsizei, ptr::null());
-        gl::EnableVertexAttribArray(1);
-        gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as GLsizei, (3 * mem::size_of::<f32>()) as *const _);
+        let cube_vao = VaoBuilder::new()
+            .attrib(0, 3, gl::FLOAT)
+            .attrib(1, 2, gl::FLOAT)
+            .build(&cube_vertices);
         // skybox VAO
-        let (mut skybox_vao, mut skybox_vbo) = (0u32, 0u32);
-        gl::GenVertexArrays(1, &mut skybox_vao);
-        gl::GenBuffers(1, &mut skybox_vbo);
-        gl::BindVertexArray(skybox_vao);
-        gl::BindBuffer(gl::ARRAY_BUFFER, skybox_vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&skybox_vertices) as GLsizeiptr, ptr::addr_of!(skybox_vertices) as *const _, gl::STATIC_DRAW);
-        gl::EnableVertexAttribArray(0);
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (3 * mem::size_of::<f32>()) as GLsizei, ptr::null());
+        let skybox_vao = VaoBuilder::new()
+            .attrib(0, 3, gl::FLOAT)
+            .build(&skybox_vertices);
 
         // load textures
         // -------------
-        let cube_texture = load_texture(filesystem::get_path("resources/textures/container.jpg".to_string()));
+        let cube_texture = util::image::load_texture_2d_with_options(
+            filesystem::get_path("resources/textures/container.jpg".to_string()),
+            false,
+            &TextureOptions { flip_vertically: false, ..Default::default() }
+        );
 
         let faces = vec![
             filesystem::get_path("resources/textures/skybox/right.jpg".to_string()),
@@ -205,7 +184,7 @@ fn main() {
             filesystem::get_path("resources/textures/skybox/front.jpg".to_string()),
             filesystem::get_path("resources/textures/skybox/back.jpg".to_string())
         ];
-        let cubemap_texture = load_cubemap(&faces);
+        let cubemap_texture = util::image::load_cubemap(&faces);
 
         // shader configuration
         // --------------------
@@ -215,213 +194,86 @@ fn main() {
         skybox_shader.use_shader();
         skybox_shader.set_int("skybox".to_string(), 0);
 
-        // render loop
-        // -----------
-        while !window.should_close() {
-            // per-frame time logic
-            // --------------------
-            let current_frame = glfw.get_time() as f32;
-            DELTA_TIME = current_frame - LAST_FRAME;
-            LAST_FRAME = current_frame;
-
-            // input
-            // -----
-            process_input(&mut window);
-
-            // render
-            // ------
-            gl::ClearColor(0.1, 0.1, 0.1, 1.0);
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        reflection_shader.use_shader();
+        reflection_shader.set_int("environment".to_string(), 0);
+
+        // all shaders read projection and view from the same uniform buffer
+        let matrices = MatricesUbo::new();
+        shader.bind_uniform_block(MatricesUbo::BLOCK_NAME, MatricesUbo::BINDING);
+        skybox_shader.bind_uniform_block(MatricesUbo::BLOCK_NAME, MatricesUbo::BINDING);
+        reflection_shader.bind_uniform_block(MatricesUbo::BLOCK_NAME, MatricesUbo::BINDING);
+
+        // follows the window's aspect ratio, so resizing doesn't stretch the scene
+        let projection_cache = app.projection();
+
+        // the cube in the center reflects the live scene, captured from its position every frame
+        let environment = DynamicCubemap::new(256);
+
+        // draws everything but the reflective cube: the cubes orbiting it and the skybox
+        let draw_scene = |shader: &Shader, skybox_shader: &Shader, projection: &glm::TMat4<f32>, view: &glm::TMat4<f32>, time: f32| {
+            matrices.set_view(view);
+            matrices.set_projection(projection);
 
-            // draw scene as normal
-            shader.use_shader();
-            let model = util::glm::diag_mat4(1.0);
-            let view = CAMERA.lock().unwrap().get_view_matrix();
-            let projection = glm::perspective(CAMERA.lock().unwrap().zoom().to_radians(), (SCR_WIDTH as f32) / (SCR_HEIGHT as f32), 0.1, 100.0);
-            shader.set_mat4("model".to_string(), &model);
-            shader.set_mat4("view".to_string(), &view);
-            shader.set_mat4("projection".to_string(), &projection);
             // cubes
-            gl::BindVertexArray(cube_vao);
+            shader.use_shader();
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, cube_texture);
-            gl::DrawArrays(gl::TRIANGLES, 0, 36);
-            gl::BindVertexArray(0);
+            for i in 0..ORBITING_CUBES {
+                let angle = time * 0.5 + i as f32 * 2.0 * PI / ORBITING_CUBES as f32;
+                let mut model = util::glm::diag_mat4(1.0);
+                model = glm::translate(&model, &glm::vec3(angle.cos() * 2.0, 0.0, angle.sin() * 2.0));
+                model = glm::scale(&model, &util::glm::scale_vec3(0.5));
+                shader.set_mat4("model".to_string(), &model);
+                cube_vao.draw(gl::TRIANGLES);
+            }
 
             // draw skybox as last
             gl::DepthFunc(gl::LEQUAL); // change depth function so depth test passes when values are equal to depth buffer's content
-            skybox_shader.use_shader();
-            let view = util::glm::mat4_wrap_mat3(&util::glm::mat3_from_mat4(&CAMERA.lock().unwrap().get_view_matrix())); // remove translation from the view matrix
-            skybox_shader.set_mat4("view".to_string(), &view);
-            skybox_shader.set_mat4("projection".to_string(), &projection);
+            skybox_shader.use_shader(); // the vertex shader removes the translation from the shared view matrix
             // skybox cube
-            gl::BindVertexArray(skybox_vao);
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap_texture);
-            gl::DrawArrays(gl::TRIANGLES, 0, 36);
-            gl::BindVertexArray(0);
+            skybox_vao.draw(gl::TRIANGLES);
             gl::DepthFunc(gl::LESS); // set depth function back to default
+        };
 
-            // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
-            // -------------------------------------------------------------------------------
-            window.swap_buffers();
-            glfw.poll_events();
-        }
-
-        // optional: de-allocate all resources once they've outlived their purpose:
-        // ------------------------------------------------------------------------
-        gl::DeleteVertexArrays(1, &cube_vao);
-        gl::DeleteVertexArrays(1, &skybox_vao);
-        gl::DeleteBuffers(1, &cube_vbo);
-        gl::DeleteBuffers(1, &skybox_vbo);
-    }
-}
-
-fn process_input(window: &mut Window) {
-    if window.get_key(Key::Escape) == Action::Press {
-        window.set_should_close(true)
-    }
-
-    if window.get_key(Key::W) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::FORWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::S) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::BACKWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::A) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::LEFT, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::D) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
-        }
-    }
-}
-
-fn framebuffer_size_callback(
-    _: &mut Window,
-    width: i32,
-    height: i32
-) {
-    unsafe {
-        gl::Viewport(0, 0, width, height);
-    }
-}
-
-fn mouse_callback(
-    _: &mut Window,
-    x_pos_in: f64,
-    y_pos_in: f64
-) {
-    let x_pos = x_pos_in as f32;
-    let y_pos = y_pos_in as f32;
-
-    unsafe {
-        if FIRST_MOUSE {
-            LAST_X = x_pos;
-            LAST_Y = y_pos;
-            FIRST_MOUSE = false;
-        }
-
-        let x_offset = x_pos - LAST_X;
-        let y_offset = LAST_Y - y_pos; // reversed since y-coordinates go from bottom to top
-        LAST_X = x_pos;
-        LAST_Y = y_pos;
-
-        CAMERA.lock().unwrap().process_mouse_movement(x_offset, y_offset);
-    }
-}
+        // render loop
+        // -----------
+        let mut time = 0.0f32;
+        let mut render = |(shader, skybox_shader, reflection_shader): &(Shader, Shader, Shader), delta_time: f32| {
+            time += delta_time;
 
-fn scroll_callback(
-    _: &mut Window,
-    _x_offset: f64,
-    y_offset: f64
-) {
-    CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
-}
+            // 1. capture the scene around the reflective cube into its environment map
+            // ------------------------------------------------------------------------
+            environment.capture(glm::vec3(0.0, 0.0, 0.0), |projection, view| draw_scene(shader, skybox_shader, projection, view, time));
 
-// utility function for loading a 2D texture from file
-// ---------------------------------------------------
-fn load_texture(path: String) -> u32 {
-    let mut texture_id = 0u32;
-    unsafe {
-        gl::GenTextures(1, &mut texture_id);
-
-        let img = util::image::load_image_data_rgba_without_flip(path)
-            .expect("Failed to load texture data.");
-        let width = img.width();
-        let height = img.height();
-        let data = img.as_raw();
-
-        gl::BindTexture(gl::TEXTURE_2D, texture_id);
-        gl::TexImage2D(
-            gl::TEXTURE_2D,
-            0,
-            gl::RGBA as GLint,
-            width as GLint,
-            height as GLint,
-            0,
-            gl::RGBA,
-            gl::UNSIGNED_BYTE,
-            data.as_ptr() as *const _
-        );
-        gl::GenerateMipmap(gl::TEXTURE_2D);
-
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-    }
+            // 2. render the scene as normal
+            // -----------------------------
+            gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
-    texture_id
-}
+            let camera = CAMERA.lock().unwrap();
+            let view = camera.get_view_matrix();
+            let projection = projection_cache.matrix(camera.zoom());
+            matrices.set_view(&view);
+            matrices.set_projection(&projection);
 
-// loads a cubemap texture from 6 individual texture faces
-// order:
-// +X (right)
-// -X (left)
-// +Y (top)
-// -Y (bottom)
-// +Z (front)
-// -Z (back)
-// -------------------------------------------------------
-fn load_cubemap(faces: &Vec<String>) -> u32 {
-    let mut texture_id = 0u32;
-    unsafe {
-        gl::GenTextures(1, &mut texture_id);
-        gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
-
-        for (i, face) in faces.iter().enumerate() {
-            let img = util::image::load_image_data_rgb_without_flip(face.clone())
-                .expect("Failed to load texture data.");
-            let width = img.width();
-            let height = img.height();
-            let data = img.as_raw();
-
-            gl::TexImage2D(
-                gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
-                0,
-                gl::RGB as GLint,
-                width as GLint,
-                height as GLint,
-                0,
-                gl::RGB,
-                gl::UNSIGNED_BYTE,
-                data.as_ptr() as *const _
-            );
+            // reflective cube, drawn before the skybox
+            reflection_shader.use_shader();
+            reflection_shader.set_mat4("model".to_string(), &util::glm::diag_mat4_nums(0.5, 0.5, 0.5, 1.0));
+            reflection_shader.set_vec3("cameraPos".to_string(), &camera.position());
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, environment.texture());
+            primitives::render_cube();
+
+            draw_scene(shader, skybox_shader, &projection, &view, time);
+        };
+        let shaders = (shader, skybox_shader, reflection_shader);
+        if headless {
+            let pixels = app.render_headless(1, |delta_time| render(&shaders, delta_time));
+            println!("{:016x}", util::image::hash_pixels(&pixels));
+        } else {
+            app.run_with(shaders, |shaders, delta_time| render(shaders, delta_time));
         }
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as GLint);
     }
-
-    texture_id
 }
\ No newline at end of file
@@ -43,6 +43,9 @@ static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
 
 fn main() {
+    // per-frame diagnostics are logged at debug level, run with RUST_LOG=debug to see them
+    util::log::init();
+
     // glfw: initialize and configure
     // ------------------------------
     let mut glfw = glfw::init(glfw::fail_on_errors)
@@ -158,7 +161,7 @@ fn main() {
             gl::BindTexture(gl::TEXTURE_2D, floor_texture);
             gl::DrawArrays(gl::TRIANGLES, 0, 6);
 
-            println!("{}", if BLINN { "Blinn-Phong" } else { "Phong" });
+            log::debug!("{}", if BLINN { "Blinn-Phong" } else { "Phong" });
 
             // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
             // -------------------------------------------------------------------------------
@@ -29,6 +29,7 @@ uniform vec3 lightPositions[4];
 uniform vec3 lightColors[4];
 uniform vec3 viewPos;
 uniform bool gamma;
+uniform bool srgbFramebuffer; // the framebuffer applies gamma correction on write
 
 vec3 BlinnPhong(vec3 normal, vec3 fragPos, vec3 lightPos, vec3 lightColor)
 {
@@ -61,7 +62,7 @@ void main()
     for(int i = 0; i < 4; ++i)
         lighting += BlinnPhong(normalize(fs_in.Normal), fs_in.FragPos, lightPositions[i], lightColors[i]);
     color *= lighting;
-    if(gamma)
+    if(gamma && !srgbFramebuffer)
         color = pow(color, vec3(1.0/2.2));
     FragColor = vec4(color, 1.0);
 }
\ No newline at end of file
@@ -17,62 +17,53 @@
 extern crate nalgebra_glm as glm;
 
 use std::{mem, ptr};
+use std::cell::Cell;
 use std::ffi::CString;
+use std::rc::Rc;
 use std::sync::Mutex;
 use gl::types::*;
-use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
-use learnopengl_shared::{filesystem, util};
+use glfw::{Action, Key};
+use learnopengl_shared::{debug, filesystem, util};
 use learnopengl_shared::shader_m::Shader;
 use lazy_static::lazy_static;
-use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::app::App;
+use learnopengl_shared::camera::Camera;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
-static mut GAMMA_ENABLED: bool = false;
-static mut GAMMA_KEY_PRESSED: bool = false;
 
 // camera
 lazy_static! {
     static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
 }
-static mut LAST_X: f32 = 800.0 / 2.0;
-static mut LAST_Y: f32 = 600.0 / 2.0;
-static mut FIRST_MOUSE: bool = false;
-
-// timing
-static mut DELTA_TIME: f32 = 0.0;
-static mut LAST_FRAME: f32 = 0.0;
 
 fn main() {
-    // glfw: initialize and configure
-    // ------------------------------
-    let mut glfw = glfw::init(glfw::fail_on_errors)
-        .expect("Failed to initialise GLFW.");
-
-    glfw.window_hint(WindowHint::ContextVersionMajor(3));
-    glfw.window_hint(WindowHint::ContextVersionMinor(3));
-    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
-    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
-
-    // glfw window creation
-    // --------------------
-    let (mut window, _) = glfw.create_window(
-        SCR_WIDTH, SCR_HEIGHT,
-        "LearnOpenGL", glfw::WindowMode::Windowed)
-        .expect("Failed to create GLFW window.");
-    window.set_framebuffer_size_callback(framebuffer_size_callback);
-    window.set_cursor_pos_callback(mouse_callback);
-    window.set_scroll_callback(scroll_callback);
-
-    window.set_key_polling(true);
-    window.make_current();
-
-    // tell GLFW to capture our mouse
-    window.set_cursor_mode(CursorMode::Disabled);
-
-    // load all OpenGL function pointers
-    // ---------------------------------
-    gl::load_with(|s| window.get_proc_address(s) as *const _);
+    // per-frame diagnostics are logged at debug level, run with RUST_LOG=debug to see them
+    util::log::init();
+
+    // glfw: initialize, create the window and load all OpenGL function pointers
+    // -------------------------------------------------------------------------
+    let mut app = App::new(SCR_WIDTH, SCR_HEIGHT, "LearnOpenGL");
+    app.set_camera(&CAMERA);
+
+    // space toggles gamma correction, G switches between letting the sRGB framebuffer apply it on
+    // write and doing it manually with pow(color, 1.0 / 2.2) at the end of the fragment shader.
+    // P prints the color that ends up on screen under the crosshair, to compare both ways at a point
+    let gamma_enabled = Rc::new(Cell::new(false));
+    let srgb_framebuffer = Rc::new(Cell::new(true));
+    let sample_requested = Rc::new(Cell::new(false));
+    let (gamma_toggle, srgb_toggle, sample_request) = (Rc::clone(&gamma_enabled), Rc::clone(&srgb_framebuffer), Rc::clone(&sample_requested));
+    app.window.set_key_callback(move |_, key, _, action, _| {
+        if action != Action::Press {
+            return;
+        }
+        match key {
+            Key::Space => gamma_toggle.set(!gamma_toggle.get()),
+            Key::G => srgb_toggle.set(!srgb_toggle.get()),
+            Key::P => sample_request.set(true),
+            _ => {}
+        }
+    });
 
     unsafe {
         // configure global opengl state
@@ -137,18 +128,14 @@ fn main() {
             util::glm::scale_vec3(1.00),
         ];
 
+        // the cursor is captured, so the crosshair is at the center of the window
+        let viewport = app.viewport();
+
         // render loop
         // -----------
-        while !window.should_close() {
-            // per-frame time logic
-            // --------------------
-            let current_frame = glfw.get_time() as f32;
-            DELTA_TIME = current_frame - LAST_FRAME;
-            LAST_FRAME = current_frame;
-
-            // input
-            // -----
-            process_input(&mut window);
+        app.run_with(shader, |shader, _| {
+            let gamma = gamma_enabled.get();
+            App::set_srgb_framebuffer(gamma && srgb_framebuffer.get());
 
             // render
             // ------
@@ -167,20 +154,24 @@ fn main() {
             let c_str = CString::new("lightColors").unwrap();
             gl::Uniform3fv(gl::GetUniformLocation(shader.id(), c_str.as_ptr()), 4, ptr::addr_of!(light_colors) as _);
             shader.set_vec3("viewPos".to_string(), &CAMERA.lock().unwrap().position());
-            shader.set_int("gamma".to_string(), if GAMMA_ENABLED { 1 } else { 0 });
+            shader.set_int("gamma".to_string(), if gamma { 1 } else { 0 });
+            shader.set_int("srgbFramebuffer".to_string(), if srgb_framebuffer.get() { 1 } else { 0 });
             // floor
             gl::BindVertexArray(plane_vao);
             gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, if GAMMA_ENABLED { floor_texture_gamma_corrected } else { floor_texture });
+            gl::BindTexture(gl::TEXTURE_2D, if gamma { floor_texture_gamma_corrected } else { floor_texture });
             gl::DrawArrays(gl::TRIANGLES, 0, 6);
 
-            println!("{}", if GAMMA_ENABLED { "Gamma enabled" } else { "Gamma disabled" });
-
-            // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
-            // -------------------------------------------------------------------------------
-            window.swap_buffers();
-            glfw.poll_events();
-        }
+            if sample_requested.replace(false) {
+                let pixel = debug::sample_pixel(viewport.width() as i32 / 2, viewport.height() as i32 / 2);
+                println!("Color at the center: ({:.3}, {:.3}, {:.3})", pixel[0], pixel[1], pixel[2]);
+            }
+            if gamma {
+                log::debug!("Gamma enabled ({})", if srgb_framebuffer.get() { "sRGB framebuffer" } else { "shader" });
+            } else {
+                log::debug!("Gamma disabled");
+            }
+        });
 
         // optional: de-allocate all resources once they've outlived their purpose:
         // ------------------------------------------------------------------------
@@ -189,85 +180,6 @@ fn main() {
     }
 }
 
-fn process_input(window: &mut Window) {
-    if window.get_key(Key::Escape) == Action::Press {
-        window.set_should_close(true)
-    }
-
-    if window.get_key(Key::W) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::FORWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::S) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::BACKWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::A) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::LEFT, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::D) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
-        }
-    }
-
-    unsafe {
-        if window.get_key(Key::Space) == Action::Press && !GAMMA_KEY_PRESSED {
-            GAMMA_ENABLED = !GAMMA_ENABLED;
-            GAMMA_KEY_PRESSED = true;
-        }
-        if window.get_key(Key::Space) == Action::Release {
-            GAMMA_KEY_PRESSED = false;
-        }
-    }
-}
-
-fn framebuffer_size_callback(
-    _: &mut Window,
-    width: i32,
-    height: i32
-) {
-    unsafe {
-        gl::Viewport(0, 0, width, height);
-    }
-}
-
-fn mouse_callback(
-    _: &mut Window,
-    x_pos_in: f64,
-    y_pos_in: f64
-) {
-    let x_pos = x_pos_in as f32;
-    let y_pos = y_pos_in as f32;
-
-    unsafe {
-        if FIRST_MOUSE {
-            LAST_X = x_pos;
-            LAST_Y = y_pos;
-            FIRST_MOUSE = false;
-        }
-
-        let x_offset = x_pos - LAST_X;
-        let y_offset = LAST_Y - y_pos; // reversed since y-coordinates go from bottom to top
-        LAST_X = x_pos;
-        LAST_Y = y_pos;
-
-        CAMERA.lock().unwrap().process_mouse_movement(x_offset, y_offset);
-    }
-}
-
-fn scroll_callback(
-    _: &mut Window,
-    _x_offset: f64,
-    y_offset: f64
-) {
-    CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
-}
-
 // utility function for loading a 2D texture from file
 // ---------------------------------------------------
 fn load_texture(path: String, gamma_correction: bool) -> u32 {
@@ -17,34 +17,22 @@
 extern crate nalgebra_glm as glm;
 
 use std::{mem, ptr};
-use std::sync::Mutex;
 use gl::types::*;
-use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
-use learnopengl_shared::{filesystem, util};
+use glfw::{Action, Context, Key, OpenGlProfileHint, Window, WindowHint};
+use learnopengl_shared::{filesystem, primitives, util};
 use learnopengl_shared::shader::Shader;
-use lazy_static::lazy_static;
-use learnopengl_shared::camera::{Camera, Movement};
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
 static mut GAMMA_ENABLED: bool = false;
 static mut GAMMA_KEY_PRESSED: bool = false;
 
-// camera
-lazy_static! {
-    static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
-}
-static mut LAST_X: f32 = 800.0 / 2.0;
-static mut LAST_Y: f32 = 600.0 / 2.0;
-static mut FIRST_MOUSE: bool = false;
+// no camera: the depth map is shown as a screen filling quad, so there's nothing to look around in
 
 // timing
 static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
 
-// meshes
-static mut PLANE_VAO: u32 = 0;
-
 fn main() {
     // glfw: initialize and configure
     // ------------------------------
@@ -63,15 +51,10 @@ fn main() {
         "LearnOpenGL", glfw::WindowMode::Windowed)
         .expect("Failed to create GLFW window.");
     window.set_framebuffer_size_callback(framebuffer_size_callback);
-    window.set_cursor_pos_callback(mouse_callback);
-    window.set_scroll_callback(scroll_callback);
 
     window.set_key_polling(true);
     window.make_current();
 
-    // tell GLFW to capture our mouse
-    window.set_cursor_mode(CursorMode::Disabled);
-
     // load all OpenGL function pointers
     // ---------------------------------
     gl::load_with(|s| window.get_proc_address(s) as *const _);
@@ -86,33 +69,6 @@ fn main() {
         let simple_depth_shader = Shader::new("3.1.1.shadow_mapping_depth.vs".to_string(), "3.1.1.shadow_mapping_depth.fs".to_string(), None);
         let debug_depth_quad = Shader::new("3.1.1.debug_quad.vs".to_string(), "3.1.1.debug_quad_depth.fs".to_string(), None);
 
-        // set up vertex data (and buffer(s)) and configure vertex attributes
-        // ------------------------------------------------------------------
-        let plane_vertices = [
-            // positions            // normals         // texcoords
-            25.0f32, -0.5,  25.0,  0.0, 1.0, 0.0,  25.0,  0.0,
-            -25.0, -0.5,  25.0,  0.0, 1.0, 0.0,   0.0,  0.0,
-            -25.0, -0.5, -25.0,  0.0, 1.0, 0.0,   0.0, 25.0,
-
-            25.0, -0.5,  25.0,  0.0, 1.0, 0.0,  25.0,  0.0,
-            -25.0, -0.5, -25.0,  0.0, 1.0, 0.0,   0.0, 25.0,
-            25.0, -0.5, -25.0,  0.0, 1.0, 0.0,  25.0, 25.0
-        ];
-        // plane VAO
-        let mut plane_vbo = 0u32;
-        gl::GenVertexArrays(1, ptr::addr_of_mut!(PLANE_VAO));
-        gl::GenBuffers(1, &mut plane_vbo);
-        gl::BindVertexArray(PLANE_VAO);
-        gl::BindBuffer(gl::ARRAY_BUFFER, plane_vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&plane_vertices) as _, ptr::addr_of!(plane_vertices) as _, gl::STATIC_DRAW);
-        gl::EnableVertexAttribArray(0);
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-        gl::EnableVertexAttribArray(1);
-        gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-        gl::EnableVertexAttribArray(2);
-        gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-        gl::BindVertexArray(0);
-
         // load textures
         // -------------
         let wood_texture = load_texture(filesystem::get_path("resources/textures/wood.png".to_string()), false);
@@ -202,11 +158,6 @@ fn main() {
             window.swap_buffers();
             glfw.poll_events();
         }
-
-        // optional: de-allocate all resources once they've outlived their purpose:
-        // ------------------------------------------------------------------------
-        gl::DeleteVertexArrays(1, ptr::addr_of!(PLANE_VAO));
-        gl::DeleteBuffers(1, &plane_vbo);
     }
 }
 
@@ -216,102 +167,24 @@ fn render_scene(shader: &Shader) {
     // floor
     let model = util::glm::diag_mat4(1.0);
     shader.set_mat4("model".to_string(), &model);
-    unsafe {
-        gl::BindVertexArray(PLANE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 6);
-    }
+    primitives::render_plane(25.0, 25.0);
     // cubes
     let mut model = util::glm::diag_mat4(1.0);
     model = glm::translate(&model, &glm::vec3(0.0, 1.5, 0.0));
     model = glm::scale(&model, &util::glm::scale_vec3(0.5));
     shader.set_mat4("model".to_string(), &model);
-    render_cube();
+    primitives::render_cube();
     let mut model = util::glm::diag_mat4(1.0);
     model = glm::translate(&model, &glm::vec3(2.0, 0.0, 1.0));
     model = glm::scale(&model, &util::glm::scale_vec3(0.5));
     shader.set_mat4("model".to_string(), &model);
-    render_cube();
+    primitives::render_cube();
     let mut model = util::glm::diag_mat4(1.0);
     model = glm::translate(&model, &glm::vec3(-1.0, 0.0, 2.0));
     model = glm::rotate(&model, 60f32.to_radians(), &glm::normalize(&glm::vec3(1.0, 0.0, 1.0)));
     model = glm::scale(&model, &util::glm::scale_vec3(0.25));
     shader.set_mat4("model".to_string(), &model);
-    render_cube();
-}
-
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right         
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right         
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left     
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right     
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
+    primitives::render_cube();
 }
 
 // renderQuad() renders a 1x1 XY quad in NDC
@@ -350,27 +223,6 @@ fn process_input(window: &mut Window) {
         window.set_should_close(true)
     }
 
-    if window.get_key(Key::W) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::FORWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::S) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::BACKWARD, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::A) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::LEFT, DELTA_TIME);
-        }
-    }
-    if window.get_key(Key::D) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
-        }
-    }
-
     unsafe {
         if window.get_key(Key::Space) == Action::Press && !GAMMA_KEY_PRESSED {
             GAMMA_ENABLED = !GAMMA_ENABLED;
@@ -392,38 +244,6 @@ fn framebuffer_size_callback(
     }
 }
 
-fn mouse_callback(
-    _: &mut Window,
-    x_pos_in: f64,
-    y_pos_in: f64
-) {
-    let x_pos = x_pos_in as f32;
-    let y_pos = y_pos_in as f32;
-
-    unsafe {
-        if FIRST_MOUSE {
-            LAST_X = x_pos;
-            LAST_Y = y_pos;
-            FIRST_MOUSE = false;
-        }
-
-        let x_offset = x_pos - LAST_X;
-        let y_offset = LAST_Y - y_pos; // reversed since y-coordinates go from bottom to top
-        LAST_X = x_pos;
-        LAST_Y = y_pos;
-
-        CAMERA.lock().unwrap().process_mouse_movement(x_offset, y_offset);
-    }
-}
-
-fn scroll_callback(
-    _: &mut Window,
-    _x_offset: f64,
-    y_offset: f64
-) {
-    CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
-}
-
 // utility function for loading a 2D texture from file
 // ---------------------------------------------------
 fn load_texture(path: String, gamma_correction: bool) -> u32 {
@@ -20,8 +20,11 @@ use std::{mem, ptr};
 use std::sync::Mutex;
 use gl::types::*;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
-use learnopengl_shared::{filesystem, util};
+use learnopengl_shared::{filesystem, primitives, util};
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::shadows::ShadowParams;
+use learnopengl_shared::primitives::render_cube;
+use learnopengl_shared::util::render::{self, CullMode};
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
 
@@ -42,9 +45,6 @@ static mut FIRST_MOUSE: bool = false;
 static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
 
-// meshes
-static mut PLANE_VAO: u32 = 0;
-
 fn main() {
     // glfw: initialize and configure
     // ------------------------------
@@ -87,33 +87,6 @@ fn main() {
         let simple_depth_shader = Shader::new("3.1.3.shadow_mapping_depth.vs".to_string(), "3.1.3.shadow_mapping_depth.fs".to_string(), None);
         let debug_depth_quad = Shader::new("3.1.3.debug_quad.vs".to_string(), "3.1.3.debug_quad_depth.fs".to_string(), None);
 
-        // set up vertex data (and buffer(s)) and configure vertex attributes
-        // ------------------------------------------------------------------
-        let plane_vertices = [
-            // positions            // normals         // texcoords
-            25.0f32, -0.5,  25.0,  0.0, 1.0, 0.0,  25.0,  0.0,
-            -25.0, -0.5,  25.0,  0.0, 1.0, 0.0,   0.0,  0.0,
-            -25.0, -0.5, -25.0,  0.0, 1.0, 0.0,   0.0, 25.0,
-
-            25.0, -0.5,  25.0,  0.0, 1.0, 0.0,  25.0,  0.0,
-            -25.0, -0.5, -25.0,  0.0, 1.0, 0.0,   0.0, 25.0,
-            25.0, -0.5, -25.0,  0.0, 1.0, 0.0,  25.0, 25.0
-        ];
-        // plane VAO
-        let mut plane_vbo = 0u32;
-        gl::GenVertexArrays(1, ptr::addr_of_mut!(PLANE_VAO));
-        gl::GenBuffers(1, &mut plane_vbo);
-        gl::BindVertexArray(PLANE_VAO);
-        gl::BindBuffer(gl::ARRAY_BUFFER, plane_vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&plane_vertices) as _, ptr::addr_of!(plane_vertices) as _, gl::STATIC_DRAW);
-        gl::EnableVertexAttribArray(0);
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-        gl::EnableVertexAttribArray(1);
-        gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-        gl::EnableVertexAttribArray(2);
-        gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-        gl::BindVertexArray(0);
-
         // load textures
         // -------------
         let wood_texture = load_texture(filesystem::get_path("resources/textures/wood.png".to_string()), false);
@@ -145,6 +118,7 @@ fn main() {
         shader.use_shader();
         shader.set_int("diffuseTexture".to_string(), 0);
         shader.set_int("shadowMap".to_string(), 1);
+        ShadowParams::directional().upload(&shader);
         debug_depth_quad.use_shader();
         debug_depth_quad.set_int("depthMap".to_string(), 0);
 
@@ -185,7 +159,11 @@ fn main() {
             gl::Clear(gl::DEPTH_BUFFER_BIT);
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, wood_texture);
+            // only the back faces go into the depth map, which moves the stored depth away from the lit
+            // surfaces and avoids peter panning
+            render::set_cull(CullMode::Front);
             render_scene(&simple_depth_shader);
+            render::set_cull(CullMode::Back);
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
             // reset viewport
@@ -223,11 +201,6 @@ fn main() {
             window.swap_buffers();
             glfw.poll_events();
         }
-
-        // optional: de-allocate all resources once they've outlived their purpose:
-        // ------------------------------------------------------------------------
-        gl::DeleteVertexArrays(1, ptr::addr_of!(PLANE_VAO));
-        gl::DeleteBuffers(1, &plane_vbo);
     }
 }
 
@@ -237,10 +210,7 @@ fn render_scene(shader: &Shader) {
     // floor
     let model = util::glm::diag_mat4(1.0);
     shader.set_mat4("model".to_string(), &model);
-    unsafe {
-        gl::BindVertexArray(PLANE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 6);
-    }
+    primitives::render_plane(25.0, 25.0);
     // cubes
     let mut model = util::glm::diag_mat4(1.0);
     model = glm::translate(&model, &glm::vec3(0.0, 1.5, 0.0));
@@ -260,81 +230,6 @@ fn render_scene(shader: &Shader) {
     render_cube();
 }
 
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
-}
-
 // renderQuad() renders a 1x1 XY quad in NDC
 // -----------------------------------------
 #[allow(dead_code)]
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+in VS_OUT {
+    vec3 FragPos;
+    vec3 Normal;
+    vec2 TexCoords;
+    float ViewDepth;
+} fs_in;
+
+// see shadows::MAX_CASCADES
+const int MAX_CASCADES = 8;
+
+uniform sampler2D diffuseTexture;
+uniform sampler2DArray shadowMap;
+
+uniform vec3 lightDir;
+uniform vec3 viewPos;
+uniform bool showCascades;
+
+// see shadows::CascadedShadowMap
+uniform int cascadeCount;
+uniform mat4 lightSpaceMatrices[MAX_CASCADES];
+uniform float cascadePlaneDistances[MAX_CASCADES];
+
+// see shadows::ShadowParams
+uniform float shadowBias;
+uniform float shadowNormalBias;
+uniform int pcfRadius;
+
+const vec3 cascadeColors[4] = vec3[](
+    vec3(1.0, 0.3, 0.3),
+    vec3(0.3, 1.0, 0.3),
+    vec3(0.3, 0.3, 1.0),
+    vec3(1.0, 1.0, 0.3)
+);
+
+int CascadeLayer()
+{
+    for (int i = 0; i < cascadeCount; ++i)
+    {
+        if (fs_in.ViewDepth < cascadePlaneDistances[i])
+            return i;
+    }
+    return cascadeCount - 1;
+}
+
+float ShadowCalculation(int layer)
+{
+    vec4 fragPosLightSpace = lightSpaceMatrices[layer] * vec4(fs_in.FragPos, 1.0);
+    // perform perspective divide
+    vec3 projCoords = fragPosLightSpace.xyz / fragPosLightSpace.w;
+    // transform to [0,1] range
+    projCoords = projCoords * 0.5 + 0.5;
+    // get depth of current fragment from light's perspective
+    float currentDepth = projCoords.z;
+    // keep the shadow at 0.0 when outside the far_plane region of the light's frustum.
+    if (currentDepth > 1.0)
+        return 0.0;
+    // calculate bias (based on depth map resolution and slope), the farther cascades cover more of the
+    // scene per texel and need less bias in depth units
+    vec3 normal = normalize(fs_in.Normal);
+    float bias = max(shadowNormalBias * (1.0 - dot(normal, lightDir)), shadowBias);
+    bias *= 1.0 / (cascadePlaneDistances[layer] * 0.5);
+    // PCF
+    float shadow = 0.0;
+    vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0));
+    for(int x = -pcfRadius; x <= pcfRadius; ++x)
+    {
+        for(int y = -pcfRadius; y <= pcfRadius; ++y)
+        {
+            float pcfDepth = texture(shadowMap, vec3(projCoords.xy + vec2(x, y) * texelSize, layer)).r;
+            shadow += currentDepth - bias > pcfDepth ? 1.0 : 0.0;
+        }
+    }
+    shadow /= float((2 * pcfRadius + 1) * (2 * pcfRadius + 1));
+
+    return shadow;
+}
+
+void main()
+{
+    vec3 color = texture(diffuseTexture, fs_in.TexCoords).rgb;
+    vec3 normal = normalize(fs_in.Normal);
+    vec3 lightColor = vec3(0.3);
+    // ambient
+    vec3 ambient = 0.3 * lightColor;
+    // diffuse
+    float diff = max(dot(lightDir, normal), 0.0);
+    vec3 diffuse = diff * lightColor;
+    // specular
+    vec3 viewDir = normalize(viewPos - fs_in.FragPos);
+    vec3 halfwayDir = normalize(lightDir + viewDir);
+    float spec = pow(max(dot(normal, halfwayDir), 0.0), 64.0);
+    vec3 specular = spec * lightColor;
+    // calculate shadow
+    int layer = CascadeLayer();
+    float shadow = ShadowCalculation(layer);
+    vec3 lighting = (ambient + (1.0 - shadow) * (diffuse + specular)) * color;
+    if (showCascades)
+        lighting *= cascadeColors[layer % 4];
+
+    FragColor = vec4(lighting, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::sync::Mutex;
+use gl::types::*;
+use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
+use learnopengl_shared::{filesystem, primitives, util};
+use learnopengl_shared::shader::Shader;
+use learnopengl_shared::shadows::{CascadedShadowMap, ShadowParams};
+use learnopengl_shared::primitives::render_cube;
+use learnopengl_shared::util::render::{self, CullMode};
+use lazy_static::lazy_static;
+use learnopengl_shared::camera::{Camera, Movement};
+
+const SCR_WIDTH: u32 = 800;
+const SCR_HEIGHT: u32 = 600;
+const NEAR_PLANE: f32 = 0.1;
+const FAR_PLANE: f32 = 60.0;
+const CASCADE_COUNT: usize = 4;
+static mut SHOW_CASCADES: bool = false;
+static mut SHOW_CASCADES_KEY_PRESSED: bool = false;
+
+// camera
+lazy_static! {
+    static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
+}
+static mut LAST_X: f32 = 800.0 / 2.0;
+static mut LAST_Y: f32 = 600.0 / 2.0;
+static mut FIRST_MOUSE: bool = false;
+
+// timing
+static mut DELTA_TIME: f32 = 0.0;
+static mut LAST_FRAME: f32 = 0.0;
+
+fn main() {
+    // glfw: initialize and configure
+    // ------------------------------
+    let mut glfw = glfw::init(glfw::fail_on_errors)
+        .expect("Failed to initialise GLFW.");
+
+    glfw.window_hint(WindowHint::ContextVersionMajor(3));
+    glfw.window_hint(WindowHint::ContextVersionMinor(3));
+    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+
+    // glfw window creation
+    // --------------------
+    let (mut window, _) = glfw.create_window(
+        SCR_WIDTH, SCR_HEIGHT,
+        "LearnOpenGL", glfw::WindowMode::Windowed)
+        .expect("Failed to create GLFW window.");
+    window.set_framebuffer_size_callback(framebuffer_size_callback);
+    window.set_cursor_pos_callback(mouse_callback);
+    window.set_scroll_callback(scroll_callback);
+
+    window.set_key_polling(true);
+    window.make_current();
+
+    // tell GLFW to capture our mouse
+    window.set_cursor_mode(CursorMode::Disabled);
+
+    // load all OpenGL function pointers
+    // ---------------------------------
+    gl::load_with(|s| window.get_proc_address(s) as *const _);
+
+    unsafe {
+        // configure global opengl state
+        // -----------------------------
+        gl::Enable(gl::DEPTH_TEST);
+
+        // build and compile shaders
+        // -------------------------
+        let shader = Shader::new("3.1.4.csm.vs".to_string(), "3.1.4.csm.fs".to_string(), None);
+        let simple_depth_shader = Shader::new("3.1.4.csm_depth.vs".to_string(), "3.1.4.csm_depth.fs".to_string(), None);
+
+        // load textures
+        // -------------
+        let wood_texture = load_texture(filesystem::get_path("resources/textures/wood.png".to_string()), false);
+
+        // configure the cascades, one 2048x2048 layer each
+        // ------------------------------------------------
+        let mut shadow_map = CascadedShadowMap::new(CASCADE_COUNT, 2048);
+
+        // shader configuration
+        // --------------------
+        shader.use_shader();
+        shader.set_int("diffuseTexture".to_string(), 0);
+        shader.set_int("shadowMap".to_string(), 1);
+        ShadowParams::directional().upload(&shader);
+
+        // lighting info
+        // -------------
+        // the direction the light shines in
+        let light_dir = glm::normalize(&glm::vec3(2.0f32, -4.0, 1.0));
+
+        // render loop
+        // -----------
+        while !window.should_close() {
+            // per-frame time logic
+            // --------------------
+            let current_frame = glfw.get_time() as f32;
+            DELTA_TIME = current_frame - LAST_FRAME;
+            LAST_FRAME = current_frame;
+
+            // input
+            // -----
+            process_input(&mut window);
+
+            // render
+            // ------
+            gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+            let projection = glm::perspective((SCR_WIDTH as f32) / (SCR_HEIGHT as f32), CAMERA.lock().unwrap().zoom().to_radians(), NEAR_PLANE, FAR_PLANE);
+            let view = CAMERA.lock().unwrap().get_view_matrix();
+
+            // 1. render depth of scene to the cascades (from light's perspective)
+            // -------------------------------------------------------------------
+            shadow_map.update(&projection, &view, NEAR_PLANE, FAR_PLANE, &light_dir);
+            simple_depth_shader.use_shader();
+            // only the back faces go into the depth map, which moves the stored depth away from the lit
+            // surfaces and avoids peter panning
+            render::set_cull(CullMode::Front);
+            shadow_map.render(|light_space_matrix| {
+                simple_depth_shader.set_mat4("lightSpaceMatrix".to_string(), light_space_matrix);
+                render_scene(&simple_depth_shader);
+            });
+            render::set_cull(CullMode::Back);
+
+            // 2. render scene as normal using the generated cascades
+            // ------------------------------------------------------
+            shader.use_shader();
+            shader.set_mat4("projection".to_string(), &projection);
+            shader.set_mat4("view".to_string(), &view);
+            // set light uniforms
+            shader.set_vec3("viewPos".to_string(), &CAMERA.lock().unwrap().position());
+            shader.set_vec3("lightDir".to_string(), &-light_dir);
+            shader.set_bool("showCascades".to_string(), SHOW_CASCADES);
+            shadow_map.upload(&shader);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, wood_texture);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, shadow_map.depth_texture());
+            render_scene(&shader);
+
+            // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
+            // -------------------------------------------------------------------------------
+            window.swap_buffers();
+            glfw.poll_events();
+        }
+    }
+}
+
+// renders the 3D scene
+// --------------------
+fn render_scene(shader: &Shader) {
+    // floor
+    let model = util::glm::diag_mat4(1.0);
+    shader.set_mat4("model".to_string(), &model);
+    primitives::render_plane(25.0, 25.0);
+    // a field of cubes reaching far away from the camera, so every cascade has something to shadow
+    for x in -4..=4 {
+        for z in -4..=4 {
+            let mut model = util::glm::diag_mat4(1.0);
+            model = glm::translate(&model, &glm::vec3(x as f32 * 2.5, 0.0, z as f32 * 2.5));
+            model = glm::rotate(&model, ((x * 7 + z * 13) as f32).to_radians() * 3.0, &glm::vec3(0.0, 1.0, 0.0));
+            model = glm::scale(&model, &util::glm::scale_vec3(0.5));
+            shader.set_mat4("model".to_string(), &model);
+            render_cube();
+        }
+    }
+    let mut model = util::glm::diag_mat4(1.0);
+    model = glm::translate(&model, &glm::vec3(0.0, 1.5, 0.0));
+    model = glm::scale(&model, &util::glm::scale_vec3(0.5));
+    shader.set_mat4("model".to_string(), &model);
+    render_cube();
+}
+
+fn process_input(window: &mut Window) {
+    if window.get_key(Key::Escape) == Action::Press {
+        window.set_should_close(true)
+    }
+
+    if window.get_key(Key::W) == Action::Press {
+        unsafe {
+            CAMERA.lock().unwrap().process_keyboard(Movement::FORWARD, DELTA_TIME);
+        }
+    }
+    if window.get_key(Key::S) == Action::Press {
+        unsafe {
+            CAMERA.lock().unwrap().process_keyboard(Movement::BACKWARD, DELTA_TIME);
+        }
+    }
+    if window.get_key(Key::A) == Action::Press {
+        unsafe {
+            CAMERA.lock().unwrap().process_keyboard(Movement::LEFT, DELTA_TIME);
+        }
+    }
+    if window.get_key(Key::D) == Action::Press {
+        unsafe {
+            CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
+        }
+    }
+
+    unsafe {
+        // tint every cascade in its own color
+        if window.get_key(Key::C) == Action::Press && !SHOW_CASCADES_KEY_PRESSED {
+            SHOW_CASCADES = !SHOW_CASCADES;
+            SHOW_CASCADES_KEY_PRESSED = true;
+        }
+        if window.get_key(Key::C) == Action::Release {
+            SHOW_CASCADES_KEY_PRESSED = false;
+        }
+    }
+}
+
+fn framebuffer_size_callback(
+    _: &mut Window,
+    width: i32,
+    height: i32
+) {
+    unsafe {
+        gl::Viewport(0, 0, width, height);
+    }
+}
+
+fn mouse_callback(
+    _: &mut Window,
+    x_pos_in: f64,
+    y_pos_in: f64
+) {
+    let x_pos = x_pos_in as f32;
+    let y_pos = y_pos_in as f32;
+
+    unsafe {
+        if FIRST_MOUSE {
+            LAST_X = x_pos;
+            LAST_Y = y_pos;
+            FIRST_MOUSE = false;
+        }
+
+        let x_offset = x_pos - LAST_X;
+        let y_offset = LAST_Y - y_pos; // reversed since y-coordinates go from bottom to top
+        LAST_X = x_pos;
+        LAST_Y = y_pos;
+
+        CAMERA.lock().unwrap().process_mouse_movement(x_offset, y_offset);
+    }
+}
+
+fn scroll_callback(
+    _: &mut Window,
+    _x_offset: f64,
+    y_offset: f64
+) {
+    CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
+}
+
+// utility function for loading a 2D texture from file
+// ---------------------------------------------------
+fn load_texture(path: String, gamma_correction: bool) -> u32 {
+    let mut texture_id = 0u32;
+    unsafe {
+        gl::GenTextures(1, &mut texture_id);
+
+        let img = util::image::load_image_data_rgba(path)
+            .expect("Failed to load texture data.");
+        let width = img.width();
+        let height = img.height();
+        let data = img.as_raw();
+
+        gl::BindTexture(gl::TEXTURE_2D, texture_id);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            if gamma_correction { gl::SRGB_ALPHA } else { gl::RGBA } as _,
+            width as GLint,
+            height as GLint,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data.as_ptr() as *const _
+        );
+        gl::GenerateMipmap(gl::TEXTURE_2D);
+
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+    }
+
+    texture_id
+}
\ No newline at end of file
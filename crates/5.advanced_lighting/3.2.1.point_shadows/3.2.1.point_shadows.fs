@@ -32,21 +32,41 @@ uniform vec3 viewPos;
 uniform float far_plane;
 uniform bool shadows;
 
-float ShadowCalculation(vec3 fragPos)
+// see shadows::ShadowParams
+uniform float shadowBias;
+uniform float shadowNormalBias;
+uniform int pcfRadius;
+
+// distance in world units between the outermost PCF samples and the fragment
+const float PCF_SPREAD = 0.1;
+
+float ShadowCalculation(vec3 fragPos, vec3 normal)
 {
     // get vector between fragment position and light position
     vec3 fragToLight = fragPos - lightPos;
-    // ise the fragment to light vector to sample from the depth map    
-    float closestDepth = texture(depthMap, fragToLight).r;
-    // it is currently in linear range between [0,1], let's re-transform it back to original depth value
-    closestDepth *= far_plane;
     // now get current linear depth as the length between the fragment and light position
     float currentDepth = length(fragToLight);
-    // test for shadows
-    float bias = 0.05; // we use a much larger bias since depth is now in [near_plane, far_plane] range
-    float shadow = currentDepth -  bias > closestDepth ? 1.0 : 0.0;        
-    // display closestDepth as debug (to visualize depth cubemap)
-    // FragColor = vec4(vec3(closestDepth / far_plane), 1.0);    
+    // we use a much larger bias since depth is now in [near_plane, far_plane] range
+    float bias = max(shadowNormalBias * (1.0 - dot(normal, normalize(-fragToLight))), shadowBias);
+    // test for shadows, averaged over a (2 * pcfRadius + 1)^3 grid of samples around the fragment
+    float shadow = 0.0;
+    float offset = PCF_SPREAD / float(max(pcfRadius, 1));
+    for(int x = -pcfRadius; x <= pcfRadius; ++x)
+    {
+        for(int y = -pcfRadius; y <= pcfRadius; ++y)
+        {
+            for(int z = -pcfRadius; z <= pcfRadius; ++z)
+            {
+                // use the fragment to light vector to sample from the depth map
+                float closestDepth = texture(depthMap, fragToLight + vec3(x, y, z) * offset).r;
+                // it is currently in linear range between [0,1], let's re-transform it back to original depth value
+                closestDepth *= far_plane;
+                shadow += currentDepth - bias > closestDepth ? 1.0 : 0.0;
+            }
+        }
+    }
+    int samplesPerAxis = 2 * pcfRadius + 1;
+    shadow /= float(samplesPerAxis * samplesPerAxis * samplesPerAxis);
         
     return shadow;
 }
@@ -70,7 +90,7 @@ void main()
     spec = pow(max(dot(normal, halfwayDir), 0.0), 64.0);
     vec3 specular = spec * lightColor;    
     // calculate shadow
-    float shadow = shadows ? ShadowCalculation(fs_in.FragPos) : 0.0;                      
+    float shadow = shadows ? ShadowCalculation(fs_in.FragPos, normal) : 0.0;                      
     vec3 lighting = (ambient + (1.0 - shadow) * (diffuse + specular)) * color;    
     
     FragColor = vec4(lighting, 1.0);
@@ -22,13 +22,16 @@ use gl::types::*;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::primitives::render_cube;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::input::{InputState, ScalarControl};
+use learnopengl_shared::time::Clock;
+use learnopengl_shared::shadows::ShadowParams;
+use learnopengl_shared::framebuffer::ShadowMapCube;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
-static mut SHADOWS: bool = false;
-static mut SHADOWS_KEY_PRESSED: bool = false;
 
 // camera
 lazy_static! {
@@ -93,27 +96,8 @@ fn main() {
 
         // configure depth map FBO
         // -----------------------
-        const SHADOW_WIDTH: u32 = 1024; const SHADOW_HEIGHT: u32 = 1024;
-        let mut depth_map_fbo = 0u32;
-        gl::GenFramebuffers(1, &mut depth_map_fbo);
-        // create depth cubemap texture
-        let mut depth_cubemap = 0u32;
-        gl::GenTextures(1, &mut depth_cubemap);
-        gl::BindTexture(gl::TEXTURE_CUBE_MAP, depth_cubemap);
-        for i in 0..6 {
-            gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32, 0, gl::DEPTH_COMPONENT as _, SHADOW_WIDTH as _, SHADOW_HEIGHT as _, 0, gl::DEPTH_COMPONENT, gl::FLOAT, ptr::null());
-        }
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
-        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as _);
-        // attach depth texture as FBO's depth buffer
-        gl::BindFramebuffer(gl::FRAMEBUFFER, depth_map_fbo);
-        gl::FramebufferTexture(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, depth_cubemap, 0);
-        gl::DrawBuffer(gl::NONE);
-        gl::ReadBuffer(gl::NONE);
-        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        const SHADOW_SIZE: u32 = 1024;
+        let shadow_map = ShadowMapCube::new(SHADOW_SIZE);
 
         // shader configuration
         // --------------------
@@ -124,6 +108,14 @@ fn main() {
         // lighting info
         // -------------
         let mut light_pos = glm::vec3(0.0, 0.0, 0.0);
+        let mut shadows = false;
+
+        let mut input = InputState::new();
+        let mut clock = Clock::from_glfw(&glfw);
+
+        // Q/E nudge the shadow bias (acne below, peter-panning above the default), R cycles the PCF radius
+        let mut shadow_params = ShadowParams::point();
+        let mut bias_control = ScalarControl::new(shadow_params.bias, 0.0, 1.0, 0.0005);
 
         // render loop
         // -----------
@@ -137,9 +129,23 @@ fn main() {
             // input
             // -----
             process_input(&mut window);
+            input.update(&window);
+            if input.just_pressed(Key::Space) {
+                shadows = !shadows;
+            }
+            shadow_params.bias = bias_control.update(&window, Key::Q, Key::E);
+            if input.just_pressed(Key::R) {
+                shadow_params.pcf_radius = (shadow_params.pcf_radius + 1) % 3;
+                println!("PCF radius: {}", shadow_params.pcf_radius);
+            }
+
+            // P freezes the light to inspect a frame
+            if input.just_pressed(Key::P) {
+                clock.toggle_pause();
+            }
 
             // move light position over time
-            light_pos.z = ((glfw.get_time() * 0.5).sin() * 3.0) as f32;
+            light_pos.z = ((clock.elapsed() * 0.5).sin() * 3.0) as f32;
 
             // render
             // ------
@@ -150,24 +156,14 @@ fn main() {
             // -----------------------------------------------
             let near_plane = 1f32;
             let far_plane = 25f32;
-            let shadow_proj = glm::perspective(90f32.to_radians(), (SHADOW_WIDTH as f32) / (SHADOW_HEIGHT as f32), near_plane, far_plane);
-            let mut shadow_transforms: Vec<glm::TMat4<f32>> = Vec::new();
-            shadow_transforms.push(shadow_proj * glm::look_at(&light_pos, &(light_pos + glm::vec3(1.0, 0.0, 0.0)), &glm::vec3(0.0, -1.0, 0.0)));
-            shadow_transforms.push(shadow_proj * glm::look_at(&light_pos, &(light_pos + glm::vec3(-1.0, 0.0, 0.0)), &glm::vec3(0.0, -1.0, 0.0)));
-            shadow_transforms.push(shadow_proj * glm::look_at(&light_pos, &(light_pos + glm::vec3(0.0, 1.0, 0.0)), &glm::vec3(0.0, 0.0, 1.0)));
-            shadow_transforms.push(shadow_proj * glm::look_at(&light_pos, &(light_pos + glm::vec3(0.0, -1.0, 0.0)), &glm::vec3(0.0, 0.0, -1.0)));
-            shadow_transforms.push(shadow_proj * glm::look_at(&light_pos, &(light_pos + glm::vec3(0.0, 0.0, 1.0)), &glm::vec3(0.0, -1.0, 0.0)));
-            shadow_transforms.push(shadow_proj * glm::look_at(&light_pos, &(light_pos + glm::vec3(0.0, 0.0, -1.0)), &glm::vec3(0.0, -1.0, 0.0)));
+            let shadow_transforms = util::glm::cubemap_light_space_matrices(&light_pos, near_plane, far_plane);
 
             // 1. render scene to depth cubemap
             // --------------------------------
-            gl::Viewport(0, 0, SHADOW_WIDTH as _, SHADOW_HEIGHT as _);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, depth_map_fbo);
+            shadow_map.bind_for_writing();
             gl::Clear(gl::DEPTH_BUFFER_BIT);
             simple_depth_shader.use_shader();
-            for (i, trans) in shadow_transforms.iter().enumerate() {
-                simple_depth_shader.set_mat4(format!("shadowMatrices[{}]", i), trans);
-            }
+            simple_depth_shader.set_mat4_array("shadowMatrices".to_string(), &shadow_transforms);
             simple_depth_shader.set_float("far_plane".to_string(), far_plane);
             simple_depth_shader.set_vec3("lightPos".to_string(), &light_pos);
             render_scene(&simple_depth_shader);
@@ -185,12 +181,13 @@ fn main() {
             // set lighting uniforms
             shader.set_vec3("lightPos".to_string(), &light_pos);
             shader.set_vec3("viewPos".to_string(), &CAMERA.lock().unwrap().position());
-            shader.set_int("shadows".to_string(), if SHADOWS { 1 } else { 0 });
+            shader.set_bool("shadows".to_string(), shadows);
             shader.set_float("far_plane".to_string(), far_plane);
+            shadow_params.upload(&shader);
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, wood_texture);
             gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_CUBE_MAP, depth_cubemap);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, shadow_map.depth_texture());
             render_scene(&shader);
 
             // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
@@ -230,81 +227,6 @@ fn render_scene(shader: &Shader) {
     render_cube();
 }
 
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
-}
-
 // renderQuad() renders a 1x1 XY quad in NDC
 // -----------------------------------------
 #[allow(dead_code)]
@@ -364,16 +286,6 @@ fn process_input(window: &mut Window) {
             CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
         }
     }
-
-    unsafe {
-        if window.get_key(Key::Space) == Action::Press && !SHADOWS_KEY_PRESSED {
-            SHADOWS = !SHADOWS;
-            SHADOWS_KEY_PRESSED = true;
-        }
-        if window.get_key(Key::Space) == Action::Release {
-            SHADOWS_KEY_PRESSED = false;
-        }
-    }
 }
 
 fn framebuffer_size_callback(
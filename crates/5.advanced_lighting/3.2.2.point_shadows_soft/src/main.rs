@@ -16,12 +16,13 @@
 
 extern crate nalgebra_glm as glm;
 
-use std::{mem, ptr};
+use std::ptr;
 use std::sync::Mutex;
 use gl::types::*;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::primitives::render_cube;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
 
@@ -162,9 +163,7 @@ fn main() {
             gl::BindFramebuffer(gl::FRAMEBUFFER, depth_map_fbo);
             gl::Clear(gl::DEPTH_BUFFER_BIT);
             simple_depth_shader.use_shader();
-            for (i, trans) in shadow_transforms.iter().enumerate() {
-                simple_depth_shader.set_mat4(format!("shadowMatrices[{}]", i), trans);
-            }
+            simple_depth_shader.set_mat4_array("shadowMatrices".to_string(), &shadow_transforms);
             simple_depth_shader.set_float("far_plane".to_string(), far_plane);
             simple_depth_shader.set_vec3("lightPos".to_string(), &light_pos);
             render_scene(&simple_depth_shader);
@@ -182,7 +181,7 @@ fn main() {
             // set lighting uniforms
             shader.set_vec3("lightPos".to_string(), &light_pos);
             shader.set_vec3("viewPos".to_string(), &CAMERA.lock().unwrap().position());
-            shader.set_int("shadows".to_string(), if SHADOWS { 1 } else { 0 });
+            shader.set_bool("shadows".to_string(), SHADOWS);
             shader.set_float("far_plane".to_string(), far_plane);
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, wood_texture);
@@ -208,9 +207,9 @@ fn render_scene(shader: &Shader) {
     unsafe {
         gl::Disable(gl::CULL_FACE); // note that we disable culling here since we render 'inside' the cube instead of the usual 'outside' which throws off the normal culling methods.
     }
-    shader.set_int("reverse_normals".to_string(), 1); // A small little hack to invert normals when drawing cube from the inside so lighting still works.
+    shader.set_bool("reverse_normals".to_string(), true); // A small little hack to invert normals when drawing cube from the inside so lighting still works.
     render_cube();
-    shader.set_int("reverse_normals".to_string(), 0); // and of course disable it
+    shader.set_bool("reverse_normals".to_string(), false); // and of course disable it
     unsafe {
         gl::Enable(gl::CULL_FACE);
     }
@@ -243,81 +242,6 @@ fn render_scene(shader: &Shader) {
     render_cube();
 }
 
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
-}
-
 fn process_input(window: &mut Window) {
     if window.get_key(Key::Escape) == Action::Press {
         window.set_should_close(true)
@@ -30,13 +30,70 @@ uniform sampler2D normalMap;
 uniform sampler2D depthMap;
 
 uniform float heightScale;
+// 0: parallax mapping, 1: steep parallax mapping, 2: parallax occlusion mapping (see ParallaxMode)
+uniform int parallaxMode;
 
-vec2 ParallaxMapping(vec2 texCoords, vec3 viewDir)
+vec2 SimpleParallaxMapping(vec2 texCoords, vec3 viewDir)
 { 
     float height =  texture(depthMap, texCoords).r;     
     return texCoords - viewDir.xy * (height * heightScale);        
 }
 
+vec2 LayeredParallaxMapping(vec2 texCoords, vec3 viewDir, bool occlusion)
+{ 
+    // number of depth layers
+    const float minLayers = 8;
+    const float maxLayers = 32;
+    float numLayers = mix(maxLayers, minLayers, abs(dot(vec3(0.0, 0.0, 1.0), viewDir)));  
+    // calculate the size of each layer
+    float layerDepth = 1.0 / numLayers;
+    // depth of current layer
+    float currentLayerDepth = 0.0;
+    // the amount to shift the texture coordinates per layer (from vector P)
+    vec2 P = viewDir.xy / viewDir.z * heightScale; 
+    vec2 deltaTexCoords = P / numLayers;
+  
+    // get initial values
+    vec2  currentTexCoords     = texCoords;
+    float currentDepthMapValue = texture(depthMap, currentTexCoords).r;
+      
+    while(currentLayerDepth < currentDepthMapValue)
+    {
+        // shift texture coordinates along direction of P
+        currentTexCoords -= deltaTexCoords;
+        // get depthmap value at current texture coordinates
+        currentDepthMapValue = texture(depthMap, currentTexCoords).r;  
+        // get depth of next layer
+        currentLayerDepth += layerDepth;  
+    }
+
+    // steep parallax mapping stops at the first layer below the surface
+    if(!occlusion)
+        return currentTexCoords;
+
+    // get texture coordinates before collision (reverse operations)
+    vec2 prevTexCoords = currentTexCoords + deltaTexCoords;
+
+    // get depth after and before collision for linear interpolation
+    float afterDepth  = currentDepthMapValue - currentLayerDepth;
+    float beforeDepth = texture(depthMap, prevTexCoords).r - currentLayerDepth + layerDepth;
+ 
+    // interpolation of texture coordinates
+    float weight = afterDepth / (afterDepth - beforeDepth);
+    vec2 finalTexCoords = prevTexCoords * weight + currentTexCoords * (1.0 - weight);
+
+    return finalTexCoords;
+}
+
+vec2 ParallaxMapping(vec2 texCoords, vec3 viewDir)
+{ 
+    if(parallaxMode == 1)
+        return LayeredParallaxMapping(texCoords, viewDir, false);
+    if(parallaxMode == 2)
+        return LayeredParallaxMapping(texCoords, viewDir, true);
+    return SimpleParallaxMapping(texCoords, viewDir);
+}
+
 void main()
 {           
     // offset texture coordinates with Parallax Mapping
@@ -20,13 +20,17 @@ use std::{mem, ptr};
 use std::sync::Mutex;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
+use learnopengl_shared::util::image::TextureOptions;
+use learnopengl_shared::util::render::ParallaxMode;
 use learnopengl_shared::shader::Shader;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::input::ScalarControl;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
-static mut HEIGHT_SCALE: f32 = 0.1;
+// 1, 2 and 3 switch between parallax mapping, steep parallax mapping and parallax occlusion mapping
+static mut PARALLAX_MODE: ParallaxMode = ParallaxMode::Simple;
 
 // camera
 lazy_static! {
@@ -41,6 +45,9 @@ static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
 
 fn main() {
+    // per-frame diagnostics are logged at debug level, run with RUST_LOG=debug to see them
+    util::log::init();
+
     // glfw: initialize and configure
     // ------------------------------
     let mut glfw = glfw::init(glfw::fail_on_errors)
@@ -82,9 +89,9 @@ fn main() {
 
         // load textures
         // -------------
-        let diffuse_map = load_texture(filesystem::get_path("resources/textures/bricks2.jpg".to_string()));
-        let normal_map = load_texture(filesystem::get_path("resources/textures/bricks2_normal.jpg".to_string()));
-        let height_map = load_texture(filesystem::get_path("resources/textures/bricks2_disp.jpg".to_string()));
+        let diffuse_map = util::image::load_texture_2d_with_options(filesystem::get_path("resources/textures/bricks2.jpg".to_string()), false, &TextureOptions::clamp_to_edge());
+        let normal_map = util::image::load_texture_2d_with_options(filesystem::get_path("resources/textures/bricks2_normal.jpg".to_string()), false, &TextureOptions::clamp_to_edge());
+        let height_map = util::image::load_texture_2d_with_options(filesystem::get_path("resources/textures/bricks2_disp.jpg".to_string()), false, &TextureOptions::clamp_to_edge());
 
         // shader configuration
         // --------------------
@@ -97,6 +104,9 @@ fn main() {
         // -------------
         let light_pos = glm::vec3(0.5, 1.0, 0.3);
 
+        // Q and E lower and raise the height scale of the parallax mapping
+        let mut height_scale_control = ScalarControl::new(0.1, 0.0, 1.0, 0.0005);
+
         // render loop
         // -----------
         while !window.should_close() {
@@ -109,6 +119,7 @@ fn main() {
             // input
             // -----
             process_input(&mut window);
+            let height_scale = height_scale_control.update(&window, Key::Q, Key::E);
 
             let camera = CAMERA.lock().unwrap();
 
@@ -127,10 +138,13 @@ fn main() {
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::rotate(&model, (glfw.get_time() as f32 * -10.0).to_radians(), &glm::normalize(&glm::vec3(1.0, 0.0, 1.0))); // rotate the quad to show normal mapping from multiple directions
             shader.set_mat4("model".to_string(), &model);
+            // the inverse-transpose is computed once here instead of for every vertex
+            shader.set_mat3("normalMatrix".to_string(), &util::glm::normal_matrix(&model));
             shader.set_vec3("viewPos".to_string(), &camera.position());
             shader.set_vec3("lightPos".to_string(), &light_pos);
-            shader.set_float("heightScale".to_string(), HEIGHT_SCALE);
-            println!("{}", HEIGHT_SCALE);
+            shader.set_float("heightScale".to_string(), height_scale);
+            PARALLAX_MODE.upload(&shader);
+            log::debug!("height scale: {}, {}", height_scale, PARALLAX_MODE.name());
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, diffuse_map);
             gl::ActiveTexture(gl::TEXTURE1);
@@ -144,6 +158,7 @@ fn main() {
             model = glm::translate(&model, &light_pos);
             model = glm::scale(&model, &util::glm::scale_vec3(0.1));
             shader.set_mat4("model".to_string(), &model);
+            shader.set_mat3("normalMatrix".to_string(), &util::glm::normal_matrix(&model));
             render_quad();
 
             drop(camera);
@@ -273,19 +288,14 @@ fn process_input(window: &mut Window) {
     }
 
     unsafe {
-        if window.get_key(Key::Q) == Action::Press {
-            if HEIGHT_SCALE > 0.0 {
-                HEIGHT_SCALE -= 0.0005;
-            } else {
-                HEIGHT_SCALE = 0.0;
-            }
+        if window.get_key(Key::Num1) == Action::Press {
+            PARALLAX_MODE = ParallaxMode::Simple;
         }
-        if window.get_key(Key::E) == Action::Press {
-            if HEIGHT_SCALE < 1.0 {
-                HEIGHT_SCALE += 0.0005;
-            } else {
-                HEIGHT_SCALE = 1.0;
-            }
+        if window.get_key(Key::Num2) == Action::Press {
+            PARALLAX_MODE = ParallaxMode::Steep;
+        }
+        if window.get_key(Key::Num3) == Action::Press {
+            PARALLAX_MODE = ParallaxMode::Occlusion;
         }
     }
 }
@@ -330,30 +340,4 @@ fn scroll_callback(
     y_offset: f64
 ) {
     CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
-}
-
-// utility function for loading a 2D texture from file
-// ---------------------------------------------------
-fn load_texture(path: String) -> u32 {
-    let mut texture_id = 0u32;
-    unsafe {
-        gl::GenTextures(1, &mut texture_id);
-
-        let img = util::image::load_image_data_rgba(path)
-            .expect("Failed to load texture data.");
-        let width = img.width();
-        let height = img.height();
-        let data = img.as_raw();
-
-        gl::BindTexture(gl::TEXTURE_2D, texture_id);
-        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as _, width as _, height as _, 0, gl::RGBA, gl::UNSIGNED_BYTE, data.as_ptr() as _);
-        gl::GenerateMipmap(gl::TEXTURE_2D);
-
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _); // for this tutorial: use GL_CLAMP_TO_EDGE to prevent semi-transparent borders. Due to interpolation it takes texels from next repeat
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as _);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
-    }
-
-    texture_id
 }
\ No newline at end of file
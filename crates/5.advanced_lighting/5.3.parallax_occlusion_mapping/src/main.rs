@@ -23,10 +23,10 @@ use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader::Shader;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::input::ScalarControl;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
-static mut HEIGHT_SCALE: f32 = 0.1;
 
 // camera
 lazy_static! {
@@ -41,6 +41,9 @@ static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
 
 fn main() {
+    // per-frame diagnostics are logged at debug level, run with RUST_LOG=debug to see them
+    util::log::init();
+
     // glfw: initialize and configure
     // ------------------------------
     let mut glfw = glfw::init(glfw::fail_on_errors)
@@ -97,6 +100,9 @@ fn main() {
         // -------------
         let light_pos = glm::vec3(0.5, 1.0, 0.3);
 
+        // Q and E lower and raise the height scale of the parallax mapping
+        let mut height_scale_control = ScalarControl::new(0.1, 0.0, 1.0, 0.0005);
+
         // render loop
         // -----------
         while !window.should_close() {
@@ -109,6 +115,7 @@ fn main() {
             // input
             // -----
             process_input(&mut window);
+            let height_scale = height_scale_control.update(&window, Key::Q, Key::E);
 
             let camera = CAMERA.lock().unwrap();
 
@@ -129,8 +136,8 @@ fn main() {
             shader.set_mat4("model".to_string(), &model);
             shader.set_vec3("viewPos".to_string(), &camera.position());
             shader.set_vec3("lightPos".to_string(), &light_pos);
-            shader.set_float("heightScale".to_string(), HEIGHT_SCALE);
-            println!("{}", HEIGHT_SCALE);
+            shader.set_float("heightScale".to_string(), height_scale);
+            log::debug!("height scale: {}", height_scale);
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, diffuse_map);
             gl::ActiveTexture(gl::TEXTURE1);
@@ -271,23 +278,6 @@ fn process_input(window: &mut Window) {
             CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
         }
     }
-
-    unsafe {
-        if window.get_key(Key::Q) == Action::Press {
-            if HEIGHT_SCALE > 0.0 {
-                HEIGHT_SCALE -= 0.0005;
-            } else {
-                HEIGHT_SCALE = 0.0;
-            }
-        }
-        if window.get_key(Key::E) == Action::Press {
-            if HEIGHT_SCALE < 1.0 {
-                HEIGHT_SCALE += 0.0005;
-            } else {
-                HEIGHT_SCALE = 1.0;
-            }
-        }
-    }
 }
 
 fn framebuffer_size_callback(
@@ -20,26 +20,12 @@ out vec4 FragColor;
 in vec2 TexCoords;
 
 uniform sampler2D hdrBuffer;
-uniform bool hdr;
-uniform float exposure;
 
 void main()
 {             
+    // without tone mapping everything above 1.0 gets clamped, only gamma correct
     const float gamma = 2.2;
     vec3 hdrColor = texture(hdrBuffer, TexCoords).rgb;
-    if(hdr)
-    {
-        // reinhard
-        // vec3 result = hdrColor / (hdrColor + vec3(1.0));
-        // exposure
-        vec3 result = vec3(1.0) - exp(-hdrColor * exposure);
-        // also gamma correct while we're at it       
-        result = pow(result, vec3(1.0 / gamma));
-        FragColor = vec4(result, 1.0);
-    }
-    else
-    {
-        vec3 result = pow(hdrColor, vec3(1.0 / gamma));
-        FragColor = vec4(result, 1.0);
-    }
+    vec3 result = pow(hdrColor, vec3(1.0 / gamma));
+    FragColor = vec4(result, 1.0);
 }
\ No newline at end of file
@@ -17,18 +17,30 @@
 extern crate nalgebra_glm as glm;
 
 use std::{mem, ptr};
+use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Mutex;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::primitives::render_cube;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::input::ScalarControl;
+use learnopengl_shared::effects::ColorGrade;
+use learnopengl_shared::framebuffer::FramebufferBuilder;
+use learnopengl_shared::text::TextRenderer;
+use learnopengl_shared::time::FrameTimer;
+use learnopengl_shared::util::post::{Operator, Tonemapper};
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
 static mut HDR: bool = true;
 static mut HDR_KEY_PRESSED: bool = false;
-static mut EXPOSURE: f32 = 1.0;
+static mut COLOR_GRADE: bool = false;
+static mut COLOR_GRADE_KEY_PRESSED: bool = false;
+static mut OPERATOR: Operator = Operator::Exposure;
+static mut OPERATOR_KEY_PRESSED: bool = false;
 
 // camera
 lazy_static! {
@@ -38,10 +50,6 @@ static mut LAST_X: f32 = 800.0 / 2.0;
 static mut LAST_Y: f32 = 600.0 / 2.0;
 static mut FIRST_MOUSE: bool = false;
 
-// timing
-static mut DELTA_TIME: f32 = 0.0;
-static mut LAST_FRAME: f32 = 0.0;
-
 fn main() {
     // glfw: initialize and configure
     // ------------------------------
@@ -59,7 +67,16 @@ fn main() {
         SCR_WIDTH, SCR_HEIGHT,
         "LearnOpenGL", glfw::WindowMode::Windowed)
         .expect("Failed to create GLFW window.");
-    window.set_framebuffer_size_callback(framebuffer_size_callback);
+    // the HDR framebuffer follows the window size, the callback hands new sizes over to the render loop
+    let resized = Rc::new(Cell::new(None));
+    let resized_callback = Rc::clone(&resized);
+    window.set_framebuffer_size_callback(move |window, width, height| {
+        framebuffer_size_callback(window, width, height);
+        // a minimized window reports a zero size, keep the old buffer around until it comes back
+        if width > 0 && height > 0 {
+            resized_callback.set(Some((width as u32, height as u32)));
+        }
+    });
     window.set_cursor_pos_callback(mouse_callback);
     window.set_scroll_callback(scroll_callback);
 
@@ -87,30 +104,16 @@ fn main() {
         // -------------
         let wood_texture = load_texture(filesystem::get_path("resources/textures/wood.png".to_string()), true); // note that we're loading the texture as an SRGB texture
 
-        // configure floating point framebuffer
-        // ------------------------------------
-        let mut hdr_fbo = 0u32;
-        gl::GenFramebuffers(1, &mut hdr_fbo);
-        // create floating point color buffer
-        let mut color_buffer = 0u32;
-        gl::GenTextures(1, &mut color_buffer);
-        gl::BindTexture(gl::TEXTURE_2D, color_buffer);
-        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA16F as _, SCR_WIDTH as _, SCR_HEIGHT as _, 0, gl::RGBA, gl::FLOAT, ptr::null());
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
-        // create depth buffer (renderbuffer)
-        let mut rbo_depth = 0u32;
-        gl::GenRenderbuffers(1, &mut rbo_depth);
-        gl::BindRenderbuffer(gl::RENDERBUFFER, rbo_depth);
-        gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT, SCR_WIDTH as _, SCR_HEIGHT as _);
-        // attach buffers
-        gl::BindFramebuffer(gl::FRAMEBUFFER, hdr_fbo);
-        gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color_buffer, 0);
-        gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, rbo_depth);
-        if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
-            println!("Framebuffer not complete!");
-        }
-        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        // configure floating point framebuffer (floating point color buffer and a depth renderbuffer)
+        // ------------------------------------------------------------------------------------------
+        let (fb_width, fb_height) = window.get_framebuffer_size();
+        let mut hdr_framebuffer = FramebufferBuilder::new(fb_width as u32, fb_height as u32)
+            .color_format(gl::RGBA16F)
+            .build();
+        let color_buffer = hdr_framebuffer.color_texture().unwrap(); // stays the same when resized
+
+        // on-screen readout of the settings and the frame rate
+        let text = TextRenderer::new(fb_width as u32, fb_height as u32);
 
         // lighting info
         // -------------
@@ -134,18 +137,33 @@ fn main() {
         hdr_shader.use_shader();
         hdr_shader.set_int("hdrBuffer".to_string(), 0);
 
+        // tone mapping of the HDR buffer, T cycles through the operators
+        let mut tonemapper = Tonemapper::new(OPERATOR);
+
+        // color grading applied after tone mapping (toggled with G)
+        let color_grade = ColorGrade::from_cube_file(filesystem::get_path("resources/luts/warm.cube".to_string()))
+            .expect("Failed to load color grading LUT.");
+
+        // Q and E lower and raise the exposure
+        let mut exposure_control = ScalarControl::new(1.0, 0.0, f32::INFINITY, 0.001);
+
         // render loop
         // -----------
+        let mut frame_timer = FrameTimer::new();
         while !window.should_close() {
             // per-frame time logic
             // --------------------
-            let current_frame = glfw.get_time() as f32;
-            DELTA_TIME = current_frame - LAST_FRAME;
-            LAST_FRAME = current_frame;
+            frame_timer.tick(glfw.get_time() as f32);
 
             // input
             // -----
-            process_input(&mut window);
+            process_input(&mut window, &frame_timer);
+            let exposure = exposure_control.update(&window, Key::Q, Key::E);
+
+            if let Some((width, height)) = resized.take() {
+                hdr_framebuffer.resize(width, height);
+                text.set_screen_size(width, height);
+            }
 
             let camera = CAMERA.lock().unwrap();
 
@@ -156,9 +174,10 @@ fn main() {
 
             // 1. render scene into floating point framebuffer
             // -----------------------------------------------
-            gl::BindFramebuffer(gl::FRAMEBUFFER, hdr_fbo);
+            hdr_framebuffer.bind();
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-            let projection = glm::perspective(camera.zoom().to_radians(), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+            let aspect = hdr_framebuffer.width() as f32 / hdr_framebuffer.height() as f32;
+            let projection = glm::perspective(camera.zoom().to_radians(), aspect, 0.1, 100.0);
             let view = camera.get_view_matrix();
             shader.use_shader();
             shader.set_mat4("projection".to_string(), &projection);
@@ -176,21 +195,32 @@ fn main() {
             model = glm::translate(&model, &glm::vec3(0.0, 0.0, 25.0));
             model = glm::scale(&model, &glm::vec3(2.5, 2.5, 27.5));
             shader.set_mat4("model".to_string(), &model);
-            shader.set_int("inverse_normals".to_string(), 1);
+            shader.set_bool("inverse_normals".to_string(), true);
             render_cube();
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
             // 2. now render floating point color buffer to 2D quad and tonemap HDR colors to default framebuffer's (clamped) color range
             // --------------------------------------------------------------------------------------------------------------------------
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-            hdr_shader.use_shader();
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, color_buffer);
-            hdr_shader.set_int("hdr".to_string(), if HDR { 1 } else { 0 });
-            hdr_shader.set_float("exposure".to_string(), EXPOSURE);
-            render_quad();
-            
-            println!("hdr: {}| exposure: {}", if HDR { "on" } else { "off" }, EXPOSURE);
+            if HDR && COLOR_GRADE {
+                // the color grade composite tone maps with the same exposure operator before applying the LUT
+                color_grade.apply(color_buffer, exposure);
+            } else if HDR {
+                tonemapper.set_operator(OPERATOR);
+                tonemapper.apply(color_buffer, exposure);
+            } else {
+                hdr_shader.use_shader();
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, color_buffer);
+                render_quad();
+            }
+
+            let readout = format!(
+                "hdr: {} | tonemap: {} | exposure: {:.2} | color grade: {} | {:.0} fps",
+                if HDR { "on" } else { "off" }, OPERATOR.name(), exposure, if COLOR_GRADE { "on" } else { "off" }, frame_timer.fps()
+            );
+            let text_y = hdr_framebuffer.height() as f32 - TextRenderer::line_height(0.5) - 8.0;
+            text.draw_text(&readout, 8.0, text_y, 0.5, glm::vec3(1.0, 1.0, 1.0));
 
             drop(camera);
 
@@ -202,81 +232,6 @@ fn main() {
     }
 }
 
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right         
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right         
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left     
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right     
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
-}
-
 // renderQuad() renders a 1x1 XY quad in NDC
 // -----------------------------------------
 static mut QUAD_VAO: u32 = 0;
@@ -308,30 +263,22 @@ fn render_quad() {
     }
 }
 
-fn process_input(window: &mut Window) {
+fn process_input(window: &mut Window, frame_timer: &FrameTimer) {
     if window.get_key(Key::Escape) == Action::Press {
         window.set_should_close(true)
     }
 
     if window.get_key(Key::W) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::FORWARD, DELTA_TIME);
-        }
+        CAMERA.lock().unwrap().process_keyboard(Movement::FORWARD, frame_timer.delta());
     }
     if window.get_key(Key::S) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::BACKWARD, DELTA_TIME);
-        }
+        CAMERA.lock().unwrap().process_keyboard(Movement::BACKWARD, frame_timer.delta());
     }
     if window.get_key(Key::A) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::LEFT, DELTA_TIME);
-        }
+        CAMERA.lock().unwrap().process_keyboard(Movement::LEFT, frame_timer.delta());
     }
     if window.get_key(Key::D) == Action::Press {
-        unsafe {
-            CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
-        }
+        CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, frame_timer.delta());
     }
 
     unsafe {
@@ -343,15 +290,20 @@ fn process_input(window: &mut Window) {
             HDR_KEY_PRESSED = false;
         }
 
-        if window.get_key(Key::Q) == Action::Press {
-            if EXPOSURE > 0.0 {
-                EXPOSURE -= 0.001;
-            } else {
-                EXPOSURE = 0.0;
-            }
+        if window.get_key(Key::G) == Action::Press && !COLOR_GRADE_KEY_PRESSED {
+            COLOR_GRADE = !COLOR_GRADE;
+            COLOR_GRADE_KEY_PRESSED = true;
+        }
+        if window.get_key(Key::G) == Action::Release {
+            COLOR_GRADE_KEY_PRESSED = false;
+        }
+
+        if window.get_key(Key::T) == Action::Press && !OPERATOR_KEY_PRESSED {
+            OPERATOR = OPERATOR.next();
+            OPERATOR_KEY_PRESSED = true;
         }
-        if window.get_key(Key::E) == Action::Press {
-            EXPOSURE += 0.001;
+        if window.get_key(Key::T) == Action::Release {
+            OPERATOR_KEY_PRESSED = false;
         }
     }
 }
@@ -16,19 +16,25 @@
 
 extern crate nalgebra_glm as glm;
 
-use std::{mem, ptr};
+use std::ptr;
 use std::sync::Mutex;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::primitives::{render_billboard, render_cube, render_quad};
+use learnopengl_shared::util::render::BlendMode;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::input::ScalarControl;
+use learnopengl_shared::framebuffer::PingPong;
+use learnopengl_shared::debug;
+use learnopengl_shared::text::TextRenderer;
+use learnopengl_shared::time::FrameTimer;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
 static mut BLOOM: bool = true;
 static mut BLOOM_KEY_PRESSED: bool = false;
-static mut EXPOSURE: f32 = 1.0;
 
 // camera
 lazy_static! {
@@ -40,7 +46,6 @@ static mut FIRST_MOUSE: bool = false;
 
 // timing
 static mut DELTA_TIME: f32 = 0.0;
-static mut LAST_FRAME: f32 = 0.0;
 
 fn main() {
     // glfw: initialize and configure
@@ -52,6 +57,7 @@ fn main() {
     glfw.window_hint(WindowHint::ContextVersionMinor(3));
     glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
     glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+    glfw.window_hint(WindowHint::OpenGlDebugContext(cfg!(debug_assertions)));
 
     // glfw window creation
     // --------------------
@@ -73,6 +79,11 @@ fn main() {
     // ---------------------------------
     gl::load_with(|s| window.get_proc_address(s) as *const _);
 
+    // log GL errors (e.g. a blur pass sampling an unbound texture unit) as soon as they're raised
+    if cfg!(debug_assertions) && !debug::enable_gl_debug_output() {
+        eprintln!("GL debug output is not available, only the glGetError checks after the blur passes are done.");
+    }
+
     unsafe {
         // configure global opengl state
         // -----------------------------
@@ -81,7 +92,11 @@ fn main() {
         // build and compile shaders
         // -------------------------
         let shader = Shader::new("7.bloom.vs".to_string(), "7.bloom.fs".to_string(), None);
-        let shader_light = Shader::new("7.bloom.vs".to_string(), "7.light_box.fs".to_string(), None);
+        let shader_light = Shader::new(
+            filesystem::get_path("resources/shaders/effects/glow.vs".to_string()),
+            filesystem::get_path("resources/shaders/effects/glow.fs".to_string()),
+            None
+        );
         let shader_blur = Shader::new("7.blur.vs".to_string(), "7.blur.fs".to_string(), None);
         let shader_bloom_final = Shader::new("7.bloom_final.vs".to_string(), "7.bloom_final.fs".to_string(), None);
 
@@ -124,24 +139,7 @@ fn main() {
         gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
         // ping-pong-framebuffer for blurring
-        let mut pingpong_fbo = [0u32; 2];
-        let mut pingpong_colorbuffers = [0u32; 2];
-        gl::GenFramebuffers(2, ptr::addr_of_mut!(pingpong_fbo) as _);
-        gl::GenTextures(2, ptr::addr_of_mut!(pingpong_colorbuffers) as _);
-        for i in 0..2 {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, pingpong_fbo[i]);
-            gl::BindTexture(gl::TEXTURE_2D, pingpong_colorbuffers[i]);
-            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA16F as _, SCR_WIDTH as _, SCR_HEIGHT as _, 0, gl::RGBA, gl::FLOAT, ptr::null());
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _); // we clamp to the edge as the blur filter would otherwise sample repeated texture values!
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
-            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, pingpong_colorbuffers[i], 0);
-            // also check if framebuffers are complete (no need for depth buffer)
-            if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
-                println!("Framebuffer not complete!");
-            }
-        }
+        let mut ping_pong = PingPong::new(SCR_WIDTH, SCR_HEIGHT, gl::RGBA16F);
 
         // lighting info
         // -------------
@@ -168,18 +166,24 @@ fn main() {
         shader_bloom_final.set_int("scene".to_string(), 0);
         shader_bloom_final.set_int("bloomBlur".to_string(), 1);
 
+        // on-screen readout of the settings and the frame rate
+        let text = TextRenderer::new(SCR_WIDTH, SCR_HEIGHT);
+        let mut frame_timer = FrameTimer::new();
+
+        // Q and E lower and raise the exposure
+        let mut exposure_control = ScalarControl::new(1.0, 0.0, f32::INFINITY, 0.001);
+
         // render loop
         // -----------
         while !window.should_close() {
             // per-frame time logic
             // --------------------
-            let current_frame = glfw.get_time() as f32;
-            DELTA_TIME = current_frame - LAST_FRAME;
-            LAST_FRAME = current_frame;
+            DELTA_TIME = frame_timer.tick(glfw.get_time() as f32);
 
             // input
             // -----
             process_input(&mut window);
+            let exposure = exposure_control.update(&window, Key::Q, Key::E);
 
             let camera = CAMERA.lock().unwrap();
 
@@ -205,82 +209,74 @@ fn main() {
                 shader.set_vec3(format!("lights[{}].Color", i), &light_colors[i]);
             }
             shader.set_vec3("viewPos".to_string(), &camera.position());
-            // create one large cube that acts as the floor
+            // create one large cube that acts as the floor; its scale is non-uniform, so its normals need the
+            // normal matrix instead of the model matrix to stay perpendicular to its faces
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::translate(&model, &glm::vec3(0.0, -1.0, 0.0));
             model = glm::scale(&model, &glm::vec3(12.5, 0.5, 12.5));
-            shader.set_mat4("model".to_string(), &model);
+            set_model(&shader, &model);
             render_cube();
             // then create multiple cubes as the scenery
             gl::BindTexture(gl::TEXTURE_2D, container_texture);
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::translate(&model, &glm::vec3(0.0, 1.5, 0.0));
             model = glm::scale(&model, &util::glm::scale_vec3(0.5));
-            shader.set_mat4("model".to_string(), &model);
+            set_model(&shader, &model);
             render_cube();
 
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::translate(&model, &glm::vec3(2.0, 0.0, 1.0));
             model = glm::scale(&model, &util::glm::scale_vec3(0.5));
-            shader.set_mat4("model".to_string(), &model);
+            set_model(&shader, &model);
             render_cube();
 
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::translate(&model, &glm::vec3(-1.0, -1.0, 2.0));
             model = glm::rotate(&model, 60f32.to_radians(), &glm::normalize(&glm::vec3(1.0, 0.0, 1.0)));
-            shader.set_mat4("model".to_string(), &model);
+            set_model(&shader, &model);
             render_cube();
 
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::translate(&model, &glm::vec3(0.0, 2.7, 4.0));
             model = glm::rotate(&model, 23f32.to_radians(), &glm::normalize(&glm::vec3(1.0, 0.0, 1.0)));
             model = glm::scale(&model, &util::glm::scale_vec3(1.25));
-            shader.set_mat4("model".to_string(), &model);
+            set_model(&shader, &model);
             render_cube();
 
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::translate(&model, &glm::vec3(-2.0, 1.0, -3.0));
             model = glm::rotate(&model, 124f32.to_radians(), &glm::normalize(&glm::vec3(1.0, 0.0, 1.0)));
-            shader.set_mat4("model".to_string(), &model);
+            set_model(&shader, &model);
             render_cube();
 
             let mut model = util::glm::diag_mat4(1.0);
             model = glm::translate(&model, &glm::vec3(-3.0, 0.0, 0.0));
             model = glm::scale(&model, &util::glm::scale_vec3(0.5));
-            shader.set_mat4("model".to_string(), &model);
+            set_model(&shader, &model);
             render_cube();
 
-            // finally show all the light sources as bright cubes
+            // finally show all the light sources as glowing points, added on top without hiding each other
             shader_light.use_shader();
             shader_light.set_mat4("projection".to_string(), &projection);
             shader_light.set_mat4("view".to_string(), &view);
-
+            BlendMode::Additive.apply();
+            gl::DepthMask(gl::FALSE);
             for (i, pos) in light_positions.iter().enumerate() {
-                let mut model = util::glm::diag_mat4(1.0);
-                model = glm::translate(&model, pos);
-                model = glm::scale(&model, &util::glm::scale_vec3(0.25));
-                shader_light.set_mat4("model".to_string(), &model);
                 shader_light.set_vec3("lightColor".to_string(), &light_colors[i]);
-                render_cube();
+                render_billboard(pos, 1.0, &view);
             }
+            gl::DepthMask(gl::TRUE);
+            BlendMode::Opaque.apply();
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
             // 2. blur bright fragments with two-pass Gaussian Blur
             // --------------------------------------------------
-            let (mut horizontal, mut first_iteration) = (true, true);
             let amount = 10;
-            shader_blur.use_shader();
-            for _ in 0..amount {
-                gl::BindFramebuffer(gl::FRAMEBUFFER, pingpong_fbo[if horizontal { 1 } else { 0 }]);
-                shader_blur.set_int("horizontal".to_string(), if horizontal { 1 } else { 0 });
-                gl::BindTexture(gl::TEXTURE0, if first_iteration { color_buffers[1] } else { pingpong_colorbuffers[if horizontal { 0 } else { 1 }] }); // bind texture of other framebuffer (or scene if first iteration)
-                render_quad();
-                horizontal = !horizontal;
-                if first_iteration {
-                    first_iteration = false;
-                }
+            let bloom_texture = ping_pong.blur(&shader_blur, color_buffers[1], amount);
+            // the passes sample the bright color buffer through unit 0, any GL error here means a broken binding
+            if cfg!(debug_assertions) {
+                debug::assert_no_gl_errors("the bloom blur passes");
             }
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
             // 3. now render floating point color buffer to 2D quad and tonemap HDR colors to default framebuffer's (clamped) color range
             // --------------------------------------------------------------------------------------------------------------------------
@@ -289,12 +285,13 @@ fn main() {
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, color_buffers[0]);
             gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, pingpong_colorbuffers[if horizontal { 0 } else { 1 }]);
-            shader_bloom_final.set_int("bloom".to_string(), if BLOOM { 1 } else { 0 });
-            shader_bloom_final.set_float("exposure".to_string(), EXPOSURE);
+            gl::BindTexture(gl::TEXTURE_2D, bloom_texture);
+            shader_bloom_final.set_bool("bloom".to_string(), BLOOM);
+            shader_bloom_final.set_float("exposure".to_string(), exposure);
             render_quad();
 
-            println!("bloom: {}| exposure: {}", if BLOOM { "on" } else { "off" }, EXPOSURE);
+            let readout = format!("bloom: {} | exposure: {:.2} | {:.0} fps", if BLOOM { "on" } else { "off" }, exposure, frame_timer.fps());
+            text.draw_text(&readout, 8.0, SCR_HEIGHT as f32 - TextRenderer::line_height(0.5) - 8.0, 0.5, glm::vec3(1.0, 1.0, 1.0));
 
             drop(camera);
 
@@ -306,110 +303,11 @@ fn main() {
     }
 }
 
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right         
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right         
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left     
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right     
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
-}
-
-// renderQuad() renders a 1x1 XY quad in NDC
-// -----------------------------------------
-static mut QUAD_VAO: u32 = 0;
-static mut QUAD_VBO: u32 = 0;
-fn render_quad() {
-    unsafe {
-        if QUAD_VAO == 0 {
-            let quad_vertices = [
-                // positions        // texture Coords
-                -1.0f32,  1.0, 0.0, 0.0, 1.0,
-                -1.0, -1.0, 0.0, 0.0, 0.0,
-                1.0,  1.0, 0.0, 1.0, 1.0,
-                1.0, -1.0, 0.0, 1.0, 0.0
-            ];
-            // setup plane VAO
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(QUAD_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(QUAD_VBO));
-            gl::BindVertexArray(QUAD_VAO);
-            gl::BindBuffer(gl::ARRAY_BUFFER, QUAD_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&quad_vertices) as _, ptr::addr_of!(quad_vertices) as _, gl::STATIC_DRAW);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-        }
-        gl::BindVertexArray(QUAD_VAO);
-        gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-        gl::BindVertexArray(0);
-    }
+// uploads a model matrix together with the normal matrix 7.bloom.vs transforms the normals with
+// ---------------------------------------------------------------------------------------------
+fn set_model(shader: &Shader, model: &glm::TMat4<f32>) {
+    shader.set_mat4("model".to_string(), model);
+    shader.set_mat3("normalMatrix".to_string(), &util::glm::normal_matrix(model));
 }
 
 fn process_input(window: &mut Window) {
@@ -446,17 +344,6 @@ fn process_input(window: &mut Window) {
         if window.get_key(Key::Space) == Action::Release {
             BLOOM_KEY_PRESSED = false;
         }
-
-        if window.get_key(Key::Q) == Action::Press {
-            if EXPOSURE > 0.0 {
-                EXPOSURE -= 0.001;
-            } else {
-                EXPOSURE = 0.0;
-            }
-        }
-        if window.get_key(Key::E) == Action::Press {
-            EXPOSURE += 0.001;
-        }
     }
 }
 
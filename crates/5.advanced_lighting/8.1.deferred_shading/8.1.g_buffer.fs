@@ -18,10 +18,12 @@
 layout (location = 0) out vec3 gPosition;
 layout (location = 1) out vec3 gNormal;
 layout (location = 2) out vec4 gAlbedoSpec;
+layout (location = 3) out float gDepth;
 
 in vec2 TexCoords;
 in vec3 FragPos;
 in vec3 Normal;
+in float ViewDepth;
 
 uniform sampler2D texture_diffuse1;
 uniform sampler2D texture_specular1;
@@ -36,4 +38,6 @@ void main()
     gAlbedoSpec.rgb = texture(texture_diffuse1, TexCoords).rgb;
     // store specular intensity in gAlbedoSpec's alpha component
     gAlbedoSpec.a = texture(texture_specular1, TexCoords).r;
+    // and the linear view space depth in the last one
+    gDepth = ViewDepth;
 }
\ No newline at end of file
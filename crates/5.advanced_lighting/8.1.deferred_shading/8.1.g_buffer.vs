@@ -22,6 +22,7 @@ layout (location = 2) in vec2 aTexCoords;
 out vec3 FragPos;
 out vec2 TexCoords;
 out vec3 Normal;
+out float ViewDepth;
 
 uniform mat4 model;
 uniform mat4 view;
@@ -36,5 +37,9 @@ void main()
     mat3 normalMatrix = transpose(inverse(mat3(model)));
     Normal = normalMatrix * aNormal;
 
-    gl_Position = projection * view * worldPos;
+    vec4 viewPos = view * worldPos;
+    // linear distance along the view direction, for the depth of field
+    ViewDepth = -viewPos.z;
+
+    gl_Position = projection * viewPos;
 }
\ No newline at end of file
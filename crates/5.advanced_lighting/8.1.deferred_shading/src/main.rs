@@ -18,18 +18,124 @@ extern crate nalgebra_glm as glm;
 
 use std::{mem, ptr};
 use std::sync::Mutex;
-use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
-use learnopengl_shared::{filesystem, util};
+use glfw::{Action, Context, CursorMode, Key, MouseButton, OpenGlProfileHint, Window, WindowHint};
+use learnopengl_shared::{filesystem, light, util};
+use learnopengl_shared::framebuffer::{self, FramebufferBuilder};
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::primitives::render_cube;
+use learnopengl_shared::util::render::{self, BlendMode, OcclusionQuery};
 use lazy_static::lazy_static;
-use rand::{RngCore, SeedableRng};
-use rand_pcg::Pcg64;
-use learnopengl_shared::camera::{Camera, Movement};
-use learnopengl_shared::model::Model;
+use learnopengl_shared::camera::{Camera, Frustum, Movement};
+use learnopengl_shared::app::FrameContext;
+use learnopengl_shared::mesh::TextureNaming;
+use learnopengl_shared::scene::Scene;
+use learnopengl_shared::util::picking::IdPicker;
+use learnopengl_shared::util::post::DepthOfField;
+use learnopengl_shared_ex::model::Model;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
 
+// the scene can be rendered with either technique, toggled at runtime with the space bar
+#[derive(Clone, Copy, PartialEq)]
+enum RenderPath {
+    Forward,
+    Deferred
+}
+static mut RENDER_PATH: RenderPath = RenderPath::Deferred;
+static mut RENDER_PATH_KEY_PRESSED: bool = false;
+
+// a left click picks the backpack under the crosshair (the center of the screen, as the cursor is captured)
+// and selects the light the crosshair ray hits first, which is then drawn larger
+static mut PICK_REQUESTED: bool = false;
+static mut PICK_BUTTON_PRESSED: bool = false;
+
+// F toggles the depth of field of the deferred path, focused on whatever is under the crosshair
+static mut DEPTH_OF_FIELD: bool = true;
+static mut DEPTH_OF_FIELD_KEY_PRESSED: bool = false;
+const APERTURE: f32 = 0.6;
+
+// O toggles occlusion culling of the deferred path: every backpack's bounding box is tested against the
+// depth of the ones in front of it, and the GPU skips those that are completely hidden
+static mut OCCLUSION_CULLING: bool = false;
+static mut OCCLUSION_CULLING_KEY_PRESSED: bool = false;
+
+// the grid of backpacks, drawn by both render paths
+struct BackpackGrid {
+    backpack: Model,
+    // the backpack's bounding box (min, max), the proxy of the occlusion queries
+    bounds: (glm::TVec3<f32>, glm::TVec3<f32>),
+    object_positions: Vec<glm::TVec3<f32>>
+}
+
+impl Scene for BackpackGrid {
+    fn draw(&self, shader: &Shader) {
+        self.draw_with(shader, &TextureNaming::default());
+    }
+}
+
+impl BackpackGrid {
+    // the g-buffer shader samples texture_diffuse1/texture_specular1 while the forward shader uses
+    // material.diffuse1/material.specular1, the same model feeds both through the texture naming
+    fn draw_with(&self, shader: &Shader, naming: &TextureNaming) {
+        for pos in self.object_positions.iter() {
+            shader.set_mat4("model".to_string(), &backpack_model(pos));
+            self.backpack.draw_with(shader, naming);
+        }
+    }
+
+    // like draw, skipping the backpack meshes outside of `frustum`. Returns the number of meshes drawn.
+    fn draw_culled(&self, shader: &Shader, frustum: &Frustum) -> usize {
+        self.object_positions.iter()
+            .map(|pos| {
+                let model = backpack_model(pos);
+                shader.set_mat4("model".to_string(), &model);
+                self.backpack.draw_culled(shader, frustum, &model)
+            })
+            .sum()
+    }
+
+    // like draw_culled, drawing the backpacks from front to back and each only if its bounding box
+    // passes an occlusion test against those drawn before. `queries` holds one query per backpack.
+    // Returns the number of meshes submitted, the GPU may still skip some of them.
+    fn draw_occlusion_culled(&self, shader: &Shader, frustum: &Frustum, camera_pos: &glm::TVec3<f32>, queries: &[OcclusionQuery]) -> usize {
+        let mut order = render::sort_by_distance(&self.object_positions, camera_pos);
+        order.reverse();
+        order.into_iter()
+            .zip(queries.iter())
+            .map(|(i, query)| {
+                let model = backpack_model(&self.object_positions[i]);
+                query.test_bounding_box(shader, &model, self.bounds);
+                let mut drawn = 0;
+                query.draw_conditional(|| {
+                    shader.set_mat4("model".to_string(), &model);
+                    drawn = self.backpack.draw_culled(shader, frustum, &model);
+                });
+                drawn
+            })
+            .sum()
+    }
+
+    // the number of meshes draw renders
+    fn mesh_count(&self) -> usize {
+        self.object_positions.len() * self.backpack.meshes.len()
+    }
+
+    // renders every backpack with its index + 1 as id (0 means nothing was hit)
+    fn draw_ids(&self, picker: &IdPicker) {
+        for (i, pos) in self.object_positions.iter().enumerate() {
+            picker.set_object(i as u32 + 1, &backpack_model(pos));
+            self.backpack.draw(picker.shader());
+        }
+    }
+}
+
+fn backpack_model(pos: &glm::TVec3<f32>) -> glm::TMat4<f32> {
+    let mut model = util::glm::diag_mat4(1.0);
+    model = glm::translate(&model, pos);
+    glm::scale(&model, &util::glm::scale_vec3(0.5))
+}
+
 // camera
 lazy_static! {
     static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
@@ -40,7 +146,6 @@ static mut FIRST_MOUSE: bool = false;
 
 // timing
 static mut DELTA_TIME: f32 = 0.0;
-static mut LAST_FRAME: f32 = 0.0;
 
 fn main() {
     // glfw: initialize and configure
@@ -83,6 +188,13 @@ fn main() {
         let shader_geometry_pass = Shader::new("8.1.g_buffer.vs".to_string(), "8.1.g_buffer.fs".to_string(), None);
         let shader_lighting_pass = Shader::new("8.1.deferred_shading.vs".to_string(), "8.1.deferred_shading.fs".to_string(), None);
         let shader_light_box = Shader::new("8.1.deferred_light_box.vs".to_string(), "8.1.deferred_light_box.fs".to_string(), None);
+        let shader_forward = Shader::new("8.1.forward_shading.vs".to_string(), "8.1.forward_shading.fs".to_string(), None);
+        let shader_screen = Shader::new("8.1.fbo_debug.vs".to_string(), "8.1.fbo_debug.fs".to_string(), None);
+        let forward_naming = TextureNaming {
+            diffuse: "material.diffuse".to_string(),
+            specular: "material.specular".to_string(),
+            ..Default::default()
+        };
 
         // load models
         // -----------
@@ -97,13 +209,19 @@ fn main() {
         object_positions.push(glm::vec3(-3.0, -0.5, 3.0));
         object_positions.push(glm::vec3(0.0, -0.5, 3.0));
         object_positions.push(glm::vec3(3.0, -0.5, 3.0));
+        let scene = BackpackGrid {
+            bounds: backpack.bounding_box(),
+            backpack,
+            object_positions
+        };
+        let occlusion_queries: Vec<OcclusionQuery> = scene.object_positions.iter().map(|_| OcclusionQuery::new()).collect();
 
         // configure g-buffer framebuffer
         // ------------------------------
         let mut g_buffer = 0u32;
         gl::GenFramebuffers(1, &mut g_buffer);
         gl::BindFramebuffer(gl::FRAMEBUFFER, g_buffer);
-        let (mut g_position, mut g_normal, mut g_albedo_spec) = (0u32, 0u32, 0u32);
+        let (mut g_position, mut g_normal, mut g_albedo_spec, mut g_depth) = (0u32, 0u32, 0u32, 0u32);
         // position color buffer
         gl::GenTextures(1, &mut g_position);
         gl::BindTexture(gl::TEXTURE_2D, g_position);
@@ -125,39 +243,44 @@ fn main() {
         gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
         gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
         gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT2, gl::TEXTURE_2D, g_albedo_spec, 0);
+        // linear depth buffer
+        gl::GenTextures(1, &mut g_depth);
+        gl::BindTexture(gl::TEXTURE_2D, g_depth);
+        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::R16F as _, SCR_WIDTH as _, SCR_HEIGHT as _, 0, gl::RED, gl::FLOAT, ptr::null());
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+        gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT3, gl::TEXTURE_2D, g_depth, 0);
         // tell OpenGL which color attachments we'll use (of this framebuffer) for rendering
-        let attachments = [gl::COLOR_ATTACHMENT0, gl::COLOR_ATTACHMENT1, gl::COLOR_ATTACHMENT2];
-        gl::DrawBuffers(3, ptr::addr_of!(attachments) as _);
-        // create and attach depth buffer (renderbuffer)
+        let attachments = [gl::COLOR_ATTACHMENT0, gl::COLOR_ATTACHMENT1, gl::COLOR_ATTACHMENT2, gl::COLOR_ATTACHMENT3];
+        gl::DrawBuffers(4, ptr::addr_of!(attachments) as _);
+        // create and attach depth buffer (renderbuffer), in the format of the lit scene's framebuffer so it can be blitted there
         let mut rbo_depth = 0u32;
         gl::GenRenderbuffers(1, &mut rbo_depth);
         gl::BindRenderbuffer(gl::RENDERBUFFER, rbo_depth);
-        gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT, SCR_WIDTH as _, SCR_HEIGHT as _);
-        gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, rbo_depth);
+        gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, SCR_WIDTH as _, SCR_HEIGHT as _);
+        gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, rbo_depth);
         // finally check if framebuffer is complete
         if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
             println!("Framebuffer not complete!");
         }
         gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
+        // the deferred path lights the scene into an HDR buffer, which the depth of field reads from
+        let lit_scene = FramebufferBuilder::new(SCR_WIDTH, SCR_HEIGHT).color_format(gl::RGBA16F).build();
+        let depth_of_field = DepthOfField::new(SCR_WIDTH, SCR_HEIGHT);
+        let mut focus = 3.0f32;
+
+        // object id buffer for picking, only rendered when a click asks for it
+        let picker = IdPicker::new(SCR_WIDTH, SCR_HEIGHT);
+
         // lighting info
         // -------------
-        const NR_LIGHTS: u32 = 32;
-        let mut light_positions: Vec<glm::TVec3<f32>> = Vec::new();
-        let mut light_colors: Vec<glm::TVec3<f32>> = Vec::new();
-        let mut rng = Pcg64::seed_from_u64(13);
-        for _ in 0..NR_LIGHTS {
-            // calculate slightly random offsets
-            let x_pos = ((rng.next_u32() % 100) as f32 / 100.0) * 6.0 - 3.0;
-            let y_pos = ((rng.next_u32() % 100) as f32 / 100.0) * 6.0 - 4.0;
-            let z_pos = ((rng.next_u32() % 100) as f32 / 100.0) * 6.0 - 3.0;
-            light_positions.push(glm::vec3(x_pos, y_pos, z_pos));
-            // also calculate random color
-            let r_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
-            let g_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
-            let b_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
-            light_colors.push(glm::vec3(r_color, g_color, b_color));
-        }
+        const NR_LIGHTS: usize = 32;
+        const LIGHT_BOX_SCALE: f32 = 0.125;
+        let mut selected_light: Option<usize> = None;
+        let point_lights = light::random_point_lights(NR_LIGHTS, (glm::vec3(-3.0, -4.0, -3.0), glm::vec3(3.0, 2.0, 3.0)), 13);
+        let light_positions: Vec<glm::TVec3<f32>> = point_lights.iter().map(|light| light.position).collect();
+        let light_colors: Vec<glm::TVec3<f32>> = point_lights.iter().map(|light| light.diffuse).collect();
 
         // shader configuration
         // --------------------
@@ -165,15 +288,47 @@ fn main() {
         shader_lighting_pass.set_int("gPosition".to_string(), 0);
         shader_lighting_pass.set_int("gNormal".to_string(), 1);
         shader_lighting_pass.set_int("gAlbedoSpec".to_string(), 2);
+        shader_screen.use_shader();
+        shader_screen.set_int("fboAttachment".to_string(), 0);
+        // the light uniforms never change, so upload them once for both lighting shaders
+        for lighting_shader in [&shader_lighting_pass, &shader_forward] {
+            lighting_shader.use_shader();
+            for (i, point_light) in point_lights.iter().enumerate() {
+                lighting_shader.set_vec3(format!("lights[{}].Position", i), &point_light.position);
+                lighting_shader.set_vec3(format!("lights[{}].Color", i), &point_light.diffuse);
+                lighting_shader.set_float(format!("lights[{}].Linear", i), point_light.linear);
+                lighting_shader.set_float(format!("lights[{}].Quadratic", i), point_light.quadratic);
+            }
+        }
+
+        // frame time readout shown in the window title, averaged over half a second, together with how
+        // many backpack meshes the last frame drew (the deferred path skips those outside of the view)
+        let mut frame_context = FrameContext::new();
+        let mut meshes_drawn = scene.mesh_count();
+        let mut frame_time_sum = 0f32;
+        let mut frame_count = 0u32;
 
         // render loop
         // -----------
         while !window.should_close() {
             // per-frame time logic
             // --------------------
-            let current_frame = glfw.get_time() as f32;
-            DELTA_TIME = current_frame - LAST_FRAME;
-            LAST_FRAME = current_frame;
+            frame_context.update_timing(glfw.get_time() as f32);
+            DELTA_TIME = frame_context.delta_time();
+            frame_time_sum += DELTA_TIME;
+            frame_count += 1;
+            if frame_time_sum >= 0.5 {
+                window.set_title(format!(
+                    "LearnOpenGL | {}{} | {:.2} ms | {}/{} meshes",
+                    if RENDER_PATH == RenderPath::Deferred { "deferred" } else { "forward" },
+                    if OCCLUSION_CULLING && RENDER_PATH == RenderPath::Deferred { ", occlusion culled" } else { "" },
+                    frame_time_sum * 1000.0 / frame_count as f32,
+                    meshes_drawn,
+                    scene.mesh_count()
+                ).as_str());
+                frame_time_sum = 0.0;
+                frame_count = 0;
+            }
 
             // input
             // -----
@@ -183,74 +338,120 @@ fn main() {
 
             // render
             // ------
+            // the offscreen buffers have a fixed size, the window's viewport is restored for the final quad
+            let mut viewport = [0i32; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
             gl::ClearColor(0.0, 0.0, 0.0, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
-            // 1. geometry pass: render scene's geometry/color data into gbuffer
-            // -----------------------------------------------------------------
-            gl::BindFramebuffer(gl::FRAMEBUFFER, g_buffer);
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
             let projection = glm::perspective(camera.zoom().to_radians(), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
             let view = camera.get_view_matrix();
-            shader_geometry_pass.use_shader();
-            shader_geometry_pass.set_mat4("projection".to_string(), &projection);
-            shader_geometry_pass.set_mat4("view".to_string(), &view);
-            for pos in object_positions.iter() {
-                let mut model = util::glm::diag_mat4(1.0);
-                model = glm::translate(&model, pos);
-                model = glm::scale(&model, &util::glm::scale_vec3(0.5));
-                shader_geometry_pass.set_mat4("model".to_string(), &model);
-                backpack.draw(&shader_geometry_pass);
+            if PICK_REQUESTED {
+                picker.render(&projection, &view, |picker| scene.draw_ids(picker));
+                match picker.pick(SCR_WIDTH as i32 / 2, SCR_HEIGHT as i32 / 2) {
+                    Some(id) => println!("picked backpack {} at {:?}", id, scene.object_positions[id as usize - 1].as_slice()),
+                    None => println!("picked nothing")
+                }
+                let (origin, direction) = camera.screen_ray(SCR_WIDTH as f32 / 2.0, SCR_HEIGHT as f32 / 2.0, SCR_WIDTH as f32, SCR_HEIGHT as f32, &projection);
+                selected_light = light_positions.iter()
+                    .enumerate()
+                    .filter_map(|(i, pos)| util::glm::ray_sphere_intersect(&origin, &direction, pos, LIGHT_BOX_SCALE * 2.0).map(|t| (i, t)))
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(i, _)| i);
+                match selected_light {
+                    Some(i) => println!("selected light {} at {:?}", i, light_positions[i].as_slice()),
+                    None => println!("selected no light")
+                }
+                PICK_REQUESTED = false;
             }
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-
-            // 2. lighting pass: calculate lighting by iterating over a screen filled quad pixel-by-pixel using the gbuffer's content.
-            // -----------------------------------------------------------------------------------------------------------------------
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-            shader_lighting_pass.use_shader();
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, g_position);
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, g_normal);
-            gl::ActiveTexture(gl::TEXTURE2);
-            gl::BindTexture(gl::TEXTURE_2D, g_albedo_spec);
-            // send light relevant uniforms
-            for (i, pos) in light_positions.iter().enumerate() {
-                shader_lighting_pass.set_vec3(format!("lights[{}].Position", i), pos);
-                shader_lighting_pass.set_vec3(format!("lights[{}].Color", i), &light_colors[i]);
-                // update attenuation parameters and calculate radius
-                const LINEAR: f32 = 0.7;
-                const QUADRATIC: f32 = 1.8;
-                shader_lighting_pass.set_float(format!("lights[{}].Linear", i), LINEAR);
-                shader_lighting_pass.set_float(format!("lights[{}].Quadratic", i), QUADRATIC);
+            if RENDER_PATH == RenderPath::Deferred {
+                // 1. geometry pass: render scene's geometry/color data into gbuffer
+                // -----------------------------------------------------------------
+                gl::BindFramebuffer(gl::FRAMEBUFFER, g_buffer);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                shader_geometry_pass.use_shader();
+                shader_geometry_pass.set_mat4("projection".to_string(), &projection);
+                shader_geometry_pass.set_mat4("view".to_string(), &view);
+                let frustum = camera.extract_frustum(&projection);
+                meshes_drawn = if OCCLUSION_CULLING {
+                    scene.draw_occlusion_culled(&shader_geometry_pass, &frustum, &camera.position(), &occlusion_queries)
+                } else {
+                    scene.draw_culled(&shader_geometry_pass, &frustum)
+                };
+
+                // focus on the depth under the crosshair, keeping the last focus while looking at nothing
+                let mut center_depth = 0f32;
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT3);
+                gl::ReadPixels(SCR_WIDTH as i32 / 2, SCR_HEIGHT as i32 / 2, 1, 1, gl::RED, gl::FLOAT, ptr::addr_of_mut!(center_depth) as _);
+                if center_depth > 0.0 {
+                    focus = center_depth;
+                }
+
+                // 2. lighting pass: calculate lighting by iterating over a screen filled quad pixel-by-pixel using the gbuffer's content.
+                // -----------------------------------------------------------------------------------------------------------------------
+                lit_scene.bind();
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                shader_lighting_pass.use_shader();
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, g_position);
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, g_normal);
+                gl::ActiveTexture(gl::TEXTURE2);
+                gl::BindTexture(gl::TEXTURE_2D, g_albedo_spec);
+                shader_lighting_pass.set_vec3("viewPos".to_string(), &camera.position());
+                // finally render quad
+                render_quad();
+
+                // 2.5. copy content of geometry's depth buffer to the lit scene's depth buffer
+                // ---------------------------------------------------------------------------
+                // the depth formats of the g-buffer and the lit scene have to match, see blit_depth
+                framebuffer::blit_depth_fbo(g_buffer, lit_scene.fbo(), SCR_WIDTH, SCR_HEIGHT);
+            } else {
+                // 1. forward pass: shade every fragment against all lights right away (this also fills the depth buffer for the light boxes)
+                // ---------------------------------------------------------------------------------------------------------------------------
+                shader_forward.use_shader();
+                shader_forward.set_mat4("projection".to_string(), &projection);
+                shader_forward.set_mat4("view".to_string(), &view);
+                shader_forward.set_vec3("viewPos".to_string(), &camera.position());
+                scene.draw_with(&shader_forward, &forward_naming);
+                meshes_drawn = scene.mesh_count();
             }
-            shader_lighting_pass.set_vec3("viewPos".to_string(), &camera.position());
-            // finally render quad
-            render_quad();
-
-            // 2.5. copy content of geometry's depth buffer to default framebuffer's depth buffer
-            // ----------------------------------------------------------------------------------
-            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, g_buffer);
-            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0); // write to default framebuffer
-            // blit to default framebuffer. Note that this may or may not work as the internal formats of both the FBO and default framebuffer have to match.
-            // the internal formats are implementation defined. This works on all of my systems, but if it doesn't on yours you'll likely have to write to the
-            // depth buffer in another shader stage (or somehow see to match the default framebuffer's internal format with the FBO's internal format).
-            gl::BlitFramebuffer(0, 0, SCR_WIDTH as _, SCR_HEIGHT as _, 0, 0, SCR_WIDTH as _, SCR_HEIGHT as _, gl::DEPTH_BUFFER_BIT, gl::NEAREST);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-
-            // 3. render lights on top of scene
-            // --------------------------------
+
+            // 3. render lights on top of scene, blended additively so overlapping lights glow
+            // --------------------------------------------------------------------------------
+            BlendMode::Additive.apply();
             shader_light_box.use_shader();
             shader_light_box.set_mat4("projection".to_string(), &projection);
             shader_light_box.set_mat4("view".to_string(), &view);
             for (i, pos) in light_positions.iter().enumerate() {
                 let mut model = util::glm::diag_mat4(1.0);
                 model = glm::translate(&model, pos);
-                model = glm::scale(&model, &util::glm::scale_vec3(0.125));
+                let scale = if selected_light == Some(i) { LIGHT_BOX_SCALE * 2.0 } else { LIGHT_BOX_SCALE };
+                model = glm::scale(&model, &util::glm::scale_vec3(scale));
                 shader_light_box.set_mat4("model".to_string(), &model);
                 shader_light_box.set_vec3("lightColor".to_string(), &light_colors[i]);
                 render_cube();
             }
+            BlendMode::Opaque.apply();
+
+            if RENDER_PATH == RenderPath::Deferred {
+                // 4. blur what's out of focus and show the result
+                // -----------------------------------------------
+                let lit_texture = lit_scene.color_texture().unwrap();
+                let output = if DEPTH_OF_FIELD {
+                    depth_of_field.apply(lit_texture, g_depth, focus, APERTURE)
+                } else {
+                    lit_texture
+                };
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+                gl::Disable(gl::DEPTH_TEST);
+                shader_screen.use_shader();
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, output);
+                render_quad();
+                gl::Enable(gl::DEPTH_TEST);
+            }
 
             drop(camera);
 
@@ -262,81 +463,6 @@ fn main() {
     }
 }
 
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right         
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right         
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left     
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right     
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
-}
-
 // renderQuad() renders a 1x1 XY quad in NDC
 // -----------------------------------------
 static mut QUAD_VAO: u32 = 0;
@@ -393,6 +519,40 @@ fn process_input(window: &mut Window) {
             CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
         }
     }
+
+    unsafe {
+        if window.get_key(Key::Space) == Action::Press && !RENDER_PATH_KEY_PRESSED {
+            RENDER_PATH = if RENDER_PATH == RenderPath::Deferred { RenderPath::Forward } else { RenderPath::Deferred };
+            RENDER_PATH_KEY_PRESSED = true;
+        }
+        if window.get_key(Key::Space) == Action::Release {
+            RENDER_PATH_KEY_PRESSED = false;
+        }
+
+        if window.get_key(Key::F) == Action::Press && !DEPTH_OF_FIELD_KEY_PRESSED {
+            DEPTH_OF_FIELD = !DEPTH_OF_FIELD;
+            DEPTH_OF_FIELD_KEY_PRESSED = true;
+        }
+        if window.get_key(Key::F) == Action::Release {
+            DEPTH_OF_FIELD_KEY_PRESSED = false;
+        }
+
+        if window.get_key(Key::O) == Action::Press && !OCCLUSION_CULLING_KEY_PRESSED {
+            OCCLUSION_CULLING = !OCCLUSION_CULLING;
+            OCCLUSION_CULLING_KEY_PRESSED = true;
+        }
+        if window.get_key(Key::O) == Action::Release {
+            OCCLUSION_CULLING_KEY_PRESSED = false;
+        }
+
+        if window.get_mouse_button(MouseButton::Button1) == Action::Press && !PICK_BUTTON_PRESSED {
+            PICK_REQUESTED = true;
+            PICK_BUTTON_PRESSED = true;
+        }
+        if window.get_mouse_button(MouseButton::Button1) == Action::Release {
+            PICK_BUTTON_PRESSED = false;
+        }
+    }
 }
 
 fn framebuffer_size_callback(
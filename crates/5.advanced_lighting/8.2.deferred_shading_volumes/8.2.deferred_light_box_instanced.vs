@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aTexCoords;
+layout (location = 6) in vec3 aLightColor;
+layout (location = 7) in mat4 aInstanceMatrix;
+
+out vec3 LightColor;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+void main()
+{
+    LightColor = aLightColor;
+    gl_Position = projection * view * aInstanceMatrix * vec4(aPos, 1.0);
+}
\ No newline at end of file
@@ -20,16 +20,23 @@ use std::{mem, ptr};
 use std::sync::Mutex;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
+use learnopengl_shared::framebuffer;
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::primitives::render_cube_instanced;
 use lazy_static::lazy_static;
-use rand::{RngCore, SeedableRng};
-use rand_pcg::Pcg64;
 use learnopengl_shared::camera::{Camera, Movement};
-use learnopengl_shared::model::Model;
+use learnopengl_shared::debug::{GBufferViewer, GizmoOverlay};
+use learnopengl_shared::input::InputState;
+use learnopengl_shared::light;
+use learnopengl_shared_ex::model::Model;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
 
+// bounding sphere radii used for frustum culling: a backpack scaled by 0.25 and a light box scaled by 0.125
+const BACKPACK_RADIUS: f32 = 0.75;
+const LIGHT_BOX_RADIUS: f32 = 0.22;
+
 // camera
 lazy_static! {
     static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
@@ -82,11 +89,11 @@ fn main() {
         // -------------------------
         let shader_geometry_pass = Shader::new("8.2.g_buffer.vs".to_string(), "8.2.g_buffer.fs".to_string(), None);
         let shader_lighting_pass = Shader::new("8.2.deferred_shading.vs".to_string(), "8.2.deferred_shading.fs".to_string(), None);
-        let shader_light_box = Shader::new("8.2.deferred_light_box.vs".to_string(), "8.2.deferred_light_box.fs".to_string(), None);
+        let shader_light_box = Shader::new("8.2.deferred_light_box_instanced.vs".to_string(), "8.2.deferred_light_box_instanced.fs".to_string(), None);
 
         // load models
         // -----------
-        let backpack = Model::new_without_gamma(filesystem::get_path("resources/objects/backpack/backpack.obj".to_string()));
+        let mut backpack = Model::new_without_gamma(filesystem::get_path("resources/objects/backpack/backpack.obj".to_string()));
         let mut object_positions: Vec<glm::TVec3<f32>> = Vec::new();
         object_positions.push(glm::vec3(-3.0, -0.5, -3.0));
         object_positions.push(glm::vec3(0.0, -0.5, -3.0));
@@ -142,22 +149,10 @@ fn main() {
 
         // lighting info
         // -------------
-        const NR_LIGHTS: u32 = 32;
-        let mut light_positions: Vec<glm::TVec3<f32>> = Vec::new();
-        let mut light_colors: Vec<glm::TVec3<f32>> = Vec::new();
-        let mut rng = Pcg64::seed_from_u64(13);
-        for _ in 0..NR_LIGHTS {
-            // calculate slightly random offsets
-            let x_pos = ((rng.next_u32() % 100) as f32 / 100.0) * 6.0 - 3.0;
-            let y_pos = ((rng.next_u32() % 100) as f32 / 100.0) * 6.0 - 4.0;
-            let z_pos = ((rng.next_u32() % 100) as f32 / 100.0) * 6.0 - 3.0;
-            light_positions.push(glm::vec3(x_pos, y_pos, z_pos));
-            // also calculate random color
-            let r_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
-            let g_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
-            let b_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
-            light_colors.push(glm::vec3(r_color, g_color, b_color));
-        }
+        const NR_LIGHTS: usize = 32;
+        let point_lights = light::random_point_lights(NR_LIGHTS, (glm::vec3(-3.0, -4.0, -3.0), glm::vec3(3.0, 2.0, 3.0)), 13);
+        let light_positions: Vec<glm::TVec3<f32>> = point_lights.iter().map(|light| light.position).collect();
+        let light_colors: Vec<glm::TVec3<f32>> = point_lights.iter().map(|light| light.diffuse).collect();
 
         // shader configuration
         // --------------------
@@ -166,6 +161,14 @@ fn main() {
         shader_lighting_pass.set_int("gNormal".to_string(), 1);
         shader_lighting_pass.set_int("gAlbedoSpec".to_string(), 2);
 
+        // F toggles a wireframe view of the geometry pass, V a split screen of the shaded (left) and
+        // wireframe (right) view, G cycles through the g-buffer targets, X shows a ground grid and the axes
+        let mut input = InputState::new();
+        let mut wireframe = false;
+        let mut split_view = false;
+        let mut gbuffer_viewer = GBufferViewer::new();
+        let mut gizmos = GizmoOverlay::new();
+
         // render loop
         // -----------
         while !window.should_close() {
@@ -178,6 +181,19 @@ fn main() {
             // input
             // -----
             process_input(&mut window);
+            input.update(&window);
+            if input.just_pressed(Key::F) {
+                wireframe = !wireframe;
+            }
+            if input.just_pressed(Key::V) {
+                split_view = !split_view;
+            }
+            if input.just_pressed(Key::G) {
+                println!("g-buffer view: {}", gbuffer_viewer.cycle().name());
+            }
+            if input.just_pressed(Key::X) {
+                gizmos.toggle();
+            }
 
             let camera = CAMERA.lock().unwrap();
 
@@ -190,17 +206,34 @@ fn main() {
             // -----------------------------------------------------------------
             gl::BindFramebuffer(gl::FRAMEBUFFER, g_buffer);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-            let projection = glm::perspective(camera.zoom().to_radians(), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+            // each half of the split screen shows the whole view
+            let aspect = if split_view { SCR_WIDTH as f32 / 2.0 } else { SCR_WIDTH as f32 } / SCR_HEIGHT as f32;
+            let projection = glm::perspective(camera.zoom().to_radians(), aspect, 0.1, 100.0);
             let view = camera.get_view_matrix();
+            // objects outside of the view frustum are skipped entirely
+            let frustum = camera.extract_frustum(&projection);
             shader_geometry_pass.use_shader();
             shader_geometry_pass.set_mat4("projection".to_string(), &projection);
             shader_geometry_pass.set_mat4("view".to_string(), &view);
-            for pos in object_positions.iter() {
-                let mut model = util::glm::diag_mat4(1.0);
-                model = glm::translate(&model, pos);
-                model = glm::scale(&model, &util::glm::scale_vec3(0.25));
-                shader_geometry_pass.set_mat4("model".to_string(), &model);
-                backpack.draw(&shader_geometry_pass);
+            // all visible backpacks are drawn in a single instanced call, each with its own model matrix
+            let transforms: Vec<glm::TMat4<f32>> = object_positions.iter()
+                .filter(|pos| frustum.contains_sphere(pos, BACKPACK_RADIUS))
+                .map(|pos| {
+                    let mut model = util::glm::diag_mat4(1.0);
+                    model = glm::translate(&model, pos);
+                    glm::scale(&model, &util::glm::scale_vec3(0.25))
+                })
+                .collect();
+            backpack.set_instance_buffer(&transforms);
+            let draw_backpacks = |wireframe: bool| {
+                util::render::set_wireframe(wireframe);
+                backpack.draw_instanced(&shader_geometry_pass, transforms.len() as i32);
+                util::render::set_wireframe(false); // the lighting pass needs a filled screen quad
+            };
+            if split_view {
+                util::render::split_screen(|| draw_backpacks(false), || draw_backpacks(true));
+            } else {
+                draw_backpacks(wireframe);
             }
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
@@ -225,8 +258,7 @@ fn main() {
                 shader_lighting_pass.set_float(format!("lights[{}].Linear", i), linear);
                 shader_lighting_pass.set_float(format!("lights[{}].Quadratic", i), quadratic);
                 // then calculate radius of light volume/sphere
-                let max_birghtness = light_colors[i].z.max(light_colors[i].y.max(light_colors[i].x));
-                let radius = (-linear + (linear * linear - 4.0 * quadratic * (constant - (256.0 / 5.0) * max_birghtness)).sqrt()) / (2.0 * quadratic);
+                let radius = light::point_light_radius(&light_colors[i], constant, linear, quadratic, light::LIGHT_RADIUS_THRESHOLD);
                 shader_lighting_pass.set_float(format!("lights[{}].Radius", i), radius);
             }
             shader_lighting_pass.set_vec3("viewPos".to_string(), &camera.position());
@@ -235,27 +267,42 @@ fn main() {
 
             // 2.5. copy content of geometry's depth buffer to default framebuffer's depth buffer
             // ----------------------------------------------------------------------------------
-            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, g_buffer);
-            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0); // write to default framebuffer
-            // blit to default framebuffer. Note that this may or may not work as the internal formats of both the FBO and default framebuffer have to match.
-            // the internal formats are implementation defined. This works on all of my systems, but if it doesn't on yours you'll likely have to write to the
-            // depth buffer in another shader stage (or somehow see to match the default framebuffer's internal format with the FBO's internal format).
-            gl::BlitFramebuffer(0, 0, SCR_WIDTH as _, SCR_HEIGHT as _, 0, 0, SCR_WIDTH as _, SCR_HEIGHT as _, gl::DEPTH_BUFFER_BIT, gl::NEAREST);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            // the depth formats of the g-buffer and the default framebuffer have to match, see blit_depth
+            framebuffer::blit_depth_fbo(g_buffer, 0, SCR_WIDTH, SCR_HEIGHT);
 
             // 3. render lights on top of scene
             // --------------------------------
+            // all visible light cubes are drawn in a single instanced call, each with its own color
+            let (light_box_transforms, light_box_colors): (Vec<_>, Vec<_>) = light_positions.iter()
+                .zip(light_colors.iter())
+                .filter(|(pos, _)| frustum.contains_sphere(pos, LIGHT_BOX_RADIUS))
+                .map(|(pos, color)| {
+                    let mut model = util::glm::diag_mat4(1.0);
+                    model = glm::translate(&model, pos);
+                    model = glm::scale(&model, &util::glm::scale_vec3(0.125));
+                    (model, *color)
+                })
+                .unzip();
             shader_light_box.use_shader();
             shader_light_box.set_mat4("projection".to_string(), &projection);
             shader_light_box.set_mat4("view".to_string(), &view);
-            for (i, pos) in light_positions.iter().enumerate() {
-                let mut model = util::glm::diag_mat4(1.0);
-                model = glm::translate(&model, pos);
-                model = glm::scale(&model, &util::glm::scale_vec3(0.125));
-                shader_light_box.set_mat4("model".to_string(), &model);
-                shader_light_box.set_vec3("lightColor".to_string(), &light_colors[i]);
-                render_cube();
+            let draw_light_boxes = || render_cube_instanced(&light_box_transforms, &light_box_colors);
+            if split_view {
+                util::render::split_screen(draw_light_boxes, draw_light_boxes);
+            } else {
+                draw_light_boxes();
             }
+            // the grid and axes are depth tested against the scene, like the light boxes
+            let draw_gizmos = || gizmos.draw(&projection, &view);
+            if split_view {
+                util::render::split_screen(draw_gizmos, draw_gizmos);
+            } else {
+                draw_gizmos();
+            }
+
+            // 4. raw g-buffer target on top of everything, if selected
+            // ---------------------------------------------------------
+            gbuffer_viewer.draw(g_position, g_normal, g_albedo_spec);
 
             drop(camera);
 
@@ -267,81 +314,6 @@ fn main() {
     }
 }
 
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right         
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right         
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left     
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right     
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
-}
-
 // renderQuad() renders a 1x1 XY quad in NDC
 // -----------------------------------------
 static mut QUAD_VAO: u32 = 0;
@@ -18,16 +18,21 @@ extern crate nalgebra_glm as glm;
 
 use std::{mem, ptr};
 use std::sync::Mutex;
-use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
+use glfw::{Action, Context, CursorMode, Key, MouseButton, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::primitives::render_cube;
 use lazy_static::lazy_static;
-use rand::Rng;
 use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared::debug::GBufferViewer;
+use learnopengl_shared::input::InputState;
 use learnopengl_shared::model::Model;
+use learnopengl_shared::ssao;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
+const NEAR_PLANE: f32 = 0.1;
+const FAR_PLANE: f32 = 100.0;
 
 // camera
 lazy_static! {
@@ -41,9 +46,9 @@ static mut FIRST_MOUSE: bool = false;
 static mut DELTA_TIME: f32 = 0.0;
 static mut LAST_FRAME: f32 = 0.0;
 
-fn our_lerp(a: f32, b: f32, f: f32) -> f32 {
-    a + f * (b - a)
-}
+// a left click prints the depth under the crosshair (the center of the screen, as the cursor is captured)
+static mut DEPTH_READOUT_REQUESTED: bool = false;
+static mut DEPTH_READOUT_BUTTON_PRESSED: bool = false;
 
 fn main() {
     // glfw: initialize and configure
@@ -165,27 +170,12 @@ fn main() {
 
         // generate sample kernel
         // ----------------------
-        let mut rng = rand::thread_rng();
-        let mut ssao_kernel: Vec<glm::TVec3<f32>> = Vec::new();
-        for i in 0..64 {
-            let mut sample = glm::vec3(rng.gen::<f32>() * 2.0 - 1.0, rng.gen::<f32>() * 2.0 - 1.0, rng.gen::<f32>());
-            sample = glm::normalize(&sample);
-            sample *= rng.gen::<f32>();
-            let mut scale = i as f32 / 64.0;
-
-            // scale samples s.t. they're more aligned to center of kernel
-            scale = our_lerp(0.1, 0.1, scale * scale);
-            sample *= scale;
-            ssao_kernel.push(sample);
-        }
+        let mut rng = ssao::default_rng();
+        let ssao_kernel = ssao::generate_kernel(&mut rng, 64);
 
         // generate noise texture
         // ----------------------
-        let mut ssao_noise: Vec<glm::TVec3<f32>> = Vec::new();
-        for _ in 0..16 {
-            let noise = glm::vec3(rng.gen::<f32>() * 2.0 - 1.0, rng.gen::<f32>() * 2.0 - 1.0, 0.0); // rotate around z-axis (in tangent space)
-            ssao_noise.push(noise);
-        }
+        let ssao_noise = ssao::generate_noise(&mut rng, 16);
         let mut noise_texture = 0u32; gl::GenTextures(1, &mut noise_texture);
         gl::BindTexture(gl::TEXTURE_2D, noise_texture);
         gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA32F as _, 4, 4, 0, gl::RGB, gl::FLOAT, ssao_noise.as_ptr() as _);
@@ -213,6 +203,10 @@ fn main() {
         shader_ssao_blur.use_shader();
         shader_ssao_blur.set_int("ssaoInput".to_string(), 0);
 
+        // G cycles through the g-buffer targets (view space positions)
+        let mut input = InputState::new();
+        let mut gbuffer_viewer = GBufferViewer::new();
+
         // render loop
         // -----------
         while !window.should_close() {
@@ -225,6 +219,10 @@ fn main() {
             // input
             // -----
             process_input(&mut window);
+            input.update(&window);
+            if input.just_pressed(Key::G) {
+                println!("g-buffer view: {}", gbuffer_viewer.cycle().name());
+            }
 
             let camera = CAMERA.lock().unwrap();
 
@@ -237,7 +235,7 @@ fn main() {
             // -----------------------------------------------------------------
             gl::BindFramebuffer(gl::FRAMEBUFFER, g_buffer);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-            let projection = glm::perspective(camera.zoom().to_radians(), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+            let projection = glm::perspective(camera.zoom().to_radians(), SCR_WIDTH as f32 / SCR_HEIGHT as f32, NEAR_PLANE, FAR_PLANE);
             let view = camera.get_view_matrix();
             shader_geometry_pass.use_shader();
             shader_geometry_pass.set_mat4("projection".to_string(), &projection);
@@ -257,6 +255,23 @@ fn main() {
             model = glm::scale(&model, &util::glm::scale_vec3(1.0));
             shader_geometry_pass.set_mat4("model".to_string(), &model);
             backpack.draw(&shader_geometry_pass);
+            if DEPTH_READOUT_REQUESTED {
+                // the depth buffer holds nonlinear values, undo the projection to get view space back
+                let (x, y) = (SCR_WIDTH / 2, SCR_HEIGHT / 2);
+                let mut depth = 0f32;
+                gl::ReadPixels(x as _, y as _, 1, 1, gl::DEPTH_COMPONENT, gl::FLOAT, ptr::addr_of_mut!(depth) as _);
+                let ndc = glm::vec3(
+                    (x as f32 + 0.5) / SCR_WIDTH as f32 * 2.0 - 1.0,
+                    (y as f32 + 0.5) / SCR_HEIGHT as f32 * 2.0 - 1.0,
+                    depth * 2.0 - 1.0
+                );
+                let view_pos = util::glm::reconstruct_view_pos(&ndc, &glm::inverse(&projection));
+                println!(
+                    "depth {:.4} -> linear depth {:.3}, view space position {:?}",
+                    depth, util::glm::linearize_depth(depth, NEAR_PLANE, FAR_PLANE), view_pos.as_slice()
+                );
+                DEPTH_READOUT_REQUESTED = false;
+            }
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
             // 2. generate SSAO texture
@@ -311,6 +326,10 @@ fn main() {
             gl::BindTexture(gl::TEXTURE_2D, ssao_color_buffer_blur);
             render_quad();
 
+            // 5. raw g-buffer target instead, if selected
+            // -------------------------------------------
+            gbuffer_viewer.draw(g_position, g_normal, g_albedo);
+
             drop(camera);
 
             // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
@@ -321,81 +340,6 @@ fn main() {
     }
 }
 
-// renderCube() renders a 1x1 3D cube in NDC.
-// -------------------------------------------------
-static mut CUBE_VAO: u32 = 0;
-static mut CUBE_VBO: u32 = 0;
-fn render_cube() {
-    unsafe {
-        // initialize (if necessary)
-        if CUBE_VAO == 0 {
-            let vertices = [
-                // back face
-                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right         
-                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
-                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
-                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
-                // front face
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
-                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
-                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
-                // left face
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
-                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
-                // right face
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right         
-                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
-                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
-                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left     
-                // bottom face
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
-                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
-                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
-                // top face
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right     
-                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
-                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
-                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
-            ];
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
-            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
-            // fill buffer
-            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
-            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
-            // link vertex attributes
-            gl::BindVertexArray(CUBE_VAO);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-        }
-        // render Cube
-        gl::BindVertexArray(CUBE_VAO);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
-        gl::BindVertexArray(0);
-    }
-}
-
 // renderQuad() renders a 1x1 XY quad in NDC
 // -----------------------------------------
 static mut QUAD_VAO: u32 = 0;
@@ -452,6 +396,16 @@ fn process_input(window: &mut Window) {
             CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
         }
     }
+
+    unsafe {
+        if window.get_mouse_button(MouseButton::Button1) == Action::Press && !DEPTH_READOUT_BUTTON_PRESSED {
+            DEPTH_READOUT_REQUESTED = true;
+            DEPTH_READOUT_BUTTON_PRESSED = true;
+        }
+        if window.get_mouse_button(MouseButton::Button1) == Action::Release {
+            DEPTH_READOUT_BUTTON_PRESSED = false;
+        }
+    }
 }
 
 fn framebuffer_size_callback(
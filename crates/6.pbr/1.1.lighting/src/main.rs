@@ -16,16 +16,20 @@
 
 extern crate nalgebra_glm as glm;
 
-use std::{mem, ptr};
+use std::mem;
 use std::sync::Mutex;
 use glfw::{Action, Context, CursorMode, Key, OpenGlProfileHint, Window, WindowHint};
 use learnopengl_shared::util;
 use learnopengl_shared::shader::Shader;
+use learnopengl_shared::debug::WireframeOverlay;
+use learnopengl_shared::primitives::render_sphere;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
+static mut WIREFRAME: bool = false;
+static mut WIREFRAME_KEY_PRESSED: bool = false;
 
 // camera
 lazy_static! {
@@ -97,6 +101,9 @@ fn main() {
             glm::vec3(300.0, 300.0, 300.0),
             glm::vec3(300.0, 300.0, 300.0)
         ];
+        // shaded + wireframe overlay for inspecting the sphere tessellation (toggled with F)
+        let mut wireframe_overlay = WireframeOverlay::new(glm::vec3(0.05, 0.05, 0.05));
+
         let nr_rows = 7;
         let nr_columns = 7;
         let spacing = 2.5;
@@ -135,24 +142,34 @@ fn main() {
             shader.set_vec3("camPos".to_string(), &camera.position());
 
             // render rows*column number of spheres with varying metallic/roughness values scaled by rows and columns respectively
+            wireframe_overlay.set_enabled(WIREFRAME);
             for row in 0..nr_rows {
-                shader.set_float("metallic".to_string(), row as f32 / nr_rows as f32);
                 for col in 0..nr_columns {
-                    // we clamp the roughness to 0.05 - 1.0 as perfectly smooth surfaces (roughness of 0.0) tend to look a bit off
-                    // on direct lighting.
-                    shader.set_float("roughness".to_string(), util::glm::clamp(col as f32 / nr_columns as f32, 0.05, 1.0));
-
                     let mut model = util::glm::diag_mat4(1.0);
                     model = glm::translate(&model, &glm::vec3(
                         (col - nr_columns / 2) as f32 * spacing,
                         (row - nr_rows / 2) as f32 * spacing,
                         0.0
                     ));
-                    shader.set_mat4("model".to_string(), &model);
-                    shader.set_mat3("normalMatrix".to_string(), &glm::transpose(&glm::inverse(&util::glm::mat3_from_mat4(&model))));
-                    render_sphere();
+                    // the overlay switches programs for the wireframe pass, so (re)activate the PBR shader in the solid pass
+                    wireframe_overlay.draw(|| {
+                        shader.use_shader();
+                        shader.set_float("metallic".to_string(), row as f32 / nr_rows as f32);
+                        // we clamp the roughness to 0.05 - 1.0 as perfectly smooth surfaces (roughness of 0.0) tend to look a bit off
+                        // on direct lighting.
+                        shader.set_float("roughness".to_string(), util::glm::clamp(col as f32 / nr_columns as f32, 0.05, 1.0));
+                        shader.set_mat4("model".to_string(), &model);
+                        shader.set_mat3("normalMatrix".to_string(), &glm::transpose(&glm::inverse(&util::glm::mat3_from_mat4(&model))));
+                        render_sphere();
+                    }, |wire_shader| {
+                        wire_shader.set_mat4("projection".to_string(), &projection);
+                        wire_shader.set_mat4("view".to_string(), &view);
+                        wire_shader.set_mat4("model".to_string(), &model);
+                        render_sphere();
+                    });
                 }
             }
+            shader.use_shader();
 
             // render light source (simply re-render sphere at light positions)
             // this looks a bit off as we use the same shader, but it'll make their positions obvious and
@@ -206,6 +223,16 @@ fn process_input(window: &mut Window) {
             CAMERA.lock().unwrap().process_keyboard(Movement::RIGHT, DELTA_TIME);
         }
     }
+
+    unsafe {
+        if window.get_key(Key::F) == Action::Press && !WIREFRAME_KEY_PRESSED {
+            WIREFRAME = !WIREFRAME;
+            WIREFRAME_KEY_PRESSED = true;
+        }
+        if window.get_key(Key::F) == Action::Release {
+            WIREFRAME_KEY_PRESSED = false;
+        }
+    }
 }
 
 fn framebuffer_size_callback(
@@ -248,90 +275,4 @@ fn scroll_callback(
     y_offset: f64
 ) {
     CAMERA.lock().unwrap().process_mouse_scroll(y_offset as f32);
-}
-
-// renders (and builds at first invocation) a sphere
-// -------------------------------------------------
-static mut SPHERE_VAO: u32 = 0;
-static mut INDEX_COUNT: u32 = 0;
-fn render_sphere() {
-    unsafe {
-        if SPHERE_VAO == 0 {
-            gl::GenVertexArrays(1, ptr::addr_of_mut!(SPHERE_VAO));
-
-            let (mut vbo, mut ebo) = (0u32, 0u32);
-            gl::GenBuffers(1, &mut vbo);
-            gl::GenBuffers(1, &mut ebo);
-
-            let mut positions: Vec<glm::TVec3<f32>> = Vec::new();
-            let mut uv: Vec<glm::TVec2<f32>> = Vec::new();
-            let mut normals: Vec<glm::TVec3<f32>> = Vec::new();
-            let mut indices: Vec<u32> = Vec::new();
-
-            const X_SEGMENTS: u32 = 64;
-            const Y_SEGMENTS: u32 = 64;
-            use std::f32::consts::PI;
-            for x in 0..=X_SEGMENTS {
-                for y in 0..=Y_SEGMENTS {
-                    let x_segment = x as f32 / X_SEGMENTS as f32;
-                    let y_segment = y as f32 / Y_SEGMENTS as f32;
-                    let x_pos = (x_segment * 2.0 * PI).cos() * (y_segment * PI).sin();
-                    let y_pos = (y_segment * PI).cos();
-                    let z_pos = (x_segment * 2.0 * PI).sin() * (y_segment * PI).sin();
-
-                    positions.push(glm::vec3(x_pos, y_pos, z_pos));
-                    uv.push(glm::vec2(x_segment, y_segment));
-                    normals.push(glm::vec3(x_pos, y_pos, z_pos));
-                }
-            }
-
-            let mut odd_row = false;
-            for y in 0..Y_SEGMENTS {
-                if !odd_row { // even rows: y == 0, y == 2; and so on
-                    for x in 0..=X_SEGMENTS {
-                        indices.push(y * (X_SEGMENTS + 1) + x);
-                        indices.push((y + 1) * (X_SEGMENTS + 1) + x);
-                    }
-                } else {
-                    for x in (0..=X_SEGMENTS).rev() {
-                        indices.push((y + 1) * (X_SEGMENTS + 1) + x);
-                        indices.push(y * (X_SEGMENTS + 1) + x);
-                    }
-                }
-                odd_row = !odd_row;
-            }
-            INDEX_COUNT = indices.len() as u32;
-
-            let mut data: Vec<f32> = Vec::new();
-            for i in 0..positions.len() {
-                data.push(positions[i].x);
-                data.push(positions[i].y);
-                data.push(positions[i].z);
-                if normals.len() > 0 {
-                    data.push(normals[i].x);
-                    data.push(normals[i].y);
-                    data.push(normals[i].z);
-                }
-                if uv.len() > 0 {
-                    data.push(uv[i].x);
-                    data.push(uv[i].y);
-                }
-            }
-            gl::BindVertexArray(SPHERE_VAO);
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl::BufferData(gl::ARRAY_BUFFER, (data.len() * mem::size_of::<f32>()) as _, data.as_ptr() as _, gl::STATIC_DRAW);
-            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-            gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (indices.len() * mem::size_of::<u32>()) as _, indices.as_ptr() as _, gl::STATIC_DRAW);
-            let stride = (3 + 2 + 3) * mem::size_of::<f32>();
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride as _, ptr::null());
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride as _, (3 * mem::size_of::<f32>()) as _);
-            gl::EnableVertexAttribArray(2);
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, stride as _, (6 * mem::size_of::<f32>()) as _);
-        }
-
-        gl::BindVertexArray(SPHERE_VAO);
-        gl::DrawElements(gl::TRIANGLE_STRIP, INDEX_COUNT as _, gl::UNSIGNED_INT, ptr::null());
-    }
 }
\ No newline at end of file
@@ -24,6 +24,7 @@ use learnopengl_shared::{filesystem, util};
 use learnopengl_shared::shader::Shader;
 use lazy_static::lazy_static;
 use learnopengl_shared::camera::{Camera, Movement};
+use learnopengl_shared_ex::model::Model;
 
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
@@ -79,6 +80,8 @@ fn main() {
         // build and compile shaders
         // -------------------------
         let shader = Shader::new("1.2.pbr.vs".to_string(), "1.2.pbr.fs".to_string(), None);
+        // same lighting, but reading the material from the textures of a glTF model
+        let gltf_shader = Shader::new("1.2.pbr.vs".to_string(), "1.2.gltf_pbr.fs".to_string(), None);
 
         shader.use_shader();
         shader.set_int("albedoMap".to_string(), 0);
@@ -95,6 +98,10 @@ fn main() {
         let roughness = load_texture(filesystem::get_path("resources/textures/pbr/rusted_iron/roughness.png".to_string()));
         let ao = load_texture(filesystem::get_path("resources/textures/pbr/rusted_iron/ao.png".to_string()));
 
+        // load a glTF model with its embedded metallic-roughness material
+        // ----------------------------------------------------------------
+        let gltf_cube = Model::from_gltf(filesystem::get_path("resources/objects/gltf_cube/cube.glb".to_string()));
+
         // lights
         // ------
         let light_positions = [
@@ -113,6 +120,8 @@ fn main() {
         let projection = glm::perspective(camera.zoom(), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
         shader.use_shader();
         shader.set_mat4("projection".to_string(), &projection);
+        gltf_shader.use_shader();
+        gltf_shader.set_mat4("projection".to_string(), &projection);
         drop(camera);
 
         // render loop
@@ -184,6 +193,20 @@ fn main() {
                 render_sphere();
             }
 
+            // render the glTF cube left of the sphere grid, lit by the same lights
+            gltf_shader.use_shader();
+            gltf_shader.set_mat4("view".to_string(), &view);
+            gltf_shader.set_vec3("camPos".to_string(), &camera.position());
+            for (i, light_position) in light_positions.iter().enumerate() {
+                gltf_shader.set_vec3(format!("lightPositions[{}]", i), light_position);
+                gltf_shader.set_vec3(format!("lightColors[{}]", i), &light_colors[i]);
+            }
+            let mut model = util::glm::diag_mat4(1.0);
+            model = glm::translate(&model, &glm::vec3((-(nr_columns / 2) - 1) as f32 * spacing, 0.0, 0.0));
+            gltf_shader.set_mat4("model".to_string(), &model);
+            gltf_shader.set_mat3("normalMatrix".to_string(), &util::glm::normal_matrix(&model));
+            gltf_cube.draw(&gltf_shader);
+
             drop(camera);
 
             // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
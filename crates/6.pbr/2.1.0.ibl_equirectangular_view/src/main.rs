@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use glfw::{Action, Context, Key, OpenGlProfileHint, Window, WindowHint};
+use learnopengl_shared::{filesystem, primitives, util};
+use learnopengl_shared::shader::Shader;
+
+// settings
+const SCR_WIDTH: u32 = 1024;
+const SCR_HEIGHT: u32 = 512;
+
+// tone mapping exposure, adjusted with the up/down arrow keys
+static mut EXPOSURE: f32 = 1.0;
+
+// timing
+static mut DELTA_TIME: f32 = 0.0;
+static mut LAST_FRAME: f32 = 0.0;
+
+fn main() {
+    // glfw: initialize and configure
+    // ------------------------------
+    let mut glfw = glfw::init(glfw::fail_on_errors)
+        .expect("Failed to initialise GLFW.");
+
+    glfw.window_hint(WindowHint::ContextVersionMajor(3));
+    glfw.window_hint(WindowHint::ContextVersionMinor(3));
+    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+
+    // glfw window creation
+    // --------------------
+    let (mut window, _events) = glfw.create_window(
+        SCR_WIDTH, SCR_HEIGHT,
+        "LearnOpenGL", glfw::WindowMode::Windowed)
+        .expect("Failed to create GLFW window.");
+    window.set_framebuffer_size_callback(framebuffer_size_callback);
+    window.make_current();
+
+    // load all OpenGL function pointers
+    // ---------------------------------
+    gl::load_with(|s| window.get_proc_address(s) as *const _);
+
+    unsafe {
+        // build and compile shaders
+        // -------------------------
+        let shader = Shader::new("2.1.0.equirectangular_view.vs".to_string(), "2.1.0.equirectangular_view.fs".to_string(), None);
+
+        // load the HDR environment map as a floating point texture
+        // ---------------------------------------------------------
+        let hdr_image = util::image::load_hdr(filesystem::get_path("resources/textures/hdr/newport_loft.hdr".to_string()))
+            .expect("Failed to load HDR image.");
+        let hdr_texture = util::image::upload_hdr_texture(&hdr_image);
+
+        // shader configuration
+        // --------------------
+        shader.use_shader();
+        shader.set_int("equirectangularMap".to_string(), 0);
+
+        // render loop
+        // -----------
+        while !window.should_close() {
+            // per-frame time logic
+            // --------------------
+            let current_frame = glfw.get_time() as f32;
+            DELTA_TIME = current_frame - LAST_FRAME;
+            LAST_FRAME = current_frame;
+
+            // input
+            // -----
+            process_input(&mut window);
+
+            // render
+            // ------
+            gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+
+            // draw the whole equirectangular map onto a screen filling quad
+            shader.use_shader();
+            shader.set_float("exposure".to_string(), EXPOSURE);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, hdr_texture);
+            primitives::render_quad();
+
+            // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
+            // -------------------------------------------------------------------------------
+            window.swap_buffers();
+            glfw.poll_events();
+        }
+
+        // optional: de-allocate all resources once they've outlived their purpose:
+        // ------------------------------------------------------------------------
+        gl::DeleteTextures(1, &hdr_texture);
+    }
+}
+
+// process all input: query GLFW whether relevant keys are pressed/released this frame and react accordingly
+// ---------------------------------------------------------------------------------------------------------
+fn process_input(window: &mut Window) {
+    if window.get_key(Key::Escape) == Action::Press {
+        window.set_should_close(true);
+    }
+
+    unsafe {
+        if window.get_key(Key::Down) == Action::Press {
+            if EXPOSURE > 0.0 {
+                EXPOSURE -= 0.5 * DELTA_TIME;
+            } else {
+                EXPOSURE = 0.0;
+            }
+        } else if window.get_key(Key::Up) == Action::Press {
+            EXPOSURE += 0.5 * DELTA_TIME;
+        }
+    }
+}
+
+// glfw: whenever the window size changed (by OS or user resize) this callback function executes
+// ---------------------------------------------------------------------------------------------
+fn framebuffer_size_callback(
+    _: &mut Window,
+    width: i32,
+    height: i32
+) {
+    // make sure the viewport matches the new window dimensions; note that width and
+    // height will be significantly larger than specified on retina displays.
+    unsafe {
+        gl::Viewport(0, 0, width, height);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+extern crate nalgebra_glm as glm;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Mutex;
+use glfw::{Action, Key};
+use lazy_static::lazy_static;
+use learnopengl_shared::{filesystem, primitives, texture, util};
+use learnopengl_shared::app::App;
+use learnopengl_shared::camera::Camera;
+use learnopengl_shared::shader::Shader;
+use learnopengl_shared::skybox::EquirectSkybox;
+
+// settings
+const SCR_WIDTH: u32 = 1280;
+const SCR_HEIGHT: u32 = 720;
+// edge length of the faces of the environment cubemap
+const ENVIRONMENT_SIZE: u32 = 512;
+
+// camera
+lazy_static! {
+    static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new_position(glm::vec3(0.0, 0.0, 3.0)));
+}
+
+fn main() {
+    // glfw: initialize, create the window and load all OpenGL function pointers
+    // -------------------------------------------------------------------------
+    let mut app = App::new(SCR_WIDTH, SCR_HEIGHT, "LearnOpenGL");
+    app.set_camera(&CAMERA);
+
+    // B switches the background between the baked cubemap and sampling the equirectangular map directly
+    let use_cubemap = Rc::new(Cell::new(true));
+    let use_cubemap_toggle = Rc::clone(&use_cubemap);
+    app.window.set_key_callback(move |_, key, _, action, _| {
+        if key == Key::B && action == Action::Press {
+            use_cubemap_toggle.set(!use_cubemap_toggle.get());
+            println!("Background: {}", if use_cubemap_toggle.get() { "cubemap" } else { "equirectangular map" });
+        }
+    });
+
+    unsafe {
+        // configure global opengl state
+        // -----------------------------
+        gl::Enable(gl::DEPTH_TEST);
+        gl::DepthFunc(gl::LEQUAL); // set depth function to less than AND equal for skybox depth trick.
+
+        // build and compile shaders
+        // -------------------------
+        let background_shader = Shader::new("2.1.1.background.vs".to_string(), "2.1.1.background.fs".to_string(), None);
+        background_shader.use_shader();
+        background_shader.set_int("environmentMap".to_string(), 0);
+        // lights the sphere in front of the camera with the diffuse irradiance of the environment only
+        let sh_shader = Shader::new(
+            filesystem::get_path("resources/shaders/ibl/sh_ambient.vs".to_string()),
+            filesystem::get_path("resources/shaders/ibl/sh_ambient.fs".to_string()),
+            None
+        );
+        sh_shader.use_shader();
+        sh_shader.set_int("texture_diffuse1".to_string(), 0);
+        sh_shader.set_bool("gamma".to_string(), true);
+
+        // pbr: load the HDR environment map and convert it to an environment cubemap
+        // ---------------------------------------------------------------------------
+        let hdr_image = util::image::load_hdr(filesystem::get_path("resources/textures/hdr/newport_loft.hdr".to_string()))
+            .expect("Failed to load HDR image.");
+        let hdr_texture = util::image::upload_hdr_texture(&hdr_image);
+        let env_cubemap = util::ibl::equirect_to_cubemap(hdr_texture, ENVIRONMENT_SIZE);
+        // owns the HDR texture from here on
+        let equirect_skybox = EquirectSkybox::from_texture(hdr_texture);
+
+        // project the (linear) environment onto spherical harmonics for the sphere's ambient lighting
+        // ---------------------------------------------------------------------------------------------
+        let sh_coefficients = texture::compute_sh_irradiance(env_cubemap, false);
+        sh_shader.use_shader();
+        texture::upload_sh_coefficients(&sh_shader, "shCoefficients", &sh_coefficients);
+        let albedo = util::image::load_texture_2d(filesystem::get_path("resources/textures/pbr/plastic/albedo.png".to_string()), true);
+
+        // render loop
+        // -----------
+        app.run_with((background_shader, sh_shader, equirect_skybox), |(background_shader, sh_shader, equirect_skybox), _| {
+            // render
+            // ------
+            gl::ClearColor(0.2, 0.3, 0.3, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+            let camera = CAMERA.lock().unwrap();
+            let projection = glm::perspective(camera.zoom().to_radians(), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+
+            // render the sphere with the spherical harmonics ambient
+            sh_shader.use_shader();
+            sh_shader.set_mat4("projection".to_string(), &projection);
+            sh_shader.set_mat4("view".to_string(), &camera.get_view_matrix());
+            sh_shader.set_mat4("model".to_string(), &util::glm::diag_mat4(1.0));
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, albedo);
+            primitives::render_sphere();
+
+            // render skybox (render as last to prevent overdraw)
+            if !use_cubemap.get() {
+                equirect_skybox.draw(&camera.get_view_matrix(), &projection);
+                return;
+            }
+            background_shader.use_shader();
+            background_shader.set_mat4("projection".to_string(), &projection);
+            background_shader.set_mat4("view".to_string(), &camera.get_view_matrix());
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, env_cubemap);
+            primitives::render_cube();
+        });
+
+        // optional: de-allocate all resources once they've outlived their purpose:
+        // ------------------------------------------------------------------------
+        gl::DeleteTextures(1, &env_cubemap);
+        gl::DeleteTextures(1, &albedo);
+    }
+}
\ No newline at end of file
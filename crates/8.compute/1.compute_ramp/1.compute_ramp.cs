@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 430 core
+layout (local_size_x = 64) in;
+
+layout (std430, binding = 0) buffer Ramp
+{
+    float values[];
+};
+
+uniform float step;
+
+void main()
+{
+    uint index = gl_GlobalInvocationID.x;
+    if (index < values.length())
+        values[index] = float(index) * step;
+}
\ No newline at end of file
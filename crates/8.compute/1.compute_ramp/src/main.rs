@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use glfw::{Context, OpenGlProfileHint, WindowHint};
+use learnopengl_shared::shader::Shader;
+use learnopengl_shared::ssbo::Ssbo;
+
+// number of floats written by the compute shader and the work group size declared in it
+const VALUE_COUNT: usize = 1024;
+const LOCAL_SIZE_X: usize = 64;
+const STEP: f32 = 0.5;
+
+fn main() {
+    // glfw: initialize and configure
+    // ------------------------------
+    let mut glfw = glfw::init(glfw::fail_on_errors)
+        .expect("Failed to initialise GLFW.");
+
+    // compute shaders are core since OpenGL 4.3
+    glfw.window_hint(WindowHint::ContextVersionMajor(4));
+    glfw.window_hint(WindowHint::ContextVersionMinor(3));
+    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+    // nothing gets drawn, we only need the context
+    glfw.window_hint(WindowHint::Visible(false));
+
+    // glfw window creation
+    // --------------------
+    let (mut window, _events) = glfw.create_window(
+        1, 1,
+        "LearnOpenGL", glfw::WindowMode::Windowed)
+        .expect("Failed to create GLFW window.");
+    window.make_current();
+
+    // load all OpenGL function pointers
+    // ---------------------------------
+    gl::load_with(|s| window.get_proc_address(s) as *const _);
+
+    // build and compile our compute shader program
+    // --------------------------------------------
+    let compute_shader = Shader::new_compute("1.compute_ramp.cs".to_string());
+
+    // allocate the shader storage buffer the compute shader writes into
+    // -----------------------------------------------------------------
+    let ssbo = Ssbo::new(&[0f32; VALUE_COUNT], 0);
+
+    // fill the buffer with a ramp: values[i] = i * step
+    // -------------------------------------------------
+    compute_shader.use_shader();
+    compute_shader.set_float("step".to_string(), STEP);
+    compute_shader.dispatch(VALUE_COUNT.div_ceil(LOCAL_SIZE_X) as u32, 1, 1);
+    // make sure the shader writes are visible before reading the buffer back
+    Shader::memory_barrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+
+    // read back and verify the results
+    // --------------------------------
+    let values = ssbo.read_back();
+    let mismatches = values.iter()
+        .enumerate()
+        .filter(|(i, value)| **value != *i as f32 * STEP)
+        .count();
+    if mismatches == 0 {
+        println!("compute dispatch wrote all {} values: {:?} ... {:?}", VALUE_COUNT, &values[..4], &values[VALUE_COUNT - 4..]);
+    } else {
+        println!("compute dispatch produced {} wrong values out of {}", mismatches, VALUE_COUNT);
+    }
+
+    // glfw: terminate, clearing all previously allocated GLFW resources.
+    // ------------------------------------------------------------------
+    // glfw will terminate automatically by dropping,
+    // hence we don't need to terminate it manually.
+}
\ No newline at end of file
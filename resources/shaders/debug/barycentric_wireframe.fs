@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+in vec3 Barycentric;
+in vec3 FaceNormal;
+
+uniform vec3 wireColor;
+uniform vec3 fillColor;
+uniform float lineWidth;
+
+void main()
+{
+    // distance to the closest edge in screen space pixels, fwidth keeps the lines equally thick at every distance
+    vec3 d = fwidth(Barycentric);
+    vec3 a = smoothstep(vec3(0.0), d * lineWidth, Barycentric);
+    float edge = 1.0 - min(min(a.x, a.y), a.z);
+    // simple head light so the shape stays readable
+    vec3 shaded = fillColor * (0.3 + 0.7 * abs(FaceNormal.z));
+    FragColor = vec4(mix(shaded, wireColor, edge), 1.0);
+}
\ No newline at end of file
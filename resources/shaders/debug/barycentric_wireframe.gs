@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+layout (triangles) in;
+layout (triangle_strip, max_vertices = 3) out;
+
+in VS_OUT {
+    vec3 viewPos;
+} gs_in[];
+
+out vec3 Barycentric;
+out vec3 FaceNormal;
+
+void main()
+{
+    // flat face normal in view space, so meshes without (or with broken) normals still shade
+    vec3 normal = normalize(cross(gs_in[1].viewPos - gs_in[0].viewPos, gs_in[2].viewPos - gs_in[0].viewPos));
+    for (int i = 0; i < 3; i++)
+    {
+        gl_Position = gl_in[i].gl_Position;
+        Barycentric = vec3(0.0);
+        Barycentric[i] = 1.0;
+        FaceNormal = normal;
+        EmitVertex();
+    }
+    EndPrimitive();
+}
\ No newline at end of file
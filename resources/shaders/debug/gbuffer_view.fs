@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D gBufferTexture;
+// 1: position, 2: normal, 3: albedo, see debug::GBufferView
+uniform int gBufferView;
+uniform float positionScale;
+
+void main()
+{
+    vec3 value = texture(gBufferTexture, TexCoords).rgb;
+    vec3 color;
+    if (gBufferView == 1)
+        color = value * positionScale * 0.5 + 0.5; // [-1 / positionScale, 1 / positionScale] to [0, 1]
+    else if (gBufferView == 2)
+        color = value * 0.5 + 0.5;
+    else
+        color = value;
+    FragColor = vec4(color, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D hdrBuffer;
+uniform sampler3D lut;
+uniform float lutSize;
+uniform vec3 domainMin;
+uniform vec3 domainMax;
+uniform float exposure;
+uniform float intensity;
+
+void main()
+{
+    const float gamma = 2.2;
+    vec3 hdrColor = texture(hdrBuffer, TexCoords).rgb;
+    // exposure tone mapping and gamma correction first, the LUT expects display referred colors
+    vec3 mapped = vec3(1.0) - exp(-hdrColor * exposure);
+    mapped = pow(mapped, vec3(1.0 / gamma));
+    // then remap the color through the LUT, sampling at the texel centers
+    vec3 lutCoords = clamp((mapped - domainMin) / (domainMax - domainMin), 0.0, 1.0);
+    lutCoords = lutCoords * ((lutSize - 1.0) / lutSize) + 0.5 / lutSize;
+    vec3 graded = texture(lut, lutCoords).rgb;
+    FragColor = vec4(mix(mapped, graded, intensity), 1.0);
+}
\ No newline at end of file
@@ -13,25 +13,24 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-
 #version 330 core
 layout (location = 0) out vec4 FragColor;
+// only written when a second color buffer is bound, e.g. the bright color buffer of bloom
 layout (location = 1) out vec4 BrightColor;
 
-in VS_OUT {
-    vec3 FragPos;
-    vec3 Normal;
-    vec2 TexCoords;
-} fs_in;
+in vec2 TexCoords;
 
 uniform vec3 lightColor;
 
+// a round glow fading out towards the edge of the billboard, meant to be blended additively
 void main()
-{           
-    FragColor = vec4(lightColor, 1.0);
+{
+    float distance = length(TexCoords * 2.0 - 1.0);
+    float falloff = 1.0 - smoothstep(0.0, 1.0, distance);
+    FragColor = vec4(lightColor * falloff * falloff, 1.0);
     float brightness = dot(FragColor.rgb, vec3(0.2126, 0.7152, 0.0722));
     if(brightness > 1.0)
         BrightColor = vec4(FragColor.rgb, 1.0);
-	else
-		BrightColor = vec4(0.0, 0.0, 0.0, 1.0);
+    else
+        BrightColor = vec4(0.0, 0.0, 0.0, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+in vec3 Normal;
+in vec2 TexCoords;
+
+// coefficients computed by texture::compute_sh_irradiance (already convolved and divided by PI)
+uniform vec3 shCoefficients[9];
+uniform sampler2D texture_diffuse1;
+uniform bool gamma;
+
+vec3 evaluateSH(vec3 n)
+{
+    return shCoefficients[0] * 0.282095
+         + shCoefficients[1] * 0.488603 * n.y
+         + shCoefficients[2] * 0.488603 * n.z
+         + shCoefficients[3] * 0.488603 * n.x
+         + shCoefficients[4] * 1.092548 * n.x * n.y
+         + shCoefficients[5] * 1.092548 * n.y * n.z
+         + shCoefficients[6] * 0.315392 * (3.0 * n.z * n.z - 1.0)
+         + shCoefficients[7] * 1.092548 * n.x * n.z
+         + shCoefficients[8] * 0.546274 * (n.x * n.x - n.y * n.y);
+}
+
+void main()
+{
+    vec3 albedo = texture(texture_diffuse1, TexCoords).rgb;
+    vec3 ambient = max(evaluateSH(normalize(Normal)), vec3(0.0)) * albedo;
+    if (gamma)
+        ambient = pow(ambient, vec3(1.0 / 2.2));
+    FragColor = vec4(ambient, 1.0);
+}
\ No newline at end of file
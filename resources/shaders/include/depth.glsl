@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// depth helpers shared by the shaders, pulled in with #include "resources/shaders/include/depth.glsl"
+// (see util::glm::linearize_depth and util::glm::reconstruct_view_pos for the CPU side)
+
+// turns a [0, 1] depth buffer value of a perspective projection back into the linear distance along
+// the view direction, near for 0 and far for 1
+float LinearizeDepth(float depth, float near, float far)
+{
+    float z = depth * 2.0 - 1.0; // back to NDC
+    return (2.0 * near * far) / (far + near - z * (far - near));
+}
+
+// the view space position of the fragment at `texCoords` with the [0, 1] depth buffer value `depth`
+vec3 ReconstructViewPos(vec2 texCoords, float depth, mat4 inverseProjection)
+{
+    vec4 ndc = vec4(vec3(texCoords, depth) * 2.0 - 1.0, 1.0);
+    vec4 viewPos = inverseProjection * ndc;
+    return viewPos.xyz / viewPos.w;
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D image;
+
+// one pass of a separable 9-tap gaussian blur, alternated between the two directions
+uniform bool horizontal;
+uniform float weight[5] = float[] (0.2270270270, 0.1945945946, 0.1216216216, 0.0540540541, 0.0162162162);
+
+void main()
+{
+    vec2 texelSize = 1.0 / textureSize(image, 0);
+    vec2 direction = horizontal ? vec2(texelSize.x, 0.0) : vec2(0.0, texelSize.y);
+    vec3 result = texture(image, TexCoords).rgb * weight[0];
+    for(int i = 1; i < 5; ++i)
+    {
+        result += texture(image, TexCoords + direction * i).rgb * weight[i];
+        result += texture(image, TexCoords - direction * i).rgb * weight[i];
+    }
+    FragColor = vec4(result, 1.0);
+}
\ No newline at end of file
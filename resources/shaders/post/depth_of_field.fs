@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D sharpImage;
+uniform sampler2D blurredImage;
+// linear view space depth in the red channel, 0 where nothing was drawn
+uniform sampler2D depthMap;
+
+uniform float focus;
+uniform float aperture;
+
+void main()
+{
+    // the circle of confusion grows with the aperture and the relative distance to the focal plane,
+    // it picks how much of the blurred image replaces the sharp one
+    float depth = max(texture(depthMap, TexCoords).r, 0.0001);
+    float coc = clamp(aperture * abs(depth - focus) / depth, 0.0, 1.0);
+    vec3 sharp = texture(sharpImage, TexCoords).rgb;
+    vec3 blurred = texture(blurredImage, TexCoords).rgb;
+    FragColor = vec4(mix(sharp, blurred, coc), 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+// matches the order of util::post::Operator
+const int REINHARD = 0;
+const int EXPOSURE = 1;
+const int ACES = 2;
+const int UNCHARTED2 = 3;
+
+uniform sampler2D hdrBuffer;
+uniform int tonemapOperator;
+uniform float exposure;
+uniform float gamma;
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+vec3 aces(vec3 x)
+{
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+    return clamp((x * (a * x + b)) / (x * (c * x + d) + e), 0.0, 1.0);
+}
+
+// John Hable's filmic curve from Uncharted 2
+vec3 uncharted2Curve(vec3 x)
+{
+    const float A = 0.15; // shoulder strength
+    const float B = 0.50; // linear strength
+    const float C = 0.10; // linear angle
+    const float D = 0.20; // toe strength
+    const float E = 0.02; // toe numerator
+    const float F = 0.30; // toe denominator
+    return ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F;
+}
+
+vec3 uncharted2(vec3 x)
+{
+    const float whitePoint = 11.2;
+    const float exposureBias = 2.0;
+    return uncharted2Curve(exposureBias * x) / uncharted2Curve(vec3(whitePoint));
+}
+
+void main()
+{
+    vec3 hdrColor = texture(hdrBuffer, TexCoords).rgb;
+    vec3 mapped;
+    if (tonemapOperator == EXPOSURE)
+        mapped = vec3(1.0) - exp(-hdrColor * exposure);
+    else if (tonemapOperator == ACES)
+        mapped = aces(hdrColor * exposure);
+    else if (tonemapOperator == UNCHARTED2)
+        mapped = uncharted2(hdrColor * exposure);
+    else
+        mapped = (hdrColor * exposure) / (hdrColor * exposure + vec3(1.0));
+    // also gamma correct while we're at it
+    FragColor = vec4(pow(mapped, vec3(1.0 / gamma)), 1.0);
+}
\ No newline at end of file
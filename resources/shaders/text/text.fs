@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#version 330 core
+in vec2 TexCoords;
+out vec4 color;
+
+uniform sampler2D glyphs;
+uniform vec3 textColor;
+
+void main()
+{
+    // the atlas is white, its alpha channel holds the glyph coverage
+    color = vec4(textColor, texture(glyphs, TexCoords).a);
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+extern crate nalgebra_glm as glm;
+
+use learnopengl_shared::debug;
+use learnopengl_shared::shader::Shader;
+use crate::model::Model;
+
+// Default normal line values
+const MAGNITUDE: f32 = 0.05;
+const LINE_COLOR: [f32; 3] = [1.0, 1.0, 0.0];
+
+// Draws a short line along the normal of every vertex of a model, useful for checking the normals of
+// a model before lighting it. Meant to be drawn on top of the regular rendering of the same model.
+pub struct DebugNormals {
+    shader: Shader,
+    magnitude: f32,
+    color: glm::TVec3<f32>
+}
+
+impl DebugNormals {
+    pub fn new() -> Self {
+        let shader = debug::debug_normals_shader().unwrap_or_else(|err| panic!("{}", err));
+        Self {
+            shader,
+            magnitude: MAGNITUDE,
+            color: glm::vec3(LINE_COLOR[0], LINE_COLOR[1], LINE_COLOR[2])
+        }
+    }
+
+    // `mvp` is the full projection * view * model matrix of the model, the lines are computed in
+    // the model's own space
+    pub fn draw(&self, model: &Model, mvp: &glm::TMat4<f32>) {
+        self.shader.use_shader();
+        self.shader.set_mat4("mvp".to_string(), mvp);
+        self.shader.set_float("magnitude".to_string(), self.magnitude);
+        self.shader.set_vec3("lineColor".to_string(), &self.color);
+        model.draw(&self.shader);
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        self.magnitude
+    }
+
+    pub fn color(&self) -> glm::TVec3<f32> {
+        self.color
+    }
+
+    // length of the lines in the model's units
+    pub fn set_magnitude(&mut self, magnitude: f32) {
+        self.magnitude = magnitude;
+    }
+
+    pub fn set_color(&mut self, color: glm::TVec3<f32>) {
+        self.color = color;
+    }
+}
+
+impl Default for DebugNormals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
\ No newline at end of file
@@ -14,4 +14,5 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub mod model;
\ No newline at end of file
+pub mod model;
+pub mod debug;
\ No newline at end of file
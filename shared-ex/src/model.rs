@@ -17,34 +17,52 @@
 extern crate nalgebra_glm as glm;
 
 use gl::types::*;
+use image::RgbaImage;
 use russimp::node::Node;
 use russimp::scene::PostProcess;
 use russimp::scene::Scene as AIScene;
 use russimp::mesh::Mesh as AIMesh;
-use russimp::material::{Material as AIMaterial, TextureType};
+use russimp::material::{DataContent, Material as AIMaterial, PropertyTypeInfo, TextureType};
 use russimp::material::TextureType as AITextureType;
-use learnopengl_shared::mesh::{Mesh, Texture, Vertex};
+use learnopengl_shared::mesh::{Material, Mesh, Texture, TextureNaming, Vertex};
+use learnopengl_shared::mesh;
+use learnopengl_shared::camera::Frustum;
 use learnopengl_shared::shader::Shader;
-use learnopengl_shared::util;
+use learnopengl_shared::{filesystem, util};
+use std::collections::HashMap;
+use std::mem;
+use std::path::{Path, PathBuf};
 
 pub struct Model {
     // model data
     pub textures_loaded: Vec<Texture>, // stores all the textures loaded so far, optimization to make sure textures aren't loaded more than once.
     pub meshes: Vec<Mesh>,
     pub directory: String,
-    pub gamma_correction: bool
+    pub gamma_correction: bool,
+    texture_cache: TextureCache,
+    instance_vbo: u32 // per-instance model matrices shared by all meshes, 0 until set_instance_buffer is called
 }
 
 impl Model {
     // constructor, expects a filepath to a 3D model.
     pub fn new(path: String, gamma: bool) -> Self {
+        Self::new_ex(path, gamma, false)
+    }
+
+    // like new, optionally with compute_tangents applied right after loading
+    pub fn new_ex(path: String, gamma: bool, compute_tangents: bool) -> Self {
         let mut result = Self {
             textures_loaded: Vec::new(),
             meshes: Vec::new(),
             directory: String::new(),
-            gamma_correction: gamma
+            gamma_correction: gamma,
+            texture_cache: TextureCache::default(),
+            instance_vbo: 0
         };
         result.load_model(path);
+        if compute_tangents {
+            result.compute_tangents();
+        }
         result
     }
 
@@ -52,16 +70,59 @@ impl Model {
         Self::new(path, false)
     }
 
+    // loads a glTF 2.0 model (.gltf or .glb). Its metallic-roughness materials are mapped onto the usual sampler names:
+    // base color: texture_diffuseN
+    // metallic-roughness (roughness in G, metallic in B): texture_metallic_roughnessN
+    // normal: texture_normalN
+    // occlusion: texture_aoN
+    // emissive: texture_emissiveN
+    pub fn from_gltf(path: String) -> Self {
+        Self::from_gltf_ex(path, false)
+    }
+
+    pub fn from_gltf_ex(path: String, compute_tangents: bool) -> Self {
+        let mut result = Self {
+            textures_loaded: Vec::new(),
+            meshes: Vec::new(),
+            directory: String::new(),
+            gamma_correction: false,
+            texture_cache: TextureCache::default(),
+            instance_vbo: 0
+        };
+        result.load_model_with(path, Self::process_gltf_mesh);
+        if compute_tangents {
+            result.compute_tangents();
+        }
+        result
+    }
+
     // loads a model with supported ASSIMP extensions from file and stores the resulting meshes in the meshes vector.
     fn load_model(&mut self, path: String) {
-        // read file via ASSIMP
-        let scene = AIScene::from_file(
+        self.load_model_with(path, Self::process_mesh)
+    }
+
+    fn load_model_with(&mut self, path: String, process_mesh: MeshProcessor) {
+        // read file via ASSIMP, a missing file is reported by name rather than as a generic import error
+        if let Err(err) = filesystem::check_exists(&path) {
+            panic!("Failed to load model: {}", err);
+        }
+        let scene = match AIScene::from_file(
             path.as_str(),
             vec![PostProcess::Triangulate,
                  PostProcess::GenerateSmoothNormals,
                  PostProcess::FlipUVs,
                  PostProcess::CalculateTangentSpace]
-        ).unwrap();
+        ) {
+            Ok(scene) => scene,
+            // without a working ASSIMP, OBJ files can still be read by tobj
+            #[cfg(feature = "tobj")]
+            Err(err) if is_obj(&path) => {
+                log::warn!("ASSIMP failed to load {} ({}), falling back to tobj.", path, err);
+                self.load_obj(path);
+                return;
+            }
+            Err(err) => panic!("Failed to load model {}: {}", path, err)
+        };
         // retrieve the directory path of the filepath
         let path_bytes: Vec<u8> = path.bytes().collect();
         self.directory = String::from_utf8(
@@ -70,24 +131,24 @@ impl Model {
 
         // process ASSIMP's root node recursively
         if let Some(root) = &scene.root {
-            self.process_node(root, &scene);
+            self.process_node(root, &scene, process_mesh);
         }
     }
 
     // processes a node in a recursive fashion. Processes each individual mesh located at the node and repeats this process on its children nodes (if any).
-    fn process_node(&mut self, node: &Node, scene: &AIScene) {
+    fn process_node(&mut self, node: &Node, scene: &AIScene, process_mesh: MeshProcessor) {
         // process each mesh located at the current node
         for &mesh_i in node.meshes.iter() {
             let mesh_i = mesh_i as usize;
             // the node object only contains indices to index the actual objects in the scene.
             // the scene contains all the data, node is just to keep stuff organized (like relations between nodes).
             let mesh = &scene.meshes[mesh_i];
-            let result = self.process_mesh(mesh, scene);
+            let result = process_mesh(self, mesh, scene);
             self.meshes.push(result);
         }
         // after we've processed all of the meshes (if any) we then recursively process each of the children nodes
         for child in node.children.borrow().iter() {
-            self.process_node(child, scene);
+            self.process_node(child, scene, process_mesh);
         }
     }
 
@@ -97,9 +158,71 @@ impl Model {
         scene: &AIScene
     ) -> Mesh {
         // data to fill
+        let (vertices, indices) = Self::process_geometry(mesh);
+        let mut textures: Vec<Texture> = Vec::new();
+
+        // process materials
+        let material = &scene.materials[mesh.material_index as usize];
+        // we assume a convention for sampler names in the shaders. Each diffuse texture should be named
+        // as 'texture_diffuseN' where N is a sequential number ranging from 1 to MAX_SAMPLER_NUMBER.
+        // Same applies to other texture as the following list summarizes:
+        // diffuse: texture_diffuseN
+        // specular: texture_specularN
+        // normal: texture_normalN
+
+        // 1. diffuse maps
+        let diffuse_maps = self.load_material_textures(material, AITextureType::Diffuse, "texture_diffuse".to_string());
+        diffuse_maps.iter().for_each(|it| textures.push(it.clone()));
+        // 2. specular maps
+        let specular_maps = self.load_material_textures(material, AITextureType::Specular, "texture_specular".to_string());
+        specular_maps.iter().for_each(|it| textures.push(it.clone()));
+        // 3. normal maps
+        let normal_maps = self.load_material_textures(material, AITextureType::Height, "texture_normal".to_string());
+        normal_maps.iter().for_each(|it| textures.push(it.clone()));
+        // 4. height maps
+        let height_maps = self.load_material_textures(material, TextureType::Ambient, "texture_height".to_string());
+        height_maps.iter().for_each(|it| textures.push(it.clone()));
+
+        // return a mesh object created from the extracted mesh data
+        Mesh::new_ex(vertices, indices, textures, material_properties(material))
+    }
+
+    // glTF meshes carry the same vertex attributes, only their materials follow the metallic-roughness model
+    fn process_gltf_mesh(
+        &mut self,
+        mesh: &AIMesh,
+        scene: &AIScene
+    ) -> Mesh {
+        let (vertices, indices) = Self::process_geometry(mesh);
+        let mut textures: Vec<Texture> = Vec::new();
+
+        let material = &scene.materials[mesh.material_index as usize];
+        // ASSIMP reports the base color both as base color and as diffuse texture, the combined
+        // metallic-roughness texture ends up as an unknown texture type
+        let slots = [
+            (AITextureType::BaseColor, "texture_diffuse"),
+            (AITextureType::Unknown, "texture_metallic_roughness"),
+            (AITextureType::Normals, "texture_normal"),
+            (AITextureType::LightMap, "texture_ao"),
+            (AITextureType::Emissive, "texture_emissive")
+        ];
+        for (t_type, type_name) in slots {
+            let maps = self.load_material_textures(material, t_type, type_name.to_string());
+            textures.extend(maps);
+        }
+        // older ASSIMP versions only report the base color as diffuse texture
+        if !textures.iter().any(|it| it.type_s == "texture_diffuse") {
+            let diffuse_maps = self.load_material_textures(material, AITextureType::Diffuse, "texture_diffuse".to_string());
+            textures.extend(diffuse_maps);
+        }
+
+        Mesh::new_ex(vertices, indices, textures, material_properties(material))
+    }
+
+    // extracts the vertices and the triangle indices of a mesh
+    fn process_geometry(mesh: &AIMesh) -> (Vec<Vertex>, Vec<u32>) {
         let mut vertices: Vec<Vertex> = Vec::new();
         let mut indices: Vec<u32> = Vec::new();
-        let mut textures: Vec<Texture> = Vec::new();
 
         // walk through each of the mesh's vertices
         for (i, vertice) in mesh.vertices.iter().enumerate() {
@@ -148,30 +271,8 @@ impl Model {
                 indices.push(index);
             }
         }
-        // process materials
-        let material = &scene.materials[mesh.material_index as usize];
-        // we assume a convention for sampler names in the shaders. Each diffuse texture should be named
-        // as 'texture_diffuseN' where N is a sequential number ranging from 1 to MAX_SAMPLER_NUMBER.
-        // Same applies to other texture as the following list summarizes:
-        // diffuse: texture_diffuseN
-        // specular: texture_specularN
-        // normal: texture_normalN
-
-        // 1. diffuse maps
-        let diffuse_maps = self.load_material_textures(material, AITextureType::Diffuse, "texture_diffuse".to_string());
-        diffuse_maps.iter().for_each(|it| textures.push(it.clone()));
-        // 2. specular maps
-        let specular_maps = self.load_material_textures(material, AITextureType::Specular, "texture_specular".to_string());
-        specular_maps.iter().for_each(|it| textures.push(it.clone()));
-        // 3. normal maps
-        let normal_maps = self.load_material_textures(material, AITextureType::Height, "texture_normal".to_string());
-        normal_maps.iter().for_each(|it| textures.push(it.clone()));
-        // 4. height maps
-        let height_maps = self.load_material_textures(material, TextureType::Ambient, "texture_height".to_string());
-        height_maps.iter().for_each(|it| textures.push(it.clone()));
 
-        // return a mesh object created from the extracted mesh data
-        Mesh::new(vertices, indices, textures)
+        (vertices, indices)
     }
 
     // checks all material textures of a given type and loads the textures if they're not loaded yet.
@@ -199,7 +300,20 @@ impl Model {
             if !skip {
                 // if texture hasn't been loaded already, load it
                 let mut texture_load = Texture::default();
-                texture_load.id = texture_from_file(texture.1.borrow().filename.as_str(), self.directory.clone());
+                let filename = texture.1.borrow().filename.clone();
+                texture_load.id = match &texture.1.borrow().data {
+                    // binary glTF files embed their (usually PNG/JPEG encoded) images
+                    DataContent::Bytes(bytes) if !bytes.is_empty() => {
+                        self.texture_cache.get_or_upload(PathBuf::from(&filename), |_| texture_from_memory(bytes))
+                    }
+                    _ => match resolve_texture_path(&filename, Path::new(&self.directory)) {
+                        Some(path) => self.texture_cache.get_or_upload(path, texture_from_file),
+                        None => {
+                            log::warn!("Texture {} of the model in {} not found.", filename, self.directory);
+                            0
+                        }
+                    }
+                };
                 texture_load.type_s = type_name.clone();
                 texture_load.path = texture.1.borrow().filename.clone();
                 textures.push(texture_load.clone());
@@ -209,22 +323,316 @@ impl Model {
         textures
     }
 
+    // the number of GL textures created for the model, every texture file is uploaded once no matter
+    // how many of its meshes use it
+    pub fn texture_count(&self) -> usize {
+        self.texture_cache.texture_count()
+    }
+
     pub fn draw(&self, shader: &Shader) {
         for mesh in self.meshes.iter() {
             mesh.draw(shader);
         }
     }
+
+    // draws the model with the textures bound to the sampler names of `naming`, see mesh::TextureNaming
+    pub fn draw_with(&self, shader: &Shader, naming: &TextureNaming) {
+        for mesh in self.meshes.iter() {
+            mesh.draw_with(shader, naming);
+        }
+    }
+
+    // draws the meshes that `frustum` can see with `model` applied and skips the others, see
+    // mesh::draw_culled. Returns the number of meshes drawn.
+    pub fn draw_culled(&self, shader: &Shader, frustum: &Frustum, model: &glm::TMat4<f32>) -> usize {
+        mesh::draw_culled(self.meshes.iter(), shader, frustum, model)
+    }
+
+    // derives tangents and bitangents (vertex attributes 3 and 4) from the triangles and texture
+    // coordinates of every mesh, replacing whatever the file provided, so normal mapping shaders
+    // can be used on models that come without a tangent space
+    pub fn compute_tangents(&mut self) {
+        for mesh in self.meshes.iter_mut() {
+            mesh.compute_tangents();
+        }
+    }
+
+    // axis-aligned bounding box (min, max) enclosing all meshes of the model
+    pub fn bounding_box(&self) -> (glm::TVec3<f32>, glm::TVec3<f32>) {
+        let mut boxes = self.meshes.iter().filter(|mesh| !mesh.vertices.is_empty()).map(|mesh| mesh.bounding_box());
+        let first = match boxes.next() {
+            Some(bounds) => bounds,
+            None => return (util::glm::empty_vec3(), util::glm::empty_vec3())
+        };
+        boxes.fold(first, |(min, max), (mesh_min, mesh_max)| {
+            (glm::min2(&min, &mesh_min), glm::max2(&max, &mesh_max))
+        })
+    }
+
+    // center of the bounding box
+    pub fn center(&self) -> glm::TVec3<f32> {
+        let (min, max) = self.bounding_box();
+        (min + max) * 0.5
+    }
+
+    // radius of the sphere around center() that encloses the bounding box
+    pub fn bounding_radius(&self) -> f32 {
+        let (min, max) = self.bounding_box();
+        glm::length(&(max - min)) * 0.5
+    }
+
+    // draws count instances of the model in a single call per mesh, see set_instance_buffer
+    pub fn draw_instanced(&self, shader: &Shader, count: i32) {
+        for mesh in self.meshes.iter() {
+            mesh.draw_instanced(shader, count);
+        }
+    }
+
+    // uploads one model matrix per instance and wires it up as an instanced vertex attribute
+    // (locations mesh::INSTANCE_MATRIX_LOCATION to +3) on every mesh
+    pub fn set_instance_buffer(&mut self, transforms: &[glm::TMat4<f32>]) {
+        unsafe {
+            if self.instance_vbo == 0 {
+                gl::GenBuffers(1, &mut self.instance_vbo);
+            }
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                mem::size_of_val(transforms) as GLsizeiptr,
+                transforms.as_ptr() as *const _,
+                gl::DYNAMIC_DRAW
+            );
+        }
+        for mesh in self.meshes.iter() {
+            mesh.set_instance_buffer(self.instance_vbo);
+        }
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
 }
 
-fn texture_from_file(path: &str, directory: String) -> u32 {
-    let mut filename = String::from(path);
-    filename = directory + "/" + filename.as_str();
+// Deletes the textures and the instance buffer, the meshes delete their own buffers. Like every GL
+// resource a model has to be dropped before the window (and with it the context) is destroyed.
+impl Drop for Model {
+    fn drop(&mut self) {
+        // every texture is shared by the meshes (and the cache) that use it, so each id is deleted once
+        let mut textures: Vec<u32> = self.texture_cache.textures.values().copied()
+            .chain(self.textures_loaded.iter().map(|texture| texture.id))
+            .chain(self.meshes.iter().flat_map(|mesh| mesh.textures.iter().map(|texture| texture.id)))
+            .filter(|id| *id != 0)
+            .collect();
+        textures.sort_unstable();
+        textures.dedup();
+        unsafe {
+            if !textures.is_empty() {
+                gl::DeleteTextures(textures.len() as GLsizei, textures.as_ptr());
+            }
+            if self.instance_vbo != 0 {
+                gl::DeleteBuffers(1, &self.instance_vbo);
+            }
+        }
+    }
+}
 
+// the OBJ loading without ASSIMP, producing the same meshes and sampler names as Model::new
+#[cfg(feature = "tobj")]
+impl Model {
+    // loads a Wavefront OBJ file (and its MTL materials) with tobj instead of ASSIMP
+    pub fn from_obj(path: String, gamma: bool) -> Self {
+        let mut result = Self {
+            textures_loaded: Vec::new(),
+            meshes: Vec::new(),
+            directory: String::new(),
+            gamma_correction: gamma,
+            texture_cache: TextureCache::default(),
+            instance_vbo: 0
+        };
+        result.load_obj(path);
+        result
+    }
+
+    fn load_obj(&mut self, path: String) {
+        if let Err(err) = filesystem::check_exists(&path) {
+            panic!("Failed to load model: {}", err);
+        }
+        let (models, materials) = tobj::load_obj(&path, &tobj::GPU_LOAD_OPTIONS)
+            .unwrap_or_else(|err| panic!("Failed to load model {}: {}", path, err));
+        let materials = materials.unwrap_or_else(|err| {
+            log::warn!("Failed to load the materials of {}: {}", path, err);
+            Vec::new()
+        });
+        self.directory = path[0..path.rfind('/').unwrap_or(0)].to_string();
+
+        for model in models.iter() {
+            let (vertices, indices) = obj_geometry(&model.mesh);
+            let mut textures: Vec<Texture> = Vec::new();
+            let mut properties = Material::default();
+            if let Some(material) = model.mesh.material_id.and_then(|id| materials.get(id)) {
+                properties.shininess = material.shininess;
+                // same sampler names as ASSIMP gives the MTL maps, see process_mesh
+                let slots = [
+                    (&material.diffuse_texture, "texture_diffuse"),
+                    (&material.specular_texture, "texture_specular"),
+                    (&material.normal_texture, "texture_normal"),
+                    (&material.ambient_texture, "texture_height")
+                ];
+                for (filename, type_name) in slots {
+                    if let Some(filename) = filename {
+                        textures.push(self.load_texture_reference(filename, type_name));
+                    }
+                }
+            }
+            self.meshes.push(Mesh::new_ex(vertices, indices, textures, properties));
+        }
+    }
+
+    // the texture a material refers to by file name, loaded once per model like in load_material_textures
+    fn load_texture_reference(&mut self, filename: &str, type_name: &str) -> Texture {
+        if let Some(texture_loaded) = self.textures_loaded.iter().find(|it| it.path == filename && it.type_s == type_name) {
+            return texture_loaded.clone();
+        }
+        let id = match resolve_texture_path(filename, Path::new(&self.directory)) {
+            Some(path) => self.texture_cache.get_or_upload(path, texture_from_file),
+            None => {
+                log::warn!("Texture {} of the model in {} not found.", filename, self.directory);
+                0
+            }
+        };
+        let texture_load = Texture { id, type_s: type_name.to_string(), path: filename.to_string() };
+        self.textures_loaded.push(texture_load.clone());
+        texture_load
+    }
+}
+
+#[cfg(feature = "tobj")]
+fn is_obj(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("obj"))
+}
+
+// the vertices and indices of a (triangulated, single indexed) tobj mesh, with the post processing
+// ASSIMP applies in load_model_with: flipped texture coordinates, smooth normals where the file has
+// none and the tangent space
+#[cfg(feature = "tobj")]
+fn obj_geometry(mesh: &tobj::Mesh) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices: Vec<Vertex> = Vec::new();
+    for (i, position) in mesh.positions.chunks_exact(3).enumerate() {
+        let normal = match mesh.normals.get(i * 3..i * 3 + 3) {
+            Some(normal) => glm::vec3(normal[0], normal[1], normal[2]),
+            None => util::glm::empty_vec3()
+        };
+        let tex_coords = match mesh.texcoords.get(i * 2..i * 2 + 2) {
+            Some(tex_coords) => glm::vec2(tex_coords[0], 1.0 - tex_coords[1]),
+            None => glm::vec2(0.0, 0.0)
+        };
+        vertices.push(Vertex {
+            position: glm::vec3(position[0], position[1], position[2]),
+            normal,
+            tex_coords,
+            ..Vertex::default()
+        });
+    }
+    let indices = mesh.indices.clone();
+
+    if mesh.normals.is_empty() {
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize];
+            // not normalized, so larger triangles weigh more
+            let face_normal = glm::cross(
+                &(vertices[b].position - vertices[a].position),
+                &(vertices[c].position - vertices[a].position)
+            );
+            for index in [a, b, c] {
+                vertices[index].normal += face_normal;
+            }
+        }
+        for vertex in vertices.iter_mut() {
+            if glm::length(&vertex.normal) > 0.0 {
+                vertex.normal = glm::normalize(&vertex.normal);
+            }
+        }
+    }
+    if !mesh.texcoords.is_empty() {
+        mesh::compute_tangents(&mut vertices, &indices);
+    }
+
+    (vertices, indices)
+}
+
+// the scalar properties of an ASSIMP material: its shininess (Ns of MTL files) and, for glTF
+// materials, the metallic and roughness factors
+fn material_properties(material: &AIMaterial) -> Material {
+    let scalar = |key: &str| material.properties.iter()
+        .filter(|property| property.key == key)
+        .find_map(|property| match &property.data {
+            PropertyTypeInfo::FloatArray(values) => values.first().copied(),
+            _ => None
+        });
+    Material {
+        shininess: scalar("$mat.shininess"),
+        metallic: scalar("$mat.metallicFactor"),
+        roughness: scalar("$mat.roughnessFactor")
+    }
+}
+
+// selects how the meshes of a scene are turned into our own Mesh representation
+type MeshProcessor = fn(&mut Model, &AIMesh, &AIScene) -> Mesh;
+
+fn texture_from_memory(bytes: &[u8]) -> u32 {
+    let img = image::load_from_memory(bytes).expect("Failed to decode embedded texture data.").flipv().to_rgba8();
+    upload_texture(&img)
+}
+
+// uploads every texture once per model, keyed by the resolved file path (or the name of an embedded
+// texture), so meshes whose materials refer to the same file share one GL texture
+#[derive(Default)]
+struct TextureCache {
+    textures: HashMap<PathBuf, u32>
+}
+
+impl TextureCache {
+    fn get_or_upload<F: FnOnce(&Path) -> u32>(&mut self, path: PathBuf, upload: F) -> u32 {
+        // different references to one file (like "a/../b.png" and "b.png") share the canonical path
+        let key = path.canonicalize().unwrap_or(path);
+        *self.textures.entry(key).or_insert_with_key(|path| upload(path))
+    }
+
+    // textures that failed to load are cached as 0 too (so they're only tried once), but aren't counted
+    fn texture_count(&self) -> usize {
+        self.textures.values().filter(|id| **id != 0).count()
+    }
+}
+
+// a broken texture only logs a warning and yields texture 0, so the rest of the model still shows up
+fn texture_from_file(filename: &Path) -> u32 {
+    match util::image::load_image_data_rgba(filename.to_string_lossy().into_owned()) {
+        Ok(img) => upload_texture(&img),
+        Err(err) => {
+            log::warn!("Failed to load texture data from {}: {}", filename.display(), err);
+            0
+        }
+    }
+}
+
+// Finds the file a material refers to. Materials exported elsewhere often use Windows separators or
+// absolute paths of the exporting machine, so the separators are normalized, relative paths are taken
+// relative to the model's directory and, if nothing is there, the file name alone is looked up in it.
+fn resolve_texture_path(reference: &str, directory: &Path) -> Option<PathBuf> {
+    let normalized = reference.replace('\\', "/");
+    let candidate = directory.join(&normalized);
+    if candidate.is_file() {
+        return Some(candidate);
+    }
+    let file_name = Path::new(&normalized).file_name()?;
+    let fallback = directory.join(file_name);
+    fallback.is_file().then_some(fallback)
+}
+
+fn upload_texture(img: &RgbaImage) -> u32 {
     let mut texture_id = 0u32;
     unsafe {
         gl::GenTextures(1, &mut texture_id);
 
-        let img = util::image::load_image_data_rgba(filename).expect("Failed to load texture data.");
         let width = img.width();
         let height = img.height();
         let data = img.as_raw();
@@ -250,4 +658,102 @@ fn texture_from_file(path: &str, directory: String) -> u32 {
     };
 
     texture_id
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use learnopengl_shared::app::App;
+    use super::*;
+
+    #[test]
+    fn windows_texture_path_resolves_in_model_directory() {
+        let directory = std::env::temp_dir().join(format!("learnopengl_windows_texture_path_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("foo.png"), b"").unwrap();
+        fs::write(directory.join("model.mtl"), "newmtl material\nmap_Kd ..\\textures\\foo.png\n").unwrap();
+        fs::write(directory.join("model.obj"), "mtllib model.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl material\nf 1 2 3\n").unwrap();
+
+        let scene = AIScene::from_file(directory.join("model.obj").to_str().unwrap(), vec![]).unwrap();
+        let reference = scene.materials.iter()
+            .flat_map(|material| material.textures.iter())
+            .find(|texture| *texture.0 == TextureType::Diffuse)
+            .map(|texture| texture.1.borrow().filename.clone())
+            .expect("The material should reference a diffuse texture.");
+        assert!(reference.ends_with("textures\\foo.png"), "Got {}.", reference);
+
+        // ../textures doesn't exist next to the model, the file name alone is found in its directory
+        assert_eq!(resolve_texture_path(&reference, &directory), Some(directory.join("foo.png")));
+        assert_eq!(resolve_texture_path("C:\\models\\bar.png", &directory), None);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn shared_texture_is_uploaded_once() {
+        // loading the textures needs a GL context, skipped on machines without one
+        let Ok(_app) = App::new_headless(4, 4) else {
+            return;
+        };
+        let directory = std::env::temp_dir().join(format!("learnopengl_shared_texture_test_{}", std::process::id()));
+        fs::create_dir_all(directory.join("textures")).unwrap();
+        RgbaImage::from_pixel(1, 1, image::Rgba([255, 0, 0, 255]))
+            .save(directory.join("shared.png"))
+            .unwrap();
+        // two materials make two meshes, both refer to the same file (once through a detour)
+        fs::write(
+            directory.join("model.mtl"),
+            "newmtl first\nmap_Kd shared.png\nnewmtl second\nmap_Kd textures\\..\\shared.png\n"
+        ).unwrap();
+        fs::write(
+            directory.join("model.obj"),
+            "mtllib model.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\n\
+             o first\nusemtl first\nf 1 2 3\no second\nusemtl second\nf 2 4 3\n"
+        ).unwrap();
+
+        let model = Model::new_without_gamma(directory.join("model.obj").to_string_lossy().into_owned());
+        assert_eq!(model.meshes.len(), 2);
+        assert_eq!(model.texture_count(), 1);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn mtl_shininess_is_read() {
+        let directory = std::env::temp_dir().join(format!("learnopengl_mtl_shininess_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("model.mtl"), "newmtl shiny\nNs 64\n").unwrap();
+        fs::write(directory.join("model.obj"), "mtllib model.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl shiny\nf 1 2 3\n").unwrap();
+
+        let scene = AIScene::from_file(directory.join("model.obj").to_str().unwrap(), vec![]).unwrap();
+        let material = &scene.materials[scene.meshes[0].material_index as usize];
+        let properties = material_properties(material);
+        assert_eq!(properties.shininess, Some(64.0));
+        assert_eq!(properties.metallic, None);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[cfg(feature = "tobj")]
+    #[test]
+    fn obj_geometry_test_quad() {
+        // a quad of two triangles, the shared corners are single vertices
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                   vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+                   f 1/1 2/2 3/3 4/4\n";
+        let (models, _) = tobj::load_obj_buf(
+            &mut std::io::BufReader::new(obj.as_bytes()),
+            &tobj::GPU_LOAD_OPTIONS,
+            |_| Err(tobj::LoadError::OpenFileFailed)
+        ).unwrap();
+        assert_eq!(models.len(), 1);
+
+        let (vertices, indices) = obj_geometry(&models[0].mesh);
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices.len(), 6);
+        // the normals are generated, the texture coordinates flipped like ASSIMP's FlipUVs does
+        for vertex in vertices.iter() {
+            assert!((vertex.normal - glm::vec3(0.0, 0.0, 1.0)).norm() < 1e-5);
+        }
+        assert_eq!(vertices[0].tex_coords, glm::vec2(0.0, 1.0));
+        assert!(is_obj("resources/objects/backpack/backpack.OBJ"));
+        assert!(!is_obj("resources/objects/backpack/backpack.mtl"));
+    }
 }
\ No newline at end of file
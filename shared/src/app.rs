@@ -0,0 +1,637 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::cell::Cell;
+use std::error::Error;
+use std::rc::Rc;
+use std::sync::Mutex;
+use image::RgbaImage;
+use glfw::{Action, Context, CursorMode, Glfw, Key, OpenGlProfileHint, PWindow, Window, WindowHint};
+use crate::camera::{self, Camera, Movement};
+use crate::debug;
+use crate::framebuffer::OffscreenTarget;
+
+// Default timing values
+const MAX_DELTA_TIME: f32 = 0.1;
+// the delta every headless frame reports, so that the rendered images don't depend on timing
+const HEADLESS_DELTA_TIME: f32 = 1.0 / 60.0;
+// Default clip planes of the projection
+const NEAR_PLANE: f32 = 0.1;
+const FAR_PLANE: f32 = 100.0;
+
+// Per-frame state that is shared by the render loops of the examples.
+pub struct FrameContext {
+    // timing
+    delta_time: f32,
+    last_frame: f32,
+    // the largest delta a single frame may report. When the window is dragged or the process is
+    // stalled for whatever reason, the raw delta gets huge and would teleport the camera.
+    max_delta_time: f32,
+    // if set, a stalled frame is reported as skipped (delta of zero) instead of a clamped one
+    skip_stalled_frames: bool,
+    first_frame: bool
+}
+
+impl FrameContext {
+    pub fn new() -> Self {
+        Self {
+            delta_time: 0.0,
+            last_frame: 0.0,
+            max_delta_time: MAX_DELTA_TIME,
+            skip_stalled_frames: false,
+            first_frame: true
+        }
+    }
+
+    // per-frame time logic, `now` is the current time in seconds (e.g. `glfw.get_time()`).
+    // Returns false if the frame should be skipped because it came right after a stall.
+    pub fn update_timing(&mut self, now: f32) -> bool {
+        let raw_delta = if self.first_frame { 0.0 } else { now - self.last_frame };
+        self.last_frame = now;
+        self.first_frame = false;
+
+        if raw_delta > self.max_delta_time {
+            if self.skip_stalled_frames {
+                self.delta_time = 0.0;
+                return false;
+            }
+            self.delta_time = self.max_delta_time;
+        } else {
+            // a clock going backwards is treated the same as no time passing at all
+            self.delta_time = raw_delta.max(0.0);
+        }
+        true
+    }
+
+    pub fn delta_time(&self) -> f32 {
+        self.delta_time
+    }
+
+    pub fn last_frame(&self) -> f32 {
+        self.last_frame
+    }
+
+    pub fn max_delta_time(&self) -> f32 {
+        self.max_delta_time
+    }
+
+    pub fn skip_stalled_frames(&self) -> bool {
+        self.skip_stalled_frames
+    }
+
+    pub fn set_max_delta_time(&mut self, max_delta_time: f32) {
+        self.max_delta_time = max_delta_time;
+    }
+
+    pub fn set_skip_stalled_frames(&mut self, skip_stalled_frames: bool) {
+        self.skip_stalled_frames = skip_stalled_frames;
+    }
+}
+
+impl Default for FrameContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Perspective projection that is only rebuilt when the zoom, the aspect ratio or the clip planes
+// changed. The app keeps the aspect ratio in sync with the framebuffer size; its methods take &self
+// so a render closure can hold on to it through `App::projection` while `run` borrows the app.
+pub struct ProjectionCache {
+    near: Cell<f32>,
+    far: Cell<f32>,
+    aspect: Cell<f32>,
+    // inputs (zoom, aspect, near, far) of the cached matrix, None until the first build
+    key: Cell<Option<(f32, f32, f32, f32)>>,
+    matrix: Cell<glm::TMat4<f32>>,
+    rebuilds: Cell<u32>
+}
+
+impl ProjectionCache {
+    pub fn new(aspect: f32) -> Self {
+        Self {
+            near: Cell::new(NEAR_PLANE),
+            far: Cell::new(FAR_PLANE),
+            aspect: Cell::new(aspect),
+            key: Cell::new(None),
+            matrix: Cell::new(glm::identity()),
+            rebuilds: Cell::new(0)
+        }
+    }
+
+    // the projection for a vertical field of view of `zoom` degrees (e.g. `Camera::zoom`)
+    pub fn matrix(&self, zoom: f32) -> glm::TMat4<f32> {
+        let key = (zoom, self.aspect.get(), self.near.get(), self.far.get());
+        if self.key.get() != Some(key) {
+            self.matrix.set(glm::perspective(key.1, zoom.to_radians(), key.2, key.3));
+            self.key.set(Some(key));
+            self.rebuilds.set(self.rebuilds.get() + 1);
+        }
+        self.matrix.get()
+    }
+
+    pub fn set_clip_planes(&self, near: f32, far: f32) {
+        self.near.set(near);
+        self.far.set(far);
+    }
+
+    pub fn set_aspect(&self, aspect: f32) {
+        self.aspect.set(aspect);
+    }
+
+    pub fn near(&self) -> f32 {
+        self.near.get()
+    }
+
+    pub fn far(&self) -> f32 {
+        self.far.get()
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.aspect.get()
+    }
+}
+
+// The current size of the window's framebuffer, kept up to date by the app's resize callback. Shared
+// like `ProjectionCache` so render closures can read it through `App::viewport` while `run` borrows the app.
+pub struct ViewportState {
+    width: Cell<u32>,
+    height: Cell<u32>
+}
+
+impl ViewportState {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width: Cell::new(width),
+            height: Cell::new(height)
+        }
+    }
+
+    // a minimized window reports a zero size, the last size (and so the aspect ratio) is kept then
+    pub fn resize(&self, width: i32, height: i32) {
+        if width > 0 && height > 0 {
+            self.width.set(width as u32);
+            self.height.set(height as u32);
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width.get()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height.get()
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.width.get() as f32 / self.height.get() as f32
+    }
+}
+
+// Window and GL context bootstrap shared by the examples: creates a 3.3 core context window with the
+// mouse captured, keeps the viewport in sync with the framebuffer size and drives the render loop.
+// A headless app renders into an off-screen framebuffer of a hidden window instead (see `new_headless`).
+//
+// GL objects (shaders, models, textures, ...) delete themselves when dropped, which needs the context
+// of the window. They have to be gone before the App is: declare them after it, or hand them to
+// `run_with`, which drops them right after the loop.
+pub struct App {
+    // declared first so the framebuffer is deleted while the context of the window is still alive
+    offscreen: Option<OffscreenTarget>,
+    pub glfw: Glfw,
+    pub window: PWindow,
+    frame_context: FrameContext,
+    camera: Option<&'static Mutex<Camera>>,
+    viewport: Rc<ViewportState>,
+    projection: Rc<ProjectionCache>,
+    // set by `with_debug` if the context has no debug output, glGetError is polled after each frame then
+    check_gl_errors: bool
+}
+
+impl App {
+    pub fn new(width: u32, height: u32, title: &str) -> Self {
+        Self::new_with_samples(width, height, title, 0)
+    }
+
+    // like `new`, but asks for a default framebuffer with `samples` samples per pixel (MSAA) if
+    // `samples` isn't 0. gl::MULTISAMPLE is enabled in that case.
+    pub fn new_with_samples(width: u32, height: u32, title: &str, samples: u32) -> Self {
+        // glfw: initialize and configure
+        // ------------------------------
+        let mut glfw = glfw::init(glfw::fail_on_errors)
+            .expect("Failed to initialise GLFW.");
+
+        glfw.window_hint(WindowHint::ContextVersionMajor(3));
+        glfw.window_hint(WindowHint::ContextVersionMinor(3));
+        glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+        glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+        if samples > 0 {
+            glfw.window_hint(WindowHint::Samples(Some(samples)));
+        }
+        // lets `set_srgb_framebuffer` gamma correct what's written to the window
+        glfw.window_hint(WindowHint::SRgbCapable(true));
+        // debug builds ask for a debug context so `with_debug` can install the GL debug output
+        glfw.window_hint(WindowHint::OpenGlDebugContext(cfg!(debug_assertions)));
+
+        // glfw window creation
+        // --------------------
+        let (mut window, _) = glfw.create_window(
+            width, height,
+            title, glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+        let viewport = Rc::new(ViewportState::new(width, height));
+        let projection = Rc::new(ProjectionCache::new(viewport.aspect()));
+        let viewport_callback = Rc::clone(&viewport);
+        let projection_callback = Rc::clone(&projection);
+        window.set_framebuffer_size_callback(move |window, width, height| {
+            framebuffer_size_callback(window, width, height);
+            resize(&viewport_callback, &projection_callback, width, height);
+        });
+
+        window.set_key_polling(true);
+        window.make_current();
+
+        // tell GLFW to capture our mouse
+        window.set_cursor_mode(CursorMode::Disabled);
+
+        // load all OpenGL function pointers
+        // ---------------------------------
+        gl::load_with(|s| window.get_proc_address(s) as *const _);
+
+        if samples > 0 {
+            unsafe {
+                gl::Enable(gl::MULTISAMPLE); // enabled by default on some drivers, but not all so always enable to make sure
+            }
+        }
+
+        Self {
+            offscreen: None,
+            glfw,
+            window,
+            frame_context: FrameContext::new(),
+            camera: None,
+            viewport,
+            projection,
+            check_gl_errors: false
+        }
+    }
+
+    // Creates an app with an invisible window whose frames go into a width x height off-screen
+    // framebuffer, for rendering without a display server (e.g. golden image tests in CI). Fails
+    // instead of panicking if glfw or the GL context isn't available.
+    pub fn new_headless(width: u32, height: u32) -> Result<Self, Box<dyn Error>> {
+        let mut glfw = glfw::init(glfw::log_errors)?;
+
+        glfw.window_hint(WindowHint::ContextVersionMajor(3));
+        glfw.window_hint(WindowHint::ContextVersionMinor(3));
+        glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+        glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+        glfw.window_hint(WindowHint::Visible(false));
+
+        let (mut window, _) = glfw.create_window(
+            width, height,
+            "LearnOpenGL (headless)", glfw::WindowMode::Windowed)
+            .ok_or("Failed to create a hidden GLFW window for headless rendering (no OpenGL 3.3 context available).")?;
+        window.make_current();
+        gl::load_with(|s| window.get_proc_address(s) as *const _);
+
+        let offscreen = OffscreenTarget::new(width, height);
+        if !offscreen.is_complete() {
+            return Err("The off-screen framebuffer for headless rendering is not complete.".into());
+        }
+
+        Ok(Self {
+            offscreen: Some(offscreen),
+            glfw,
+            window,
+            frame_context: FrameContext::new(),
+            camera: None,
+            viewport: Rc::new(ViewportState::new(width, height)),
+            projection: Rc::new(ProjectionCache::new(width as f32 / height as f32)),
+            check_gl_errors: false
+        })
+    }
+
+    // Logs GL errors and warnings to stderr: through the GL debug output if the context supports it
+    // (debug builds request a debug context), otherwise by checking glGetError after every frame of `run`.
+    pub fn with_debug(mut self) -> Self {
+        self.check_gl_errors = !debug::enable_gl_debug_output();
+        self
+    }
+
+    // With enabled set, GL applies gamma correction whenever a color is written to the framebuffer
+    // (draws, clears and blends): linear colors written by the shaders are converted to sRGB, so the
+    // shaders must not apply pow(color, 1.0 / 2.2) themselves anymore. Only affects sRGB capable
+    // framebuffers, like the window's one and the headless target, and textures with an sRGB format.
+    pub fn set_srgb_framebuffer(enabled: bool) {
+        unsafe {
+            if enabled {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            } else {
+                gl::Disable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.offscreen.is_some()
+    }
+
+    // Calls `frame` `frames` times with a fixed delta time while the off-screen framebuffer is bound
+    // and returns what the last frame left in it. Only available for apps made by `new_headless`.
+    pub fn render_headless(&mut self, frames: u32, mut frame: impl FnMut(f32)) -> RgbaImage {
+        let offscreen = self.offscreen.as_ref()
+            .expect("Headless rendering needs an App created by App::new_headless.");
+        for _ in 0..frames {
+            // bound again every frame as the frame itself may switch framebuffers
+            offscreen.bind();
+            frame(HEADLESS_DELTA_TIME);
+        }
+        let pixels = offscreen.read_pixels();
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        pixels
+    }
+
+    // lets the mouse look around and zoom with the given camera and WASD move it during `run`
+    pub fn set_camera(&mut self, camera: &'static Mutex<Camera>) {
+        let (mut last_x, mut last_y) = (0f32, 0f32);
+        let mut first_mouse = true;
+        self.window.set_cursor_pos_callback(move |_, x_pos_in, y_pos_in| {
+            let x_pos = x_pos_in as f32;
+            let y_pos = y_pos_in as f32;
+
+            if first_mouse {
+                last_x = x_pos;
+                last_y = y_pos;
+                first_mouse = false;
+            }
+
+            let x_offset = x_pos - last_x;
+            let y_offset = last_y - y_pos; // reversed since y-coordinates go from bottom to top
+            last_x = x_pos;
+            last_y = y_pos;
+
+            camera.lock().unwrap().process_mouse_movement(x_offset, y_offset);
+        });
+        self.window.set_scroll_callback(move |_, _x_offset, y_offset| {
+            camera.lock().unwrap().process_mouse_scroll(y_offset as f32);
+        });
+        self.camera = Some(camera);
+    }
+
+    // render loop: calls `frame` with the delta time once per frame until the window is closed
+    // (escape closes it), swapping buffers and polling events in between.
+    pub fn run(&mut self, mut frame: impl FnMut(f32)) {
+        while !self.window.should_close() {
+            // per-frame time logic
+            // --------------------
+            if self.frame_context.update_timing(self.glfw.get_time() as f32) {
+                let delta_time = self.frame_context.delta_time();
+
+                // input
+                // -----
+                self.process_input(delta_time);
+
+                // render
+                // ------
+                frame(delta_time);
+                if self.check_gl_errors {
+                    debug::check_gl_error("frame");
+                }
+
+                // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
+                // -------------------------------------------------------------------------------
+                self.window.swap_buffers();
+            }
+            self.glfw.poll_events();
+        }
+    }
+
+    // like `run`, with the resources the frames need owned by the loop: `frame` gets them along with the
+    // delta time, and they're dropped as soon as the window is closed, while the context is still current
+    pub fn run_with<R>(&mut self, mut resources: R, mut frame: impl FnMut(&mut R, f32)) {
+        self.run(|delta_time| frame(&mut resources, delta_time));
+    }
+
+    fn process_input(&mut self, delta_time: f32) {
+        if self.window.get_key(Key::Escape) == Action::Press {
+            self.window.set_should_close(true)
+        }
+
+        if let Some(camera) = self.camera {
+            let mut camera = camera.lock().unwrap();
+            if self.window.get_key(Key::W) == Action::Press {
+                camera.process_keyboard(Movement::FORWARD, delta_time);
+            }
+            if self.window.get_key(Key::S) == Action::Press {
+                camera.process_keyboard(Movement::BACKWARD, delta_time);
+            }
+            if self.window.get_key(Key::A) == Action::Press {
+                camera.process_keyboard(Movement::LEFT, delta_time);
+            }
+            if self.window.get_key(Key::D) == Action::Press {
+                camera.process_keyboard(Movement::RIGHT, delta_time);
+            }
+            // only moves the camera if smoothing is enabled
+            camera.update(delta_time);
+        }
+    }
+
+    // near and far plane of `projection_matrix`, 0.1 and 100.0 unless set otherwise
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.projection.set_clip_planes(near, far);
+    }
+
+    // perspective projection for the zoom of the camera (see `set_camera`, 45 degrees without one)
+    // and the aspect ratio of the framebuffer, only rebuilt when one of them changed
+    pub fn projection_matrix(&self) -> glm::TMat4<f32> {
+        let zoom = match self.camera {
+            Some(camera) => camera.lock().unwrap().zoom(),
+            None => camera::ZOOM
+        };
+        self.projection.set_aspect(self.viewport.aspect());
+        self.projection.matrix(zoom)
+    }
+
+    // the cache behind `projection_matrix`, for render closures that can't borrow the app during `run`
+    pub fn projection(&self) -> Rc<ProjectionCache> {
+        Rc::clone(&self.projection)
+    }
+
+    // the current framebuffer size, for render closures that can't borrow the app during `run`
+    pub fn viewport(&self) -> Rc<ViewportState> {
+        Rc::clone(&self.viewport)
+    }
+
+    pub fn frame_context(&self) -> &FrameContext {
+        &self.frame_context
+    }
+
+    pub fn frame_context_mut(&mut self) -> &mut FrameContext {
+        &mut self.frame_context
+    }
+}
+
+// records the new framebuffer size and fits the projection's aspect ratio to it
+fn resize(viewport: &ViewportState, projection: &ProjectionCache, width: i32, height: i32) {
+    viewport.resize(width, height);
+    projection.set_aspect(viewport.aspect());
+}
+
+fn framebuffer_size_callback(
+    _: &mut Window,
+    width: i32,
+    height: i32
+) {
+    unsafe {
+        gl::Viewport(0, 0, width, height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::gl_test_lock;
+
+    #[test]
+    fn update_timing_test_regular() {
+        let mut ctx = FrameContext::new();
+        assert!(ctx.update_timing(1.0));
+        assert_eq!(ctx.delta_time(), 0.0); // there is no previous frame yet
+        assert!(ctx.update_timing(1.016));
+        assert!((ctx.delta_time() - 0.016).abs() < 1e-6);
+    }
+
+    #[test]
+    fn update_timing_test_clamped() {
+        let mut ctx = FrameContext::new();
+        ctx.update_timing(1.0);
+        assert!(ctx.update_timing(6.0));
+        assert_eq!(ctx.delta_time(), MAX_DELTA_TIME);
+        assert_eq!(ctx.last_frame(), 6.0);
+    }
+
+    #[test]
+    fn update_timing_test_custom_max() {
+        let mut ctx = FrameContext::new();
+        ctx.set_max_delta_time(0.05);
+        ctx.update_timing(1.0);
+        ctx.update_timing(1.5);
+        assert_eq!(ctx.delta_time(), 0.05);
+    }
+
+    #[test]
+    fn update_timing_test_skipped() {
+        let mut ctx = FrameContext::new();
+        ctx.set_skip_stalled_frames(true);
+        ctx.update_timing(1.0);
+        assert!(!ctx.update_timing(6.0), "A stalled frame should be skipped.");
+        assert_eq!(ctx.delta_time(), 0.0);
+        // the frame after the stall runs normally again
+        assert!(ctx.update_timing(6.02));
+        assert!((ctx.delta_time() - 0.02).abs() < 1e-5);
+    }
+
+    #[test]
+    fn update_timing_test_backwards() {
+        let mut ctx = FrameContext::new();
+        ctx.update_timing(2.0);
+        assert!(ctx.update_timing(1.0));
+        assert_eq!(ctx.delta_time(), 0.0);
+    }
+
+    #[test]
+    fn projection_cache_test_zoom_change() {
+        let projection = ProjectionCache::new(4.0 / 3.0);
+        let first = projection.matrix(45.0);
+        assert_eq!(first, glm::perspective(4.0 / 3.0, 45f32.to_radians(), NEAR_PLANE, FAR_PLANE));
+        assert_eq!(projection.matrix(45.0), first);
+        assert_eq!(projection.rebuilds.get(), 1);
+
+        let zoomed = projection.matrix(30.0);
+        assert_ne!(zoomed, first);
+        assert_eq!(projection.rebuilds.get(), 2);
+    }
+
+    #[test]
+    fn projection_cache_test_planes_and_aspect() {
+        let projection = ProjectionCache::new(1.0);
+        projection.matrix(45.0);
+        projection.set_clip_planes(1.0, 25.0);
+        assert_eq!(projection.matrix(45.0), glm::perspective(1.0, 45f32.to_radians(), 1.0, 25.0));
+        projection.set_aspect(2.0);
+        assert_eq!(projection.matrix(45.0), glm::perspective(2.0, 45f32.to_radians(), 1.0, 25.0));
+        assert_eq!(projection.rebuilds.get(), 3);
+    }
+
+    #[test]
+    fn resize_test_aspect_follows_framebuffer() {
+        let viewport = ViewportState::new(800, 600);
+        let projection = ProjectionCache::new(viewport.aspect());
+        resize(&viewport, &projection, 1600, 600);
+        assert_eq!((viewport.width(), viewport.height()), (1600, 600));
+        assert!((projection.aspect() - 2.666).abs() < 1e-3, "Got {}.", projection.aspect());
+        assert_eq!(projection.matrix(45.0), glm::perspective(1600.0 / 600.0, 45f32.to_radians(), NEAR_PLANE, FAR_PLANE));
+        // minimizing keeps the last size
+        resize(&viewport, &projection, 0, 0);
+        assert_eq!((viewport.width(), viewport.height()), (1600, 600));
+        assert!((projection.aspect() - 2.666).abs() < 1e-3);
+    }
+
+    #[test]
+    fn render_headless_test_clear_color() {
+        let _lock = gl_test_lock();
+        let Ok(mut app) = App::new_headless(8, 4) else {
+            return;
+        };
+        assert!(app.is_headless());
+        let mut deltas = Vec::new();
+        let pixels = app.render_headless(2, |delta_time| unsafe {
+            deltas.push(delta_time);
+            gl::ClearColor(0.0, 0.0, 1.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        });
+        assert_eq!(deltas, vec![HEADLESS_DELTA_TIME; 2]);
+        assert_eq!(pixels.dimensions(), (8, 4));
+        assert!(pixels.pixels().all(|it| it.0 == [0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn set_srgb_framebuffer_test_mid_gray() {
+        let _lock = gl_test_lock();
+        let Ok(mut app) = App::new_headless(2, 2) else {
+            return;
+        };
+        let clear_mid_gray = |_: f32| unsafe {
+            gl::ClearColor(0.5, 0.5, 0.5, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        };
+        let linear = app.render_headless(1, clear_mid_gray).get_pixel(0, 0).0;
+        App::set_srgb_framebuffer(true);
+        let srgb = app.render_headless(1, clear_mid_gray).get_pixel(0, 0).0;
+        App::set_srgb_framebuffer(false);
+        // written as is: 0.5 * 255
+        assert!((127..=128).contains(&linear[0]), "{:?}", linear);
+        // converted to sRGB: 0.5 becomes about 0.735
+        assert!((186..=190).contains(&srgb[0]), "{:?}", srgb);
+        assert_eq!(srgb[0], srgb[1]);
+        assert_eq!(srgb[3], 255);
+    }
+}
\ No newline at end of file
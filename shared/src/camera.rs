@@ -17,6 +17,10 @@
 extern crate nalgebra_glm as glm;
 
 use crate::util;
+#[cfg(feature = "serde")]
+use std::{fs::File, io::{self, BufReader, BufWriter}};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 // Defines several possible options for camera movement. Used as abstraction to stay away from window-system specific input methods
 pub enum Movement {
@@ -31,7 +35,9 @@ const YAW: f32 = -90.0;
 const PITCH: f32 = 0.0;
 const SPEED: f32 = 2.5;
 const SENSITIVITY: f32 = 0.1;
-const ZOOM: f32 = 45.0;
+pub const ZOOM: f32 = 45.0;
+// how quickly a smoothed camera picks up and loses speed, per second
+const SMOOTHING_DAMPING: f32 = 8.0;
 
 pub struct Camera {
     // camera Attributes
@@ -46,7 +52,13 @@ pub struct Camera {
     // camera options
     movement_speed: f32,
     mouse_sensitivity: f32,
-    zoom: f32
+    zoom: f32,
+    // smoothed movement: keyboard input sets the velocity to approach, `update` moves the camera
+    smoothing: bool,
+    velocity: glm::TVec3<f32>,
+    target_velocity: glm::TVec3<f32>,
+    // a fixed camera ignores all input, see `new_fixed`
+    fixed: bool
 }
 
 impl Camera {
@@ -67,7 +79,11 @@ impl Camera {
             pitch,
             movement_speed: SPEED,
             mouse_sensitivity: SENSITIVITY,
-            zoom: ZOOM
+            zoom: ZOOM,
+            smoothing: false,
+            velocity: util::glm::empty_vec3(),
+            target_velocity: util::glm::empty_vec3(),
+            fixed: false
         };
         result.update_camera_vectors();
         result
@@ -94,7 +110,11 @@ impl Camera {
             pitch,
             movement_speed: SPEED,
             mouse_sensitivity: SENSITIVITY,
-            zoom: ZOOM
+            zoom: ZOOM,
+            smoothing: false,
+            velocity: util::glm::empty_vec3(),
+            target_velocity: util::glm::empty_vec3(),
+            fixed: false
         };
         result.update_camera_vectors();
         result
@@ -104,8 +124,21 @@ impl Camera {
         Self::new(position, glm::vec3(0.0, 1.0, 0.0), YAW, PITCH)
     }
 
-    // calculates the front vector from the Camera's (updated) Euler Angles
-    fn update_camera_vectors(&mut self) {
+    // a camera at `position` looking at `target` that stays there: keyboard, mouse and scroll input
+    // (and `update`) are ignored, so the view matrix never changes. For demos with a fixed viewpoint.
+    // Saving and loading the camera keeps it fixed.
+    pub fn new_fixed(position: glm::TVec3<f32>, target: glm::TVec3<f32>) -> Self {
+        let front = glm::normalize(&(target - position));
+        let yaw = front.z.atan2(front.x).to_degrees();
+        let pitch = front.y.asin().to_degrees();
+        let mut result = Self::new(position, glm::vec3(0.0, 1.0, 0.0), yaw, pitch);
+        result.fixed = true;
+        result
+    }
+
+    // calculates the front vector from the Camera's (updated) Euler Angles. Call this after changing
+    // yaw or pitch directly through their setters
+    pub fn update_camera_vectors(&mut self) {
         // calculate the new Front vector
         let mut front = util::glm::empty_vec3();
         front.x = self.yaw.to_radians().cos() * self.pitch.to_radians().cos();
@@ -122,11 +155,45 @@ impl Camera {
         glm::look_at_rh(&self.position, &(self.position + self.front), &self.up)
     }
 
+    // extracts the view frustum of this camera for the given projection, e.g. to skip drawing off-screen objects
+    pub fn extract_frustum(&self, projection: &glm::TMat4<f32>) -> Frustum {
+        Frustum::from_matrix(&(projection * self.get_view_matrix()))
+    }
+
+    // the world space ray through the window position (`mouse_x`, `mouse_y`), in pixels from the top left
+    // corner of a `width` x `height` window, as origin on the near plane and normalized direction
+    pub fn screen_ray(&self, mouse_x: f32, mouse_y: f32, width: f32, height: f32, projection: &glm::TMat4<f32>) -> (glm::TVec3<f32>, glm::TVec3<f32>) {
+        let ndc_x = 2.0 * mouse_x / width - 1.0;
+        let ndc_y = 1.0 - 2.0 * mouse_y / height; // window coordinates go from top to bottom
+        let inverse_view_projection = glm::inverse(&(projection * self.get_view_matrix()));
+        let unproject = |ndc_z: f32| {
+            let world = inverse_view_projection * glm::vec4(ndc_x, ndc_y, ndc_z, 1.0);
+            util::glm::vec3_from_vec4(&world) / world.w
+        };
+        let near = unproject(-1.0);
+        let far = unproject(1.0);
+        (near, glm::normalize(&(far - near)))
+    }
+
     // processes input received from any keyboard-like input system. Accepts input parameter in the form of camera defined ENUM (to abstract it from windowing systems)
+    // With smoothing enabled the camera doesn't move right away, it accelerates in `update` instead.
     pub fn process_keyboard(
         &mut self, direction: Movement,
         delta_time: f32
     ) {
+        if self.fixed {
+            return;
+        }
+        if self.smoothing {
+            self.target_velocity += match direction {
+                Movement::FORWARD => self.front,
+                Movement::BACKWARD => -self.front,
+                Movement::LEFT => -self.right,
+                Movement::RIGHT => self.right
+            } * self.movement_speed;
+            return;
+        }
+
         let velocity = self.movement_speed * delta_time;
         match direction {
             Movement::FORWARD => {
@@ -144,6 +211,20 @@ impl Camera {
         }
     }
 
+    // Moves a smoothed camera, call once per frame after the keyboard input has been processed. The
+    // velocity approaches the one the pressed keys ask for with exponential damping, so the camera
+    // eases in when a key is pressed and glides out once it's released. Does nothing without smoothing.
+    pub fn update(&mut self, delta_time: f32) {
+        if !self.smoothing || self.fixed {
+            return;
+        }
+        let blend = 1.0 - (-SMOOTHING_DAMPING * delta_time).exp();
+        self.velocity = util::glm::lerp_vec3(&self.velocity, &self.target_velocity, blend);
+        self.position += self.velocity * delta_time;
+        // the keys have to ask again next frame
+        self.target_velocity = util::glm::empty_vec3();
+    }
+
     // processes input received from a mouse input system. Expects the offset value in both the x and y direction.
     pub fn process_mouse_movement_ex(
         &mut self,
@@ -151,6 +232,9 @@ impl Camera {
         mut y_offset: f32,
         constrain_pitch: bool
     ) {
+        if self.fixed {
+            return;
+        }
         x_offset *= self.mouse_sensitivity;
         y_offset *= self.mouse_sensitivity;
 
@@ -159,12 +243,7 @@ impl Camera {
 
         // make sure that when pitch is out of bounds, screen doesn't get flipped
         if constrain_pitch {
-            if self.pitch > 89.0 {
-                self.pitch = 89.0;
-            }
-            if self.pitch < -89.0 {
-                self.pitch = -89.0;
-            }
+            self.constrain_pitch();
         }
 
         // update Front, Right and Up Vectors using the updated Euler angles
@@ -173,6 +252,9 @@ impl Camera {
 
     // processes input received from a mouse scroll-wheel event. Only requires input on the vertical wheel-axis
     pub fn process_mouse_scroll(&mut self, y_offset: f32) {
+        if self.fixed {
+            return;
+        }
         self.zoom -= y_offset;
         if self.zoom < 1.0 {
             self.zoom = 1.0;
@@ -182,6 +264,15 @@ impl Camera {
         }
     }
 
+    fn constrain_pitch(&mut self) {
+        if self.pitch > 89.0 {
+            self.pitch = 89.0;
+        }
+        if self.pitch < -89.0 {
+            self.pitch = -89.0;
+        }
+    }
+
     pub fn process_mouse_movement(
         &mut self,
         x_offset: f32,
@@ -230,6 +321,18 @@ impl Camera {
         self.zoom
     }
 
+    pub fn smoothing(&self) -> bool {
+        self.smoothing
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        self.fixed
+    }
+
+    pub fn velocity(&self) -> glm::TVec3<f32> {
+        self.velocity
+    }
+
 
     pub fn set_position(&mut self, position: glm::TVec3<f32>) {
         self.position = position;
@@ -255,8 +358,16 @@ impl Camera {
         self.yaw = yaw;
     }
     
-    pub fn set_pitch(&mut self, pitch: f32) {
+    // the camera vectors are not refreshed here, call update_camera_vectors once all angles are set
+    pub fn set_pitch_ex(&mut self, pitch: f32, constrain_pitch: bool) {
         self.pitch = pitch;
+        if constrain_pitch {
+            self.constrain_pitch();
+        }
+    }
+
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.set_pitch_ex(pitch, true)
     }
     
     pub fn set_movement_speed(&mut self, movement_speed: f32) {
@@ -270,4 +381,441 @@ impl Camera {
     pub fn set_zoom(&mut self, zoom: f32) {
         self.zoom = zoom;
     }
+
+    // switching smoothing in either direction stops the camera
+    pub fn enable_smoothing(&mut self, enabled: bool) {
+        self.smoothing = enabled;
+        self.velocity = util::glm::empty_vec3();
+        self.target_velocity = util::glm::empty_vec3();
+    }
+}
+
+// Default orbit camera values
+const ORBIT_MIN_RADIUS: f32 = 0.5;
+const ORBIT_MAX_RADIUS: f32 = 50.0;
+const ORBIT_ZOOM_SPEED: f32 = 0.5;
+
+// An arcball style camera that keeps looking at a target point while the eye rotates around it on a
+// sphere. Mouse movement changes the azimuth (yaw) and elevation (pitch), scrolling changes the radius.
+pub struct OrbitCamera {
+    target: glm::TVec3<f32>,
+    radius: f32,
+    world_up: glm::TVec3<f32>,
+    // euler Angles of the eye as seen from the target
+    yaw: f32,
+    pitch: f32,
+    // camera options
+    mouse_sensitivity: f32,
+    zoom: f32
+}
+
+impl OrbitCamera {
+    pub fn new(target: glm::TVec3<f32>, radius: f32) -> Self {
+        Self {
+            target,
+            radius: util::glm::clamp(radius, ORBIT_MIN_RADIUS, ORBIT_MAX_RADIUS),
+            world_up: glm::vec3(0.0, 1.0, 0.0),
+            // start in front of the target, i.e. on its +z side
+            yaw: 90.0,
+            pitch: PITCH,
+            mouse_sensitivity: SENSITIVITY,
+            zoom: ZOOM
+        }
+    }
+
+    // the eye position on the orbit sphere
+    pub fn position(&self) -> glm::TVec3<f32> {
+        let offset = glm::vec3(
+            self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
+            self.pitch.to_radians().sin(),
+            self.yaw.to_radians().sin() * self.pitch.to_radians().cos()
+        );
+        self.target + offset * self.radius
+    }
+
+    // returns the view matrix looking from the eye towards the target
+    pub fn get_view_matrix(&self) -> glm::TMat4<f32> {
+        glm::look_at_rh(&self.position(), &self.target, &self.world_up)
+    }
+
+    // rotates the eye around the target. Pitch is constrained so the eye never passes over the poles.
+    pub fn process_mouse_orbit(&mut self, dx: f32, dy: f32) {
+        self.yaw = (self.yaw + dx * self.mouse_sensitivity) % 360.0;
+        self.pitch = util::glm::clamp(self.pitch + dy * self.mouse_sensitivity, -89.0, 89.0);
+    }
+
+    // moves the eye towards (positive offset) or away from the target
+    pub fn process_scroll_zoom(&mut self, y_offset: f32) {
+        self.radius = util::glm::clamp(self.radius - y_offset * ORBIT_ZOOM_SPEED, ORBIT_MIN_RADIUS, ORBIT_MAX_RADIUS);
+    }
+
+    pub fn target(&self) -> glm::TVec3<f32> {
+        self.target
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    pub fn mouse_sensitivity(&self) -> f32 {
+        self.mouse_sensitivity
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn set_target(&mut self, target: glm::TVec3<f32>) {
+        self.target = target;
+    }
+
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = util::glm::clamp(radius, ORBIT_MIN_RADIUS, ORBIT_MAX_RADIUS);
+    }
+
+    pub fn set_mouse_sensitivity(&mut self, mouse_sensitivity: f32) {
+        self.mouse_sensitivity = mouse_sensitivity;
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom;
+    }
+}
+
+// six world space planes (left, right, bottom, top, near, far) with normals pointing inwards,
+// stored as (a, b, c, d) so that a point p lies inside a plane when dot(abc, p) + d >= 0
+pub struct Frustum {
+    planes: [glm::TVec4<f32>; 6]
+}
+
+impl Frustum {
+    // Gribb/Hartmann plane extraction from a combined projection * view matrix
+    pub fn from_matrix(m: &glm::TMat4<f32>) -> Self {
+        let row = |i: usize| glm::vec4(m[(i, 0)], m[(i, 1)], m[(i, 2)], m[(i, 3)]);
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+        let planes = [
+            r3 + r0, // left
+            r3 - r0, // right
+            r3 + r1, // bottom
+            r3 - r1, // top
+            r3 + r2, // near
+            r3 - r2  // far
+        ].map(|plane| plane / glm::length(&util::glm::vec3_from_vec4(&plane)));
+        Self { planes }
+    }
+
+    pub fn contains_sphere(&self, center: &glm::TVec3<f32>, radius: f32) -> bool {
+        self.planes.iter().all(|plane| {
+            glm::dot(&util::glm::vec3_from_vec4(plane), center) + plane.w >= -radius
+        })
+    }
+
+    pub fn planes(&self) -> &[glm::TVec4<f32>; 6] {
+        &self.planes
+    }
+}
+
+// the persisted part of a camera; front, right and up are derived from these when loading
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct CameraState {
+    position: [f32; 3],
+    world_up: [f32; 3],
+    yaw: f32,
+    pitch: f32,
+    zoom: f32,
+    movement_speed: f32,
+    mouse_sensitivity: f32,
+    // missing in files saved before fixed cameras existed
+    #[serde(default)]
+    fixed: bool
+}
+
+#[cfg(feature = "serde")]
+impl From<&Camera> for CameraState {
+    fn from(camera: &Camera) -> Self {
+        Self {
+            position: [camera.position.x, camera.position.y, camera.position.z],
+            world_up: [camera.world_up.x, camera.world_up.y, camera.world_up.z],
+            yaw: camera.yaw,
+            pitch: camera.pitch,
+            zoom: camera.zoom,
+            movement_speed: camera.movement_speed,
+            mouse_sensitivity: camera.mouse_sensitivity,
+            fixed: camera.fixed
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<CameraState> for Camera {
+    fn from(state: CameraState) -> Self {
+        // the constructor recomputes the camera vectors from the restored angles
+        let mut camera = Camera::new(
+            glm::make_vec3(&state.position),
+            glm::make_vec3(&state.world_up),
+            state.yaw,
+            state.pitch
+        );
+        camera.zoom = state.zoom;
+        camera.movement_speed = state.movement_speed;
+        camera.mouse_sensitivity = state.mouse_sensitivity;
+        camera.fixed = state.fixed;
+        camera
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Camera {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CameraState::from(self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Camera {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        CameraState::deserialize(deserializer).map(Camera::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Camera {
+    // writes the camera as JSON so a viewpoint can be restored in a later run
+    pub fn save_to_file(&self, path: &str) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn load_from_file(path: &str) -> io::Result<Camera> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_fixed_test_ignores_input() {
+        let target = glm::vec3(1.0, 0.0, -2.0);
+        let mut camera = Camera::new_fixed(glm::vec3(0.0, 1.0, 3.0), target);
+        assert!(camera.is_fixed());
+        let view = camera.get_view_matrix();
+        let expected = glm::look_at(&camera.position(), &target, &glm::vec3(0.0, 1.0, 0.0));
+        assert!((view - expected).abs().max() < 1e-5);
+
+        camera.process_keyboard(Movement::FORWARD, 0.5);
+        camera.process_keyboard(Movement::LEFT, 0.5);
+        camera.process_mouse_movement(40.0, -25.0);
+        camera.process_mouse_scroll(10.0);
+        camera.update(0.5);
+        assert_eq!(camera.get_view_matrix(), view);
+        assert_eq!(camera.zoom(), ZOOM);
+    }
+
+    #[test]
+    fn screen_ray_test_center_hits_sphere_at_origin() {
+        const WIDTH: f32 = 800.0;
+        const HEIGHT: f32 = 600.0;
+        let camera = Camera::new_position(glm::vec3(0.0, 0.0, 3.0));
+        let projection = glm::perspective(WIDTH / HEIGHT, camera.zoom().to_radians(), 0.1, 100.0);
+        let (origin, direction) = camera.screen_ray(WIDTH / 2.0, HEIGHT / 2.0, WIDTH, HEIGHT, &projection);
+        assert!(glm::length(&(origin - glm::vec3(0.0, 0.0, 2.9))) < 1e-4, "Origin {:?}.", origin.as_slice());
+        assert!(glm::length(&(direction - glm::vec3(0.0, 0.0, -1.0))) < 1e-4, "Direction {:?}.", direction.as_slice());
+
+        let distance = util::glm::ray_sphere_intersect(&origin, &direction, &util::glm::empty_vec3(), 1.0)
+            .expect("The centered ray misses the sphere.");
+        assert!((distance - 1.9).abs() < 1e-4, "Hit at {}.", distance);
+
+        // the top left corner looks past it
+        let (origin, direction) = camera.screen_ray(0.0, 0.0, WIDTH, HEIGHT, &projection);
+        assert!(direction.x < 0.0 && direction.y > 0.0);
+        assert_eq!(util::glm::ray_sphere_intersect(&origin, &direction, &util::glm::empty_vec3(), 1.0), None);
+    }
+
+    #[test]
+    fn update_test_without_smoothing() {
+        let mut camera = Camera::new_position(glm::vec3(1.0, 2.0, 3.0));
+        camera.process_keyboard(Movement::RIGHT, 0.5);
+        let moved = camera.position();
+        camera.update(0.5);
+        assert_eq!(camera.position(), moved);
+        assert_eq!(camera.velocity(), util::glm::empty_vec3());
+    }
+
+    #[test]
+    fn update_test_smoothing() {
+        const DT: f32 = 1.0 / 60.0;
+        let start = glm::vec3(1.0, 2.0, 3.0);
+        let mut camera = Camera::new_position(start);
+        camera.enable_smoothing(true);
+        assert!(camera.smoothing());
+
+        // a single frame of input moves the camera less than the unsmoothed step would
+        camera.process_keyboard(Movement::FORWARD, DT);
+        assert_eq!(camera.position(), start);
+        camera.update(DT);
+        let first_step = glm::length(&(camera.position() - start));
+        assert!(first_step > 0.0 && first_step < camera.movement_speed() * DT, "Moved {}.", first_step);
+
+        // after the key is released the camera glides to a halt
+        for _ in 0..600 {
+            camera.update(DT);
+        }
+        let resting = camera.position();
+        camera.update(DT);
+        assert!(glm::length(&(camera.position() - resting)) < 1e-6);
+        assert!(glm::dot(&(resting - start), &camera.front()) > first_step);
+
+        // holding the key converges to the unsmoothed speed
+        for _ in 0..600 {
+            camera.process_keyboard(Movement::FORWARD, DT);
+            camera.update(DT);
+        }
+        assert!((glm::length(&camera.velocity()) - camera.movement_speed()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn process_keyboard_uses_movement_speed() {
+        let mut camera = Camera::new_position(glm::vec3(1.0, 2.0, 3.0));
+        camera.set_movement_speed(10.0);
+        assert_eq!(camera.movement_speed(), 10.0);
+        let front = camera.front();
+        camera.process_keyboard(Movement::FORWARD, 1.0);
+        let moved = camera.position() - glm::vec3(1.0, 2.0, 3.0);
+        assert!(glm::length(&(moved - front * 10.0)) < 1e-5);
+    }
+
+    #[test]
+    fn process_mouse_movement_uses_mouse_sensitivity() {
+        let mut camera = Camera::new_position(util::glm::empty_vec3());
+        camera.set_mouse_sensitivity(0.5);
+        assert_eq!(camera.mouse_sensitivity(), 0.5);
+        camera.process_mouse_movement(10.0, 4.0);
+        assert_eq!(camera.yaw(), YAW + 5.0);
+        assert_eq!(camera.pitch(), PITCH + 2.0);
+    }
+
+    fn test_frustum() -> Frustum {
+        // looking down -z from the origin
+        let camera = Camera::new_position(util::glm::empty_vec3());
+        let projection = glm::perspective(4.0 / 3.0, 45f32.to_radians(), 0.1, 100.0);
+        camera.extract_frustum(&projection)
+    }
+
+    #[test]
+    fn frustum_contains_sphere_in_view() {
+        assert!(test_frustum().contains_sphere(&glm::vec3(0.0, 0.0, -5.0), 1.0));
+    }
+
+    #[test]
+    fn frustum_rejects_sphere_behind_camera() {
+        assert!(!test_frustum().contains_sphere(&glm::vec3(0.0, 0.0, 5.0), 1.0));
+    }
+
+    #[test]
+    fn frustum_rejects_sphere_outside_planes() {
+        let frustum = test_frustum();
+        // far off to the side and beyond the far plane
+        assert!(!frustum.contains_sphere(&glm::vec3(50.0, 0.0, -5.0), 1.0));
+        assert!(!frustum.contains_sphere(&glm::vec3(0.0, 0.0, -150.0), 1.0));
+        // partially overlapping the near plane still counts as visible
+        assert!(frustum.contains_sphere(&glm::vec3(0.0, 0.0, 0.5), 1.0));
+        for plane in frustum.planes() {
+            assert!((glm::length(&util::glm::vec3_from_vec4(plane)) - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn orbit_full_circle_returns_to_start() {
+        let mut camera = OrbitCamera::new(glm::vec3(1.0, 0.5, -2.0), 4.0);
+        camera.process_mouse_orbit(0.0, 150.0);
+        let start = camera.position();
+        // 360 degrees worth of horizontal mouse movement, in several steps
+        let steps = 36;
+        for _ in 0..steps {
+            camera.process_mouse_orbit(360.0 / camera.mouse_sensitivity() / steps as f32, 0.0);
+        }
+        assert!(glm::length(&(camera.position() - start)) < 1e-3);
+    }
+
+    #[test]
+    fn orbit_keeps_distance_and_looks_at_target() {
+        let target = glm::vec3(1.0, 0.5, -2.0);
+        let mut camera = OrbitCamera::new(target, 4.0);
+        camera.process_mouse_orbit(123.0, -45.0);
+        assert!((glm::distance(&camera.position(), &target) - 4.0).abs() < 1e-4);
+        // the target ends up straight ahead on the view space -z axis
+        let target_view = camera.get_view_matrix() * util::glm::vec4_wrap_vec3(&target, 1.0);
+        assert!(target_view.x.abs() < 1e-4 && target_view.y.abs() < 1e-4);
+        assert!((target_view.z + 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn orbit_scroll_changes_radius_within_limits() {
+        let mut camera = OrbitCamera::new(util::glm::empty_vec3(), 4.0);
+        camera.process_scroll_zoom(2.0);
+        assert_eq!(camera.radius(), 3.0);
+        camera.process_scroll_zoom(100.0);
+        assert_eq!(camera.radius(), ORBIT_MIN_RADIUS);
+        camera.process_scroll_zoom(-1000.0);
+        assert_eq!(camera.radius(), ORBIT_MAX_RADIUS);
+    }
+
+    #[test]
+    fn set_pitch_updates_front_after_recompute() {
+        let mut camera = Camera::new_position(util::glm::empty_vec3());
+        camera.set_pitch(45.0);
+        camera.update_camera_vectors();
+        assert!((camera.front().y - 45f32.to_radians().sin()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn set_pitch_constrains_unless_disabled() {
+        let mut camera = Camera::new_position(util::glm::empty_vec3());
+        camera.set_pitch(120.0);
+        assert_eq!(camera.pitch(), 89.0);
+        camera.set_pitch(-120.0);
+        assert_eq!(camera.pitch(), -89.0);
+        camera.set_pitch_ex(120.0, false);
+        assert_eq!(camera.pitch(), 120.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut camera = Camera::new(glm::vec3(1.0, 2.0, 3.0), glm::vec3(0.0, 1.0, 0.0), -45.0, 20.0);
+        camera.set_zoom(30.0);
+        camera.set_movement_speed(7.5);
+        camera.set_mouse_sensitivity(0.2);
+        let path = std::env::temp_dir().join(format!("learnopengl_camera_round_trip_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        camera.save_to_file(path).expect("The camera should be saved.");
+        let loaded = Camera::load_from_file(path).expect("The camera should be loaded.");
+        std::fs::remove_file(path).unwrap();
+
+        let difference = camera.get_view_matrix() - loaded.get_view_matrix();
+        assert!(difference.iter().all(|it| it.abs() < 1e-5));
+        assert_eq!(loaded.zoom(), 30.0);
+        assert_eq!(loaded.movement_speed(), 7.5);
+        assert_eq!(loaded.mouse_sensitivity(), 0.2);
+        assert!(!loaded.is_fixed());
+
+        let camera = Camera::new_fixed(glm::vec3(0.0, 1.0, 3.0), util::glm::empty_vec3());
+        camera.save_to_file(path).expect("The camera should be saved.");
+        let loaded = Camera::load_from_file(path).expect("The camera should be loaded.");
+        std::fs::remove_file(path).unwrap();
+        assert!(loaded.is_fixed());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,726 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::ffi::{c_void, CStr};
+use std::ptr;
+use gl::types::{GLchar, GLenum, GLsizei, GLuint};
+use crate::{filesystem, primitives};
+use crate::shader::{Shader, ShaderError};
+use crate::util;
+
+// Default overlay values
+const LINE_WIDTH: f32 = 1.0;
+const FILL_COLOR: [f32; 3] = [0.8, 0.8, 0.8];
+const OFFSET_FACTOR: f32 = 1.0;
+const OFFSET_UNITS: f32 = 1.0;
+// positions within [-10, 10] on every axis show up in the g-buffer position view
+const POSITION_SCALE: f32 = 0.1;
+const GRID_SIZE: f32 = 10.0;
+const GRID_DIVISIONS: u32 = 20;
+const AXIS_LENGTH: f32 = 2.0;
+
+// Draws the shaded geometry first and then the very same geometry again as thin lines on top of it.
+// The solid pass is pushed back with polygon offset so the lines don't z-fight with the surface.
+pub struct WireframeOverlay {
+    shader: Shader,
+    enabled: bool,
+    color: glm::TVec3<f32>,
+    line_width: f32,
+    offset_factor: f32,
+    offset_units: f32
+}
+
+impl WireframeOverlay {
+    pub fn new(color: glm::TVec3<f32>) -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/debug/wireframe_overlay.vs".to_string()),
+            filesystem::get_path("resources/shaders/debug/wireframe_overlay.fs".to_string()),
+            None
+        );
+        Self {
+            shader,
+            enabled: false,
+            color,
+            line_width: LINE_WIDTH,
+            offset_factor: OFFSET_FACTOR,
+            offset_units: OFFSET_UNITS
+        }
+    }
+
+    // renders one frame worth of geometry. `solid_pass` is expected to draw the geometry with the
+    // caller's own shader, `wire_pass` receives the (already activated) overlay shader and should set
+    // the `projection`, `view` and `model` uniforms before issuing the same draw calls again.
+    pub fn draw<S, W>(&self, solid_pass: S, wire_pass: W)
+    where
+        S: FnOnce(),
+        W: FnOnce(&Shader)
+    {
+        if !self.enabled {
+            solid_pass();
+            return;
+        }
+
+        // 1. shaded pass, slightly pushed back into the depth buffer
+        util::render::set_polygon_offset(true, self.offset_factor, self.offset_units);
+        solid_pass();
+        util::render::set_polygon_offset(false, 0.0, 0.0);
+
+        // 2. the same geometry as lines on top
+        self.shader.use_shader();
+        self.shader.set_vec3("wireColor".to_string(), &self.color);
+        util::render::set_line_width(self.line_width);
+        unsafe {
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+        }
+        wire_pass(&self.shader);
+        unsafe {
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+        }
+        util::render::set_line_width(1.0);
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn color(&self) -> glm::TVec3<f32> {
+        self.color
+    }
+
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    pub fn offset_factor(&self) -> f32 {
+        self.offset_factor
+    }
+
+    pub fn offset_units(&self) -> f32 {
+        self.offset_units
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn set_color(&mut self, color: glm::TVec3<f32>) {
+        self.color = color;
+    }
+
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.line_width = line_width;
+    }
+
+    pub fn set_polygon_offset(&mut self, factor: f32, units: f32) {
+        self.offset_factor = factor;
+        self.offset_units = units;
+    }
+}
+
+// Single pass wireframe: a geometry shader hands out barycentric coordinates per triangle and the
+// fragment shader darkens everything close to an edge. As there's only one pass no polygon offset is
+// required and z-fighting can't happen. Works with any mesh that provides positions at location 0.
+pub struct BarycentricWireframe {
+    shader: Shader,
+    wire_color: glm::TVec3<f32>,
+    fill_color: glm::TVec3<f32>,
+    line_width: f32
+}
+
+impl BarycentricWireframe {
+    pub fn new(wire_color: glm::TVec3<f32>) -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/debug/barycentric_wireframe.vs".to_string()),
+            filesystem::get_path("resources/shaders/debug/barycentric_wireframe.fs".to_string()),
+            Some(filesystem::get_path("resources/shaders/debug/barycentric_wireframe.gs".to_string()))
+        );
+        Self {
+            shader,
+            wire_color,
+            fill_color: glm::vec3(FILL_COLOR[0], FILL_COLOR[1], FILL_COLOR[2]),
+            line_width: LINE_WIDTH
+        }
+    }
+
+    // activates the program and uploads the camera matrices and colors. `draw_geometry` receives the
+    // program so it can set the `model` uniform before issuing its draw calls.
+    pub fn draw<F>(&self, projection: &glm::TMat4<f32>, view: &glm::TMat4<f32>, draw_geometry: F)
+    where
+        F: FnOnce(&Shader)
+    {
+        self.shader.use_shader();
+        self.shader.set_mat4("projection".to_string(), projection);
+        self.shader.set_mat4("view".to_string(), view);
+        self.shader.set_vec3("wireColor".to_string(), &self.wire_color);
+        self.shader.set_vec3("fillColor".to_string(), &self.fill_color);
+        self.shader.set_float("lineWidth".to_string(), self.line_width);
+        draw_geometry(&self.shader);
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn wire_color(&self) -> glm::TVec3<f32> {
+        self.wire_color
+    }
+
+    pub fn fill_color(&self) -> glm::TVec3<f32> {
+        self.fill_color
+    }
+
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    pub fn set_wire_color(&mut self, wire_color: glm::TVec3<f32>) {
+        self.wire_color = wire_color;
+    }
+
+    pub fn set_fill_color(&mut self, fill_color: glm::TVec3<f32>) {
+        self.fill_color = fill_color;
+    }
+
+    // line width in pixels
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.line_width = line_width;
+    }
+}
+
+// the g-buffer target GBufferViewer shows, Off being the regular (lit) output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GBufferView {
+    Off,
+    Position,
+    Normal,
+    Albedo
+}
+
+impl GBufferView {
+    pub const ALL: [GBufferView; 4] = [GBufferView::Off, GBufferView::Position, GBufferView::Normal, GBufferView::Albedo];
+
+    // the value of the `gBufferView` uniform of gbuffer_view.fs
+    fn id(self) -> i32 {
+        match self {
+            GBufferView::Off => 0,
+            GBufferView::Position => 1,
+            GBufferView::Normal => 2,
+            GBufferView::Albedo => 3
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GBufferView::Off => "off",
+            GBufferView::Position => "position",
+            GBufferView::Normal => "normal",
+            GBufferView::Albedo => "albedo"
+        }
+    }
+
+    // the following view, wrapping around, for cycling through them with a key
+    pub fn next(self) -> Self {
+        Self::ALL[(self.id() as usize + 1) % Self::ALL.len()]
+    }
+}
+
+// Shows one raw target of a deferred renderer's g-buffer fullscreen, remapped into displayable colors:
+// normals from [-1, 1] to [0, 1] and positions scaled by `position_scale` before the same remapping.
+// Meant to be drawn after the lighting pass, covering it.
+pub struct GBufferViewer {
+    shader: Shader,
+    view: GBufferView,
+    position_scale: f32
+}
+
+impl GBufferViewer {
+    pub fn new() -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/effects/fullscreen.vs".to_string()),
+            filesystem::get_path("resources/shaders/debug/gbuffer_view.fs".to_string()),
+            None
+        );
+        shader.use_shader();
+        shader.set_int("gBufferTexture".to_string(), 0);
+        Self {
+            shader,
+            view: GBufferView::Off,
+            position_scale: POSITION_SCALE
+        }
+    }
+
+    // renders the target of the current view as a fullscreen quad into the bound framebuffer, ignoring
+    // (and keeping) the depth buffer. Draws nothing and returns false if the view is off.
+    pub fn draw(&self, g_position: u32, g_normal: u32, g_albedo: u32) -> bool {
+        let texture = match self.view {
+            GBufferView::Off => return false,
+            GBufferView::Position => g_position,
+            GBufferView::Normal => g_normal,
+            GBufferView::Albedo => g_albedo
+        };
+        self.shader.use_shader();
+        self.shader.set_int("gBufferView".to_string(), self.view.id());
+        self.shader.set_float("positionScale".to_string(), self.position_scale);
+        unsafe {
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            gl::Disable(gl::DEPTH_TEST);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            primitives::render_quad();
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+        true
+    }
+
+    // switches to the next view and returns it
+    pub fn cycle(&mut self) -> GBufferView {
+        self.view = self.view.next();
+        self.view
+    }
+
+    pub fn view(&self) -> GBufferView {
+        self.view
+    }
+
+    pub fn position_scale(&self) -> f32 {
+        self.position_scale
+    }
+
+    pub fn set_view(&mut self, view: GBufferView) {
+        self.view = view;
+    }
+
+    // positions within [-1 / position_scale, 1 / position_scale] are shown
+    pub fn set_position_scale(&mut self, position_scale: f32) {
+        self.position_scale = position_scale;
+    }
+}
+
+impl Default for GBufferViewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Orientation aid for flying around a scene: a ground grid through the origin and the X, Y and Z axes
+// (red, green, blue) drawn with primitives::render_grid and primitives::render_axes. The lines are
+// depth tested against what's already in the bound framebuffer.
+pub struct GizmoOverlay {
+    shader: Shader,
+    enabled: bool,
+    grid_size: f32,
+    grid_divisions: u32,
+    axis_length: f32,
+    line_width: f32
+}
+
+impl GizmoOverlay {
+    pub fn new() -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/debug/gizmo.vs".to_string()),
+            filesystem::get_path("resources/shaders/debug/gizmo.fs".to_string()),
+            None
+        );
+        Self {
+            shader,
+            enabled: false,
+            grid_size: GRID_SIZE,
+            grid_divisions: GRID_DIVISIONS,
+            axis_length: AXIS_LENGTH,
+            line_width: LINE_WIDTH
+        }
+    }
+
+    // draws the grid and the axes if enabled. The program in use and the line width are restored
+    // afterwards, so the overlay can be dropped in between the passes of a frame.
+    pub fn draw(&self, projection: &glm::TMat4<f32>, view: &glm::TMat4<f32>) {
+        if !self.enabled {
+            return;
+        }
+        unsafe {
+            let mut previous_program = 0i32;
+            let mut previous_line_width = 0f32;
+            gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut previous_program);
+            gl::GetFloatv(gl::LINE_WIDTH, &mut previous_line_width);
+
+            self.shader.use_shader();
+            self.shader.set_mat4("projection".to_string(), projection);
+            self.shader.set_mat4("view".to_string(), view);
+            util::render::set_line_width(self.line_width);
+            primitives::render_grid(self.grid_size, self.grid_divisions);
+            primitives::render_axes(self.axis_length);
+
+            util::render::set_line_width(previous_line_width);
+            gl::UseProgram(previous_program as _);
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn grid_size(&self) -> f32 {
+        self.grid_size
+    }
+
+    pub fn grid_divisions(&self) -> u32 {
+        self.grid_divisions
+    }
+
+    pub fn axis_length(&self) -> f32 {
+        self.axis_length
+    }
+
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    // the grid spans [-size, size] on X and Z with `divisions` cells along each axis
+    pub fn set_grid(&mut self, size: f32, divisions: u32) {
+        self.grid_size = size;
+        self.grid_divisions = divisions;
+    }
+
+    pub fn set_axis_length(&mut self, axis_length: f32) {
+        self.axis_length = axis_length;
+    }
+
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.line_width = line_width;
+    }
+}
+
+impl Default for GizmoOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// builds the program of the built-in normal visualization (resources/shaders/debug/debug_normals.*):
+// its geometry shader turns every triangle into a short line along the normal of each vertex. The
+// program expects positions at location 0, normals at location 1 and the `mvp`, `magnitude` and
+// `lineColor` uniforms. `learnopengl_shared_ex::debug::DebugNormals` wraps it for whole models.
+pub fn debug_normals_shader() -> Result<Shader, ShaderError> {
+    Shader::try_new(
+        filesystem::get_path("resources/shaders/debug/debug_normals.vs".to_string()),
+        filesystem::get_path("resources/shaders/debug/debug_normals.fs".to_string()),
+        Some(filesystem::get_path("resources/shaders/debug/debug_normals.gs".to_string()))
+    )
+}
+
+// returns the name of an error code reported by glGetError
+pub fn gl_error_name(error: GLenum) -> &'static str {
+    match error {
+        gl::NO_ERROR => "NO_ERROR",
+        gl::INVALID_ENUM => "INVALID_ENUM",
+        gl::INVALID_VALUE => "INVALID_VALUE",
+        gl::INVALID_OPERATION => "INVALID_OPERATION",
+        gl::INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION",
+        gl::OUT_OF_MEMORY => "OUT_OF_MEMORY",
+        gl::STACK_UNDERFLOW => "STACK_UNDERFLOW",
+        gl::STACK_OVERFLOW => "STACK_OVERFLOW",
+        _ => "UNKNOWN_ERROR"
+    }
+}
+
+// drains the GL error queue, returning every pending error in the order it was raised
+pub fn take_gl_errors() -> Vec<GLenum> {
+    let mut errors = Vec::new();
+    unsafe {
+        loop {
+            let error = gl::GetError();
+            if error == gl::NO_ERROR {
+                break;
+            }
+            errors.push(error);
+        }
+    }
+    errors
+}
+
+// panics if any GL error is pending, `context` names the code that was just run
+pub fn assert_no_gl_errors(context: &str) {
+    let errors = take_gl_errors();
+    if !errors.is_empty() {
+        let names: Vec<_> = errors.iter().map(|error| gl_error_name(*error)).collect();
+        panic!("GL errors after {}: {}", context, names.join(", "));
+    }
+}
+
+// logs every pending GL error to stderr, `label` names the code that was just run (e.g. a pass of
+// the frame). Returns whether there were any. This is the fallback for contexts without debug output.
+pub fn check_gl_error(label: &str) -> bool {
+    let errors = take_gl_errors();
+    for error in errors.iter() {
+        eprintln!("GL error after {}: {} (0x{:04X})", label, gl_error_name(*error), error);
+    }
+    !errors.is_empty()
+}
+
+// reads the color of the default framebuffer at window position (`x`, `y`), counted in pixels from the
+// top left corner like cursor positions, as normalized RGBA floats. What's read is what ends up on screen
+// (after the sRGB conversion of an sRGB framebuffer), so it's meant for checking the final output, e.g.
+// linear vs gamma corrected colors. Call it before swapping buffers, it reads the back buffer.
+pub fn sample_pixel(x: i32, y: i32) -> [f32; 4] {
+    let mut pixel = [0f32; 4];
+    unsafe {
+        // the window's height is the top of the viewport, GL counts rows from the bottom
+        let mut viewport = [0i32; 4];
+        gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        let flipped_y = viewport[1] + viewport[3] - 1 - y;
+
+        let mut previous_read_framebuffer = 0i32;
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read_framebuffer);
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        gl::ReadPixels(x, flipped_y, 1, 1, gl::RGBA, gl::FLOAT, pixel.as_mut_ptr() as _);
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read_framebuffer as _);
+    }
+    pixel
+}
+
+// Registers a callback that logs every message of the GL debug output to stderr (errors right where
+// they're raised, with source, type and severity). Only debug contexts (glfw's OpenGlDebugContext
+// hint) with KHR_debug / GL 4.3 provide it; returns false if it's not available, in which case
+// check_gl_error can be called after each frame instead.
+pub fn enable_gl_debug_output() -> bool {
+    unsafe {
+        let mut flags = 0i32;
+        gl::GetIntegerv(gl::CONTEXT_FLAGS, &mut flags);
+        if flags & gl::CONTEXT_FLAG_DEBUG_BIT as i32 == 0 || !gl::DebugMessageCallback::is_loaded() {
+            return false;
+        }
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS); // makes sure errors are displayed synchronously
+        gl::DebugMessageCallback(Some(gl_debug_output), ptr::null());
+        gl::DebugMessageControl(gl::DONT_CARE, gl::DONT_CARE, gl::DONT_CARE, 0, ptr::null(), gl::TRUE);
+    }
+    true
+}
+
+extern "system" fn gl_debug_output(
+    source: GLenum,
+    gltype: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    _length: GLsizei,
+    message: *const GLchar,
+    _user_param: *mut c_void
+) {
+    if id == 131169 || id == 131185 || id == 131218 || id == 131204 { // ignore these non-significant error codes
+        return;
+    }
+    let message_str = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    eprintln!(
+        "GL debug message ({}) [{}, {}, severity {}]: {}",
+        id, debug_source_name(source), debug_type_name(gltype), debug_severity_name(severity), message_str
+    );
+}
+
+fn debug_source_name(source: GLenum) -> &'static str {
+    match source {
+        gl::DEBUG_SOURCE_API => "API",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "Window System",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "Shader Compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "Third Party",
+        gl::DEBUG_SOURCE_APPLICATION => "Application",
+        _ => "Other"
+    }
+}
+
+fn debug_type_name(gltype: GLenum) -> &'static str {
+    match gltype {
+        gl::DEBUG_TYPE_ERROR => "Error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "Deprecated Behaviour",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "Undefined Behaviour",
+        gl::DEBUG_TYPE_PORTABILITY => "Portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "Performance",
+        gl::DEBUG_TYPE_MARKER => "Marker",
+        gl::DEBUG_TYPE_PUSH_GROUP => "Push Group",
+        gl::DEBUG_TYPE_POP_GROUP => "Pop Group",
+        _ => "Other"
+    }
+}
+
+fn debug_severity_name(severity: GLenum) -> &'static str {
+    match severity {
+        gl::DEBUG_SEVERITY_HIGH => "high",
+        gl::DEBUG_SEVERITY_MEDIUM => "medium",
+        gl::DEBUG_SEVERITY_LOW => "low",
+        _ => "notification"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+
+    #[test]
+    fn gl_error_name_test() {
+        assert_eq!(gl_error_name(gl::INVALID_ENUM), "INVALID_ENUM");
+        assert_eq!(gl_error_name(gl::INVALID_FRAMEBUFFER_OPERATION), "INVALID_FRAMEBUFFER_OPERATION");
+        assert_eq!(gl_error_name(0xFFFF), "UNKNOWN_ERROR");
+    }
+
+    #[test]
+    fn debug_names_test() {
+        assert_eq!(debug_source_name(gl::DEBUG_SOURCE_SHADER_COMPILER), "Shader Compiler");
+        assert_eq!(debug_type_name(gl::DEBUG_TYPE_ERROR), "Error");
+        assert_eq!(debug_severity_name(gl::DEBUG_SEVERITY_HIGH), "high");
+        assert_eq!(debug_severity_name(gl::DEBUG_SEVERITY_NOTIFICATION), "notification");
+    }
+
+    #[test]
+    fn debug_normals_shader_test_links() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let shader = debug_normals_shader().unwrap_or_else(|err| panic!("{}", err));
+        let mut attached = 0i32;
+        unsafe {
+            gl::GetProgramiv(shader.id(), gl::ATTACHED_SHADERS, &mut attached);
+        }
+        // the stages are deleted after linking but stay attached to the program until it's deleted
+        assert_eq!(attached, 3);
+    }
+
+    #[test]
+    fn check_gl_error_test() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        take_gl_errors();
+        assert!(!check_gl_error("nothing"));
+        unsafe {
+            gl::Enable(0xFFFF); // not a capability
+        }
+        assert!(check_gl_error("enabling an invalid capability"));
+        // the error queue has been drained
+        assert!(take_gl_errors().is_empty());
+    }
+
+    #[test]
+    fn sample_pixel_test_reads_back_clear_color() {
+        let Some(_context) = hidden_gl_context(8, 8) else {
+            return;
+        };
+        unsafe {
+            gl::ClearColor(0.25, 0.5, 0.75, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            // only the top left quarter turns red: the scissor box starts at y = 4 as GL counts rows from the
+            // bottom, sample_pixel flips y so it takes window coordinates with the origin at the top left
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(0, 4, 4, 4);
+            gl::ClearColor(1.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::Disable(gl::SCISSOR_TEST);
+        }
+        let expect = |pixel: [f32; 4], color: [f32; 4]| {
+            for (channel, expected) in pixel.iter().zip(color.iter()) {
+                assert!((channel - expected).abs() < 1.0 / 255.0, "Sampled {:?}, expected {:?}.", pixel, color);
+            }
+        };
+        expect(sample_pixel(1, 1), [1.0, 0.0, 0.0, 1.0]);
+        expect(sample_pixel(6, 6), [0.25, 0.5, 0.75, 1.0]);
+        expect(sample_pixel(1, 6), [0.25, 0.5, 0.75, 1.0]);
+        assert!(take_gl_errors().is_empty());
+    }
+
+    #[test]
+    fn gbuffer_viewer_test_remaps_normals() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        primitives::reset();
+        let normal = [0.6f32, -0.8, 0.0];
+        let mut g_normal = 0u32;
+        unsafe {
+            gl::GenTextures(1, &mut g_normal);
+            gl::BindTexture(gl::TEXTURE_2D, g_normal);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA16F as _, 1, 1, 0, gl::RGB, gl::FLOAT, normal.as_ptr() as *const _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl::Viewport(0, 0, 4, 4);
+        }
+
+        let mut viewer = GBufferViewer::new();
+        assert!(!viewer.draw(0, g_normal, 0));
+        viewer.set_view(GBufferView::Normal);
+        assert!(viewer.draw(0, g_normal, 0));
+        let pixel = sample_pixel(2, 2);
+        unsafe {
+            gl::DeleteTextures(1, &g_normal);
+        }
+        primitives::reset();
+        for (channel, expected) in pixel.iter().zip([0.8, 0.1, 0.5, 1.0]) {
+            assert!((channel - expected).abs() < 2.0 / 255.0, "Got {:?}.", pixel);
+        }
+        assert!(take_gl_errors().is_empty());
+    }
+
+    #[test]
+    fn gizmo_overlay_test_restores_program() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        primitives::reset();
+        let previous = GBufferViewer::new();
+        previous.shader.use_shader();
+        let mut gizmos = GizmoOverlay::new();
+        gizmos.set_enabled(true);
+        let identity = util::glm::diag_mat4(1.0);
+        gizmos.draw(&identity, &identity);
+
+        let mut program = 0i32;
+        let mut line_width = 0f32;
+        unsafe {
+            gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut program);
+            gl::GetFloatv(gl::LINE_WIDTH, &mut line_width);
+        }
+        primitives::reset();
+        assert_eq!(program as u32, previous.shader.id());
+        assert_eq!(line_width, 1.0);
+        assert!(take_gl_errors().is_empty());
+    }
+
+    #[test]
+    fn gbuffer_view_test_cycles() {
+        let mut view = GBufferView::Off;
+        for expected in [GBufferView::Position, GBufferView::Normal, GBufferView::Albedo, GBufferView::Off] {
+            view = view.next();
+            assert_eq!(view, expected);
+        }
+    }
+}
\ No newline at end of file
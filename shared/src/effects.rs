@@ -0,0 +1,383 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::error::Error;
+use std::fs;
+use gl::types::*;
+use crate::{filesystem, primitives};
+use crate::shader::Shader;
+
+// Default color grading values
+const IDENTITY_LUT_SIZE: u32 = 16;
+const INTENSITY: f32 = 1.0;
+
+// A 3D color lookup table as stored in an Adobe/Resolve `.cube` file. The entries are RGB triples
+// with the red component changing fastest, then green, then blue - which is exactly the memory
+// layout glTexImage3D expects for a (r, g, b) -> (x, y, z) mapping.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubeLut {
+    pub title: String,
+    pub size: u32,
+    pub domain_min: glm::TVec3<f32>,
+    pub domain_max: glm::TVec3<f32>,
+    pub data: Vec<f32>
+}
+
+// size^3, the number of RGB entries of a LUT, None if that doesn't fit in a usize
+fn entry_count(size: u32) -> Option<usize> {
+    (size as usize).checked_mul(size as usize)?.checked_mul(size as usize)
+}
+
+impl CubeLut {
+    // a neutral LUT that maps every color onto itself. Panics if `size` is less than 2 (both ends of
+    // each channel need an entry) or the table wouldn't fit into memory.
+    pub fn identity(size: u32) -> Self {
+        assert!(size >= 2, "A LUT needs a size of at least 2, got {}.", size);
+        let capacity = entry_count(size)
+            .and_then(|entries| entries.checked_mul(3))
+            .unwrap_or_else(|| panic!("A LUT of size {} is too large.", size));
+        let mut data = Vec::with_capacity(capacity);
+        let max = (size - 1) as f32;
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push(r as f32 / max);
+                    data.push(g as f32 / max);
+                    data.push(b as f32 / max);
+                }
+            }
+        }
+        Self {
+            title: String::new(),
+            size,
+            domain_min: glm::vec3(0.0, 0.0, 0.0),
+            domain_max: glm::vec3(1.0, 1.0, 1.0),
+            data
+        }
+    }
+
+    pub fn from_file(path: String) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(content.as_str())
+    }
+
+    // parses the textual `.cube` format: keyword lines (TITLE, LUT_3D_SIZE, DOMAIN_MIN, DOMAIN_MAX),
+    // comments starting with '#' and finally size^3 lines of "r g b" values.
+    pub fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
+        let mut title = String::new();
+        let mut size: Option<u32> = None;
+        let mut domain_min = glm::vec3(0.0, 0.0, 0.0);
+        let mut domain_max = glm::vec3(1.0, 1.0, 1.0);
+        let mut data: Vec<f32> = Vec::new();
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut tokens = line.split_whitespace();
+            let keyword = tokens.next().unwrap();
+            match keyword {
+                "TITLE" => {
+                    title = line["TITLE".len()..].trim().trim_matches('"').to_string();
+                }
+                "LUT_3D_SIZE" => {
+                    let value: u32 = tokens.next()
+                        .ok_or(format!("Line {}: LUT_3D_SIZE without a value.", i + 1))?
+                        .parse()?;
+                    if value < 2 {
+                        return Err(format!("Line {}: LUT_3D_SIZE must be at least 2.", i + 1).into());
+                    }
+                    size = Some(value);
+                }
+                "LUT_1D_SIZE" => {
+                    return Err(format!("Line {}: 1D LUTs are not supported.", i + 1).into());
+                }
+                "DOMAIN_MIN" => {
+                    domain_min = Self::parse_triple(tokens, i)?;
+                }
+                "DOMAIN_MAX" => {
+                    domain_max = Self::parse_triple(tokens, i)?;
+                }
+                _ => {
+                    // anything else has to be a table entry
+                    let entry = Self::parse_triple(line.split_whitespace(), i)?;
+                    data.push(entry.x);
+                    data.push(entry.y);
+                    data.push(entry.z);
+                }
+            }
+        }
+
+        let size = size.ok_or("Missing LUT_3D_SIZE.")?;
+        let expected = entry_count(size).ok_or(format!("LUT_3D_SIZE {} is too large.", size))?;
+        if data.len() / 3 != expected {
+            return Err(format!("Expected {} LUT entries but found {}.", expected, data.len() / 3).into());
+        }
+        Ok(Self {
+            title,
+            size,
+            domain_min,
+            domain_max,
+            data
+        })
+    }
+
+    fn parse_triple<'a>(
+        mut tokens: impl Iterator<Item = &'a str>,
+        line_index: usize
+    ) -> Result<glm::TVec3<f32>, Box<dyn Error>> {
+        let mut values = [0f32; 3];
+        for value in values.iter_mut() {
+            *value = tokens.next()
+                .ok_or(format!("Line {}: expected three values.", line_index + 1))?
+                .parse()?;
+        }
+        if tokens.next().is_some() {
+            return Err(format!("Line {}: expected three values.", line_index + 1).into());
+        }
+        Ok(glm::vec3(values[0], values[1], values[2]))
+    }
+
+    // returns the table entry at the given lattice coordinates
+    pub fn entry(&self, r: u32, g: u32, b: u32) -> glm::TVec3<f32> {
+        let i = ((b * self.size * self.size + g * self.size + r) * 3) as usize;
+        glm::vec3(self.data[i], self.data[i + 1], self.data[i + 2])
+    }
+}
+
+// uploads the LUT as a RGB floating point 3D texture with linear filtering so the hardware
+// does the trilinear interpolation between the lattice points for us.
+pub fn upload_lut_texture(lut: &CubeLut) -> u32 {
+    let mut texture_id = 0u32;
+    unsafe {
+        gl::GenTextures(1, &mut texture_id);
+        gl::BindTexture(gl::TEXTURE_3D, texture_id);
+        gl::TexImage3D(
+            gl::TEXTURE_3D,
+            0,
+            gl::RGB16F as GLint,
+            lut.size as GLsizei,
+            lut.size as GLsizei,
+            lut.size as GLsizei,
+            0,
+            gl::RGB,
+            gl::FLOAT,
+            lut.data.as_ptr() as *const _
+        );
+        gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+        gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+        gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as GLint);
+        gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+        gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+        gl::BindTexture(gl::TEXTURE_3D, 0);
+    }
+    texture_id
+}
+
+// Final composite stage: exposure tone maps the HDR scene, gamma corrects it and finally remaps
+// the resulting display color through a 3D LUT.
+pub struct ColorGrade {
+    shader: Shader,
+    lut_texture: u32,
+    lut: CubeLut,
+    intensity: f32
+}
+
+impl ColorGrade {
+    pub fn new(lut: CubeLut) -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/effects/fullscreen.vs".to_string()),
+            filesystem::get_path("resources/shaders/effects/color_grade.fs".to_string()),
+            None
+        );
+        shader.use_shader();
+        shader.set_int("hdrBuffer".to_string(), 0);
+        shader.set_int("lut".to_string(), 1);
+        let lut_texture = upload_lut_texture(&lut);
+        Self {
+            shader,
+            lut_texture,
+            lut,
+            intensity: INTENSITY
+        }
+    }
+
+    // a color grade that leaves the image untouched
+    pub fn new_identity() -> Self {
+        Self::new(CubeLut::identity(IDENTITY_LUT_SIZE))
+    }
+
+    pub fn from_cube_file(path: String) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(CubeLut::from_file(path)?))
+    }
+
+    // renders the graded image of `hdr_texture` as a fullscreen quad into the currently bound framebuffer
+    pub fn apply(&self, hdr_texture: u32, exposure: f32) {
+        self.shader.use_shader();
+        self.shader.set_float("lutSize".to_string(), self.lut.size as f32);
+        self.shader.set_vec3("domainMin".to_string(), &self.lut.domain_min);
+        self.shader.set_vec3("domainMax".to_string(), &self.lut.domain_max);
+        self.shader.set_float("exposure".to_string(), exposure);
+        self.shader.set_float("intensity".to_string(), self.intensity);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, hdr_texture);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_3D, self.lut_texture);
+        }
+        primitives::render_quad();
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+
+    // swaps the LUT, e.g. to compare several looks at runtime
+    pub fn set_lut(&mut self, lut: CubeLut) {
+        unsafe {
+            gl::DeleteTextures(1, &self.lut_texture);
+        }
+        self.lut_texture = upload_lut_texture(&lut);
+        self.lut = lut;
+    }
+
+    pub fn lut(&self) -> &CubeLut {
+        &self.lut
+    }
+
+    pub fn lut_texture(&self) -> u32 {
+        self.lut_texture
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    // blends between the ungraded (0.0) and the fully graded (1.0) image
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+}
+
+impl Drop for ColorGrade {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.lut_texture);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_CUBE: &str = "# a tiny LUT\n\
+TITLE \"Tiny\"\n\
+LUT_3D_SIZE 2\n\
+DOMAIN_MIN 0.0 0.0 0.0\n\
+DOMAIN_MAX 1.0 1.0 1.0\n\
+\n\
+0.0 0.0 0.0\n\
+1.0 0.0 0.0\n\
+0.0 1.0 0.0\n\
+1.0 1.0 0.0\n\
+0.0 0.0 1.0\n\
+1.0 0.0 1.0\n\
+0.0 1.0 1.0\n\
+0.5 0.25 0.125\n";
+
+    #[test]
+    fn identity_test() {
+        let lut = CubeLut::identity(4);
+        assert_eq!(lut.data.len(), 4 * 4 * 4 * 3);
+        assert_eq!(lut.entry(0, 0, 0), glm::vec3(0.0, 0.0, 0.0));
+        assert_eq!(lut.entry(3, 0, 0), glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(lut.entry(0, 3, 0), glm::vec3(0.0, 1.0, 0.0));
+        assert_eq!(lut.entry(0, 0, 3), glm::vec3(0.0, 0.0, 1.0));
+        assert_eq!(lut.entry(3, 3, 3), glm::vec3(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn parse_test() {
+        let lut = CubeLut::parse(SMALL_CUBE).expect("The LUT should be valid.");
+        assert_eq!(lut.title, "Tiny");
+        assert_eq!(lut.size, 2);
+        assert_eq!(lut.domain_min, glm::vec3(0.0, 0.0, 0.0));
+        assert_eq!(lut.domain_max, glm::vec3(1.0, 1.0, 1.0));
+        // red changes fastest
+        assert_eq!(lut.entry(1, 0, 0), glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(lut.entry(0, 1, 0), glm::vec3(0.0, 1.0, 0.0));
+        assert_eq!(lut.entry(1, 1, 1), glm::vec3(0.5, 0.25, 0.125));
+    }
+
+    #[test]
+    fn parse_test_identity_matches() {
+        let mut content = String::from("LUT_3D_SIZE 3\n");
+        for b in 0..3 {
+            for g in 0..3 {
+                for r in 0..3 {
+                    content += format!("{} {} {}\n", r as f32 / 2.0, g as f32 / 2.0, b as f32 / 2.0).as_str();
+                }
+            }
+        }
+        let lut = CubeLut::parse(content.as_str()).expect("The LUT should be valid.");
+        assert_eq!(lut, CubeLut::identity(3));
+    }
+
+    #[test]
+    fn parse_test_missing_size() {
+        assert!(CubeLut::parse("0.0 0.0 0.0\n").is_err(), "A LUT without size must be rejected.");
+    }
+
+    #[test]
+    fn parse_test_wrong_entry_count() {
+        let content = SMALL_CUBE.replace("0.5 0.25 0.125\n", "");
+        assert!(CubeLut::parse(content.as_str()).is_err(), "A LUT with missing entries must be rejected.");
+    }
+
+    #[test]
+    fn parse_test_malformed_entry() {
+        let content = SMALL_CUBE.replace("0.5 0.25 0.125", "0.5 zero 0.125");
+        assert!(CubeLut::parse(content.as_str()).is_err(), "A malformed entry must be rejected.");
+    }
+
+    #[test]
+    fn parse_test_huge_size() {
+        assert!(CubeLut::parse("LUT_3D_SIZE 4294967295\n").is_err(), "A LUT too large to hold must be rejected.");
+    }
+
+    #[test]
+    #[should_panic]
+    fn identity_test_size_too_small() {
+        CubeLut::identity(1);
+    }
+
+    #[test]
+    fn parse_test_1d_unsupported() {
+        assert!(CubeLut::parse("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err(), "1D LUTs aren't supported.");
+    }
+
+    #[test]
+    fn from_file_test_existing() {
+        let lut = CubeLut::from_file(filesystem::get_path("resources/luts/warm.cube".to_string()))
+            .expect("The file should exist and be valid.");
+        assert_eq!(lut.size, 16);
+        assert_eq!(lut.data.len(), 16 * 16 * 16 * 3);
+    }
+}
\ No newline at end of file
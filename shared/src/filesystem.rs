@@ -14,7 +14,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{env, io};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
 const LOGL_ROOT_PATH: &str = include_str!(concat!(env!("OUT_DIR"), "/git_repo_root_path.txt"));
+// overrides where the resources are loaded from, the directory containing `resources/`
+pub const ROOT_ENV_VAR: &str = "LEARNOPENGL_ROOT";
+const RESOURCES_DIR: &str = "resources";
 
 type Builder = Box<dyn Fn(String) -> String>;
 
@@ -23,8 +31,70 @@ pub fn get_path(path: String) -> String {
     path_builder(path)
 }
 
+// like `get_path`, but fails with a `NotFound` error naming the resource and where it was looked
+// for if there's no such file, rather than leaving that to whatever loader opens it later
+pub fn get_path_checked(path: String) -> io::Result<PathBuf> {
+    let resolved = PathBuf::from(get_path(path.clone()));
+    if resolved.exists() {
+        Ok(resolved)
+    } else {
+        Err(missing_resource(&path))
+    }
+}
+
+// the check of `get_path_checked` for a path that has been resolved already, as the loaders get
+// them. Paths into the resource root are reported relative to it.
+pub fn check_exists(path: &str) -> io::Result<PathBuf> {
+    let resolved = PathBuf::from(path);
+    if resolved.exists() {
+        return Ok(resolved);
+    }
+    match path.strip_prefix(&get_path(String::new())) {
+        Some(relative) => Err(missing_resource(relative)),
+        None => Err(io::Error::new(io::ErrorKind::NotFound, format!("File {} not found.", path)))
+    }
+}
+
+fn missing_resource(relative: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Resource {} not found, searched in {}.", relative, search_root())
+    )
+}
+
+// where `get_path` resolves resources from, for error messages
+fn search_root() -> String {
+    if get_root().len() == 0 {
+        let working_directory = env::current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_else(|_| "the working directory".to_string());
+        format!("{} relative to {}", get_path_relative_binary(String::new()), working_directory)
+    } else {
+        get_root().to_string()
+    }
+}
+
+// the root is looked up once, on the first resource requested
 fn get_root() -> &'static str {
-    LOGL_ROOT_PATH
+    static ROOT: OnceLock<String> = OnceLock::new();
+    ROOT.get_or_init(|| find_root(env::var_os(ROOT_ENV_VAR), env::current_exe().ok(), LOGL_ROOT_PATH))
+}
+
+// the directory resources are resolved from: `env_root` (the value of LEARNOPENGL_ROOT) if set, else the
+// closest parent directory of the executable containing `resources/` (so installed binaries work from
+// any working directory), else the git repository the crate was built in. Empty if none of them is
+// known, `get_path` falls back to the layout of target/<profile>/<binary> then.
+fn find_root(env_root: Option<OsString>, executable: Option<PathBuf>, build_root: &str) -> String {
+    if let Some(root) = env_root.filter(|root| !root.is_empty()) {
+        return root.to_string_lossy().into_owned();
+    }
+    let beside_executable = executable.as_deref()
+        .and_then(Path::parent)
+        .and_then(|dir| dir.ancestors().find(|ancestor| ancestor.join(RESOURCES_DIR).is_dir()));
+    if let Some(root) = beside_executable {
+        return root.to_string_lossy().into_owned();
+    }
+    build_root.to_string()
 }
 
 fn get_path_builder() -> Builder {
@@ -36,7 +106,11 @@ fn get_path_builder() -> Builder {
 }
 
 fn get_path_relative_root(path: String) -> String {
-    format!("{}/{}", get_root(), path)
+    join_root(get_root(), path)
+}
+
+fn join_root(root: &str, path: String) -> String {
+    format!("{}/{}", root.trim_end_matches('/'), path)
 }
 
 fn get_path_relative_binary(path: String) -> String {
@@ -46,7 +120,6 @@ fn get_path_relative_binary(path: String) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::Path;
 
     const FILE: &str = "resources/textures/awesomeface.png";
     const DIR: &str = "resources/textures";
@@ -70,4 +143,47 @@ mod tests {
         assert!(file_path.is_dir(), "{} must mean a directory.", FILE);
         assert!(!file_path.is_file(), "{} mustn't mean a file.", DIR);
     }
+
+    #[test]
+    fn find_root_test_env_var_overrides() {
+        let executable = env::current_exe().ok();
+        let default_root = find_root(None, executable.clone(), LOGL_ROOT_PATH);
+        let custom_root = env::temp_dir().join("learnopengl_custom_root");
+        let root = find_root(Some(custom_root.clone().into_os_string()), executable.clone(), LOGL_ROOT_PATH);
+        assert_eq!(Path::new(&root), custom_root);
+        assert_ne!(root, default_root);
+        assert_eq!(Path::new(&join_root(&root, FILE.to_string())), custom_root.join(FILE));
+        // an empty variable counts as unset
+        assert_eq!(find_root(Some(OsString::new()), executable, LOGL_ROOT_PATH), default_root);
+    }
+
+    #[test]
+    fn find_root_test_searches_upward_from_executable() {
+        let install = env::temp_dir().join(format!("learnopengl_install_{}", std::process::id()));
+        let bin = install.join("bin").join("examples");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::create_dir_all(install.join(RESOURCES_DIR)).unwrap();
+        let root = find_root(None, Some(bin.join("learnopengl")), "");
+        std::fs::remove_dir_all(&install).unwrap();
+        assert_eq!(Path::new(&root), install);
+    }
+
+    #[test]
+    fn get_path_checked_test_existing() {
+        let file_path = get_path_checked(FILE.to_string()).expect("The file should be found.");
+        assert!(file_path.is_file());
+        assert_eq!(check_exists(&get_path(FILE.to_string())).unwrap(), file_path);
+    }
+
+    #[test]
+    fn get_path_checked_test_missing() {
+        const MISSING: &str = "resources/textures/i_am_not_existing.png";
+        let err = get_path_checked(MISSING.to_string()).expect_err("The file shouldn't exist.");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let message = err.to_string();
+        assert!(message.contains(MISSING), "Got {}.", message);
+        assert!(message.contains(&search_root()), "Got {}.", message);
+        // the loaders' check names the same resource
+        assert_eq!(check_exists(&get_path(MISSING.to_string())).unwrap_err().to_string(), message);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,788 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ptr;
+use gl::types::*;
+use image::RgbaImage;
+use crate::primitives::render_quad;
+use crate::shader::Shader;
+use crate::util::image::read_pixels_rgba;
+
+// Two framebuffers with a single color attachment each that are rendered into alternately, e.g. for
+// the separable two-pass Gaussian blur of the bloom example: every pass reads the color buffer the
+// previous pass wrote and writes into the other one.
+pub struct PingPong {
+    fbos: [u32; 2],
+    color_buffers: [u32; 2],
+    width: u32,
+    height: u32
+}
+
+impl PingPong {
+    // `format` is the internal format of the color buffers, e.g. gl::RGBA16F for HDR input
+    pub fn new(width: u32, height: u32, format: GLenum) -> Self {
+        let mut fbos = [0u32; 2];
+        let mut color_buffers = [0u32; 2];
+        unsafe {
+            gl::GenFramebuffers(2, fbos.as_mut_ptr());
+            gl::GenTextures(2, color_buffers.as_mut_ptr());
+            for i in 0..2 {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, fbos[i]);
+                gl::BindTexture(gl::TEXTURE_2D, color_buffers[i]);
+                gl::TexImage2D(gl::TEXTURE_2D, 0, format as _, width as _, height as _, 0, gl::RGBA, gl::FLOAT, ptr::null());
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _); // we clamp to the edge as the blur filter would otherwise sample repeated texture values!
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color_buffers[i], 0);
+                // also check if framebuffers are complete (no need for depth buffer)
+                if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                    println!("Framebuffer not complete!");
+                }
+            }
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self {
+            fbos,
+            color_buffers,
+            width,
+            height
+        }
+    }
+
+    // Runs `iterations` alternating horizontal/vertical passes of `shader` over `src_tex` and returns
+    // the texture holding the result (`src_tex` itself if there are no iterations). The shader is
+    // expected to sample unit 0 and to switch directions on its `horizontal` bool uniform.
+    pub fn blur(&mut self, shader: &Shader, src_tex: u32, iterations: u32) -> u32 {
+        let mut horizontal = true;
+        let mut source = src_tex;
+        unsafe {
+            let mut viewport = [0 as GLint; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            gl::Viewport(0, 0, self.width as _, self.height as _);
+
+            shader.use_shader();
+            for _ in 0..iterations {
+                let target = if horizontal { 1 } else { 0 };
+                gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbos[target]);
+                shader.set_bool("horizontal".to_string(), horizontal);
+                // bind texture of other framebuffer (or the source if first iteration)
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, source);
+                render_quad();
+                source = self.color_buffers[target];
+                horizontal = !horizontal;
+            }
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+        source
+    }
+
+    pub fn fbos(&self) -> &[u32; 2] {
+        &self.fbos
+    }
+
+    pub fn color_buffers(&self) -> &[u32; 2] {
+        &self.color_buffers
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drop for PingPong {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(2, self.fbos.as_ptr());
+            gl::DeleteTextures(2, self.color_buffers.as_ptr());
+        }
+    }
+}
+
+// Configures a `Framebuffer` with one color attachment and an optional depth/stencil attachment.
+// Without samples the color attachment is a texture that can be sampled afterwards, with samples
+// both attachments are multisampled renderbuffers that have to be resolved (see `resolve_to`).
+pub struct FramebufferBuilder {
+    width: u32,
+    height: u32,
+    samples: u32,
+    color_format: GLenum,
+    depth: bool
+}
+
+impl FramebufferBuilder {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            samples: 0,
+            color_format: gl::RGBA8,
+            depth: true
+        }
+    }
+
+    // the number of samples per pixel, 0 (the default) for a regular framebuffer
+    pub fn samples(mut self, samples: u32) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    // internal format of the color attachment, e.g. gl::RGBA16F for HDR rendering
+    pub fn color_format(mut self, color_format: GLenum) -> Self {
+        self.color_format = color_format;
+        self
+    }
+
+    // whether a depth/stencil renderbuffer is attached (the default)
+    pub fn depth(mut self, depth: bool) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn build(self) -> Framebuffer {
+        let mut fbo = 0u32;
+        let mut color_buffer = 0u32;
+        let mut depth_buffer = 0u32;
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            if self.samples > 0 {
+                // create a multisampled color attachment renderbuffer
+                gl::GenRenderbuffers(1, &mut color_buffer);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, color_buffer);
+                gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, self.samples as _, self.color_format, self.width as _, self.height as _);
+                gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, color_buffer);
+            } else {
+                // create a color attachment texture
+                gl::GenTextures(1, &mut color_buffer);
+                gl::BindTexture(gl::TEXTURE_2D, color_buffer);
+                gl::TexImage2D(gl::TEXTURE_2D, 0, self.color_format as _, self.width as _, self.height as _, 0, gl::RGBA, gl::FLOAT, ptr::null());
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color_buffer, 0);
+            }
+            if self.depth {
+                // create a renderbuffer object for depth and stencil attachment (with as many samples as the color buffer)
+                gl::GenRenderbuffers(1, &mut depth_buffer);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, depth_buffer);
+                gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, self.samples as _, gl::DEPTH24_STENCIL8, self.width as _, self.height as _);
+                gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, depth_buffer);
+            }
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                println!("ERROR::FRAMEBUFFER:: Framebuffer is not complete!");
+            }
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Framebuffer {
+            fbo,
+            color_buffer,
+            depth_buffer,
+            width: self.width,
+            height: self.height,
+            samples: self.samples,
+            color_format: self.color_format
+        }
+    }
+}
+
+// A framebuffer made by `FramebufferBuilder`.
+pub struct Framebuffer {
+    fbo: u32,
+    // a texture if there are no samples, a renderbuffer otherwise
+    color_buffer: u32,
+    // 0 if there is no depth/stencil attachment
+    depth_buffer: u32,
+    width: u32,
+    height: u32,
+    samples: u32,
+    color_format: GLenum
+}
+
+impl Framebuffer {
+    // binds the framebuffer and sets the viewport to cover all of it
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, self.width as _, self.height as _);
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status == gl::FRAMEBUFFER_COMPLETE
+        }
+    }
+
+    // Blits the color buffer into the one of `target`, which resolves the samples if this framebuffer
+    // is multisampled. Both framebuffers are expected to have the same size. Leaves the default
+    // framebuffer bound.
+    pub fn resolve_to(&self, target: &Framebuffer) {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target.fbo);
+            gl::BlitFramebuffer(0, 0, self.width as _, self.height as _, 0, 0, target.width as _, target.height as _, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    // Reallocates the attachments at the new size, e.g. when the window was resized. The GL objects
+    // (and so the ids handed out by `fbo` and `color_texture`) stay the same, their contents are lost.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == self.width && height == self.height {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        unsafe {
+            if self.samples > 0 {
+                gl::BindRenderbuffer(gl::RENDERBUFFER, self.color_buffer);
+                gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, self.samples as _, self.color_format, width as _, height as _);
+            } else {
+                gl::BindTexture(gl::TEXTURE_2D, self.color_buffer);
+                gl::TexImage2D(gl::TEXTURE_2D, 0, self.color_format as _, width as _, height as _, 0, gl::RGBA, gl::FLOAT, ptr::null());
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+            if self.depth_buffer != 0 {
+                gl::BindRenderbuffer(gl::RENDERBUFFER, self.depth_buffer);
+                gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, self.samples as _, gl::DEPTH24_STENCIL8, width as _, height as _);
+            }
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+    }
+
+    pub fn fbo(&self) -> u32 {
+        self.fbo
+    }
+
+    // the color attachment texture, None for multisampled framebuffers as those can't be sampled
+    // like regular textures
+    pub fn color_texture(&self) -> Option<u32> {
+        if self.samples > 0 { None } else { Some(self.color_buffer) }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+}
+
+// Copies the depth buffer of `src` into the one of the framebuffer `dst_fbo` (0 for the default
+// framebuffer), e.g. so forward rendered objects depth test against deferred geometry. The lower
+// left width x height area is copied. Both depth formats have to match, which for the default
+// framebuffer is implementation defined; DEPTH24_STENCIL8 (what `Framebuffer` uses) works on most
+// drivers, otherwise the depth has to be written in a shader instead. Leaves `dst_fbo` bound.
+pub fn blit_depth(src: &Framebuffer, dst_fbo: u32, width: u32, height: u32) {
+    blit_depth_fbo(src.fbo, dst_fbo, width, height);
+}
+
+// `blit_depth` for framebuffers not made by `FramebufferBuilder`, like g-buffers with several color
+// attachments
+pub fn blit_depth_fbo(src_fbo: u32, dst_fbo: u32, width: u32, height: u32) {
+    unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, src_fbo);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, dst_fbo);
+        gl::BlitFramebuffer(0, 0, width as _, height as _, 0, 0, width as _, height as _, gl::DEPTH_BUFFER_BIT, gl::NEAREST);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, dst_fbo);
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            if self.samples > 0 {
+                gl::DeleteRenderbuffers(1, &self.color_buffer);
+            } else {
+                gl::DeleteTextures(1, &self.color_buffer);
+            }
+            if self.depth_buffer != 0 {
+                gl::DeleteRenderbuffers(1, &self.depth_buffer);
+            }
+        }
+    }
+}
+
+// Depth-only framebuffer for directional/spot light shadows. Everything outside of the map counts as
+// fully lit: the depth texture clamps to a border of depth 1.0 instead of repeating.
+pub struct ShadowMap2D {
+    fbo: u32,
+    depth_texture: u32,
+    width: u32,
+    height: u32
+}
+
+impl ShadowMap2D {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut fbo = 0u32;
+        let mut depth_texture = 0u32;
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            // create depth texture
+            gl::GenTextures(1, &mut depth_texture);
+            gl::BindTexture(gl::TEXTURE_2D, depth_texture);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::DEPTH_COMPONENT as _, width as _, height as _, 0, gl::DEPTH_COMPONENT, gl::FLOAT, ptr::null());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as _);
+            let border_color = [1f32, 1.0, 1.0, 1.0];
+            gl::TexParameterfv(gl::TEXTURE_2D, gl::TEXTURE_BORDER_COLOR, border_color.as_ptr());
+            // attach depth texture as FBO's depth buffer
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::TEXTURE_2D, depth_texture, 0);
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self {
+            fbo,
+            depth_texture,
+            width,
+            height
+        }
+    }
+
+    // binds the framebuffer and sets the viewport to the size of the map, ready for the depth pass
+    pub fn bind_for_writing(&self) {
+        unsafe {
+            gl::Viewport(0, 0, self.width as _, self.height as _);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+        }
+    }
+
+    pub fn fbo(&self) -> u32 {
+        self.fbo
+    }
+
+    pub fn depth_texture(&self) -> u32 {
+        self.depth_texture
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drop for ShadowMap2D {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteTextures(1, &self.depth_texture);
+        }
+    }
+}
+
+// Depth-only framebuffer with a cubemap attached as a whole, for omnidirectional point light shadows
+// rendered in a single pass with a geometry shader selecting the face (gl_Layer).
+pub struct ShadowMapCube {
+    fbo: u32,
+    depth_cubemap: u32,
+    size: u32
+}
+
+impl ShadowMapCube {
+    // `size` is the width and height of each of the six faces
+    pub fn new(size: u32) -> Self {
+        let mut fbo = 0u32;
+        let mut depth_cubemap = 0u32;
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            // create depth cubemap texture
+            gl::GenTextures(1, &mut depth_cubemap);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, depth_cubemap);
+            for i in 0..6 {
+                gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + i, 0, gl::DEPTH_COMPONENT as _, size as _, size as _, 0, gl::DEPTH_COMPONENT, gl::FLOAT, ptr::null());
+            }
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as _);
+            // attach depth texture as FBO's depth buffer
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, depth_cubemap, 0);
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self {
+            fbo,
+            depth_cubemap,
+            size
+        }
+    }
+
+    // binds the framebuffer and sets the viewport to the face size, ready for the depth pass
+    pub fn bind_for_writing(&self) {
+        unsafe {
+            gl::Viewport(0, 0, self.size as _, self.size as _);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+        }
+    }
+
+    pub fn fbo(&self) -> u32 {
+        self.fbo
+    }
+
+    // the depth cubemap, to be bound to a samplerCube
+    pub fn depth_texture(&self) -> u32 {
+        self.depth_cubemap
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+
+impl Drop for ShadowMapCube {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteTextures(1, &self.depth_cubemap);
+        }
+    }
+}
+
+// Color (sRGB capable RGBA8, like most default framebuffers) and depth/stencil renderbuffers for
+// rendering without a visible window, e.g. for the headless mode of `App`. The result is meant to be read back with `read_pixels`.
+pub struct OffscreenTarget {
+    fbo: u32,
+    color_buffer: u32,
+    depth_buffer: u32,
+    width: u32,
+    height: u32
+}
+
+impl OffscreenTarget {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut fbo = 0u32;
+        let (mut color_buffer, mut depth_buffer) = (0u32, 0u32);
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::GenRenderbuffers(1, &mut color_buffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, color_buffer);
+            // values are stored as they are written unless gl::FRAMEBUFFER_SRGB is enabled
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::SRGB8_ALPHA8, width as _, height as _);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, color_buffer);
+            gl::GenRenderbuffers(1, &mut depth_buffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_buffer);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, width as _, height as _);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, depth_buffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self {
+            fbo,
+            color_buffer,
+            depth_buffer,
+            width,
+            height
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status == gl::FRAMEBUFFER_COMPLETE
+        }
+    }
+
+    // binds the framebuffer and sets the viewport to cover all of it
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, self.width as _, self.height as _);
+        }
+    }
+
+    // the current contents of the color buffer, top row first
+    pub fn read_pixels(&self) -> RgbaImage {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+        }
+        let pixels = read_pixels_rgba(self.width, self.height);
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        }
+        pixels
+    }
+
+    pub fn fbo(&self) -> u32 {
+        self.fbo
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drop for OffscreenTarget {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteRenderbuffers(1, &self.color_buffer);
+            gl::DeleteRenderbuffers(1, &self.depth_buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+    use crate::primitives;
+
+    const BLUR_VS: &str = "#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec2 aTexCoords;
+out vec2 TexCoords;
+void main()
+{
+    TexCoords = aTexCoords;
+    gl_Position = vec4(aPos, 1.0);
+}";
+    const BLUR_FS: &str = "#version 330 core
+out vec4 FragColor;
+in vec2 TexCoords;
+uniform sampler2D image;
+uniform bool horizontal;
+uniform float weight[5] = float[] (0.2270270270, 0.1945945946, 0.1216216216, 0.0540540541, 0.0162162162);
+void main()
+{
+    vec2 tex_offset = 1.0 / textureSize(image, 0);
+    vec2 direction = horizontal ? vec2(tex_offset.x, 0.0) : vec2(0.0, tex_offset.y);
+    vec3 result = texture(image, TexCoords).rgb * weight[0];
+    for(int i = 1; i < 5; ++i)
+    {
+        result += texture(image, TexCoords + direction * i).rgb * weight[i];
+        result += texture(image, TexCoords - direction * i).rgb * weight[i];
+    }
+    FragColor = vec4(result, 1.0);
+}";
+
+    #[test]
+    fn blur_test_one_iteration() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        // the blur draws the shared quad, which must not be a VAO of another test's context
+        primitives::reset();
+        let shader = Shader::from_sources(BLUR_VS, BLUR_FS, None).expect("The blur shader should build.");
+        let mut ping_pong = PingPong::new(4, 4, gl::RGBA16F);
+
+        // a constant input stays constant under the (normalized) blur kernel
+        let texels = [1f32; 4 * 4 * 4];
+        let mut src_tex = 0u32;
+        let mut result = [0f32; 4 * 4 * 4];
+        let blurred;
+        unsafe {
+            gl::GenTextures(1, &mut src_tex);
+            gl::BindTexture(gl::TEXTURE_2D, src_tex);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA16F as _, 4, 4, 0, gl::RGBA, gl::FLOAT, texels.as_ptr() as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+
+            blurred = ping_pong.blur(&shader, src_tex, 1);
+
+            gl::BindTexture(gl::TEXTURE_2D, blurred);
+            gl::GetTexImage(gl::TEXTURE_2D, 0, gl::RGBA, gl::FLOAT, result.as_mut_ptr() as _);
+            assert_eq!(gl::GetError(), gl::NO_ERROR);
+            gl::DeleteTextures(1, &src_tex);
+        }
+        primitives::reset();
+
+        // the first (horizontal) pass writes into the second buffer
+        assert_eq!(blurred, ping_pong.color_buffers()[1]);
+        for texel in result.chunks(4) {
+            for channel in &texel[0..3] {
+                assert!((channel - 1.0).abs() < 1e-2, "Blurred texel {:?} should stay white.", texel);
+            }
+        }
+    }
+
+    fn assert_complete(fbo: u32) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            assert_eq!(gl::CheckFramebufferStatus(gl::FRAMEBUFFER), gl::FRAMEBUFFER_COMPLETE);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    #[test]
+    fn shadow_map_2d_test_complete() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let shadow_map = ShadowMap2D::new(64, 32);
+        assert_complete(shadow_map.fbo());
+        assert_eq!((shadow_map.width(), shadow_map.height()), (64, 32));
+    }
+
+    #[test]
+    fn shadow_map_cube_test_complete() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let shadow_map = ShadowMapCube::new(64);
+        assert_complete(shadow_map.fbo());
+    }
+
+    #[test]
+    fn offscreen_target_test_read_pixels() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let target = OffscreenTarget::new(8, 4);
+        assert!(target.is_complete());
+        target.bind();
+        unsafe {
+            gl::ClearColor(0.0, 1.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        let pixels = target.read_pixels();
+        assert_eq!(pixels.dimensions(), (8, 4));
+        assert!(pixels.pixels().all(|it| it.0 == [0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn framebuffer_builder_test_multisampled_complete() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let multisampled = FramebufferBuilder::new(32, 16).samples(4).build();
+        assert_complete(multisampled.fbo());
+        assert_eq!(multisampled.samples(), 4);
+        assert_eq!(multisampled.color_texture(), None);
+        let mut samples = 0;
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, multisampled.fbo());
+            gl::GetIntegerv(gl::SAMPLES, &mut samples);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        // implementations may round the sample count up
+        assert!(samples >= 4, "Got {} samples.", samples);
+    }
+
+    #[test]
+    fn framebuffer_test_resolve_to() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let multisampled = FramebufferBuilder::new(8, 8).samples(4).build();
+        let resolved = FramebufferBuilder::new(8, 8).depth(false).build();
+        assert_complete(resolved.fbo());
+        multisampled.bind();
+        let mut texels = [0u8; 8 * 8 * 4];
+        unsafe {
+            gl::ClearColor(1.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            multisampled.resolve_to(&resolved);
+
+            gl::BindTexture(gl::TEXTURE_2D, resolved.color_texture().unwrap());
+            gl::GetTexImage(gl::TEXTURE_2D, 0, gl::RGBA, gl::UNSIGNED_BYTE, texels.as_mut_ptr() as _);
+            assert_eq!(gl::GetError(), gl::NO_ERROR);
+        }
+        assert!(texels.chunks(4).all(|it| it == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn framebuffer_test_resize() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let mut framebuffer = FramebufferBuilder::new(4, 4).color_format(gl::RGBA16F).build();
+        let texture = framebuffer.color_texture().unwrap();
+        framebuffer.resize(16, 8);
+        assert_eq!((framebuffer.width(), framebuffer.height()), (16, 8));
+        assert_eq!(framebuffer.color_texture(), Some(texture));
+        assert_complete(framebuffer.fbo());
+
+        let (mut width, mut height) = (0 as GLint, 0 as GLint);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_WIDTH, &mut width);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_HEIGHT, &mut height);
+        }
+        assert_eq!((width, height), (16, 8));
+    }
+
+    #[test]
+    fn blit_depth_test_copies_depth() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let src = FramebufferBuilder::new(4, 4).build();
+        let dst = FramebufferBuilder::new(4, 4).build();
+        let mut depth = [0f32; 16];
+        unsafe {
+            dst.bind();
+            gl::ClearDepth(1.0);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+            src.bind();
+            gl::ClearDepth(0.25);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+            gl::ClearDepth(1.0);
+        }
+
+        blit_depth(&src, dst.fbo(), 4, 4);
+
+        unsafe {
+            let mut bound = 0 as GLint;
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut bound);
+            assert_eq!(bound as u32, dst.fbo());
+            gl::ReadPixels(0, 0, 4, 4, gl::DEPTH_COMPONENT, gl::FLOAT, depth.as_mut_ptr() as *mut _);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        assert!(depth.iter().all(|d| (d - 0.25).abs() < 1e-4), "{:?}", depth);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Mutex, MutexGuard};
+use glfw::{Context, OpenGlProfileHint, WindowHint};
+
+// glfw must not be initialized from several test threads at once, so GL tests take turns
+static GL_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+// A current 3.3 core context of an invisible window, kept alive as long as this value lives.
+pub struct GlTestContext {
+    pub window: glfw::PWindow,
+    pub glfw: glfw::Glfw,
+    _lock: MutexGuard<'static, ()>
+}
+
+// serializes tests that initialize glfw themselves (e.g. through `App::new_headless`)
+pub fn gl_test_lock() -> MutexGuard<'static, ()> {
+    GL_TEST_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// creates the context for tests that need to talk to the GPU. Returns None on machines without a
+// display or GL driver, such tests are expected to return early (i.e. they are skipped there).
+pub fn hidden_gl_context(width: u32, height: u32) -> Option<GlTestContext> {
+    hidden_gl_context_version(width, height, 3, 3)
+}
+
+// like `hidden_gl_context` with a newer core context, e.g. 4.3 for compute shaders and storage buffers.
+// Also returns None if the driver doesn't support that version.
+pub fn hidden_gl_context_version(width: u32, height: u32, major: u32, minor: u32) -> Option<GlTestContext> {
+    create_context(width, height, major, minor, false)
+}
+
+// a 4.3 core debug context, so tests can check the messages of the GL debug output (e.g. with
+// glGetDebugMessageLog). Also returns None if the driver doesn't support that version.
+pub fn hidden_gl_debug_context(width: u32, height: u32) -> Option<GlTestContext> {
+    create_context(width, height, 4, 3, true)
+}
+
+fn create_context(width: u32, height: u32, major: u32, minor: u32, debug: bool) -> Option<GlTestContext> {
+    let lock = gl_test_lock();
+    let mut glfw = glfw::init(glfw::log_errors).ok()?;
+    glfw.window_hint(WindowHint::ContextVersionMajor(major));
+    glfw.window_hint(WindowHint::ContextVersionMinor(minor));
+    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+    glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+    glfw.window_hint(WindowHint::Visible(false));
+    glfw.window_hint(WindowHint::OpenGlDebugContext(debug));
+    let (mut window, _) = glfw.create_window(width, height, "test", glfw::WindowMode::Windowed)?;
+    window.make_current();
+    gl::load_with(|s| window.get_proc_address(s) as *const _);
+    Some(GlTestContext {
+        window,
+        glfw,
+        _lock: lock
+    })
+}
\ No newline at end of file
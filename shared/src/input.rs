@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use glfw::{Action, Key, Window};
+use crate::util;
+
+// Anything the current up/down state of a key can be polled from, usually the glfw window.
+pub trait KeySource {
+    fn is_key_down(&self, key: Key) -> bool;
+}
+
+impl KeySource for Window {
+    fn is_key_down(&self, key: Key) -> bool {
+        self.get_key(key) != Action::Release
+    }
+}
+
+// the keys movement_axis is built from, they are polled right from the first update
+const MOVEMENT_KEYS: [Key; 4] = [Key::W, Key::A, Key::S, Key::D];
+
+// Keyboard state of the current and the previous frame, replacing the `static mut *_KEY_PRESSED`
+// flags of the examples. Call `update` once per frame, then query keys as often as needed.
+//
+// Only keys that have been asked for are polled: the first query of a key registers it and
+// reports it as up, from the next `update` on it is tracked like any other.
+pub struct InputState {
+    down: HashMap<Key, bool>,
+    previous: HashMap<Key, bool>,
+    // keys queried since the last update, shared so queries only need `&self`
+    requested: Mutex<HashSet<Key>>
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self {
+            down: HashMap::new(),
+            previous: HashMap::new(),
+            requested: Mutex::new(MOVEMENT_KEYS.iter().copied().collect())
+        }
+    }
+
+    // polls all registered keys from the window, call once per frame before any query
+    pub fn update(&mut self, window: &Window) {
+        self.update_from(window);
+    }
+
+    pub fn update_from<S: KeySource + ?Sized>(&mut self, source: &S) {
+        for key in self.requested.get_mut().unwrap().drain() {
+            self.down.entry(key).or_insert(false);
+        }
+        self.previous.clone_from(&self.down);
+        for (key, down) in self.down.iter_mut() {
+            *down = source.is_key_down(*key);
+        }
+    }
+
+    pub fn is_down(&self, key: Key) -> bool {
+        match self.down.get(&key) {
+            Some(down) => *down,
+            None => {
+                self.requested.lock().unwrap().insert(key);
+                false
+            }
+        }
+    }
+
+    // true only for the frame in which the key went from up to down
+    pub fn just_pressed(&self, key: Key) -> bool {
+        self.is_down(key) && !self.previous.get(&key).copied().unwrap_or(false)
+    }
+
+    // true only for the frame in which the key went from down to up
+    pub fn just_released(&self, key: Key) -> bool {
+        !self.is_down(key) && self.previous.get(&key).copied().unwrap_or(false)
+    }
+
+    // WASD as a (right, forward) direction of unit length, zero if no (or opposing) keys are held
+    pub fn movement_axis(&self) -> glm::TVec2<f32> {
+        let axis = |positive: Key, negative: Key| {
+            (self.is_down(positive) as i32 - self.is_down(negative) as i32) as f32
+        };
+        let movement = glm::vec2(axis(Key::D, Key::A), axis(Key::W, Key::S));
+        if movement == util::glm::empty_vec2() {
+            movement
+        } else {
+            glm::normalize(&movement)
+        }
+    }
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A value nudged up and down by holding two keys, e.g. the exposure of the HDR examples or the
+// height scale of the parallax mapping ones. Every update the value moves by `step` per held key and
+// is clamped to [min, max].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScalarControl {
+    value: f32,
+    min: f32,
+    max: f32,
+    step: f32
+}
+
+impl ScalarControl {
+    pub fn new(initial: f32, min: f32, max: f32, step: f32) -> Self {
+        Self {
+            value: initial.clamp(min, max),
+            min,
+            max,
+            step
+        }
+    }
+
+    // polls `dec` and `inc` from the window, call once per frame. Returns the new value.
+    pub fn update(&mut self, window: &Window, dec: Key, inc: Key) -> f32 {
+        self.update_from(window, dec, inc)
+    }
+
+    pub fn update_from<S: KeySource + ?Sized>(&mut self, source: &S, dec: Key, inc: Key) -> f32 {
+        if source.is_key_down(dec) {
+            self.value -= self.step;
+        }
+        if source.is_key_down(inc) {
+            self.value += self.step;
+        }
+        self.value = self.value.clamp(self.min, self.max);
+        self.value
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(self.min, self.max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // key source that reports the keys of the current (simulated) frame as held down
+    struct FakeKeys(HashSet<Key>);
+
+    impl KeySource for FakeKeys {
+        fn is_key_down(&self, key: Key) -> bool {
+            self.0.contains(&key)
+        }
+    }
+
+    fn frame(keys: &[Key]) -> FakeKeys {
+        FakeKeys(keys.iter().copied().collect())
+    }
+
+    #[test]
+    fn just_pressed_test_toggle_once_per_press() {
+        let mut input = InputState::new();
+        let mut shadows = false;
+        let mut toggles = 0;
+        input.just_pressed(Key::Space); // registers the key, like an example querying it every frame
+        // the space bar is held for three frames, released, and then pressed again for two
+        let frames = [false, true, true, true, false, false, true, true, false];
+        for space_down in frames {
+            input.update_from(&frame(if space_down { &[Key::Space] } else { &[] }));
+            if input.just_pressed(Key::Space) {
+                shadows = !shadows;
+                toggles += 1;
+            }
+        }
+        assert_eq!(toggles, 2);
+        assert!(!shadows);
+    }
+
+    #[test]
+    fn is_down_test_first_query() {
+        let mut input = InputState::new();
+        input.update_from(&frame(&[Key::Space]));
+        assert!(!input.is_down(Key::Space), "An unregistered key is reported as up.");
+        input.update_from(&frame(&[Key::Space]));
+        assert!(input.is_down(Key::Space));
+        assert!(input.just_pressed(Key::Space));
+        input.update_from(&frame(&[]));
+        assert!(input.just_released(Key::Space));
+    }
+
+    #[test]
+    fn movement_axis_test() {
+        let mut input = InputState::new();
+        input.update_from(&frame(&[]));
+        assert_eq!(input.movement_axis(), glm::vec2(0.0, 0.0));
+        input.update_from(&frame(&[Key::W]));
+        assert_eq!(input.movement_axis(), glm::vec2(0.0, 1.0));
+        input.update_from(&frame(&[Key::A, Key::D]));
+        assert_eq!(input.movement_axis(), glm::vec2(0.0, 0.0));
+        input.update_from(&frame(&[Key::W, Key::D]));
+        let diagonal = input.movement_axis();
+        assert!((glm::length(&diagonal) - 1.0).abs() < 1e-6);
+        assert!((diagonal.x - diagonal.y).abs() < 1e-6);
+    }
+
+    #[test]
+    fn scalar_control_test_steps_and_clamps() {
+        let mut control = ScalarControl::new(0.1, 0.0, 0.2, 0.04);
+        // one step per frame the key is held
+        assert!((control.update_from(&frame(&[Key::E]), Key::Q, Key::E) - 0.14).abs() < 1e-6);
+        assert!((control.update_from(&frame(&[]), Key::Q, Key::E) - 0.14).abs() < 1e-6);
+        assert!((control.update_from(&frame(&[Key::Q]), Key::Q, Key::E) - 0.1).abs() < 1e-6);
+        // both keys cancel out
+        assert!((control.update_from(&frame(&[Key::Q, Key::E]), Key::Q, Key::E) - 0.1).abs() < 1e-6);
+
+        // holding a key stops at the bounds
+        for _ in 0..10 {
+            control.update_from(&frame(&[Key::E]), Key::Q, Key::E);
+        }
+        assert_eq!(control.value(), 0.2);
+        for _ in 0..10 {
+            control.update_from(&frame(&[Key::Q]), Key::Q, Key::E);
+        }
+        assert_eq!(control.value(), 0.0);
+
+        // out of range values are clamped as well
+        assert_eq!(ScalarControl::new(5.0, 0.0, 1.0, 0.1).value(), 1.0);
+        control.set_value(-1.0);
+        assert_eq!(control.value(), 0.0);
+    }
+}
\ No newline at end of file
@@ -20,4 +20,22 @@ pub mod shader_m;
 pub mod util;
 pub mod camera;
 pub mod shader;
-pub mod mesh;
\ No newline at end of file
+pub mod mesh;
+pub mod debug;
+pub mod primitives;
+pub mod app;
+pub mod effects;
+pub mod texture;
+pub mod framebuffer;
+pub mod time;
+pub mod input;
+pub mod light;
+pub mod scene;
+pub mod ssao;
+pub mod ssbo;
+pub mod ubo;
+pub mod text;
+pub mod skybox;
+pub mod shadows;
+#[cfg(test)]
+mod gl_test;
\ No newline at end of file
@@ -0,0 +1,348 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use rand::{RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+use crate::{shader, shader_m};
+use crate::camera::Camera;
+
+// Default light volume values: 5/256 is the darkest visible brightness of an 8 bit channel (with some margin)
+pub const LIGHT_RADIUS_THRESHOLD: f32 = 5.0 / 256.0;
+// narrowest edge between the inner and outer cone of a spot light in degrees. The shaders divide by
+// the difference of the cosines, so equal angles would end the light in a hard (or NaN) edge.
+pub const MIN_SPOT_EDGE_DEGREES: f32 = 0.5;
+
+// Anything light uniforms can be uploaded to. Implemented for both shader flavours the examples use
+// (and for recorders in the tests, which don't need a GL context).
+pub trait UniformTarget {
+    fn set_float(&self, name: String, value: f32);
+    fn set_vec3(&self, name: String, value: &glm::TVec3<f32>);
+}
+
+impl UniformTarget for shader::Shader {
+    fn set_float(&self, name: String, value: f32) {
+        shader::Shader::set_float(self, name, value);
+    }
+
+    fn set_vec3(&self, name: String, value: &glm::TVec3<f32>) {
+        shader::Shader::set_vec3(self, name, value);
+    }
+}
+
+impl UniformTarget for shader_m::Shader {
+    fn set_float(&self, name: String, value: f32) {
+        shader_m::Shader::set_float(self, name, value);
+    }
+
+    fn set_vec3(&self, name: String, value: &glm::TVec3<f32>) {
+        shader_m::Shader::set_vec3(self, name, value);
+    }
+}
+
+// Mirrors `struct DirLight` of the lighting shaders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DirLight {
+    pub direction: glm::TVec3<f32>,
+
+    pub ambient: glm::TVec3<f32>,
+    pub diffuse: glm::TVec3<f32>,
+    pub specular: glm::TVec3<f32>
+}
+
+impl DirLight {
+    // sets every member of the GLSL struct found at `uniform_prefix`, e.g. "dirLight"
+    pub fn upload<S: UniformTarget + ?Sized>(&self, shader: &S, uniform_prefix: &str) {
+        shader.set_vec3(format!("{}.direction", uniform_prefix), &self.direction);
+        shader.set_vec3(format!("{}.ambient", uniform_prefix), &self.ambient);
+        shader.set_vec3(format!("{}.diffuse", uniform_prefix), &self.diffuse);
+        shader.set_vec3(format!("{}.specular", uniform_prefix), &self.specular);
+    }
+}
+
+// Mirrors `struct PointLight` of the lighting shaders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointLight {
+    pub position: glm::TVec3<f32>,
+
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+
+    pub ambient: glm::TVec3<f32>,
+    pub diffuse: glm::TVec3<f32>,
+    pub specular: glm::TVec3<f32>
+}
+
+impl PointLight {
+    // sets every member of the GLSL struct found at `uniform_prefix`, e.g. "pointLights[0]"
+    pub fn upload<S: UniformTarget + ?Sized>(&self, shader: &S, uniform_prefix: &str) {
+        shader.set_vec3(format!("{}.position", uniform_prefix), &self.position);
+        shader.set_float(format!("{}.constant", uniform_prefix), self.constant);
+        shader.set_float(format!("{}.linear", uniform_prefix), self.linear);
+        shader.set_float(format!("{}.quadratic", uniform_prefix), self.quadratic);
+        shader.set_vec3(format!("{}.ambient", uniform_prefix), &self.ambient);
+        shader.set_vec3(format!("{}.diffuse", uniform_prefix), &self.diffuse);
+        shader.set_vec3(format!("{}.specular", uniform_prefix), &self.specular);
+    }
+}
+
+// Mirrors `struct SpotLight` of the lighting shaders. Like in GLSL the cutoffs are the cosines of
+// the inner and outer cone angles, e.g. `12.5f32.to_radians().cos()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpotLight {
+    pub position: glm::TVec3<f32>,
+    pub direction: glm::TVec3<f32>,
+    pub cut_off: f32,
+    pub outer_cut_off: f32,
+
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+
+    pub ambient: glm::TVec3<f32>,
+    pub diffuse: glm::TVec3<f32>,
+    pub specular: glm::TVec3<f32>
+}
+
+impl SpotLight {
+    // A white flashlight with the attenuation of the tutorial (reaching about 50 units) whose light
+    // fades out between the `inner_deg` and `outer_deg` cone angles. The outer cone is widened to
+    // at least MIN_SPOT_EDGE_DEGREES beyond the inner one. Position it with `follow_camera`.
+    pub fn with_cone(inner_deg: f32, outer_deg: f32) -> Self {
+        let outer_deg = outer_deg.max(inner_deg + MIN_SPOT_EDGE_DEGREES);
+        Self {
+            position: glm::vec3(0.0, 0.0, 0.0),
+            direction: glm::vec3(0.0, 0.0, -1.0),
+            cut_off: inner_deg.to_radians().cos(),
+            outer_cut_off: outer_deg.to_radians().cos(),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+            ambient: glm::vec3(0.0, 0.0, 0.0),
+            diffuse: glm::vec3(1.0, 1.0, 1.0),
+            specular: glm::vec3(1.0, 1.0, 1.0)
+        }
+    }
+
+    // shines from the camera position in the direction it looks at
+    pub fn follow_camera(&mut self, camera: &Camera) {
+        self.position = camera.position();
+        self.direction = camera.front();
+    }
+
+    // sets every member of the GLSL struct found at `uniform_prefix`, e.g. "spotLight"
+    pub fn upload<S: UniformTarget + ?Sized>(&self, shader: &S, uniform_prefix: &str) {
+        shader.set_vec3(format!("{}.position", uniform_prefix), &self.position);
+        shader.set_vec3(format!("{}.direction", uniform_prefix), &self.direction);
+        shader.set_float(format!("{}.cutOff", uniform_prefix), self.cut_off);
+        shader.set_float(format!("{}.outerCutOff", uniform_prefix), self.outer_cut_off);
+        shader.set_float(format!("{}.constant", uniform_prefix), self.constant);
+        shader.set_float(format!("{}.linear", uniform_prefix), self.linear);
+        shader.set_float(format!("{}.quadratic", uniform_prefix), self.quadratic);
+        shader.set_vec3(format!("{}.ambient", uniform_prefix), &self.ambient);
+        shader.set_vec3(format!("{}.diffuse", uniform_prefix), &self.diffuse);
+        shader.set_vec3(format!("{}.specular", uniform_prefix), &self.specular);
+    }
+}
+
+// Radius of the light volume of a point light, i.e. the distance at which the attenuated brightest
+// color channel drops below `threshold` (usually LIGHT_RADIUS_THRESHOLD). Solves
+// constant + linear * d + quadratic * d^2 = max_brightness / threshold for d.
+pub fn point_light_radius(color: &glm::TVec3<f32>, constant: f32, linear: f32, quadratic: f32, threshold: f32) -> f32 {
+    let max_brightness = color.z.max(color.y.max(color.x));
+    (-linear + (linear * linear - 4.0 * quadratic * (constant - max_brightness / threshold)).sqrt()) / (2.0 * quadratic)
+}
+
+// `count` point lights of random colors (each channel between 0.5 and 1.0) scattered within the box
+// from `bounds.0` to `bounds.1`, always the same for the same `seed`. They use the attenuation of the
+// deferred shading examples (linear 0.7, quadratic 1.8) and have their color as diffuse and specular.
+pub fn random_point_lights(count: usize, bounds: (glm::TVec3<f32>, glm::TVec3<f32>), seed: u64) -> Vec<PointLight> {
+    let (min, max) = bounds;
+    let extent = max - min;
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let mut lights = Vec::with_capacity(count);
+    for _ in 0..count {
+        // calculate slightly random offsets
+        let x_pos = ((rng.next_u32() % 100) as f32 / 100.0) * extent.x + min.x;
+        let y_pos = ((rng.next_u32() % 100) as f32 / 100.0) * extent.y + min.y;
+        let z_pos = ((rng.next_u32() % 100) as f32 / 100.0) * extent.z + min.z;
+        // also calculate random color
+        let r_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
+        let g_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
+        let b_color = ((rng.next_u32() & 100) as f32 / 200.0) + 0.5; // between 0.5 and 1.0
+        let color = glm::vec3(r_color, g_color, b_color);
+        lights.push(PointLight {
+            position: glm::vec3(x_pos, y_pos, z_pos),
+            constant: 1.0,
+            linear: 0.7,
+            quadratic: 1.8,
+            ambient: color * 0.1,
+            diffuse: color,
+            specular: color
+        });
+    }
+    lights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use crate::util;
+
+    // remembers the names of all uniforms set on it, in order
+    #[derive(Default)]
+    struct UniformRecorder(RefCell<Vec<String>>);
+
+    impl UniformTarget for UniformRecorder {
+        fn set_float(&self, name: String, _value: f32) {
+            self.0.borrow_mut().push(name);
+        }
+
+        fn set_vec3(&self, name: String, _value: &glm::TVec3<f32>) {
+            self.0.borrow_mut().push(name);
+        }
+    }
+
+    fn names(recorder: UniformRecorder) -> Vec<String> {
+        recorder.0.into_inner()
+    }
+
+    #[test]
+    fn dir_light_upload_test_names() {
+        let light = DirLight {
+            direction: glm::vec3(-0.2, -1.0, -0.3),
+            ambient: util::glm::empty_vec3(),
+            diffuse: util::glm::empty_vec3(),
+            specular: util::glm::empty_vec3()
+        };
+        let recorder = UniformRecorder::default();
+        light.upload(&recorder, "dirLight");
+        assert_eq!(names(recorder), ["dirLight.direction", "dirLight.ambient", "dirLight.diffuse", "dirLight.specular"]);
+    }
+
+    #[test]
+    fn point_light_upload_test_names() {
+        let light = PointLight {
+            position: util::glm::empty_vec3(),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+            ambient: util::glm::empty_vec3(),
+            diffuse: util::glm::empty_vec3(),
+            specular: util::glm::empty_vec3()
+        };
+        let recorder = UniformRecorder::default();
+        light.upload(&recorder, &format!("pointLights[{}]", 2));
+        assert_eq!(names(recorder), [
+            "pointLights[2].position",
+            "pointLights[2].constant",
+            "pointLights[2].linear",
+            "pointLights[2].quadratic",
+            "pointLights[2].ambient",
+            "pointLights[2].diffuse",
+            "pointLights[2].specular"
+        ]);
+    }
+
+    #[test]
+    fn spot_light_upload_test_names() {
+        let light = SpotLight {
+            position: util::glm::empty_vec3(),
+            direction: glm::vec3(0.0, 0.0, -1.0),
+            cut_off: 12.5f32.to_radians().cos(),
+            outer_cut_off: 15f32.to_radians().cos(),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+            ambient: util::glm::empty_vec3(),
+            diffuse: util::glm::scale_vec3(1.0),
+            specular: util::glm::scale_vec3(1.0)
+        };
+        let recorder = UniformRecorder::default();
+        light.upload(&recorder, "spotLight");
+        assert_eq!(names(recorder), [
+            "spotLight.position",
+            "spotLight.direction",
+            "spotLight.cutOff",
+            "spotLight.outerCutOff",
+            "spotLight.constant",
+            "spotLight.linear",
+            "spotLight.quadratic",
+            "spotLight.ambient",
+            "spotLight.diffuse",
+            "spotLight.specular"
+        ]);
+    }
+
+    #[test]
+    fn spot_light_with_cone_test_cosine_order() {
+        let light = SpotLight::with_cone(12.5, 13.0);
+        // a wider angle has the smaller cosine
+        assert!(light.cut_off > light.outer_cut_off);
+        assert_eq!(light.cut_off, 12.5f32.to_radians().cos());
+        assert_eq!(light.outer_cut_off, 13f32.to_radians().cos());
+
+        // no cone edge (or an inverted one) gets the minimum one
+        let light = SpotLight::with_cone(10.0, 8.0);
+        assert_eq!(light.outer_cut_off, (10.0 + MIN_SPOT_EDGE_DEGREES).to_radians().cos());
+    }
+
+    #[test]
+    fn spot_light_follow_camera_test() {
+        let camera = Camera::new_position(glm::vec3(1.0, 2.0, 3.0));
+        let mut light = SpotLight::with_cone(12.5, 15.0);
+        light.follow_camera(&camera);
+        assert_eq!(light.position, camera.position());
+        assert_eq!(light.direction, camera.front());
+    }
+
+    #[test]
+    fn point_light_radius_test_known_values() {
+        // values of the deferred_shading_volumes example
+        let radius = point_light_radius(&glm::vec3(1.0, 1.0, 1.0), 1.0, 0.7, 1.8, LIGHT_RADIUS_THRESHOLD);
+        assert!((radius - 5.090127).abs() < 1e-4, "Got radius {}.", radius);
+        // only the brightest channel counts
+        let radius = point_light_radius(&glm::vec3(0.1, 0.5, 0.2), 1.0, 0.7, 1.8, LIGHT_RADIUS_THRESHOLD);
+        assert!((radius - 3.507511).abs() < 1e-4, "Got radius {}.", radius);
+    }
+
+    #[test]
+    fn point_light_radius_test_threshold() {
+        // at the radius the attenuated brightness is exactly the threshold
+        let (constant, linear, quadratic) = (1.0, 0.09, 0.032);
+        let radius = point_light_radius(&glm::vec3(0.8, 0.8, 0.8), constant, linear, quadratic, 0.01);
+        let attenuation = 1.0 / (constant + linear * radius + quadratic * radius * radius);
+        assert!((0.8 * attenuation - 0.01).abs() < 1e-5);
+    }
+
+    #[test]
+    fn random_point_lights_test_deterministic_within_bounds() {
+        let bounds = (glm::vec3(-3.0, -4.0, -3.0), glm::vec3(3.0, 2.0, 3.0));
+        let lights = random_point_lights(32, bounds, 13);
+        assert_eq!(lights.len(), 32);
+        assert_eq!(lights, random_point_lights(32, bounds, 13));
+        assert_ne!(lights, random_point_lights(32, bounds, 14));
+        for light in lights.iter() {
+            for axis in 0..3 {
+                assert!(light.position[axis] >= bounds.0[axis] && light.position[axis] <= bounds.1[axis], "Light at {:?}.", light.position.as_slice());
+                assert!((0.5..=1.0).contains(&light.diffuse[axis]));
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -17,12 +17,17 @@
 extern crate nalgebra_glm as glm;
 
 use std::{mem, ptr};
+use std::collections::HashMap;
 use std::ffi::CString;
 use gl::types::*;
+use crate::camera::Frustum;
 use crate::shader::Shader;
 use crate::util;
 
 const MAX_BONE_INFLUENCE: usize = 4;
+// first attribute location of the per-instance model matrix, which occupies four consecutive vec4 slots
+// right after the per-vertex attributes (0 to 6)
+pub const INSTANCE_MATRIX_LOCATION: u32 = 7;
 
 #[repr(C)]
 #[derive(Clone)]
@@ -51,14 +56,86 @@ pub struct Texture {
     pub path: String
 }
 
+// sampler uniform name prefixes for the texture roles of a mesh, the number of the texture within
+// its role gets appended (e.g. "material.diffuse" becomes "material.diffuse1"). This lets the same
+// mesh feed shaders that name their samplers differently. Textures of any other type keep their
+// type as prefix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureNaming {
+    pub diffuse: String,
+    pub specular: String,
+    pub normal: String,
+    pub height: String
+}
+
+impl TextureNaming {
+    // the sampler prefix for a texture of type `type_s` ("texture_diffuse", "texture_specular", ...)
+    pub fn prefix<'a>(&'a self, type_s: &'a str) -> &'a str {
+        match type_s {
+            "texture_diffuse" => &self.diffuse,
+            "texture_specular" => &self.specular,
+            "texture_normal" => &self.normal,
+            "texture_height" => &self.height,
+            _ => type_s
+        }
+    }
+}
+
+// the names `Mesh::draw` uses: texture_diffuseN, texture_specularN, texture_normalN and texture_heightN
+impl Default for TextureNaming {
+    fn default() -> Self {
+        Self {
+            diffuse: "texture_diffuse".to_string(),
+            specular: "texture_specular".to_string(),
+            normal: "texture_normal".to_string(),
+            height: "texture_height".to_string()
+        }
+    }
+}
+
+// the values uploaded for material properties the model file doesn't set
+const DEFAULT_SHININESS: f32 = 32.0;
+const DEFAULT_METALLIC: f32 = 0.0;
+const DEFAULT_ROUGHNESS: f32 = 1.0;
+
+// scalar material properties of a mesh, as authored in the model file (Ns of an MTL material, the
+// metallic and roughness factors of a glTF material). Unset ones are uploaded with a default, since
+// uniforms persist across draws and would otherwise keep the previous mesh's value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Material {
+    pub shininess: Option<f32>,
+    pub metallic: Option<f32>,
+    pub roughness: Option<f32>
+}
+
+impl Material {
+    // the uniforms `Mesh::draw` sets: material.shininess, material.metallic and material.roughness
+    pub fn uniforms(&self) -> Vec<(&'static str, f32)> {
+        vec![
+            ("material.shininess", self.shininess.unwrap_or(DEFAULT_SHININESS)),
+            ("material.metallic", self.metallic.unwrap_or(DEFAULT_METALLIC)),
+            ("material.roughness", self.roughness.unwrap_or(DEFAULT_ROUGHNESS))
+        ]
+    }
+
+    pub fn upload(&self, shader: &Shader) {
+        for (name, value) in self.uniforms() {
+            shader.set_float(name.to_string(), value);
+        }
+    }
+}
+
 pub struct Mesh {
     // mesh Data
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
     pub textures: Vec<Texture>,
+    pub material: Material,
     pub vao: u32,
     vbo: u32,
-    ebo: u32
+    ebo: u32,
+    // (center, radius) of `bounding_sphere`, computed once as `in_frustum` runs every frame
+    bounding_sphere: (glm::TVec3<f32>, f32)
 }
 
 impl Default for Vertex {
@@ -87,13 +164,21 @@ impl Default for Texture {
 
 impl Mesh {
     pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>, textures: Vec<Texture>) -> Self {
+        Self::new_ex(vertices, indices, textures, Material::default())
+    }
+
+    // like new, with the scalar properties of the mesh's material
+    pub fn new_ex(vertices: Vec<Vertex>, indices: Vec<u32>, textures: Vec<Texture>, material: Material) -> Self {
+        let bounding_sphere = bounding_sphere(&vertices);
         let mut result = Self {
             vertices,
             indices,
             textures,
+            material,
             vao: 0,
             vbo: 0,
-            ebo: 0
+            ebo: 0,
+            bounding_sphere
         };
         result.setup_mesh();
         result
@@ -202,48 +287,387 @@ impl Mesh {
         }
     }
 
+    // axis-aligned bounding box (min, max) of the vertex positions. An empty mesh yields a degenerate box at the origin.
+    pub fn bounding_box(&self) -> (glm::TVec3<f32>, glm::TVec3<f32>) {
+        bounding_box(&self.vertices)
+    }
+
+    // sphere (center, radius) around the center of the bounding box that encloses it, as of creating the mesh
+    pub fn bounding_sphere(&self) -> (glm::TVec3<f32>, f32) {
+        self.bounding_sphere
+    }
+
+    // whether the bounding sphere of the mesh, transformed by `model`, reaches into `frustum`. The
+    // radius grows with the largest scale of `model`, so the test stays conservative for non-uniform scales.
+    pub fn in_frustum(&self, frustum: &Frustum, model: &glm::TMat4<f32>) -> bool {
+        let (center, radius) = self.bounding_sphere();
+        let center = util::glm::vec3_from_vec4(&(model * util::glm::vec4_wrap_vec3(&center, 1.0)));
+        let scale = (0..3)
+            .map(|i| glm::length(&util::glm::vec3_from_vec4(&model.column(i).into_owned())))
+            .fold(0.0, f32::max);
+        frustum.contains_sphere(&center, radius * scale)
+    }
+
+    // (re)computes the tangent and bitangent of every vertex from the triangles and texture coordinates
+    // and uploads them, e.g. for normal mapping meshes whose file doesn't provide a tangent space
+    pub fn compute_tangents(&mut self) {
+        compute_tangents(&mut self.vertices, &self.indices);
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                (self.vertices.len() * mem::size_of::<Vertex>()) as GLsizeiptr,
+                self.vertices.as_ptr() as *const _
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
+
     // render the mesh
     pub fn draw(&self, shader: &Shader) {
+        self.draw_with(shader, &TextureNaming::default());
+    }
+
+    // render the mesh with its textures bound to the samplers `naming` gives them
+    pub fn draw_with(&self, shader: &Shader, naming: &TextureNaming) {
+        self.bind_textures(shader, naming);
+
+        unsafe {
+            // draw mesh
+            gl::BindVertexArray(self.vao);
+            gl::DrawElements(gl::TRIANGLES, self.indices.len() as GLsizei, gl::UNSIGNED_INT, ptr::null());
+            gl::BindVertexArray(0);
+
+            // always good practice to set everything back to defaults once configured.
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+
+    // render count instances of the mesh, each transformed by its own matrix of the instance buffer
+    pub fn draw_instanced(&self, shader: &Shader, count: i32) {
+        self.bind_textures(shader, &TextureNaming::default());
+
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::DrawElementsInstanced(gl::TRIANGLES, self.indices.len() as GLsizei, gl::UNSIGNED_INT, ptr::null(), count);
+            gl::BindVertexArray(0);
+
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+
+    // hooks a buffer of tightly packed mat4s up to the instance matrix attribute (location 7 to 10) of this mesh
+    pub fn set_instance_buffer(&self, buffer: u32) {
+        let mat4_size = mem::size_of::<glm::TMat4<f32>>();
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, buffer);
+            // set attribute pointers for matrix (4 times vec4)
+            for i in 0..4 {
+                let location = INSTANCE_MATRIX_LOCATION + i;
+                gl::EnableVertexAttribArray(location);
+                gl::VertexAttribPointer(
+                    location,
+                    4,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    mat4_size as GLsizei,
+                    (i as usize * mem::size_of::<glm::TVec4<f32>>()) as *const _
+                );
+                gl::VertexAttribDivisor(location, 1);
+            }
+            gl::BindVertexArray(0);
+        }
+    }
+
+    fn bind_textures(&self, shader: &Shader, naming: &TextureNaming) {
+        self.material.upload(shader);
+
         // bind appropriate textures
-        let mut diffuse_nr = 1u32;
-        let mut specular_nr = 1u32;
-        let mut normal_nr = 1u32;
-        let mut height_nr = 1u32;
+        let sampler_names = sampler_names(&self.textures, naming);
 
         unsafe {
-            for (i, texture) in self.textures.iter().enumerate() {
+            for (i, (texture, sampler_name)) in self.textures.iter().zip(sampler_names).enumerate() {
                 gl::ActiveTexture(gl::TEXTURE0 + i as u32); // active proper texture unit before binding
-                // retrieve texture number (the N in diffuse_textureN)
-                let mut number = String::new();
-                let name = texture.type_s.clone();
-                if name == "texture_diffuse" {
-                    diffuse_nr += 1;
-                    number = diffuse_nr.to_string();
-                } else if name == "texture_specular" {
-                    specular_nr += 1;
-                    number = specular_nr.to_string(); // transfer unsigned int to string
-                } else if name == "texture_normal" {
-                    normal_nr += 1;
-                    number = normal_nr.to_string(); // transfer unsigned int to string
-                } else if name == "texture_height" {
-                    height_nr += 1;
-                    number = height_nr.to_string(); // transfer unsigned int to string
-                }
-
-                let c_str = CString::new(name + number.as_str()).unwrap();
+                let c_str = CString::new(sampler_name).unwrap();
                 // now set the sampler to the correct texture unit
                 gl::Uniform1i(gl::GetUniformLocation(shader.id(), c_str.as_ptr()), i as GLint);
                 // and finally bind the texture
                 gl::BindTexture(gl::TEXTURE_2D, texture.id);
             }
+        }
+    }
+}
 
-            // draw mesh
-            gl::BindVertexArray(self.vao);
-            gl::DrawElements(gl::TRIANGLES, self.indices.len() as GLsizei, gl::UNSIGNED_INT, ptr::null());
-            gl::BindVertexArray(0);
+// tangent and bitangent (not normalized) of a triangle, i.e. the directions in which its texture's u and v
+// coordinates increase, solved from its edges and the matching texture coordinate deltas
+pub fn triangle_tangents(
+    positions: [&glm::TVec3<f32>; 3],
+    uvs: [&glm::TVec2<f32>; 3]
+) -> (glm::TVec3<f32>, glm::TVec3<f32>) {
+    let edge1 = positions[1] - positions[0];
+    let edge2 = positions[2] - positions[0];
+    let delta_uv1 = uvs[1] - uvs[0];
+    let delta_uv2 = uvs[2] - uvs[0];
 
-            // always good practice to set everything back to defaults once configured.
-            gl::ActiveTexture(gl::TEXTURE0);
+    let f = 1.0f32 / (delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y);
+
+    let tangent = (edge1 * delta_uv2.y - edge2 * delta_uv1.y) * f;
+    let bitangent = (edge2 * delta_uv1.x - edge1 * delta_uv2.x) * f;
+    (tangent, bitangent)
+}
+
+// sets the tangent space of the vertices of an indexed triangle list: vertices shared by several
+// triangles get the normalized sum of their tangents. Triangles without usable texture coordinates
+// (all on a line in uv space) don't contribute.
+pub fn compute_tangents(vertices: &mut [Vertex], indices: &[u32]) {
+    for vertex in vertices.iter_mut() {
+        vertex.tangent = util::glm::empty_vec3();
+        vertex.bitangent = util::glm::empty_vec3();
+    }
+    for triangle in indices.chunks_exact(3) {
+        let [i0, i1, i2] = [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize];
+        let (tangent, bitangent) = triangle_tangents(
+            [&vertices[i0].position, &vertices[i1].position, &vertices[i2].position],
+            [&vertices[i0].tex_coords, &vertices[i1].tex_coords, &vertices[i2].tex_coords]
+        );
+        if !tangent.iter().chain(bitangent.iter()).all(|it| it.is_finite()) {
+            continue;
+        }
+        for i in [i0, i1, i2] {
+            vertices[i].tangent += tangent;
+            vertices[i].bitangent += bitangent;
+        }
+    }
+    for vertex in vertices.iter_mut() {
+        if glm::length(&vertex.tangent) > 0.0 {
+            vertex.tangent = glm::normalize(&vertex.tangent);
+        }
+        if glm::length(&vertex.bitangent) > 0.0 {
+            vertex.bitangent = glm::normalize(&vertex.bitangent);
         }
     }
+}
+
+// retrieves the sampler uniform name of every texture: the prefix of its type followed by its number (the N in
+// texture_diffuseN), counting from 1 separately for each texture type
+fn sampler_names(textures: &[Texture], naming: &TextureNaming) -> Vec<String> {
+    let mut numbers: HashMap<&str, u32> = HashMap::new();
+    textures.iter().map(|texture| {
+        let number = numbers.entry(texture.type_s.as_str()).or_insert(0);
+        *number += 1;
+        format!("{}{}", naming.prefix(&texture.type_s), number)
+    }).collect()
+}
+
+// The buffers are deleted with the mesh, which has to happen while its GL context is still current.
+// The textures are left alone as they're usually shared between meshes (see Model).
+impl Drop for Mesh {
+    fn drop(&mut self) {
+        // meshes that never made it to the GPU (e.g. built by hand in tests) have nothing to delete
+        if self.vao == 0 {
+            return;
+        }
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteBuffers(1, &self.ebo);
+        }
+    }
+}
+
+fn bounding_box(vertices: &[Vertex]) -> (glm::TVec3<f32>, glm::TVec3<f32>) {
+    let mut positions = vertices.iter().map(|vertex| vertex.position);
+    let first = match positions.next() {
+        Some(position) => position,
+        None => return (util::glm::empty_vec3(), util::glm::empty_vec3())
+    };
+    positions.fold((first, first), |(min, max), position| {
+        (glm::min2(&min, &position), glm::max2(&max, &position))
+    })
+}
+
+fn bounding_sphere(vertices: &[Vertex]) -> (glm::TVec3<f32>, f32) {
+    let (min, max) = bounding_box(vertices);
+    ((min + max) * 0.5, glm::length(&(max - min)) * 0.5)
+}
+
+// draws the meshes whose bounding spheres, transformed by `model`, reach into `frustum` and skips the
+// others. Returns the number of meshes drawn.
+pub fn draw_culled<'a>(meshes: impl IntoIterator<Item = &'a Mesh>, shader: &Shader, frustum: &Frustum, model: &glm::TMat4<f32>) -> usize {
+    let mut drawn = 0;
+    for mesh in meshes {
+        if mesh.in_frustum(frustum, model) {
+            mesh.draw(shader);
+            drawn += 1;
+        }
+    }
+    drawn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+
+    fn texture(type_s: &str) -> Texture {
+        Texture {
+            id: 0,
+            type_s: type_s.to_string(),
+            path: String::new()
+        }
+    }
+
+    #[test]
+    fn bounding_box_of_unit_cube() {
+        let mut vertices = Vec::new();
+        for &x in [-0.5, 0.5].iter() {
+            for &y in [-0.5, 0.5].iter() {
+                for &z in [-0.5, 0.5].iter() {
+                    vertices.push(Vertex {
+                        position: glm::vec3(x, y, z),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        let mesh = Mesh {
+            bounding_sphere: bounding_sphere(&vertices),
+            vertices,
+            indices: Vec::new(),
+            textures: Vec::new(),
+            material: Material::default(),
+            vao: 0,
+            vbo: 0,
+            ebo: 0
+        };
+        let (min, max) = mesh.bounding_box();
+        assert_eq!(min, glm::vec3(-0.5, -0.5, -0.5));
+        assert_eq!(max, glm::vec3(0.5, 0.5, 0.5));
+        let (center, radius) = mesh.bounding_sphere();
+        assert_eq!(center, util::glm::empty_vec3());
+        assert!((radius - 0.75f32.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn draw_culled_test_skips_meshes_outside_frustum() {
+        let Some(_context) = hidden_gl_context(64, 64) else { return; };
+        const VS: &str = "#version 330 core\nlayout (location = 0) in vec3 aPos;\nvoid main() { gl_Position = vec4(aPos, 1.0); }";
+        const FS: &str = "#version 330 core\nout vec4 FragColor;\nvoid main() { FragColor = vec4(1.0); }";
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        // a unit triangle, never uploaded: culled meshes don't touch their buffers
+        let vertices: Vec<Vertex> = (0..3).map(|i| Vertex {
+            position: glm::vec3((i % 2) as f32, (i / 2) as f32, 0.0),
+            ..Default::default()
+        }).collect();
+        let mesh = Mesh {
+            bounding_sphere: bounding_sphere(&vertices),
+            vertices,
+            indices: vec![0, 1, 2],
+            textures: Vec::new(),
+            material: Material::default(),
+            vao: 0,
+            vbo: 0,
+            ebo: 0
+        };
+        // a camera at the origin looking down -z, the mesh is moved behind it
+        let projection = glm::perspective(1.0, 45f32.to_radians(), 0.1, 100.0);
+        let view = glm::look_at(&util::glm::empty_vec3(), &glm::vec3(0.0, 0.0, -1.0), &glm::vec3(0.0, 1.0, 0.0));
+        let frustum = Frustum::from_matrix(&(projection * view));
+        let behind = glm::translate(&util::glm::diag_mat4(1.0), &glm::vec3(0.0, 0.0, 10.0));
+        assert_eq!(draw_culled([&mesh], &shader, &frustum, &behind), 0);
+        let in_front = glm::translate(&util::glm::diag_mat4(1.0), &glm::vec3(0.0, 0.0, -10.0));
+        assert!(mesh.in_frustum(&frustum, &in_front));
+        // scaling grows the bounding sphere until it reaches into the frustum
+        assert!(mesh.in_frustum(&frustum, &glm::scale(&behind, &util::glm::scale_vec3(20.0))));
+    }
+
+    #[test]
+    fn compute_tangents_of_rotated_uv_triangle() {
+        // the texture is turned by 90 degrees on the triangle: u runs along -y, v along +x
+        let positions = [glm::vec3(0.0, 0.0, 0.0), glm::vec3(2.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0)];
+        let uvs = [glm::vec2(0.0, 0.0), glm::vec2(0.0, 1.0), glm::vec2(-1.0, 0.0)];
+        // by hand: edge1 = (2, 0, 0), edge2 = (0, 1, 0), duv1 = (0, 1), duv2 = (-1, 0), f = 1 / (0 * 0 - (-1) * 1) = 1
+        // tangent = f * (duv2.y * edge1 - duv1.y * edge2) = (0, -1, 0)
+        // bitangent = f * (-duv2.x * edge1 + duv1.x * edge2) = (2, 0, 0)
+        let (tangent, bitangent) = triangle_tangents(
+            [&positions[0], &positions[1], &positions[2]],
+            [&uvs[0], &uvs[1], &uvs[2]]
+        );
+        assert_eq!(tangent, glm::vec3(0.0, -1.0, 0.0));
+        assert_eq!(bitangent, glm::vec3(2.0, 0.0, 0.0));
+
+        let mut vertices: Vec<Vertex> = positions.iter().zip(uvs.iter()).map(|(position, uv)| Vertex {
+            position: *position,
+            normal: glm::vec3(0.0, 0.0, 1.0),
+            tex_coords: *uv,
+            ..Default::default()
+        }).collect();
+        compute_tangents(&mut vertices, &[0, 1, 2]);
+        for vertex in vertices.iter() {
+            assert_eq!(vertex.tangent, glm::vec3(0.0, -1.0, 0.0));
+            assert_eq!(vertex.bitangent, glm::vec3(1.0, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn compute_tangents_skips_degenerate_uvs() {
+        let mut vertices: Vec<Vertex> = (0..3).map(|i| Vertex {
+            position: glm::vec3(i as f32, (i * i) as f32, 0.0),
+            ..Default::default()
+        }).collect();
+        compute_tangents(&mut vertices, &[0, 1, 2]);
+        assert!(vertices.iter().all(|it| it.tangent == util::glm::empty_vec3()));
+    }
+
+    #[test]
+    fn sampler_names_count_per_type_from_one() {
+        let textures = [
+            texture("texture_diffuse"),
+            texture("texture_specular"),
+            texture("texture_diffuse"),
+            texture("texture_normal"),
+            texture("texture_metallic_roughness")
+        ];
+        assert_eq!(sampler_names(&textures, &TextureNaming::default()), vec![
+            "texture_diffuse1",
+            "texture_specular1",
+            "texture_diffuse2",
+            "texture_normal1",
+            "texture_metallic_roughness1"
+        ]);
+    }
+
+    #[test]
+    fn sampler_names_with_custom_naming() {
+        let textures = [
+            texture("texture_diffuse"),
+            texture("texture_specular")
+        ];
+        let naming = TextureNaming {
+            diffuse: "material.diffuse".to_string(),
+            specular: "material.specular".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(sampler_names(&textures, &naming), vec![
+            "material.diffuse1",
+            "material.specular1"
+        ]);
+    }
+
+    #[test]
+    fn material_uniforms_default_unset_properties() {
+        assert_eq!(Material::default().uniforms(), vec![
+            ("material.shininess", 32.0),
+            ("material.metallic", 0.0),
+            ("material.roughness", 1.0)
+        ]);
+        let material = Material { shininess: Some(64.0), roughness: Some(0.5), ..Default::default() };
+        assert_eq!(material.uniforms(), vec![
+            ("material.shininess", 64.0),
+            ("material.metallic", 0.0),
+            ("material.roughness", 0.5)
+        ]);
+    }
 }
\ No newline at end of file
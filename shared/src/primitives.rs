@@ -0,0 +1,668 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::{mem, ptr};
+use gl::types::GLint;
+use crate::mesh::INSTANCE_MATRIX_LOCATION;
+
+// Shared primitives used throughout the examples. Their vertex arrays are lazily created on first use
+// and cached, which ties them to the GL context that was current at that time.
+//
+// IMPORTANT: the cached ids are only valid for the context they were created in. Whenever the context
+// is destroyed and recreated (e.g. a window gets recreated or several contexts are spun up one after
+// another) call `reset()` once the new context is current so the next draw rebuilds them.
+
+// renderCube() renders a 1x1 3D cube in NDC.
+// -------------------------------------------------
+static mut CUBE_VAO: u32 = 0;
+static mut CUBE_VBO: u32 = 0;
+pub fn render_cube() {
+    init_cube();
+    unsafe {
+        // render Cube
+        gl::BindVertexArray(CUBE_VAO);
+        gl::DrawArrays(gl::TRIANGLES, 0, 36);
+        gl::BindVertexArray(0);
+    }
+}
+
+// creates the cube's vertex array and buffer (if necessary), shared by render_cube and render_cube_instanced
+fn init_cube() {
+    unsafe {
+        // initialize (if necessary)
+        if CUBE_VAO == 0 {
+            let vertices = [
+                // back face
+                -1.0f32, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
+                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
+                1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 0.0, // bottom-right
+                1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 1.0, 1.0, // top-right
+                -1.0, -1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 0.0, // bottom-left
+                -1.0,  1.0, -1.0,  0.0,  0.0, -1.0, 0.0, 1.0, // top-left
+                // front face
+                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
+                1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 0.0, // bottom-right
+                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
+                1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 1.0, 1.0, // top-right
+                -1.0,  1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 1.0, // top-left
+                -1.0, -1.0,  1.0,  0.0,  0.0,  1.0, 0.0, 0.0, // bottom-left
+                // left face
+                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
+                -1.0,  1.0, -1.0, -1.0,  0.0,  0.0, 1.0, 1.0, // top-left
+                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
+                -1.0, -1.0, -1.0, -1.0,  0.0,  0.0, 0.0, 1.0, // bottom-left
+                -1.0, -1.0,  1.0, -1.0,  0.0,  0.0, 0.0, 0.0, // bottom-right
+                -1.0,  1.0,  1.0, -1.0,  0.0,  0.0, 1.0, 0.0, // top-right
+                // right face
+                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
+                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
+                1.0,  1.0, -1.0,  1.0,  0.0,  0.0, 1.0, 1.0, // top-right
+                1.0, -1.0, -1.0,  1.0,  0.0,  0.0, 0.0, 1.0, // bottom-right
+                1.0,  1.0,  1.0,  1.0,  0.0,  0.0, 1.0, 0.0, // top-left
+                1.0, -1.0,  1.0,  1.0,  0.0,  0.0, 0.0, 0.0, // bottom-left
+                // bottom face
+                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
+                1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 1.0, 1.0, // top-left
+                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
+                1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 1.0, 0.0, // bottom-left
+                -1.0, -1.0,  1.0,  0.0, -1.0,  0.0, 0.0, 0.0, // bottom-right
+                -1.0, -1.0, -1.0,  0.0, -1.0,  0.0, 0.0, 1.0, // top-right
+                // top face
+                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
+                1.0,  1.0 , 1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
+                1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 1.0, 1.0, // top-right
+                1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 1.0, 0.0, // bottom-right
+                -1.0,  1.0, -1.0,  0.0,  1.0,  0.0, 0.0, 1.0, // top-left
+                -1.0,  1.0,  1.0,  0.0,  1.0,  0.0, 0.0, 0.0  // bottom-left
+            ];
+            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_VAO));
+            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_VBO));
+            // fill buffer
+            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
+            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&vertices) as _, ptr::addr_of!(vertices) as _, gl::STATIC_DRAW);
+            // link vertex attributes
+            gl::BindVertexArray(CUBE_VAO);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (8 * mem::size_of::<f32>()) as _, (6 * mem::size_of::<f32>()) as _);
+            debug_assert_attribs_enabled(3);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+// renderCubeInstanced() renders one cube per transform in a single draw call, the i-th one with colors[i]
+// as its color. Shaders read the model matrix at locations 7 to 10 (like instanced meshes, see
+// `mesh::INSTANCE_MATRIX_LOCATION`) and the color at `INSTANCE_COLOR_LOCATION`.
+// -------------------------------------------------
+pub const INSTANCE_COLOR_LOCATION: u32 = 6; // free on the cube, which only has attributes 0 to 2
+static mut CUBE_INSTANCED_VAO: u32 = 0;
+static mut CUBE_INSTANCE_MATRIX_VBO: u32 = 0;
+static mut CUBE_INSTANCE_COLOR_VBO: u32 = 0;
+pub fn render_cube_instanced(transforms: &[glm::TMat4<f32>], colors: &[glm::TVec3<f32>]) {
+    assert_eq!(transforms.len(), colors.len(), "Every cube instance needs both a transform and a color.");
+    init_cube();
+    unsafe {
+        // initialize (if necessary)
+        if CUBE_INSTANCED_VAO == 0 {
+            gl::GenVertexArrays(1, ptr::addr_of_mut!(CUBE_INSTANCED_VAO));
+            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_INSTANCE_MATRIX_VBO));
+            gl::GenBuffers(1, ptr::addr_of_mut!(CUBE_INSTANCE_COLOR_VBO));
+            gl::BindVertexArray(CUBE_INSTANCED_VAO);
+            // per-vertex attributes straight from the cube's buffer
+            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_VBO);
+            let stride = 8 * mem::size_of::<f32>();
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride as _, ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride as _, (3 * mem::size_of::<f32>()) as _);
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, stride as _, (6 * mem::size_of::<f32>()) as _);
+            debug_assert_attribs_enabled(3);
+            // per-instance model matrix (4 times vec4)
+            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_INSTANCE_MATRIX_VBO);
+            for i in 0..4 {
+                let location = INSTANCE_MATRIX_LOCATION + i;
+                gl::EnableVertexAttribArray(location);
+                gl::VertexAttribPointer(location, 4, gl::FLOAT, gl::FALSE, mem::size_of::<glm::TMat4<f32>>() as _, (i as usize * mem::size_of::<glm::TVec4<f32>>()) as _);
+                gl::VertexAttribDivisor(location, 1);
+            }
+            // per-instance color
+            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_INSTANCE_COLOR_VBO);
+            gl::EnableVertexAttribArray(INSTANCE_COLOR_LOCATION);
+            gl::VertexAttribPointer(INSTANCE_COLOR_LOCATION, 3, gl::FLOAT, gl::FALSE, mem::size_of::<glm::TVec3<f32>>() as _, ptr::null());
+            gl::VertexAttribDivisor(INSTANCE_COLOR_LOCATION, 1);
+            gl::BindVertexArray(0);
+        }
+
+        // the instances usually change every frame (e.g. culled lights), so they're uploaded on every call
+        gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_INSTANCE_MATRIX_VBO);
+        gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(transforms) as _, transforms.as_ptr() as _, gl::DYNAMIC_DRAW);
+        gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_INSTANCE_COLOR_VBO);
+        gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(colors) as _, colors.as_ptr() as _, gl::DYNAMIC_DRAW);
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+        // render cubes
+        gl::BindVertexArray(CUBE_INSTANCED_VAO);
+        gl::DrawArraysInstanced(gl::TRIANGLES, 0, 36, transforms.len() as _);
+        gl::BindVertexArray(0);
+    }
+}
+
+// renderQuad() renders a 1x1 XY quad in NDC
+// -----------------------------------------
+static mut QUAD_VAO: u32 = 0;
+static mut QUAD_VBO: u32 = 0;
+pub fn render_quad() {
+    unsafe {
+        if QUAD_VAO == 0 {
+            let quad_vertices = [
+                // positions        // texture Coords
+                -1.0f32,  1.0, 0.0, 0.0, 1.0,
+                -1.0, -1.0, 0.0, 0.0, 0.0,
+                1.0,  1.0, 0.0, 1.0, 1.0,
+                1.0, -1.0, 0.0, 1.0, 0.0
+            ];
+            // setup plane VAO
+            gl::GenVertexArrays(1, ptr::addr_of_mut!(QUAD_VAO));
+            gl::GenBuffers(1, ptr::addr_of_mut!(QUAD_VBO));
+            gl::BindVertexArray(QUAD_VAO);
+            gl::BindBuffer(gl::ARRAY_BUFFER, QUAD_VBO);
+            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(&quad_vertices) as _, ptr::addr_of!(quad_vertices) as _, gl::STATIC_DRAW);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as _, ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
+        }
+        gl::BindVertexArray(QUAD_VAO);
+        gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+        gl::BindVertexArray(0);
+    }
+}
+
+// renders a square of `size` world units centered at `center` that always faces the camera of `view`,
+// e.g. as a glowing marker for a light. Positions are in world space already (no model matrix), texture
+// coordinates go from 0 to 1 across it.
+// -----------------------------------------------------------------------------------------------------
+static mut BILLBOARD_VAO: u32 = 0;
+static mut BILLBOARD_VBO: u32 = 0;
+pub fn render_billboard(center: &glm::TVec3<f32>, size: f32, view: &glm::TMat4<f32>) {
+    unsafe {
+        if BILLBOARD_VAO == 0 {
+            gl::GenVertexArrays(1, ptr::addr_of_mut!(BILLBOARD_VAO));
+            gl::GenBuffers(1, ptr::addr_of_mut!(BILLBOARD_VBO));
+            gl::BindVertexArray(BILLBOARD_VAO);
+            gl::BindBuffer(gl::ARRAY_BUFFER, BILLBOARD_VBO);
+            gl::BufferData(gl::ARRAY_BUFFER, (4 * 5 * mem::size_of::<f32>()) as _, ptr::null(), gl::DYNAMIC_DRAW);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as _, ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<f32>()) as _, (3 * mem::size_of::<f32>()) as _);
+            debug_assert_attribs_enabled(2);
+        }
+        // the corners move with the camera, so they're refilled on every draw
+        let vertices = billboard_geometry(center, size, view);
+        gl::BindBuffer(gl::ARRAY_BUFFER, BILLBOARD_VBO);
+        gl::BufferSubData(gl::ARRAY_BUFFER, 0, mem::size_of_val(&vertices) as _, vertices.as_ptr() as _);
+        gl::BindVertexArray(BILLBOARD_VAO);
+        gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+        gl::BindVertexArray(0);
+    }
+}
+
+// builds the interleaved position/uv data of the billboard's triangle strip. The camera's right and up
+// axes in world space are the first two rows of the (rotation part of the) view matrix.
+fn billboard_geometry(center: &glm::TVec3<f32>, size: f32, view: &glm::TMat4<f32>) -> [f32; 4 * 5] {
+    let half = size / 2.0;
+    let right = glm::vec3(view[(0, 0)], view[(0, 1)], view[(0, 2)]) * half;
+    let up = glm::vec3(view[(1, 0)], view[(1, 1)], view[(1, 2)]) * half;
+    let top_left = center - right + up;
+    let bottom_left = center - right - up;
+    let top_right = center + right + up;
+    let bottom_right = center + right - up;
+    [
+        // positions                                     // texture Coords
+        top_left.x, top_left.y, top_left.z,              0.0, 1.0,
+        bottom_left.x, bottom_left.y, bottom_left.z,     0.0, 0.0,
+        top_right.x, top_right.y, top_right.z,           1.0, 1.0,
+        bottom_right.x, bottom_right.y, bottom_right.z,  1.0, 0.0
+    ]
+}
+
+// renders (and builds at first invocation) a unit sphere
+// -------------------------------------------------------
+static mut SPHERE_VAO: u32 = 0;
+static mut SPHERE_VBO: u32 = 0;
+static mut SPHERE_EBO: u32 = 0;
+static mut SPHERE_INDEX_COUNT: u32 = 0;
+const SPHERE_X_SEGMENTS: u32 = 64;
+const SPHERE_Y_SEGMENTS: u32 = 64;
+pub fn render_sphere() {
+    unsafe {
+        if SPHERE_VAO == 0 {
+            let (data, indices) = sphere_geometry(SPHERE_X_SEGMENTS, SPHERE_Y_SEGMENTS);
+            SPHERE_INDEX_COUNT = indices.len() as u32;
+
+            gl::GenVertexArrays(1, ptr::addr_of_mut!(SPHERE_VAO));
+            gl::GenBuffers(1, ptr::addr_of_mut!(SPHERE_VBO));
+            gl::GenBuffers(1, ptr::addr_of_mut!(SPHERE_EBO));
+            gl::BindVertexArray(SPHERE_VAO);
+            gl::BindBuffer(gl::ARRAY_BUFFER, SPHERE_VBO);
+            gl::BufferData(gl::ARRAY_BUFFER, (data.len() * mem::size_of::<f32>()) as _, data.as_ptr() as _, gl::STATIC_DRAW);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, SPHERE_EBO);
+            gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (indices.len() * mem::size_of::<u32>()) as _, indices.as_ptr() as _, gl::STATIC_DRAW);
+            let stride = (3 + 2 + 3) * mem::size_of::<f32>();
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride as _, ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride as _, (3 * mem::size_of::<f32>()) as _);
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, stride as _, (6 * mem::size_of::<f32>()) as _);
+            debug_assert_attribs_enabled(3);
+            gl::BindVertexArray(0);
+        }
+
+        gl::BindVertexArray(SPHERE_VAO);
+        gl::DrawElements(gl::TRIANGLE_STRIP, SPHERE_INDEX_COUNT as _, gl::UNSIGNED_INT, ptr::null());
+        gl::BindVertexArray(0);
+    }
+}
+
+// builds the interleaved position/normal/uv data and the triangle strip indices of a unit sphere
+fn sphere_geometry(x_segments: u32, y_segments: u32) -> (Vec<f32>, Vec<u32>) {
+    use std::f32::consts::PI;
+
+    let mut data: Vec<f32> = Vec::new();
+    for x in 0..=x_segments {
+        for y in 0..=y_segments {
+            let x_segment = x as f32 / x_segments as f32;
+            let y_segment = y as f32 / y_segments as f32;
+            let x_pos = (x_segment * 2.0 * PI).cos() * (y_segment * PI).sin();
+            let y_pos = (y_segment * PI).cos();
+            let z_pos = (x_segment * 2.0 * PI).sin() * (y_segment * PI).sin();
+
+            // position, normal (identical on a unit sphere) and uv
+            data.extend_from_slice(&[x_pos, y_pos, z_pos, x_pos, y_pos, z_pos, x_segment, y_segment]);
+        }
+    }
+
+    let mut indices: Vec<u32> = Vec::new();
+    let mut odd_row = false;
+    for y in 0..y_segments {
+        if !odd_row { // even rows: y == 0, y == 2; and so on
+            for x in 0..=x_segments {
+                indices.push(y * (x_segments + 1) + x);
+                indices.push((y + 1) * (x_segments + 1) + x);
+            }
+        } else {
+            for x in (0..=x_segments).rev() {
+                indices.push((y + 1) * (x_segments + 1) + x);
+                indices.push(y * (x_segments + 1) + x);
+            }
+        }
+        odd_row = !odd_row;
+    }
+
+    (data, indices)
+}
+
+// renderPlane() renders a horizontal floor plane at y = -0.5 spanning [-size, size] on x and z, with its
+// texture coordinates repeating tex_repeat times across it.
+// -------------------------------------------------
+static mut PLANE_VAO: u32 = 0;
+static mut PLANE_VBO: u32 = 0;
+static mut PLANE_SIZE: f32 = 0.0;
+static mut PLANE_TEX_REPEAT: f32 = 0.0;
+pub fn render_plane(size: f32, tex_repeat: f32) {
+    unsafe {
+        // initialize (if necessary)
+        if PLANE_VAO == 0 {
+            gl::GenVertexArrays(1, ptr::addr_of_mut!(PLANE_VAO));
+            gl::GenBuffers(1, ptr::addr_of_mut!(PLANE_VBO));
+            gl::BindVertexArray(PLANE_VAO);
+            gl::BindBuffer(gl::ARRAY_BUFFER, PLANE_VBO);
+            let stride = 8 * mem::size_of::<f32>();
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride as _, ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride as _, (3 * mem::size_of::<f32>()) as _);
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, stride as _, (6 * mem::size_of::<f32>()) as _);
+            debug_assert_attribs_enabled(3);
+            // force the upload below
+            PLANE_SIZE = f32::NAN;
+        }
+        // the buffer only holds one plane, so refill it whenever a different size or tiling is asked for
+        if PLANE_SIZE != size || PLANE_TEX_REPEAT != tex_repeat {
+            let vertices = plane_geometry(size, tex_repeat);
+            gl::BindBuffer(gl::ARRAY_BUFFER, PLANE_VBO);
+            gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * mem::size_of::<f32>()) as _, vertices.as_ptr() as _, gl::STATIC_DRAW);
+            PLANE_SIZE = size;
+            PLANE_TEX_REPEAT = tex_repeat;
+        }
+
+        // render plane
+        gl::BindVertexArray(PLANE_VAO);
+        gl::DrawArrays(gl::TRIANGLES, 0, 6);
+        gl::BindVertexArray(0);
+    }
+}
+
+// builds the interleaved position/normal/uv data of the two triangles making up the floor plane. They're
+// wound counter-clockwise when seen from above, so back face culling keeps the side facing up.
+fn plane_geometry(size: f32, tex_repeat: f32) -> Vec<f32> {
+    vec![
+        // positions              // normals      // texcoords
+        size, -0.5, size,         0.0, 1.0, 0.0,  tex_repeat, 0.0,
+        -size, -0.5, -size,       0.0, 1.0, 0.0,  0.0, tex_repeat,
+        -size, -0.5, size,        0.0, 1.0, 0.0,  0.0, 0.0,
+
+        size, -0.5, size,         0.0, 1.0, 0.0,  tex_repeat, 0.0,
+        size, -0.5, -size,        0.0, 1.0, 0.0,  tex_repeat, tex_repeat,
+        -size, -0.5, -size,       0.0, 1.0, 0.0,  0.0, tex_repeat,
+    ]
+}
+
+// renderGrid() renders a ground grid of GL_LINES on the XZ plane at y = 0, spanning [-size, size]
+// with `divisions` cells along each axis. Every vertex has a position (location 0) and a color (location 1).
+// -------------------------------------------------
+static mut GRID_VAO: u32 = 0;
+static mut GRID_VBO: u32 = 0;
+static mut GRID_SIZE: f32 = 0.0;
+static mut GRID_DIVISIONS: u32 = 0;
+static mut GRID_VERTEX_COUNT: i32 = 0;
+const GRID_COLOR: [f32; 3] = [0.5, 0.5, 0.5];
+pub fn render_grid(size: f32, divisions: u32) {
+    unsafe {
+        // initialize (if necessary)
+        if GRID_VAO == 0 {
+            init_line_vao(ptr::addr_of_mut!(GRID_VAO), ptr::addr_of_mut!(GRID_VBO));
+            // force the upload below
+            GRID_SIZE = f32::NAN;
+        }
+        // like the plane, the buffer is refilled whenever a different grid is asked for
+        if GRID_SIZE != size || GRID_DIVISIONS != divisions {
+            let vertices = grid_geometry(size, divisions);
+            gl::BindBuffer(gl::ARRAY_BUFFER, GRID_VBO);
+            gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * mem::size_of::<f32>()) as _, vertices.as_ptr() as _, gl::STATIC_DRAW);
+            GRID_SIZE = size;
+            GRID_DIVISIONS = divisions;
+            GRID_VERTEX_COUNT = (vertices.len() / 6) as i32;
+        }
+
+        // render grid
+        gl::BindVertexArray(GRID_VAO);
+        gl::DrawArrays(gl::LINES, 0, GRID_VERTEX_COUNT);
+        gl::BindVertexArray(0);
+    }
+}
+
+// renderAxes() renders the X, Y and Z axis from the origin as red, green and blue GL_LINES of the
+// given length, with the same vertex layout as render_grid.
+// -------------------------------------------------
+static mut AXES_VAO: u32 = 0;
+static mut AXES_VBO: u32 = 0;
+static mut AXES_LENGTH: f32 = 0.0;
+pub fn render_axes(length: f32) {
+    unsafe {
+        // initialize (if necessary)
+        if AXES_VAO == 0 {
+            init_line_vao(ptr::addr_of_mut!(AXES_VAO), ptr::addr_of_mut!(AXES_VBO));
+            // force the upload below
+            AXES_LENGTH = f32::NAN;
+        }
+        if AXES_LENGTH != length {
+            let vertices = axes_geometry(length);
+            gl::BindBuffer(gl::ARRAY_BUFFER, AXES_VBO);
+            gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * mem::size_of::<f32>()) as _, vertices.as_ptr() as _, gl::STATIC_DRAW);
+            AXES_LENGTH = length;
+        }
+
+        // render axes
+        gl::BindVertexArray(AXES_VAO);
+        gl::DrawArrays(gl::LINES, 0, 6);
+        gl::BindVertexArray(0);
+    }
+}
+
+// creates a vertex array for interleaved position/color lines, the buffer is filled by the caller
+unsafe fn init_line_vao(vao: *mut u32, vbo: *mut u32) {
+    gl::GenVertexArrays(1, vao);
+    gl::GenBuffers(1, vbo);
+    gl::BindVertexArray(*vao);
+    gl::BindBuffer(gl::ARRAY_BUFFER, *vbo);
+    let stride = 6 * mem::size_of::<f32>();
+    gl::EnableVertexAttribArray(0);
+    gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride as _, ptr::null());
+    gl::EnableVertexAttribArray(1);
+    gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride as _, (3 * mem::size_of::<f32>()) as _);
+    debug_assert_attribs_enabled(2);
+}
+
+// builds the interleaved position/color data of the grid: divisions + 1 lines along each axis, two
+// vertices per line
+fn grid_geometry(size: f32, divisions: u32) -> Vec<f32> {
+    let divisions = divisions.max(1);
+    let mut vertices = Vec::with_capacity((divisions as usize + 1) * 2 * 2 * 6);
+    for i in 0..=divisions {
+        let offset = -size + 2.0 * size * i as f32 / divisions as f32;
+        // a line along z and one along x through the same offset
+        for [x, z] in [[offset, -size], [offset, size], [-size, offset], [size, offset]] {
+            vertices.extend_from_slice(&[x, 0.0, z]);
+            vertices.extend_from_slice(&GRID_COLOR);
+        }
+    }
+    vertices
+}
+
+// builds the interleaved position/color data of the three axes
+fn axes_geometry(length: f32) -> Vec<f32> {
+    vec![
+        // positions          // colors
+        0.0, 0.0, 0.0,        1.0, 0.0, 0.0,
+        length, 0.0, 0.0,     1.0, 0.0, 0.0,
+        0.0, 0.0, 0.0,        0.0, 1.0, 0.0,
+        0.0, length, 0.0,     0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0,        0.0, 0.0, 1.0,
+        0.0, 0.0, length,     0.0, 0.0, 1.0,
+    ]
+}
+
+// catches copy-pasted attribute setup (e.g. enabling array 0 three times instead of 0, 1 and 2) in debug
+// builds. Checks the currently bound vertex array.
+fn debug_assert_attribs_enabled(count: u32) {
+    if cfg!(debug_assertions) {
+        for location in 0..count {
+            let mut enabled = 0 as GLint;
+            unsafe {
+                gl::GetVertexAttribiv(location, gl::VERTEX_ATTRIB_ARRAY_ENABLED, &mut enabled);
+            }
+            debug_assert_eq!(enabled, gl::TRUE as GLint, "Vertex attribute array {} is not enabled.", location);
+        }
+    }
+}
+
+// forgets every cached vertex array/buffer id so the primitives get rebuilt on their next draw.
+// This must be called after the GL context has been recreated, the old ids don't exist in the new context
+// (and might even alias unrelated objects). The old objects are not deleted here since the context that
+// owned them is usually gone already.
+pub fn reset() {
+    unsafe {
+        CUBE_VAO = 0;
+        CUBE_VBO = 0;
+        CUBE_INSTANCED_VAO = 0;
+        CUBE_INSTANCE_MATRIX_VBO = 0;
+        CUBE_INSTANCE_COLOR_VBO = 0;
+        QUAD_VAO = 0;
+        QUAD_VBO = 0;
+        BILLBOARD_VAO = 0;
+        BILLBOARD_VBO = 0;
+        SPHERE_VAO = 0;
+        SPHERE_VBO = 0;
+        SPHERE_EBO = 0;
+        SPHERE_INDEX_COUNT = 0;
+        PLANE_VAO = 0;
+        PLANE_VBO = 0;
+        PLANE_SIZE = 0.0;
+        PLANE_TEX_REPEAT = 0.0;
+        GRID_VAO = 0;
+        GRID_VBO = 0;
+        GRID_SIZE = 0.0;
+        GRID_DIVISIONS = 0;
+        GRID_VERTEX_COUNT = 0;
+        AXES_VAO = 0;
+        AXES_VBO = 0;
+        AXES_LENGTH = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+
+    #[test]
+    fn sphere_vertices_lie_on_the_unit_sphere() {
+        let (data, _) = sphere_geometry(8, 8);
+        assert_eq!(data.len(), 9 * 9 * 8);
+        for vertex in data.chunks(8) {
+            let length = (vertex[0] * vertex[0] + vertex[1] * vertex[1] + vertex[2] * vertex[2]).sqrt();
+            assert!((length - 1.0).abs() < 1e-5);
+            // the normal of a unit sphere equals its position
+            assert_eq!(&vertex[0..3], &vertex[3..6]);
+            assert!((0.0..=1.0).contains(&vertex[6]) && (0.0..=1.0).contains(&vertex[7]));
+        }
+    }
+
+    #[test]
+    fn sphere_indices_stay_in_range() {
+        let (data, indices) = sphere_geometry(8, 4);
+        // two indices per column for every row of the strip
+        assert_eq!(indices.len(), 4 * 9 * 2);
+        let vertex_count = (data.len() / 8) as u32;
+        assert!(indices.iter().all(|i| *i < vertex_count));
+    }
+
+    #[test]
+    fn plane_has_six_upward_facing_vertices() {
+        let data = plane_geometry(25.0, 25.0);
+        assert_eq!(data.len(), 6 * 8);
+        for vertex in data.chunks(8) {
+            assert_eq!(&vertex[3..6], &[0.0, 1.0, 0.0]);
+            assert_eq!(vertex[1], -0.5);
+            assert_eq!(vertex[0].abs(), 25.0);
+            assert_eq!(vertex[2].abs(), 25.0);
+        }
+    }
+
+    #[test]
+    fn plane_triangles_face_up() {
+        let data = plane_geometry(2.0, 1.0);
+        for triangle in data.chunks(3 * 8) {
+            let corner = |i: usize| glm::vec3(triangle[i * 8], triangle[i * 8 + 1], triangle[i * 8 + 2]);
+            let face_normal = glm::cross(&(corner(1) - corner(0)), &(corner(2) - corner(0)));
+            assert!(face_normal.y > 0.0, "Got {:?}.", face_normal);
+        }
+    }
+
+    #[test]
+    fn plane_uvs_follow_tex_repeat() {
+        let data = plane_geometry(10.0, 4.0);
+        let max_uv = data.chunks(8).flat_map(|vertex| [vertex[6], vertex[7]]).fold(0.0f32, f32::max);
+        assert_eq!(max_uv, 4.0);
+    }
+
+    #[test]
+    fn grid_has_two_lines_per_division_line() {
+        let data = grid_geometry(5.0, 10);
+        assert_eq!(data.len() / 6, (10 + 1) * 2 * 2);
+        for vertex in data.chunks(6) {
+            assert_eq!(vertex[1], 0.0);
+            assert!(vertex[0].abs() <= 5.0 + 1e-5 && vertex[2].abs() <= 5.0 + 1e-5);
+        }
+        assert_eq!(axes_geometry(2.0).len() / 6, 6);
+    }
+
+    #[test]
+    fn billboard_corners_face_the_camera() {
+        let eye = glm::vec3(2.0, 3.0, 4.0);
+        let center = glm::vec3(-1.0, 0.5, 0.0);
+        let view = glm::look_at(&eye, &center, &glm::vec3(0.0, 1.0, 0.0));
+        let data = billboard_geometry(&center, 0.5, &view);
+        let view_direction = glm::normalize(&(center - eye));
+        for vertex in data.chunks(5) {
+            let offset = glm::vec3(vertex[0], vertex[1], vertex[2]) - center;
+            assert!(glm::dot(&offset, &view_direction).abs() < 1e-5, "Corner {:?} is tilted.", offset.as_slice());
+            // half the diagonal of a 0.5 square
+            assert!((glm::length(&offset) - 0.5 * 2f32.sqrt() / 2.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn cube_enables_all_attribute_arrays() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        reset();
+        render_cube();
+        unsafe {
+            gl::BindVertexArray(CUBE_VAO);
+        }
+        for location in 0..3 {
+            let mut enabled = 0 as GLint;
+            unsafe {
+                gl::GetVertexAttribiv(location, gl::VERTEX_ATTRIB_ARRAY_ENABLED, &mut enabled);
+            }
+            assert_eq!(enabled, gl::TRUE as GLint, "Attribute array {} is disabled.", location);
+        }
+        unsafe {
+            gl::BindVertexArray(0);
+        }
+        reset();
+    }
+
+    #[test]
+    fn cube_instances_get_one_color_each() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        reset();
+        let transforms = [glm::translation(&glm::vec3(1.0, 0.0, 0.0)), glm::translation(&glm::vec3(-1.0, 0.0, 0.0)), glm::identity()];
+        let colors = [glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0), glm::vec3(0.0, 0.0, 1.0)];
+        render_cube_instanced(&transforms, &colors);
+
+        let (mut size, mut divisor, mut matrix_divisor) = (0 as GLint, 0 as GLint, 0 as GLint);
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, CUBE_INSTANCE_COLOR_VBO);
+            gl::GetBufferParameteriv(gl::ARRAY_BUFFER, gl::BUFFER_SIZE, &mut size);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(CUBE_INSTANCED_VAO);
+            gl::GetVertexAttribiv(INSTANCE_COLOR_LOCATION, gl::VERTEX_ATTRIB_ARRAY_DIVISOR, &mut divisor);
+            gl::GetVertexAttribiv(INSTANCE_MATRIX_LOCATION, gl::VERTEX_ATTRIB_ARRAY_DIVISOR, &mut matrix_divisor);
+            gl::BindVertexArray(0);
+        }
+        assert_eq!(size as usize, colors.len() * 3 * mem::size_of::<f32>());
+        assert_eq!(divisor, 1);
+        assert_eq!(matrix_divisor, 1);
+        reset();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use crate::light::{DirLight, PointLight, SpotLight, UniformTarget};
+use crate::shader::Shader;
+
+// A set of objects that can be drawn with any shader following the conventional uniform names
+// (`model` plus the `texture_diffuseN`/`texture_specularN` samplers bound by the meshes). Keeping the
+// scene separate from the passes lets the very same geometry feed e.g. a forward shader and a g-buffer.
+pub trait Scene {
+    // draws all objects of the scene with the given (already activated) shader, setting each
+    // object's `model` uniform before its draw call
+    fn draw(&self, shader: &Shader);
+}
+
+// The look of a lit scene: the clear color and the lights of the multiple lights shaders (`dirLight`,
+// `pointLights[i]` and `spotLight`). The point lights only get their colors here, their positions
+// belong to the scene and are passed to `upload_lights`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Environment {
+    pub clear_color: glm::TVec3<f32>,
+    pub dir_light: DirLight,
+    pub point_light_colors: Vec<glm::TVec3<f32>>,
+    // attenuation shared by all point lights
+    pub point_linear: f32,
+    pub point_quadratic: f32,
+    // a flashlight, move it with `SpotLight::follow_camera` before uploading
+    pub spot_light: SpotLight
+}
+
+impl Environment {
+    // hot and dusty: warm sunlight and orange, red and yellow lamps
+    pub fn desert() -> Self {
+        Self {
+            clear_color: glm::vec3(0.75, 0.52, 0.3),
+            dir_light: DirLight {
+                direction: glm::vec3(-0.2, -1.0, -0.3),
+                ambient: glm::vec3(0.3, 0.24, 0.14),
+                diffuse: glm::vec3(0.7, 0.42, 0.26),
+                specular: glm::vec3(0.5, 0.5, 0.5)
+            },
+            point_light_colors: vec![
+                glm::vec3(1.0, 0.6, 0.0),
+                glm::vec3(1.0, 0.0, 0.0),
+                glm::vec3(1.0, 1.0, 0.0),
+                glm::vec3(0.2, 0.2, 1.0)
+            ],
+            point_linear: 0.09,
+            point_quadratic: 0.032,
+            spot_light: SpotLight {
+                diffuse: glm::vec3(0.8, 0.8, 0.0),
+                specular: glm::vec3(0.8, 0.8, 0.0),
+                ..SpotLight::with_cone(12.5, 13.0)
+            }
+        }
+    }
+
+    // dim and bluish with a far reaching flashlight
+    pub fn factory() -> Self {
+        Self {
+            clear_color: glm::vec3(0.1, 0.1, 0.1),
+            dir_light: DirLight {
+                direction: glm::vec3(-0.2, -1.0, -0.3),
+                ambient: glm::vec3(0.05, 0.05, 0.1),
+                diffuse: glm::vec3(0.2, 0.2, 0.7),
+                specular: glm::vec3(0.7, 0.7, 0.7)
+            },
+            point_light_colors: vec![
+                glm::vec3(0.2, 0.2, 0.6),
+                glm::vec3(0.3, 0.3, 0.7),
+                glm::vec3(0.0, 0.0, 0.3),
+                glm::vec3(0.4, 0.4, 0.4)
+            ],
+            point_linear: 0.09,
+            point_quadratic: 0.032,
+            spot_light: SpotLight {
+                linear: 0.009,
+                ..SpotLight::with_cone(10.0, 12.5)
+            }
+        }
+    }
+
+    // pitch black apart from a few weak, short ranged lamps and the flashlight
+    pub fn horror() -> Self {
+        Self {
+            clear_color: glm::vec3(0.0, 0.0, 0.0),
+            dir_light: DirLight {
+                direction: glm::vec3(-0.2, -1.0, -0.3),
+                ambient: glm::vec3(0.0, 0.0, 0.0),
+                diffuse: glm::vec3(0.05, 0.05, 0.05),
+                specular: glm::vec3(0.2, 0.2, 0.2)
+            },
+            point_light_colors: vec![
+                glm::vec3(0.1, 0.1, 0.1),
+                glm::vec3(0.1, 0.1, 0.1),
+                glm::vec3(0.1, 0.1, 0.1),
+                glm::vec3(0.3, 0.1, 0.1)
+            ],
+            point_linear: 0.14,
+            point_quadratic: 0.07,
+            spot_light: SpotLight::with_cone(10.0, 15.0)
+        }
+    }
+
+    // brightly lit biochemical lab with green lamps and a cyan flashlight
+    pub fn lab() -> Self {
+        Self {
+            clear_color: glm::vec3(0.9, 0.9, 0.9),
+            dir_light: DirLight {
+                direction: glm::vec3(-0.2, -1.0, -0.3),
+                ambient: glm::vec3(0.5, 0.5, 0.5),
+                diffuse: glm::vec3(1.0, 1.0, 1.0),
+                specular: glm::vec3(1.0, 1.0, 1.0)
+            },
+            point_light_colors: vec![glm::vec3(0.4, 0.7, 0.1); 4],
+            point_linear: 0.07,
+            point_quadratic: 0.017,
+            spot_light: SpotLight {
+                linear: 0.07,
+                quadratic: 0.017,
+                diffuse: glm::vec3(0.0, 1.0, 1.0),
+                specular: glm::vec3(0.0, 1.0, 1.0),
+                ..SpotLight::with_cone(7.0, 10.0)
+            }
+        }
+    }
+
+    // clears the color (to `clear_color`) and depth buffers
+    pub fn clear(&self) {
+        unsafe {
+            gl::ClearColor(self.clear_color.x, self.clear_color.y, self.clear_color.z, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    // a point light at each of `positions` with the colors in order, as many as there are of both
+    pub fn point_lights(&self, positions: &[glm::TVec3<f32>]) -> Vec<PointLight> {
+        positions.iter().zip(self.point_light_colors.iter())
+            .map(|(position, color)| PointLight {
+                position: *position,
+                constant: 1.0,
+                linear: self.point_linear,
+                quadratic: self.point_quadratic,
+                ambient: color * 0.1,
+                diffuse: *color,
+                specular: *color
+            })
+            .collect()
+    }
+
+    // sets `dirLight`, `pointLights[i]` for the lights at `positions` and `spotLight`
+    pub fn upload_lights<S: UniformTarget + ?Sized>(&self, shader: &S, positions: &[glm::TVec3<f32>]) {
+        self.dir_light.upload(shader, "dirLight");
+        for (i, point_light) in self.point_lights(positions).iter().enumerate() {
+            point_light.upload(shader, &format!("pointLights[{}]", i));
+        }
+        self.spot_light.upload(shader, "spotLight");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn horror_test_no_directional_ambient() {
+        assert_eq!(Environment::horror().dir_light.ambient, glm::vec3(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn point_lights_test_colors_follow_positions() {
+        let environment = Environment::desert();
+        let positions = [glm::vec3(1.0, 2.0, 3.0), glm::vec3(-1.0, 0.0, 0.0)];
+        let lights = environment.point_lights(&positions);
+        assert_eq!(lights.len(), 2);
+        assert_eq!(lights[1].position, positions[1]);
+        assert_eq!(lights[1].diffuse, environment.point_light_colors[1]);
+        assert_eq!(lights[1].ambient, environment.point_light_colors[1] * 0.1);
+        assert_eq!(lights[1].linear, environment.point_linear);
+    }
+}
\ No newline at end of file
@@ -16,71 +16,89 @@
 
 extern crate nalgebra_glm as glm;
 
-use std::ffi::CString;
-use std::{fs, ptr};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::{CStr, CString};
+use std::path::PathBuf;
+use std::{fmt, fs, io, ptr};
+use gl::types::{GLbitfield, GLenum, GLint, GLsizei};
+use crate::filesystem;
 
 pub struct Shader {
-    id: u32
+    id: u32,
+    uniform_cache: UniformLocationCache
 }
 
 impl Shader {
-    // constructor generates the shader on the fly
+    // constructor generates the shader on the fly, aborting on any failure
     // ------------------------------------------------------------------------
     pub fn new(
         vertex_path: String,
         fragment_path: String,
         geometry_path: Option<String>
     ) -> Self {
-        let mut result = Self {
-            id: 0
-        };
+        match Self::try_new(vertex_path, fragment_path, geometry_path) {
+            Ok(shader) => shader,
+            Err(err) => panic!("{}", err)
+        }
+    }
 
+    // same as new, but reports file, compile and link failures to the caller
+    // ------------------------------------------------------------------------
+    pub fn try_new(
+        vertex_path: String,
+        fragment_path: String,
+        geometry_path: Option<String>
+    ) -> Result<Self, ShaderError> {
         // 1. retrieve the vertex/fragment source code from filePath
-        let vertex_code = fs::read_to_string(vertex_path)
-            .expect("ERROR::SHADER::FILE_NOT_SUCCESSFULLY_READ");
-        let fragment_code = fs::read_to_string(fragment_path)
-            .expect("ERROR::SHADER::FILE_NOT_SUCCESSFULLY_READ");
+        let vertex_code = read_source(vertex_path)?;
+        let fragment_code = read_source(fragment_path)?;
         // if geometry shader path is present, also load a geometry shader
         let geometry_code = match geometry_path {
-            Some(geometry_path) => {
-                Some(fs::read(geometry_path)
-                    .expect("ERROR::SHADER::FILE_NOT_SUCCESSFULLY_READ"))
-            }
+            Some(geometry_path) => Some(read_source(geometry_path)?),
             None => None
         };
-        let v_shader_code = CString::new(vertex_code).unwrap();
-        let f_shader_code = CString::new(fragment_code).unwrap();
-        let g_shader_code = match geometry_code {
-            Some(geometry_code) => {
-                Some(CString::new(geometry_code).unwrap())
-            }
-            None => None
+        Self::from_sources(&vertex_code, &fragment_code, geometry_code.as_deref())
+    }
+
+    // builds the program straight from GLSL source strings
+    // ------------------------------------------------------------------------
+    pub fn from_sources(
+        vertex_code: &str,
+        fragment_code: &str,
+        geometry_code: Option<&str>
+    ) -> Result<Self, ShaderError> {
+        let mut result = Self {
+            id: 0,
+            uniform_cache: UniformLocationCache::new()
         };
+
         unsafe {
             // 2. compile shaders
             // vertex shader
-            let vertex = gl::CreateShader(gl::VERTEX_SHADER);
-            gl::ShaderSource(vertex, 1, &v_shader_code.as_ptr(), ptr::null());
-            gl::CompileShader(vertex);
-            Self::check_compile_errors(vertex, "VERTEX");
+            let vertex = Self::compile_stage(gl::VERTEX_SHADER, vertex_code)
+                .map_err(ShaderError::VertexCompile)?;
             // fragment Shader
-            let fragment = gl::CreateShader(gl::FRAGMENT_SHADER);
-            gl::ShaderSource(fragment, 1, &f_shader_code.as_ptr(), ptr::null());
-            gl::CompileShader(fragment);
-            Self::check_compile_errors(fragment, "FRAGMENT");
-            // if geometry shader is given, compile geometry shader
-            let geometry;
-            match g_shader_code {
-                Some(g_shader_code) => {
-                    geometry = Some(gl::CreateShader(gl::GEOMETRY_SHADER));
-                    gl::ShaderSource(geometry.unwrap(), 1, &g_shader_code.as_ptr(), ptr::null());
-                    gl::CompileShader(geometry.unwrap());
-                    Self::check_compile_errors(geometry.unwrap(), "GEOMETRY");
-                }
-                None => {
-                    geometry = None;
+            let fragment = match Self::compile_stage(gl::FRAGMENT_SHADER, fragment_code) {
+                Ok(fragment) => fragment,
+                Err(log) => {
+                    gl::DeleteShader(vertex);
+                    return Err(ShaderError::FragmentCompile(log));
                 }
-            }
+            };
+            // if geometry shader is given, compile geometry shader
+            let geometry = match geometry_code {
+                Some(geometry_code) => match Self::compile_stage(gl::GEOMETRY_SHADER, geometry_code) {
+                    Ok(geometry) => Some(geometry),
+                    Err(log) => {
+                        gl::DeleteShader(vertex);
+                        gl::DeleteShader(fragment);
+                        return Err(ShaderError::GeometryCompile(log));
+                    }
+                },
+                None => None
+            };
             // shader Program
             result.id = gl::CreateProgram();
             gl::AttachShader(result.id, vertex);
@@ -89,16 +107,50 @@ impl Shader {
                 gl::AttachShader(result.id, geometry);
             }
             gl::LinkProgram(result.id);
-            Self::check_compile_errors(result.id, "PROGRAM");
+            let link_status = Self::check_link_errors(result.id);
             // delete the shaders as they're linked into our program now and no longer necessary
             gl::DeleteShader(vertex);
             gl::DeleteShader(fragment);
             if let Some(geometry) = geometry {
                 gl::DeleteShader(geometry);
             }
+            // dropping result on failure deletes the program as well
+            link_status.map_err(ShaderError::Link)?;
         }
 
-        result
+        Ok(result)
+    }
+
+    // constructor for a compute-only program, aborting on any failure
+    // ------------------------------------------------------------------------
+    pub fn new_compute(compute_path: String) -> Self {
+        match Self::try_new_compute(compute_path) {
+            Ok(shader) => shader,
+            Err(err) => panic!("{}", err)
+        }
+    }
+
+    // compute shaders require an OpenGL 4.3 context
+    // ------------------------------------------------------------------------
+    pub fn try_new_compute(compute_path: String) -> Result<Self, ShaderError> {
+        let compute_code = read_source(compute_path)?;
+        let mut result = Self {
+            id: 0,
+            uniform_cache: UniformLocationCache::new()
+        };
+
+        unsafe {
+            let compute = Self::compile_stage(gl::COMPUTE_SHADER, &compute_code)
+                .map_err(ShaderError::ComputeCompile)?;
+            result.id = gl::CreateProgram();
+            gl::AttachShader(result.id, compute);
+            gl::LinkProgram(result.id);
+            let link_status = Self::check_link_errors(result.id);
+            gl::DeleteShader(compute);
+            link_status.map_err(ShaderError::Link)?;
+        }
+
+        Ok(result)
     }
 
     // activate the shader
@@ -109,136 +161,184 @@ impl Shader {
         }
     }
 
+    // launch the compute program over the given number of work groups; the
+    // program has to be in use (see use_shader) before dispatching
+    // ------------------------------------------------------------------------
+    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
+        unsafe {
+            gl::DispatchCompute(x, y, z);
+        }
+    }
+
+    // make the writes of a previous dispatch visible to the given kinds of access
+    // ------------------------------------------------------------------------
+    pub fn memory_barrier(bits: GLbitfield) {
+        unsafe {
+            gl::MemoryBarrier(bits);
+        }
+    }
+
+    // uniform locations are looked up once per name and cached afterwards; the
+    // cache has to be cleared whenever the program gets relinked
+    // ------------------------------------------------------------------------
+    pub fn clear_uniform_cache(&self) {
+        self.uniform_cache.clear();
+    }
+
+    fn uniform_location(&self, name: String) -> GLint {
+        self.uniform_cache.location(name, |name_c_str| unsafe {
+            gl::GetUniformLocation(self.id, name_c_str.as_ptr())
+        })
+    }
+
     // utility uniform functions
     // ------------------------------------------------------------------------
     pub fn set_bool(&self, name: String, value: bool) {
         let v = if value { 1i32 } else { 0 };
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform1i(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), v);
+            gl::Uniform1i(self.uniform_location(name), v);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_int(&self, name: String, value: i32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform1i(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), value);
+            gl::Uniform1i(self.uniform_location(name), value);
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn set_uint(&self, name: String, value: u32) {
+        unsafe {
+            gl::Uniform1ui(self.uniform_location(name), value);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_float(&self, name: String, value: f32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform1f(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), value);
+            gl::Uniform1f(self.uniform_location(name), value);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_vec2(&self, name: String, value: &glm::TVec2<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform2fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::Uniform2fv(self.uniform_location(name),
                            1, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_vec2_coords(&self, name: String, x: f32, y: f32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform2f(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), x, y);
+            gl::Uniform2f(self.uniform_location(name), x, y);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_vec3(&self, name: String, value: &glm::TVec3<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform3fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::Uniform3fv(self.uniform_location(name),
                            1, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_vec3_coords(&self, name: String, x: f32, y: f32, z: f32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform3f(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), x, y, z);
+            gl::Uniform3f(self.uniform_location(name), x, y, z);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_vec4(&self, name: String, value: &glm::TVec4<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform4fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::Uniform4fv(self.uniform_location(name),
                            1, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_vec4_coords(&self, name: String, x: f32, y: f32, z: f32, w: f32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform4f(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), x, y, z, w);
+            gl::Uniform4f(self.uniform_location(name), x, y, z, w);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_mat2(&self, name: String, value: &glm::TMat2<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::UniformMatrix2fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::UniformMatrix2fv(self.uniform_location(name),
                                  1, gl::FALSE, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_mat3(&self, name: String, value: &glm::TMat3<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::UniformMatrix3fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::UniformMatrix3fv(self.uniform_location(name),
                                  1, gl::FALSE, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_mat4(&self, name: String, value: &glm::TMat4<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::UniformMatrix4fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::UniformMatrix4fv(self.uniform_location(name),
                                  1, gl::FALSE, &glm::value_ptr(value)[0]);
         }
     }
 
-    fn check_compile_errors(id: u32, type_str: &str) {
-        let type_str = String::from(type_str);
-        let mut success = 0i32;
-        let mut info_log = [0i8; 1024];
-        unsafe {
-            if type_str == "PROGRAM" {
-                gl::GetProgramiv(id, gl::LINK_STATUS, &mut success);
-                if success == 0 {
-                    gl::GetProgramInfoLog(id, 1024, ptr::null_mut(), &mut info_log as *mut _);
-                    let info_log_vec: Vec<_> = Vec::from(info_log).iter().map(|it| *it as u8).collect();
-                    println!(
-                        "ERROR::PROGRAM_LINKING_ERROR of type: {}\n{}\n -- --------------------------------------------------- -- ",
-                        type_str,
-                        String::from_utf8(info_log_vec).unwrap()
-                    );
-                }
-            } else {
-                gl::GetShaderiv(id, gl::COMPILE_STATUS, &mut success);
-                if success == 0 {
-                    gl::GetShaderInfoLog(id, 1024, ptr::null_mut(), &mut info_log as *mut _);
-                    let info_log_vec: Vec<_> = Vec::from(info_log).iter().map(|it| *it as u8).collect();
-                    println!(
-                        "ERROR::SHADER_COMPILATION_ERROR of type: {}\n{}\n -- --------------------------------------------------- -- ",
-                        type_str,
-                        String::from_utf8(info_log_vec).unwrap()
-                    );
-                }
+    // uploads a whole `mat4` array (e.g. "shadowMatrices") starting at its first element with a
+    // single call, instead of one lookup per "name[i]"
+    pub fn set_mat4_array(&self, name: String, values: &[glm::TMat4<f32>]) {
+        if values.is_empty() {
+            return;
+        }
+        // nalgebra stores matrices as column major [f32; 16], so a slice of them is one contiguous
+        // float array already
+        unsafe {
+            gl::UniformMatrix4fv(self.uniform_location(name),
+                                 values.len() as GLsizei, gl::FALSE, values.as_ptr() as *const f32);
+        }
+    }
+
+    // links the uniform block `name` to the uniform buffer binding point `binding` (e.g. the one of
+    // `ubo::MatricesUbo`). Ignored if the program has no such (active) block.
+    pub fn bind_uniform_block(&self, name: &str, binding: u32) {
+        let c_str = CString::new(name).unwrap();
+        unsafe {
+            let index = gl::GetUniformBlockIndex(self.id, c_str.as_ptr());
+            if index != gl::INVALID_INDEX {
+                gl::UniformBlockBinding(self.id, index, binding);
             }
         }
     }
 
+    // compiles a single stage, handing back the info log if compilation failed
+    unsafe fn compile_stage(stage: GLenum, code: &str) -> Result<u32, String> {
+        let code = CString::new(code).map_err(|err| err.to_string())?;
+        let shader = gl::CreateShader(stage);
+        gl::ShaderSource(shader, 1, &code.as_ptr(), ptr::null());
+        gl::CompileShader(shader);
+        let mut success = 0i32;
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+        if success == 0 {
+            let mut info_log = [0i8; 1024];
+            gl::GetShaderInfoLog(shader, 1024, ptr::null_mut(), &mut info_log as *mut _);
+            gl::DeleteShader(shader);
+            return Err(info_log_to_string(&info_log));
+        }
+        Ok(shader)
+    }
+
+    unsafe fn check_link_errors(program: u32) -> Result<(), String> {
+        let mut success = 0i32;
+        gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
+        if success == 0 {
+            let mut info_log = [0i8; 1024];
+            gl::GetProgramInfoLog(program, 1024, ptr::null_mut(), &mut info_log as *mut _);
+            return Err(info_log_to_string(&info_log));
+        }
+        Ok(())
+    }
+
     pub fn id(&self) -> u32 {
         self.id
     }
@@ -250,4 +350,343 @@ impl Drop for Shader {
             gl::DeleteProgram(self.id);
         }
     }
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    FileRead(io::Error, PathBuf),
+    VertexCompile(String),
+    FragmentCompile(String),
+    GeometryCompile(String),
+    ComputeCompile(String),
+    Link(String),
+    // the chain of `#include`s that leads back to a file already being included
+    IncludeCycle(Vec<String>)
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileRead(err, path) => write!(f, "ERROR::SHADER::FILE_NOT_SUCCESSFULLY_READ: {}: {}", path.display(), err),
+            Self::VertexCompile(log) => write!(f, "ERROR::SHADER_COMPILATION_ERROR of type: VERTEX\n{}", log),
+            Self::FragmentCompile(log) => write!(f, "ERROR::SHADER_COMPILATION_ERROR of type: FRAGMENT\n{}", log),
+            Self::GeometryCompile(log) => write!(f, "ERROR::SHADER_COMPILATION_ERROR of type: GEOMETRY\n{}", log),
+            Self::ComputeCompile(log) => write!(f, "ERROR::SHADER_COMPILATION_ERROR of type: COMPUTE\n{}", log),
+            Self::Link(log) => write!(f, "ERROR::PROGRAM_LINKING_ERROR of type: PROGRAM\n{}", log),
+            Self::IncludeCycle(chain) => write!(f, "ERROR::SHADER::INCLUDE_CYCLE: {}", chain.join(" -> "))
+        }
+    }
+}
+
+impl Error for ShaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::FileRead(err, _) => Some(err),
+            _ => None
+        }
+    }
+}
+
+fn read_source(path: String) -> Result<String, ShaderError> {
+    let code = read_file(path)?;
+    resolve_includes(&code, |include| read_file(filesystem::get_path(include.to_string())))
+}
+
+fn read_file(path: String) -> Result<String, ShaderError> {
+    fs::read_to_string(&path).map_err(|err| ShaderError::FileRead(err, PathBuf::from(path)))
+}
+
+// GLSL has no includes, so every `#include "path"` line is replaced by what `load` returns for the
+// path (relative to the repository root when loading files), e.g. the snippets in resources/shaders/include.
+// Included files may include others; a file that (indirectly) includes itself is an IncludeCycle error.
+fn resolve_includes<F: Fn(&str) -> Result<String, ShaderError>>(code: &str, load: F) -> Result<String, ShaderError> {
+    resolve_includes_nested(code, &load, &mut Vec::new())
+}
+
+// `stack` holds the includes currently being resolved, outermost first
+fn resolve_includes_nested<F: Fn(&str) -> Result<String, ShaderError>>(code: &str, load: &F, stack: &mut Vec<String>) -> Result<String, ShaderError> {
+    let mut resolved = String::with_capacity(code.len());
+    for line in code.lines() {
+        match line.trim().strip_prefix("#include") {
+            Some(include) => {
+                let include = include.trim().trim_matches('"').to_string();
+                if stack.contains(&include) {
+                    let mut chain = stack.clone();
+                    chain.push(include);
+                    return Err(ShaderError::IncludeCycle(chain));
+                }
+                let source = load(&include)?;
+                stack.push(include);
+                let nested = resolve_includes_nested(&source, load, stack)?;
+                stack.pop();
+                resolved.push_str(nested.strip_suffix('\n').unwrap_or(&nested));
+            }
+            None => resolved.push_str(line)
+        }
+        resolved.push('\n');
+    }
+    Ok(resolved)
+}
+
+// GL info logs are NUL terminated C strings
+fn info_log_to_string(info_log: &[i8]) -> String {
+    let bytes: Vec<u8> = info_log.iter()
+        .take_while(|it| **it != 0)
+        .map(|it| *it as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).trim_end().to_string()
+}
+
+// maps uniform names to their locations so glGetUniformLocation only runs on the first lookup
+pub(crate) struct UniformLocationCache {
+    locations: RefCell<HashMap<String, GLint>>
+}
+
+impl UniformLocationCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            locations: RefCell::new(HashMap::new())
+        }
+    }
+
+    pub(crate) fn location<F: FnOnce(&CStr) -> GLint>(&self, name: String, lookup: F) -> GLint {
+        if let Some(location) = self.locations.borrow().get(&name) {
+            return *location;
+        }
+        let name_c_str = CString::new(name.as_str()).unwrap();
+        let location = lookup(&name_c_str);
+        self.locations.borrow_mut().insert(name, location);
+        location
+    }
+
+    pub(crate) fn clear(&self) {
+        self.locations.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use super::*;
+
+    #[test]
+    fn missing_shader_file_is_reported() {
+        let result = Shader::try_new(
+            "does/not/exist.vs".to_string(),
+            "does/not/exist.fs".to_string(),
+            None
+        );
+        match result {
+            Err(ShaderError::FileRead(err, path)) => {
+                assert_eq!(err.kind(), io::ErrorKind::NotFound);
+                assert_eq!(path, PathBuf::from("does/not/exist.vs"));
+            }
+            _ => panic!("expected a FileRead error")
+        }
+    }
+
+    #[test]
+    fn includes_are_replaced_by_their_source() {
+        let code = "#version 330 core\n#include \"resources/shaders/include/depth.glsl\"\nvoid main() {}";
+        let resolved = resolve_includes(code, |path| {
+            assert_eq!(path, "resources/shaders/include/depth.glsl");
+            Ok("float f() { return 1.0; }".to_string())
+        }).unwrap();
+        assert_eq!(resolved, "#version 330 core\nfloat f() { return 1.0; }\nvoid main() {}\n");
+    }
+
+    #[test]
+    fn missing_include_is_reported() {
+        let result = resolve_includes("#include \"missing.glsl\"", |path| {
+            Err(ShaderError::FileRead(io::Error::from(io::ErrorKind::NotFound), PathBuf::from(path)))
+        });
+        assert!(matches!(result, Err(ShaderError::FileRead(_, path)) if path == PathBuf::from("missing.glsl")));
+    }
+
+    #[test]
+    fn include_cycle_is_reported() {
+        // a.glsl includes b.glsl which includes a.glsl again, c.glsl includes itself
+        let load = |path: &str| Ok(match path {
+            "a.glsl" => "#include \"b.glsl\"",
+            "b.glsl" => "#include \"a.glsl\"",
+            _ => "#include \"c.glsl\""
+        }.to_string());
+        let result = resolve_includes("#include \"a.glsl\"", load);
+        assert!(matches!(result, Err(ShaderError::IncludeCycle(chain)) if chain == ["a.glsl", "b.glsl", "a.glsl"]));
+        let result = resolve_includes("#include \"c.glsl\"", load);
+        assert!(matches!(result, Err(ShaderError::IncludeCycle(chain)) if chain == ["c.glsl", "c.glsl"]));
+    }
+
+    #[test]
+    fn nested_includes_are_resolved() {
+        let resolved = resolve_includes("#include \"outer.glsl\"\nvoid main() {}", |path| Ok(match path {
+            "outer.glsl" => "#include \"inner.glsl\"\nfloat g() { return f(); }\n",
+            _ => "float f() { return 1.0; }"
+        }.to_string())).unwrap();
+        assert_eq!(resolved, "float f() { return 1.0; }\nfloat g() { return f(); }\nvoid main() {}\n");
+    }
+
+    #[test]
+    fn info_log_stops_at_nul() {
+        let mut info_log = [0i8; 16];
+        for (i, b) in b"0:1: error\n".iter().enumerate() {
+            info_log[i] = *b as i8;
+        }
+        info_log[12] = b'x' as i8;
+        assert_eq!(info_log_to_string(&info_log), "0:1: error");
+    }
+
+    #[test]
+    fn uniform_location_is_looked_up_once() {
+        let cache = UniformLocationCache::new();
+        let calls = Cell::new(0);
+        for _ in 0..1000 {
+            let location = cache.location("model".to_string(), |_| {
+                calls.set(calls.get() + 1);
+                7
+            });
+            assert_eq!(location, 7);
+        }
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn clearing_the_cache_forces_a_new_lookup() {
+        let cache = UniformLocationCache::new();
+        let calls = Cell::new(0);
+        let lookup = || cache.location("view".to_string(), |name| {
+            assert_eq!(name.to_str().unwrap(), "view");
+            calls.set(calls.get() + 1);
+            3
+        });
+        lookup();
+        cache.clear();
+        lookup();
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn broken_fragment_shader_is_reported() {
+        let Some(_context) = crate::gl_test::hidden_gl_context(1, 1) else {
+            return;
+        };
+        const VS: &str = "#version 330 core\nvoid main() { gl_Position = vec4(0.0, 0.0, 0.0, 1.0); }";
+        const FS: &str = "#version 330 core\nout vec4 FragColor;\nvoid main() { FragColor = undeclared; }";
+        match Shader::from_sources(VS, FS, None) {
+            Err(ShaderError::FragmentCompile(log)) => assert!(!log.is_empty(), "The info log should explain the error."),
+            Err(err) => panic!("expected a FragmentCompile error, got {}", err),
+            Ok(_) => panic!("expected a FragmentCompile error")
+        }
+    }
+
+    #[test]
+    fn set_bool_test_readback() {
+        let Some(_context) = crate::gl_test::hidden_gl_context(1, 1) else {
+            return;
+        };
+        const VS: &str = "#version 330 core\nvoid main() { gl_Position = vec4(0.0, 0.0, 0.0, 1.0); }";
+        const FS: &str = "#version 330 core
+out vec4 FragColor;
+uniform bool flag;
+void main() {
+    FragColor = flag ? vec4(1.0) : vec4(0.0);
+}";
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        shader.use_shader();
+        let name = CString::new("flag").unwrap();
+        let location = unsafe { gl::GetUniformLocation(shader.id(), name.as_ptr()) };
+        assert_ne!(location, -1);
+        for value in [true, false] {
+            shader.set_bool("flag".to_string(), value);
+            let mut read = -1;
+            unsafe {
+                gl::GetUniformiv(shader.id(), location, &mut read);
+            }
+            assert_eq!(read, value as i32);
+        }
+    }
+
+    #[test]
+    fn drop_test_deletes_program() {
+        let Some(_context) = crate::gl_test::hidden_gl_context(1, 1) else {
+            return;
+        };
+        const VS: &str = "#version 330 core\nvoid main() { gl_Position = vec4(0.0, 0.0, 0.0, 1.0); }";
+        const FS: &str = "#version 330 core\nout vec4 FragColor;\nvoid main() { FragColor = vec4(1.0); }";
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        let id = shader.id();
+        unsafe {
+            assert_eq!(gl::IsProgram(id), gl::TRUE);
+        }
+        drop(shader);
+        unsafe {
+            assert_eq!(gl::IsProgram(id), gl::FALSE);
+        }
+    }
+
+    #[test]
+    fn drop_test_debug_output_reports_deleted_program() {
+        let Some(_context) = crate::gl_test::hidden_gl_debug_context(1, 1) else {
+            return;
+        };
+        const VS: &str = "#version 330 core\nvoid main() { gl_Position = vec4(0.0, 0.0, 0.0, 1.0); }";
+        const FS: &str = "#version 330 core\nout vec4 FragColor;\nvoid main() { FragColor = vec4(1.0); }";
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        let id = shader.id();
+        drop(shader);
+        unsafe {
+            let mut flags = 0i32;
+            gl::GetIntegerv(gl::CONTEXT_FLAGS, &mut flags);
+            if flags & gl::CONTEXT_FLAG_DEBUG_BIT as i32 == 0 {
+                return;
+            }
+            // without a callback the messages are kept in the context's log
+            gl::Enable(gl::DEBUG_OUTPUT);
+            gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+            gl::DebugMessageControl(gl::DONT_CARE, gl::DONT_CARE, gl::DONT_CARE, 0, ptr::null(), gl::TRUE);
+            while gl::GetDebugMessageLog(1, 0, ptr::null_mut(), ptr::null_mut(), ptr::null_mut(),
+                                         ptr::null_mut(), ptr::null_mut(), ptr::null_mut()) > 0 {}
+
+            // using the id of a deleted program is an error the debug output has to report
+            gl::UseProgram(id);
+            let mut gltype = 0;
+            let count = gl::GetDebugMessageLog(1, 0, ptr::null_mut(), &mut gltype, ptr::null_mut(),
+                                               ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+            crate::debug::take_gl_errors();
+            assert_eq!(count, 1, "Using a deleted program should be reported by the debug output.");
+            assert_eq!(gltype, gl::DEBUG_TYPE_ERROR);
+        }
+    }
+
+    #[test]
+    fn set_mat4_array_test_ordering() {
+        let Some(_context) = crate::gl_test::hidden_gl_context(1, 1) else {
+            return;
+        };
+        const VS: &str = "#version 330 core
+uniform mat4 matrices[4];
+void main() {
+    gl_Position = matrices[0] * matrices[1] * matrices[2] * matrices[3] * vec4(0.0, 0.0, 0.0, 1.0);
+}";
+        const FS: &str = "#version 330 core
+out vec4 FragColor;
+void main() {
+    FragColor = vec4(1.0);
+}";
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        let matrices: Vec<glm::TMat4<f32>> = (0..4)
+            .map(|i| glm::translation(&glm::vec3(i as f32, 2.0 * i as f32, 3.0 * i as f32)))
+            .collect();
+        shader.use_shader();
+        shader.set_mat4_array("matrices".to_string(), &matrices);
+
+        let mut third = [0f32; 16];
+        unsafe {
+            let name = CString::new("matrices[2]").unwrap();
+            let location = gl::GetUniformLocation(shader.id(), name.as_ptr());
+            assert_ne!(location, -1);
+            gl::GetUniformfv(shader.id(), location, third.as_mut_ptr());
+        }
+        assert_eq!(&third[..], matrices[2].as_slice());
+    }
 }
\ No newline at end of file
@@ -18,9 +18,12 @@ extern crate nalgebra_glm as glm;
 
 use std::ffi::CString;
 use std::{fs, ptr};
+use gl::types::GLint;
+use crate::shader::UniformLocationCache;
 
 pub struct Shader {
-    id: u32
+    id: u32,
+    uniform_cache: UniformLocationCache
 }
 
 impl Shader {
@@ -28,7 +31,8 @@ impl Shader {
     // ------------------------------------------------------------------------
     pub fn new(vertex_path: String, fragment_path: String) -> Self {
         let mut result = Self {
-            id: 0
+            id: 0,
+            uniform_cache: UniformLocationCache::new()
         };
 
         // 1. retrieve the vertex/fragment source code from filePath
@@ -72,105 +76,125 @@ impl Shader {
         }
     }
 
+    // uniform locations are looked up once per name and cached afterwards; the
+    // cache has to be cleared whenever the program gets relinked
+    // ------------------------------------------------------------------------
+    pub fn clear_uniform_cache(&self) {
+        self.uniform_cache.clear();
+    }
+
+    fn uniform_location(&self, name: String) -> GLint {
+        self.uniform_cache.location(name, |name_c_str| unsafe {
+            gl::GetUniformLocation(self.id, name_c_str.as_ptr())
+        })
+    }
+
     // utility uniform functions
     // ------------------------------------------------------------------------
     pub fn set_bool(&self, name: String, value: bool) {
         let v = if value { 1i32 } else { 0 };
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform1i(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), v);
+            gl::Uniform1i(self.uniform_location(name), v);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_int(&self, name: String, value: i32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform1i(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), value);
+            gl::Uniform1i(self.uniform_location(name), value);
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    pub fn set_uint(&self, name: String, value: u32) {
+        unsafe {
+            gl::Uniform1ui(self.uniform_location(name), value);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_float(&self, name: String, value: f32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform1f(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), value);
+            gl::Uniform1f(self.uniform_location(name), value);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_vec2(&self, name: String, value: &glm::TVec2<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform2fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::Uniform2fv(self.uniform_location(name),
                            1, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_vec2_coords(&self, name: String, x: f32, y: f32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform2f(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), x, y);
+            gl::Uniform2f(self.uniform_location(name), x, y);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_vec3(&self, name: String, value: &glm::TVec3<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform3fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::Uniform3fv(self.uniform_location(name),
                            1, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_vec3_coords(&self, name: String, x: f32, y: f32, z: f32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform3f(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), x, y, z);
+            gl::Uniform3f(self.uniform_location(name), x, y, z);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_vec4(&self, name: String, value: &glm::TVec4<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform4fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::Uniform4fv(self.uniform_location(name),
                            1, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_vec4_coords(&self, name: String, x: f32, y: f32, z: f32, w: f32) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::Uniform4f(gl::GetUniformLocation(self.id, name_c_str.as_ptr()), x, y, z, w);
+            gl::Uniform4f(self.uniform_location(name), x, y, z, w);
         }
     }
 
     // ------------------------------------------------------------------------
     pub fn set_mat2(&self, name: String, value: &glm::TMat2<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::UniformMatrix2fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::UniformMatrix2fv(self.uniform_location(name),
                                  1, gl::FALSE, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_mat3(&self, name: String, value: &glm::TMat3<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::UniformMatrix3fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::UniformMatrix3fv(self.uniform_location(name),
                                  1, gl::FALSE, &glm::value_ptr(value)[0]);
         }
     }
 
     pub fn set_mat4(&self, name: String, value: &glm::TMat4<f32>) {
-        let name_c_str = CString::new(name).unwrap();
         unsafe {
-            gl::UniformMatrix4fv(gl::GetUniformLocation(self.id, name_c_str.as_ptr()),
+            gl::UniformMatrix4fv(self.uniform_location(name),
                                  1, gl::FALSE, &glm::value_ptr(value)[0]);
         }
     }
 
+    // links the uniform block `name` to the uniform buffer binding point `binding` (e.g. the one of
+    // `ubo::MatricesUbo`). Ignored if the program has no such (active) block.
+    pub fn bind_uniform_block(&self, name: &str, binding: u32) {
+        let c_str = CString::new(name).unwrap();
+        unsafe {
+            let index = gl::GetUniformBlockIndex(self.id, c_str.as_ptr());
+            if index != gl::INVALID_INDEX {
+                gl::UniformBlockBinding(self.id, index, binding);
+            }
+        }
+    }
+
     fn check_compile_errors(id: u32, type_str: &str) {
         let type_str = String::from(type_str);
         let mut success = 0i32;
@@ -0,0 +1,346 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+extern crate nalgebra_glm as glm;
+
+use std::ptr;
+use gl::types::*;
+use crate::shader::Shader;
+use crate::util;
+
+// the most cascades the shadow shaders declare uniform arrays for
+pub const MAX_CASCADES: usize = 8;
+// how far the light frustum of a cascade is extended towards (and away from) the light, relative to the
+// depth of the slice, so casters outside of the slice still cast their shadows into it
+const CASCADE_CASTER_MARGIN: f32 = 2.0;
+
+// A value of one of the uniforms ShadowParams sets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformValue {
+    Float(f32),
+    Int(i32)
+}
+
+// The depth comparison settings of the shadow mapping shaders, instead of constants baked into GLSL.
+// A fragment is compared against the depth map with the bias max(normal_bias * (1 - dot(N, L)), bias),
+// so surfaces facing away from the light (which suffer most from acne) get up to `normal_bias`.
+// Too little bias shows shadow acne, too much detaches the shadows from their casters (peter-panning).
+// `pcf_radius` is the number of texels sampled on each side of the fragment, 0 is a single hard sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowParams {
+    pub bias: f32,
+    pub normal_bias: f32,
+    pub pcf_radius: i32
+}
+
+impl ShadowParams {
+    // what the directional shadow mapping chapter uses: a slope scaled bias in [0.005, 0.05], 3x3 PCF
+    pub fn directional() -> Self {
+        Self {
+            bias: 0.005,
+            normal_bias: 0.05,
+            pcf_radius: 1
+        }
+    }
+
+    // what the point shadows chapter uses: a constant bias in world units (the cubemap stores
+    // distances in [near_plane, far_plane]) and a single sample
+    pub fn point() -> Self {
+        Self {
+            bias: 0.05,
+            normal_bias: 0.0,
+            pcf_radius: 0
+        }
+    }
+
+    // the uniforms `upload` sets, in order
+    pub fn uniforms(&self) -> [(&'static str, UniformValue); 3] {
+        [
+            ("shadowBias", UniformValue::Float(self.bias)),
+            ("shadowNormalBias", UniformValue::Float(self.normal_bias)),
+            ("pcfRadius", UniformValue::Int(self.pcf_radius))
+        ]
+    }
+
+    pub fn upload(&self, shader: &Shader) {
+        for (name, value) in self.uniforms() {
+            match value {
+                UniformValue::Float(value) => shader.set_float(name.to_string(), value),
+                UniformValue::Int(value) => shader.set_int(name.to_string(), value)
+            }
+        }
+    }
+}
+
+impl Default for ShadowParams {
+    fn default() -> Self {
+        Self::directional()
+    }
+}
+
+// Cascaded shadow maps for a directional light: the camera frustum is cut into `cascade_count` slices
+// along the view direction, each covered by its own tightly fitted orthographic light frustum and its
+// own layer of a depth texture array. Slices close to the camera are short, so nearby shadows get much
+// more resolution than a single map spanning the whole scene could give them.
+pub struct CascadedShadowMap {
+    fbo: u32,
+    depth_texture: u32,
+    resolution: u32,
+    cascade_count: usize,
+    split_lambda: f32,
+    splits: Vec<f32>,
+    light_space_matrices: Vec<glm::TMat4<f32>>
+}
+
+impl CascadedShadowMap {
+    // weight of the logarithmic split scheme against uniform splits, see `cascade_splits`
+    pub const SPLIT_LAMBDA: f32 = 0.9;
+
+    pub fn new(cascade_count: usize, resolution: u32) -> Self {
+        assert!((1..=MAX_CASCADES).contains(&cascade_count), "Between 1 and {} cascades are supported.", MAX_CASCADES);
+        let mut fbo = 0u32;
+        let mut depth_texture = 0u32;
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            // one depth layer per cascade
+            gl::GenTextures(1, &mut depth_texture);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, depth_texture);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY, 0, gl::DEPTH_COMPONENT32F as _,
+                resolution as _, resolution as _, cascade_count as _,
+                0, gl::DEPTH_COMPONENT, gl::FLOAT, ptr::null()
+            );
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+            // everything outside of a cascade's map counts as lit
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as _);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as _);
+            let border_color = [1f32, 1.0, 1.0, 1.0];
+            gl::TexParameterfv(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_BORDER_COLOR, border_color.as_ptr());
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, depth_texture, 0, 0);
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self {
+            fbo,
+            depth_texture,
+            resolution,
+            cascade_count,
+            split_lambda: Self::SPLIT_LAMBDA,
+            splits: Vec::new(),
+            light_space_matrices: Vec::new()
+        }
+    }
+
+    // fits the cascades to the camera: `projection` and `view` are the camera's matrices, `near` and
+    // `far` the clip planes of `projection`, `light_dir` the direction the light shines in
+    pub fn update(
+        &mut self,
+        projection: &glm::TMat4<f32>,
+        view: &glm::TMat4<f32>,
+        near: f32,
+        far: f32,
+        light_dir: &glm::TVec3<f32>
+    ) {
+        self.splits = cascade_splits(near, far, self.cascade_count, self.split_lambda);
+        let corners = frustum_corners_world(projection, view);
+        self.light_space_matrices = self.splits.windows(2)
+            .map(|split| {
+                // the edges of the frustum run from the near to the far corners, linear in view depth
+                let (from, to) = ((split[0] - near) / (far - near), (split[1] - near) / (far - near));
+                let mut slice = corners;
+                for i in 0..4 {
+                    let (near_corner, far_corner) = (corners[i], corners[i + 4]);
+                    slice[i] = near_corner + (far_corner - near_corner) * from;
+                    slice[i + 4] = near_corner + (far_corner - near_corner) * to;
+                }
+                cascade_light_space_matrix(&slice, light_dir)
+            })
+            .collect();
+    }
+
+    // renders the depth of every cascade: `draw_scene` is called once per cascade with its light space
+    // matrix, after the layer has been bound and cleared. The bound framebuffer and the viewport are
+    // restored afterwards.
+    pub fn render(&self, draw_scene: impl Fn(&glm::TMat4<f32>)) {
+        unsafe {
+            let mut previous_fbo = 0 as GLint;
+            let mut previous_viewport = [0 as GLint; 4];
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_fbo);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, self.resolution as _, self.resolution as _);
+            for (i, light_space_matrix) in self.light_space_matrices.iter().enumerate() {
+                gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, self.depth_texture, 0, i as _);
+                gl::Clear(gl::DEPTH_BUFFER_BIT);
+                draw_scene(light_space_matrix);
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_fbo as _);
+            gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        }
+    }
+
+    // sets `cascadeCount`, `lightSpaceMatrices[i]` and `cascadePlaneDistances[i]` (the far end of each
+    // cascade in view space) of the shader sampling the cascades
+    pub fn upload(&self, shader: &Shader) {
+        shader.set_int("cascadeCount".to_string(), self.light_space_matrices.len() as i32);
+        for (i, light_space_matrix) in self.light_space_matrices.iter().enumerate() {
+            shader.set_mat4(format!("lightSpaceMatrices[{}]", i), light_space_matrix);
+            shader.set_float(format!("cascadePlaneDistances[{}]", i), self.splits[i + 1]);
+        }
+    }
+
+    // one matrix per cascade, from the nearest to the farthest, empty until `update` is called
+    pub fn light_space_matrices(&self) -> &[glm::TMat4<f32>] {
+        &self.light_space_matrices
+    }
+
+    // the view space distances the cascades start and end at: near plane, the boundaries between
+    // cascades and far plane (cascade_count + 1 values), empty until `update` is called
+    pub fn cascade_splits(&self) -> &[f32] {
+        &self.splits
+    }
+
+    // the depth texture array (GL_TEXTURE_2D_ARRAY) with one layer per cascade
+    pub fn depth_texture(&self) -> u32 {
+        self.depth_texture
+    }
+
+    pub fn cascade_count(&self) -> usize {
+        self.cascade_count
+    }
+
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    pub fn split_lambda(&self) -> f32 {
+        self.split_lambda
+    }
+
+    pub fn set_split_lambda(&mut self, split_lambda: f32) {
+        self.split_lambda = split_lambda;
+    }
+}
+
+impl Drop for CascadedShadowMap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteTextures(1, &self.depth_texture);
+        }
+    }
+}
+
+// the distances from `near` to `far` (count + 1 values) the camera frustum is split at. Logarithmic
+// splits keep the ratio of the cascades' depths and their distance constant, uniform ones their depths;
+// `lambda` blends them (1.0 is purely logarithmic), as the log scheme alone makes the first cascades tiny.
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    (0..=count)
+        .map(|i| {
+            let fraction = i as f32 / count as f32;
+            let logarithmic = near * (far / near).powf(fraction);
+            let uniform = near + (far - near) * fraction;
+            util::glm::lerp(uniform, logarithmic, lambda)
+        })
+        .collect()
+}
+
+// the corners of the frustum of `projection` and `view` in world space: the four on the near plane
+// first, then the matching four on the far plane
+pub fn frustum_corners_world(projection: &glm::TMat4<f32>, view: &glm::TMat4<f32>) -> [glm::TVec3<f32>; 8] {
+    let inverse = glm::inverse(&(projection * view));
+    let mut corners = [util::glm::empty_vec3(); 8];
+    for (i, z) in [-1.0f32, 1.0].into_iter().enumerate() {
+        for (j, (x, y)) in [(-1.0f32, -1.0f32), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].into_iter().enumerate() {
+            let corner = inverse * glm::vec4(x, y, z, 1.0);
+            corners[i * 4 + j] = util::glm::vec3_from_vec4(&corner) / corner.w;
+        }
+    }
+    corners
+}
+
+// an orthographic light space matrix looking along `light_dir` that encloses all `corners`
+pub fn cascade_light_space_matrix(corners: &[glm::TVec3<f32>; 8], light_dir: &glm::TVec3<f32>) -> glm::TMat4<f32> {
+    let center = corners.iter().fold(util::glm::empty_vec3(), |sum, corner| sum + corner) / corners.len() as f32;
+    let direction = glm::normalize(light_dir);
+    // any up vector that isn't parallel to the light works
+    let up = if direction.y.abs() > 0.99 { glm::vec3(0.0, 0.0, 1.0) } else { glm::vec3(0.0, 1.0, 0.0) };
+    let light_view = glm::look_at(&(center - direction), &center, &up);
+
+    let mut min = glm::vec3(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = glm::vec3(f32::MIN, f32::MIN, f32::MIN);
+    for corner in corners.iter() {
+        let corner = util::glm::vec3_from_vec4(&(light_view * util::glm::vec4_wrap_vec3(corner, 1.0)));
+        min = glm::min2(&min, &corner);
+        max = glm::max2(&max, &corner);
+    }
+    // the light looks down -z, so the near plane lies at -max.z and the far plane at -min.z
+    let margin = (max.z - min.z) * CASCADE_CASTER_MARGIN;
+    let light_projection = glm::ortho(min.x, max.x, min.y, max.y, -max.z - margin, -min.z + margin);
+    light_projection * light_view
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cascade_splits_test_increase_from_near_to_far() {
+        for lambda in [0.0, 0.5, CascadedShadowMap::SPLIT_LAMBDA, 1.0] {
+            let splits = cascade_splits(0.1, 100.0, 4, lambda);
+            assert_eq!(splits.len(), 5);
+            assert!((splits[0] - 0.1).abs() < 1e-6, "Got {:?}.", splits);
+            assert!((splits[4] - 100.0).abs() < 1e-3, "Got {:?}.", splits);
+            assert!(splits.windows(2).all(|split| split[0] < split[1]), "Got {:?}.", splits);
+        }
+        // the logarithmic scheme keeps the first cascade close to the camera
+        assert!(cascade_splits(0.1, 100.0, 4, 1.0)[1] < cascade_splits(0.1, 100.0, 4, 0.0)[1]);
+    }
+
+    #[test]
+    fn cascade_light_space_matrix_test_encloses_corners() {
+        let projection = glm::perspective(4.0 / 3.0, 45f32.to_radians(), 1.0, 20.0);
+        let view = glm::look_at(&glm::vec3(0.0, 2.0, 5.0), &glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 1.0, 0.0));
+        let corners = frustum_corners_world(&projection, &view);
+        let light_space_matrix = cascade_light_space_matrix(&corners, &glm::vec3(-2.0, -4.0, -1.0));
+        for corner in corners.iter() {
+            let ndc = light_space_matrix * util::glm::vec4_wrap_vec3(corner, 1.0);
+            for coordinate in ndc.iter().take(3) {
+                assert!(coordinate.abs() <= 1.0 + 1e-4, "{:?} is outside of the light frustum.", ndc);
+            }
+        }
+    }
+
+    #[test]
+    fn shadow_params_test_uniforms() {
+        let params = ShadowParams { bias: 0.01, normal_bias: 0.1, pcf_radius: 2 };
+        assert_eq!(params.uniforms(), [
+            ("shadowBias", UniformValue::Float(0.01)),
+            ("shadowNormalBias", UniformValue::Float(0.1)),
+            ("pcfRadius", UniformValue::Int(2))
+        ]);
+        assert_eq!(ShadowParams::default(), ShadowParams::directional());
+        assert_eq!(ShadowParams::point().uniforms()[2], ("pcfRadius", UniformValue::Int(0)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+extern crate nalgebra_glm as glm;
+
+use gl::types::*;
+use crate::{filesystem, primitives, util};
+use crate::shader::Shader;
+
+// An HDR environment drawn as background straight from its equirectangular map: the background shader
+// turns each view direction into longitude/latitude texture coordinates, so no cubemap has to be
+// baked first (unlike util::ibl::equirect_to_cubemap). Draw it after the scene, it only covers the
+// pixels nothing else has been drawn to.
+pub struct EquirectSkybox {
+    shader: Shader,
+    texture: u32
+}
+
+impl EquirectSkybox {
+    // loads the .hdr file at `path`, see util::image::load_hdr
+    pub fn new(path: String) -> Self {
+        let image = util::image::load_hdr(path).unwrap_or_else(|err| panic!("Failed to load HDR image: {}", err));
+        Self::from_texture(util::image::upload_hdr_texture(&image))
+    }
+
+    // takes ownership of an equirectangular 2D texture
+    pub fn from_texture(texture: u32) -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/ibl/equirect_background.vs".to_string()),
+            filesystem::get_path("resources/shaders/ibl/equirect_background.fs".to_string()),
+            None
+        );
+        shader.use_shader();
+        shader.set_int("equirectangularMap".to_string(), 0);
+        Self { shader, texture }
+    }
+
+    // draws the background at the far plane with GL_LEQUAL depth testing, restoring the previous depth function
+    pub fn draw(&self, view: &glm::TMat4<f32>, projection: &glm::TMat4<f32>) {
+        self.shader.use_shader();
+        self.shader.set_mat4("projection".to_string(), projection);
+        self.shader.set_mat4("view".to_string(), &background_view(view));
+        unsafe {
+            let mut previous_depth_func = 0 as GLint;
+            gl::GetIntegerv(gl::DEPTH_FUNC, &mut previous_depth_func);
+            gl::DepthFunc(gl::LEQUAL);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            primitives::render_cube();
+            gl::DepthFunc(previous_depth_func as _);
+        }
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn texture(&self) -> u32 {
+        self.texture
+    }
+}
+
+impl Drop for EquirectSkybox {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
+
+// the view matrix the background is drawn with: only the camera's rotation, so it never gets closer
+pub fn background_view(view: &glm::TMat4<f32>) -> glm::TMat4<f32> {
+    util::glm::mat4_wrap_mat3(&util::glm::mat3_from_mat4(view))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+
+    #[test]
+    fn background_view_test_strips_translation() {
+        let view = glm::look_at(&glm::vec3(3.0, 2.0, 1.0), &glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 1.0, 0.0));
+        let stripped = background_view(&view);
+        assert_eq!(stripped * glm::vec4(0.0, 0.0, 0.0, 1.0), glm::vec4(0.0, 0.0, 0.0, 1.0));
+        let direction = glm::vec4(1.0, 0.0, 0.0, 0.0);
+        assert!((stripped * direction - view * direction).norm() < 1e-6);
+    }
+
+    #[test]
+    fn equirect_skybox_test_draws_at_far_plane() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        primitives::reset();
+        let white = [1.0f32; 3];
+        let texture = util::image::upload_hdr_texture(&util::image::HdrImage { width: 1, height: 1, data: white.to_vec() });
+        let skybox = EquirectSkybox::from_texture(texture);
+        let mut linked = 0 as GLint;
+        unsafe {
+            gl::GetProgramiv(skybox.shader().id(), gl::LINK_STATUS, &mut linked);
+            gl::Viewport(0, 0, 4, 4);
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthFunc(gl::LESS);
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::ClearDepth(1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        assert_eq!(linked, gl::TRUE as GLint);
+
+        // the camera is far outside the unit cube, only without translation it's still around it
+        let view = glm::look_at(&glm::vec3(0.0, 0.0, 50.0), &glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 1.0, 0.0));
+        let projection = glm::perspective(1.0, 90f32.to_radians(), 0.1, 100.0);
+        skybox.draw(&view, &projection);
+
+        let mut pixel = [0u8; 4];
+        let mut depth_func = 0 as GLint;
+        unsafe {
+            gl::ReadPixels(2, 2, 1, 1, gl::RGBA, gl::UNSIGNED_BYTE, pixel.as_mut_ptr() as *mut _);
+            gl::GetIntegerv(gl::DEPTH_FUNC, &mut depth_func);
+            gl::Disable(gl::DEPTH_TEST);
+        }
+        drop(skybox);
+        primitives::reset();
+        // at depth 1.0 it only passes with GL_LEQUAL: white tone mapped to 0.5, then gamma corrected
+        let expected = (0.5f32.powf(1.0 / 2.2) * 255.0).round() as i32;
+        for channel in &pixel[..3] {
+            assert!((*channel as i32 - expected).abs() <= 1, "Got {:?}.", pixel);
+        }
+        assert_eq!(depth_func, gl::LESS as GLint);
+    }
+}
\ No newline at end of file
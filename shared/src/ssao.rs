@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use crate::util;
+
+// seed of `default_rng`, any constant works as long as it doesn't change between runs
+pub const DEFAULT_SEED: u64 = 0x55a0;
+
+// a generator that yields the same kernel and noise on every run, so screenshots are reproducible
+pub fn default_rng() -> Pcg64 {
+    Pcg64::seed_from_u64(DEFAULT_SEED)
+}
+
+// `count` samples in the normal-oriented (+z) hemisphere of tangent space, scaled so that they
+// gather more densely around the center of the kernel the further they come in the list
+pub fn generate_kernel(rng: &mut impl Rng, count: u32) -> Vec<glm::TVec3<f32>> {
+    let mut ssao_kernel: Vec<glm::TVec3<f32>> = Vec::new();
+    for i in 0..count {
+        let mut sample = glm::vec3(rng.random::<f32>() * 2.0 - 1.0, rng.random::<f32>() * 2.0 - 1.0, rng.random::<f32>());
+        sample = glm::normalize(&sample);
+        sample *= rng.random::<f32>();
+        let mut scale = i as f32 / count as f32;
+
+        // scale samples s.t. they're more aligned to center of kernel
+        scale = util::glm::lerp(0.1, 1.0, scale * scale);
+        sample *= scale;
+        ssao_kernel.push(sample);
+    }
+    ssao_kernel
+}
+
+// `count` random rotation vectors around the z-axis (in tangent space), e.g. 16 for a 4x4 noise texture
+pub fn generate_noise(rng: &mut impl Rng, count: u32) -> Vec<glm::TVec3<f32>> {
+    let mut ssao_noise: Vec<glm::TVec3<f32>> = Vec::new();
+    for _ in 0..count {
+        let noise = glm::vec3(rng.random::<f32>() * 2.0 - 1.0, rng.random::<f32>() * 2.0 - 1.0, 0.0);
+        ssao_noise.push(noise);
+    }
+    ssao_noise
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_kernel_test_reproducible() {
+        let kernel = generate_kernel(&mut default_rng(), 64);
+        assert_eq!(kernel.len(), 64);
+        assert_eq!(kernel, generate_kernel(&mut default_rng(), 64));
+        assert_ne!(kernel, generate_kernel(&mut Pcg64::seed_from_u64(DEFAULT_SEED + 1), 64));
+    }
+
+    #[test]
+    fn generate_kernel_test_scaled_outwards() {
+        let kernel = generate_kernel(&mut Pcg64::seed_from_u64(7), 64);
+        let average_length = |samples: &[glm::TVec3<f32>]| {
+            samples.iter().map(|it| glm::length(it)).sum::<f32>() / samples.len() as f32
+        };
+        // the first samples are scaled by ~0.1, the last ones by almost 1.0
+        let inner = average_length(&kernel[..16]);
+        let outer = average_length(&kernel[48..]);
+        assert!(inner < 0.1, "Got {} for the inner samples.", inner);
+        assert!(outer > 2.0 * inner, "Got {} for the outer samples ({} for the inner ones).", outer, inner);
+        assert!(kernel.iter().all(|it| it.z >= 0.0 && glm::length(it) <= 1.0));
+    }
+
+    #[test]
+    fn generate_noise_test_tangent_plane() {
+        let noise = generate_noise(&mut default_rng(), 16);
+        assert_eq!(noise.len(), 16);
+        assert_eq!(noise, generate_noise(&mut default_rng(), 16));
+        assert!(noise.iter().all(|it| it.z == 0.0 && it.x.abs() <= 1.0 && it.y.abs() <= 1.0));
+    }
+}
\ No newline at end of file
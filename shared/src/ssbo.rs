@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+use std::marker::PhantomData;
+use std::mem;
+use bytemuck::Pod;
+use gl::types::*;
+
+// Shader storage buffer holding `len` values of T, bound to an indexed binding point so shaders can
+// access it as `layout (std430, binding = N) buffer`. Needs an OpenGL 4.3 context. The buffer is
+// deleted once the wrapper is dropped.
+//
+// T is uploaded byte by byte, so its layout has to match the std430 one of the shader's block
+// (plain f32/u32/i32 values and arrays of them always do).
+pub struct Ssbo<T: Pod> {
+    id: u32,
+    binding: u32,
+    len: usize,
+    _marker: PhantomData<T>
+}
+
+impl<T: Pod> Ssbo<T> {
+    // creates the buffer with a copy of `data` and binds it to binding point `binding`
+    pub fn new(data: &[T], binding: u32) -> Self {
+        let mut id = 0u32;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, id);
+            gl::BufferData(gl::SHADER_STORAGE_BUFFER, mem::size_of_val(data) as GLsizeiptr, data.as_ptr() as *const _, gl::DYNAMIC_COPY);
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, id);
+        }
+        Self {
+            id,
+            binding,
+            len: data.len(),
+            _marker: PhantomData
+        }
+    }
+
+    // overwrites the values starting at index `offset` with `data`
+    pub fn update(&self, offset: usize, data: &[T]) {
+        assert!(offset + data.len() <= self.len, "Writing {} values at {} overflows the buffer of {} values.", data.len(), offset, self.len);
+        unsafe {
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.id);
+            gl::BufferSubData(gl::SHADER_STORAGE_BUFFER, (offset * mem::size_of::<T>()) as GLintptr, mem::size_of_val(data) as GLsizeiptr, data.as_ptr() as *const _);
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        }
+    }
+
+    // copies the current contents back to the CPU. Writes of shaders only show up after a
+    // `Shader::memory_barrier(gl::BUFFER_UPDATE_BARRIER_BIT)`.
+    pub fn read_back(&self) -> Vec<T> {
+        let mut data = vec![T::zeroed(); self.len];
+        unsafe {
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.id);
+            gl::GetBufferSubData(gl::SHADER_STORAGE_BUFFER, 0, mem::size_of_val(data.as_slice()) as GLsizeiptr, data.as_mut_ptr() as *mut _);
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        }
+        data
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn binding(&self) -> u32 {
+        self.binding
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T: Pod> Drop for Ssbo<T> {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context_version;
+
+    #[test]
+    fn ssbo_test_read_back() {
+        let Some(_context) = hidden_gl_context_version(4, 4, 4, 3) else {
+            return;
+        };
+        let values: Vec<f32> = (0..10).map(|i| i as f32 * 0.5).collect();
+        let ssbo = Ssbo::new(&values, 1);
+        assert_eq!(ssbo.len(), 10);
+        assert_eq!(ssbo.read_back(), values);
+
+        let mut bound = 0 as GLint;
+        unsafe {
+            gl::GetIntegeri_v(gl::SHADER_STORAGE_BUFFER_BINDING, 1, &mut bound);
+        }
+        assert_eq!(bound as u32, ssbo.id());
+    }
+
+    #[test]
+    fn ssbo_test_update() {
+        let Some(_context) = hidden_gl_context_version(4, 4, 4, 3) else {
+            return;
+        };
+        let ssbo = Ssbo::new(&[0u32; 4], 0);
+        ssbo.update(2, &[7, 8]);
+        assert_eq!(ssbo.read_back(), vec![0, 0, 7, 8]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::{mem, ptr};
+use gl::types::*;
+use crate::{filesystem, util};
+use crate::shader::Shader;
+use crate::util::image::TextureOptions;
+
+// size of a character cell in the font atlas, the printable ASCII characters (32 to 127) are laid
+// out in rows of 16 cells
+const CELL_WIDTH: f32 = 16.0;
+const CELL_HEIGHT: f32 = 32.0;
+const ATLAS_COLUMNS: u32 = 16;
+const ATLAS_ROWS: u32 = 6;
+const FIRST_CHAR: u32 = 32;
+// characters without a cell are drawn as '?'
+const FALLBACK_CHAR: char = '?';
+// two triangles with <vec2 pos, vec2 tex> each
+const FLOATS_PER_GLYPH: usize = 6 * 4;
+
+// draws text in screen space with a monospaced bitmap font, e.g. for an FPS readout on top of the
+// rendered scene. Coordinates are in pixels with the origin at the bottom left of the screen.
+// ------------------------------------------------------------------------
+pub struct TextRenderer {
+    shader: Shader,
+    font_texture: u32,
+    vao: u32,
+    vbo: u32
+}
+
+impl TextRenderer {
+    pub fn new(screen_width: u32, screen_height: u32) -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/text/text.vs".to_string()),
+            filesystem::get_path("resources/shaders/text/text.fs".to_string()),
+            None
+        );
+        // the atlas stays top row first, so a cell's top edge has the smaller t coordinate
+        let font_texture = util::image::load_texture_2d_with_options(
+            filesystem::get_path("resources/fonts/dejavu_sans_mono_16x32.png".to_string()),
+            false,
+            &TextureOptions {
+                min_filter: gl::LINEAR,
+                flip_vertically: false,
+                ..TextureOptions::clamp_to_edge()
+            }
+        );
+
+        let (mut vao, mut vbo) = (0u32, 0u32);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 4, gl::FLOAT, gl::FALSE, (4 * mem::size_of::<f32>()) as _, ptr::null());
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+
+        let result = Self {
+            shader,
+            font_texture,
+            vao,
+            vbo
+        };
+        result.shader.use_shader();
+        result.shader.set_int("glyphs".to_string(), 0);
+        result.set_screen_size(screen_width, screen_height);
+        result
+    }
+
+    // updates the orthographic projection, call it when the window gets resized
+    pub fn set_screen_size(&self, width: u32, height: u32) {
+        let projection = glm::ortho(0.0, width as f32, 0.0, height as f32, -1.0, 1.0);
+        self.shader.use_shader();
+        self.shader.set_mat4("projection".to_string(), &projection);
+    }
+
+    // draws `text` as a single line with the bottom left corner of its first character cell at
+    // (x, y). At scale 1.0 every character is 16x32 pixels.
+    pub fn draw_text(&self, text: &str, x: f32, y: f32, scale: f32, color: glm::TVec3<f32>) {
+        let vertices = glyph_vertices(text, x, y, scale);
+        if vertices.is_empty() {
+            return;
+        }
+
+        unsafe {
+            // text goes on top of everything and blends with what is below it
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST);
+            let blend = gl::IsEnabled(gl::BLEND);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+
+            self.shader.use_shader();
+            self.shader.set_vec3("textColor".to_string(), &color);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.font_texture);
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(vertices.as_slice()) as _, vertices.as_ptr() as _, gl::DYNAMIC_DRAW);
+            gl::DrawArrays(gl::TRIANGLES, 0, (vertices.len() / 4) as _);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+
+            if depth_test == gl::TRUE {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            if blend == gl::FALSE {
+                gl::Disable(gl::BLEND);
+            }
+        }
+    }
+
+    // width in pixels `draw_text` needs for `text`, e.g. to right-align it
+    pub fn text_width(text: &str, scale: f32) -> f32 {
+        text.chars().count() as f32 * CELL_WIDTH * scale
+    }
+
+    // height in pixels of one line of text
+    pub fn line_height(scale: f32) -> f32 {
+        CELL_HEIGHT * scale
+    }
+}
+
+impl Drop for TextRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteTextures(1, &self.font_texture);
+        }
+    }
+}
+
+// returns the column and row of the atlas cell holding `c`
+fn glyph_cell(c: char) -> (u32, u32) {
+    let code = c as u32;
+    let index = if (FIRST_CHAR..FIRST_CHAR + ATLAS_COLUMNS * ATLAS_ROWS).contains(&code) {
+        code - FIRST_CHAR
+    } else {
+        FALLBACK_CHAR as u32 - FIRST_CHAR
+    };
+    (index % ATLAS_COLUMNS, index / ATLAS_COLUMNS)
+}
+
+// builds the <vec2 pos, vec2 tex> triangles of a line of text, spaces get no quad
+fn glyph_vertices(text: &str, x: f32, y: f32, scale: f32) -> Vec<f32> {
+    let (w, h) = (CELL_WIDTH * scale, CELL_HEIGHT * scale);
+    let mut vertices = Vec::with_capacity(text.len() * FLOATS_PER_GLYPH);
+    for (i, c) in text.chars().enumerate() {
+        if c == ' ' {
+            continue;
+        }
+        let (column, row) = glyph_cell(c);
+        let x_pos = x + i as f32 * w;
+        let s0 = column as f32 / ATLAS_COLUMNS as f32;
+        let s1 = (column + 1) as f32 / ATLAS_COLUMNS as f32;
+        let t_top = row as f32 / ATLAS_ROWS as f32;
+        let t_bottom = (row + 1) as f32 / ATLAS_ROWS as f32;
+        vertices.extend_from_slice(&[
+            x_pos,     y + h, s0, t_top,
+            x_pos,     y,     s0, t_bottom,
+            x_pos + w, y,     s1, t_bottom,
+
+            x_pos,     y + h, s0, t_top,
+            x_pos + w, y,     s1, t_bottom,
+            x_pos + w, y + h, s1, t_top
+        ]);
+    }
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+
+    #[test]
+    fn glyph_cell_test() {
+        assert_eq!(glyph_cell(' '), (0, 0));
+        assert_eq!(glyph_cell('A'), (1, 2));
+        assert_eq!(glyph_cell('~'), (14, 5));
+        // outside of printable ASCII
+        assert_eq!(glyph_cell('\u{e9}'), glyph_cell('?'));
+        assert_eq!(glyph_cell('\n'), glyph_cell('?'));
+    }
+
+    #[test]
+    fn glyph_vertices_test_skips_spaces() {
+        let vertices = glyph_vertices("a b", 10.0, 20.0, 0.5);
+        assert_eq!(vertices.len(), 2 * FLOATS_PER_GLYPH);
+        // the 'b' starts two cells to the right
+        assert_eq!(vertices[FLOATS_PER_GLYPH], 10.0 + 2.0 * 8.0);
+        assert_eq!(vertices[FLOATS_PER_GLYPH + 1], 20.0 + 16.0);
+    }
+
+    #[test]
+    fn draw_text_test_draws_pixels() {
+        let Some(_context) = hidden_gl_context(32, 32) else {
+            return;
+        };
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        let text = TextRenderer::new(32, 32);
+        text.draw_text("A", 0.0, 0.0, 1.0, glm::vec3(1.0, 1.0, 1.0));
+
+        let pixels = util::image::read_pixels_rgba(32, 32);
+        let lit = pixels.pixels().filter(|p| p[0] > 128).count();
+        assert!(lit > 20, "only {} pixels of the 'A' were drawn", lit);
+        // nothing is drawn right of the 16 pixel wide cell
+        assert!(pixels.enumerate_pixels().all(|(x, _, p)| x < 16 || p[0] == 0));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::f32::consts::PI;
+use std::mem;
+use bytemuck::Pod;
+use gl::types::*;
+use image::RgbImage;
+use crate::shader::Shader;
+use crate::util;
+
+// Owning wrapper around a GL texture object, the texture is deleted once the wrapper is dropped.
+// Use `into_raw` to hand the id over to code that manages its lifetime itself.
+#[derive(Debug)]
+pub struct Texture {
+    id: u32,
+    target: GLenum
+}
+
+impl Texture {
+    // takes ownership of an existing texture object of the given target (e.g. gl::TEXTURE_2D)
+    pub fn from_raw(target: GLenum, id: u32) -> Self {
+        Self { id, target }
+    }
+
+    // loads a 2D texture from file with mipmaps and the default sampling parameters, see `util::image::load_texture_2d`
+    pub fn from_file_2d(path: String, gamma: bool) -> Self {
+        Self::from_raw(gl::TEXTURE_2D, util::image::load_texture_2d(path, gamma))
+    }
+
+    // like `from_file_2d` with custom sampling parameters, see `util::image::load_texture_2d_with_options`
+    pub fn from_file_2d_with_options(path: String, gamma: bool, options: &util::image::TextureOptions) -> Self {
+        Self::from_raw(gl::TEXTURE_2D, util::image::load_texture_2d_with_options(path, gamma, options))
+    }
+
+    // allocates immutable storage (glTexStorage2D) for a width x height 2D texture with `levels` mip
+    // levels of the sized `internal_format` (e.g. gl::RGBA8 or gl::RGB16F), see `util::image::mip_level_count`
+    // for a full chain. The levels are meant to be filled one by one with `upload_mip` instead of
+    // glGenerateMipmap. Needs OpenGL 4.2.
+    pub fn with_mip_levels(width: u32, height: u32, internal_format: GLenum, levels: u32) -> Self {
+        let mut id = 0u32;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D, id);
+            gl::TexStorage2D(gl::TEXTURE_2D, levels as _, internal_format, width as _, height as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, if levels > 1 { gl::LINEAR_MIPMAP_LINEAR } else { gl::LINEAR } as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+        }
+        Self::from_raw(gl::TEXTURE_2D, id)
+    }
+
+    // replaces the whole mip level `level` (width x height texels) of a 2D texture with tightly packed
+    // `data`: u8 components for normalized formats like gl::RGBA8, f32 ones for floating point formats.
+    // The layout is picked from the internal format of the texture, see `pixel_transfer_format`.
+    pub fn upload_mip<T: Pod>(&self, level: u32, width: u32, height: u32, data: &[T]) {
+        unsafe {
+            gl::BindTexture(self.target, self.id);
+            let mut internal_format = 0 as GLint;
+            gl::GetTexLevelParameteriv(self.target, level as _, gl::TEXTURE_INTERNAL_FORMAT, &mut internal_format);
+            let (format, data_type, components, component_size) = pixel_transfer_format(internal_format as GLenum)
+                .unwrap_or_else(|| panic!("Uploading mip levels of internal format 0x{:04X} isn't supported.", internal_format));
+            assert_eq!(
+                mem::size_of_val(data), (width * height) as usize * components * component_size,
+                "The data doesn't match a {}x{} mip level.", width, height
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(self.target, level as _, 0, 0, width as _, height as _, format, data_type, data.as_ptr() as *const _);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        }
+    }
+
+    // loads a cubemap from 6 individual faces in GL order, see `util::image::load_cubemap`
+    pub fn cubemap(faces: &[String]) -> Self {
+        Self::from_raw(gl::TEXTURE_CUBE_MAP, util::image::load_cubemap(faces))
+    }
+
+    // binds the texture to texture unit `unit` (0 for GL_TEXTURE0 and so on)
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(self.target, self.id);
+        }
+    }
+
+    // switches anisotropic filtering on (at the hardware maximum) or off, ignored if it isn't supported.
+    // Leaves the texture bound to the active texture unit.
+    pub fn set_anisotropic(&self, enabled: bool) {
+        unsafe {
+            gl::BindTexture(self.target, self.id);
+        }
+        util::image::set_anisotropic_filtering(self.target, enabled);
+    }
+
+    // releases ownership of the texture object without deleting it, returning its id
+    pub fn into_raw(self) -> u32 {
+        let id = self.id;
+        mem::forget(self);
+        id
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn target(&self) -> GLenum {
+        self.target
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+// format, type, component count and component size in bytes of the pixel data `Texture::upload_mip`
+// transfers for a sized internal format, None for formats it doesn't handle
+pub fn pixel_transfer_format(internal_format: GLenum) -> Option<(GLenum, GLenum, usize, usize)> {
+    let (format, data_type, components) = match internal_format {
+        gl::R8 => (gl::RED, gl::UNSIGNED_BYTE, 1),
+        gl::RG8 => (gl::RG, gl::UNSIGNED_BYTE, 2),
+        gl::RGB8 | gl::SRGB8 => (gl::RGB, gl::UNSIGNED_BYTE, 3),
+        gl::RGBA8 | gl::SRGB8_ALPHA8 => (gl::RGBA, gl::UNSIGNED_BYTE, 4),
+        gl::R16F | gl::R32F => (gl::RED, gl::FLOAT, 1),
+        gl::RG16F | gl::RG32F => (gl::RG, gl::FLOAT, 2),
+        gl::RGB16F | gl::RGB32F => (gl::RGB, gl::FLOAT, 3),
+        gl::RGBA16F | gl::RGBA32F => (gl::RGBA, gl::FLOAT, 4),
+        _ => return None
+    };
+    let component_size = if data_type == gl::FLOAT { mem::size_of::<f32>() } else { mem::size_of::<u8>() };
+    Some((format, data_type, components, component_size))
+}
+
+// Spherical harmonics constants of the first three bands (9 coefficients)
+const SH_Y00: f32 = 0.282095;
+const SH_Y1: f32 = 0.488603;
+const SH_Y2_MIXED: f32 = 1.092548;
+const SH_Y20: f32 = 0.315392;
+const SH_Y22: f32 = 0.546274;
+// cosine lobe convolution factors per band (Ramamoorthi & Hanrahan)
+const SH_A0: f32 = PI;
+const SH_A1: f32 = 2.0 * PI / 3.0;
+const SH_A2: f32 = PI / 4.0;
+
+// Evaluates the 9 real spherical harmonics basis functions (bands 0 to 2) for the normalized
+// direction `dir`, in the order the coefficients of `compute_sh_irradiance` use.
+pub fn sh_basis(dir: &glm::TVec3<f32>) -> [f32; 9] {
+    let (x, y, z) = (dir.x, dir.y, dir.z);
+    [
+        SH_Y00,
+        SH_Y1 * y,
+        SH_Y1 * z,
+        SH_Y1 * x,
+        SH_Y2_MIXED * x * y,
+        SH_Y2_MIXED * y * z,
+        SH_Y20 * (3.0 * z * z - 1.0),
+        SH_Y2_MIXED * x * z,
+        SH_Y22 * (x * x - y * y)
+    ]
+}
+
+// Returns the (unnormalized) direction through the center of texel (x, y) of the given cubemap face.
+// Faces are in GL order: +X, -X, +Y, -Y, +Z, -Z, rows going from the top of the image downwards
+// (which is how `load_cubemap` uploads the unflipped skybox images).
+//
+// Panics if `face` isn't in 0..6.
+pub fn cubemap_texel_direction(face: usize, x: u32, y: u32, size: u32) -> glm::TVec3<f32> {
+    let sc = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+    let tc = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+    match face {
+        0 => glm::vec3(1.0, -tc, -sc),
+        1 => glm::vec3(-1.0, -tc, sc),
+        2 => glm::vec3(sc, 1.0, tc),
+        3 => glm::vec3(sc, -1.0, -tc),
+        4 => glm::vec3(sc, -tc, 1.0),
+        5 => glm::vec3(-sc, -tc, -1.0),
+        _ => panic!("A cubemap only has 6 faces, got face index {}.", face)
+    }
+}
+
+// Cheap diffuse image based lighting for LDR skyboxes: projects the environment stored in `cubemap`
+// onto the first three spherical harmonics bands by reading its six faces back (as 8 bit RGB, so HDR
+// values are clamped to 1) and integrating all texels on the CPU. The returned coefficients are
+// already convolved with the cosine lobe and divided by PI, so evaluating them for a normal (see
+// `evaluate_sh_irradiance` or `resources/shaders/ibl/sh_ambient.fs`) directly yields the diffuse
+// ambient color to multiply the albedo with.
+//
+// If `gamma` is set the faces are treated as sRGB encoded and linearized first. Needs a current GL
+// context, `cubemap` is left unbound afterwards.
+pub fn compute_sh_irradiance(cubemap: u32, gamma: bool) -> [glm::TVec3<f32>; 9] {
+    sh_irradiance_from_faces(&read_cubemap_faces(cubemap), gamma)
+}
+
+// the CPU side of `compute_sh_irradiance`, `faces` are the six cubemap faces in GL order
+fn sh_irradiance_from_faces(faces: &[RgbImage], gamma: bool) -> [glm::TVec3<f32>; 9] {
+    assert_eq!(faces.len(), 6, "A cubemap needs exactly 6 faces.");
+    let mut coefficients = [util::glm::empty_vec3(); 9];
+    let mut total_weight = 0f32;
+
+    for (face_i, face) in faces.iter().enumerate() {
+        let size = face.width();
+        assert_eq!(size, face.height(), "Cubemap faces must be square.");
+        for (x, y, pixel) in face.enumerate_pixels() {
+            let dir = cubemap_texel_direction(face_i, x, y, size);
+            // solid angle covered by this texel
+            let length_sq = glm::dot(&dir, &dir);
+            let weight = 1.0 / (length_sq * length_sq.sqrt());
+            let dir = dir / length_sq.sqrt();
+
+            let mut color = glm::vec3(
+                pixel[0] as f32 / 255.0,
+                pixel[1] as f32 / 255.0,
+                pixel[2] as f32 / 255.0
+            );
+            if gamma {
+                color = glm::vec3(color.x.powf(2.2), color.y.powf(2.2), color.z.powf(2.2));
+            }
+
+            let basis = sh_basis(&dir);
+            for (coefficient, y_lm) in coefficients.iter_mut().zip(basis.iter()) {
+                *coefficient += color * (*y_lm * weight);
+            }
+            total_weight += weight;
+        }
+    }
+
+    // normalize so the weights sum up to the full sphere (4 PI), then apply the cosine lobe and 1/PI
+    let normalization = 4.0 * PI / total_weight;
+    let band_factors = [SH_A0, SH_A1, SH_A1, SH_A1, SH_A2, SH_A2, SH_A2, SH_A2, SH_A2];
+    for (coefficient, a_l) in coefficients.iter_mut().zip(band_factors.iter()) {
+        *coefficient *= normalization * *a_l / PI;
+    }
+    coefficients
+}
+
+// CPU counterpart of the evaluation in `sh_ambient.fs`: returns the diffuse ambient color the
+// coefficients of `compute_sh_irradiance` give for `normal` (which doesn't need to be normalized).
+pub fn evaluate_sh_irradiance(coefficients: &[glm::TVec3<f32>; 9], normal: &glm::TVec3<f32>) -> glm::TVec3<f32> {
+    let basis = sh_basis(&glm::normalize(normal));
+    let mut result = util::glm::empty_vec3();
+    for (coefficient, y_lm) in coefficients.iter().zip(basis.iter()) {
+        result += coefficient * *y_lm;
+    }
+    result
+}
+
+// Uploads the coefficients of `compute_sh_irradiance` to the `vec3 name[9]` uniform array of
+// `shader` (`shCoefficients` in `sh_ambient.fs`). The shader has to be in use.
+pub fn upload_sh_coefficients(shader: &Shader, name: &str, coefficients: &[glm::TVec3<f32>; 9]) {
+    for (i, coefficient) in coefficients.iter().enumerate() {
+        shader.set_vec3(format!("{}[{}]", name, i), coefficient);
+    }
+}
+
+// reads the six faces of a cubemap back from the GPU as 8 bit RGB images
+fn read_cubemap_faces(cubemap: u32) -> Vec<RgbImage> {
+    let mut faces = Vec::with_capacity(6);
+    unsafe {
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        for i in 0..6 {
+            let target = gl::TEXTURE_CUBE_MAP_POSITIVE_X + i;
+            let (mut width, mut height) = (0 as GLint, 0 as GLint);
+            gl::GetTexLevelParameteriv(target, 0, gl::TEXTURE_WIDTH, &mut width);
+            gl::GetTexLevelParameteriv(target, 0, gl::TEXTURE_HEIGHT, &mut height);
+            let mut face = RgbImage::new(width as u32, height as u32);
+            gl::GetTexImage(target, 0, gl::RGB, gl::UNSIGNED_BYTE, face.as_mut_ptr() as *mut _);
+            faces.push(face);
+        }
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+    }
+    faces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+    use crate::gl_test::hidden_gl_context_version;
+
+    const FACE_SIZE: u32 = 16;
+
+    fn uniform_faces(color: [u8; 3]) -> Vec<RgbImage> {
+        (0..6).map(|_| RgbImage::from_pixel(FACE_SIZE, FACE_SIZE, Rgb(color))).collect()
+    }
+
+    fn assert_vec3_near(a: &glm::TVec3<f32>, b: &glm::TVec3<f32>, epsilon: f32) {
+        assert!((a - b).abs().max() < epsilon, "{:?} and {:?} differ by more than {}.", a, b, epsilon);
+    }
+
+    #[test]
+    fn cubemap_texel_direction_test_face_centers() {
+        // the texels right next to the center of each face point (mostly) along the major axis
+        let expected = [
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(-1.0, 0.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(0.0, -1.0, 0.0),
+            glm::vec3(0.0, 0.0, 1.0),
+            glm::vec3(0.0, 0.0, -1.0)
+        ];
+        for (face, axis) in expected.iter().enumerate() {
+            let dir = glm::normalize(&cubemap_texel_direction(face, FACE_SIZE / 2, FACE_SIZE / 2, FACE_SIZE));
+            assert!(glm::dot(&dir, axis) > 0.99, "Face {} points to {:?}.", face, dir);
+        }
+    }
+
+    #[test]
+    fn compute_sh_irradiance_test_uniform_white() {
+        // a uniformly lit white environment gives an ambient of exactly 1 in every direction
+        let coefficients = sh_irradiance_from_faces(&uniform_faces([255, 255, 255]), false);
+        for coefficient in coefficients.iter().skip(1) {
+            assert_vec3_near(coefficient, &util::glm::empty_vec3(), 1e-4);
+        }
+        for normal in [glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, -1.0, 0.0), glm::vec3(1.0, 1.0, 1.0)] {
+            assert_vec3_near(&evaluate_sh_irradiance(&coefficients, &normal), &glm::vec3(1.0, 1.0, 1.0), 1e-3);
+        }
+    }
+
+    #[test]
+    fn compute_sh_irradiance_test_gamma() {
+        let linear = sh_irradiance_from_faces(&uniform_faces([128, 128, 128]), false);
+        let srgb = sh_irradiance_from_faces(&uniform_faces([128, 128, 128]), true);
+        let up = glm::vec3(0.0, 1.0, 0.0);
+        assert_vec3_near(&evaluate_sh_irradiance(&linear, &up), &util::glm::scale_vec3(128.0 / 255.0), 1e-3);
+        assert_vec3_near(&evaluate_sh_irradiance(&srgb, &up), &util::glm::scale_vec3((128f32 / 255.0).powf(2.2)), 1e-3);
+    }
+
+    #[test]
+    fn compute_sh_irradiance_test_sky_light() {
+        // only the top face emits light: normals facing up receive much more of it than those facing down
+        let mut faces = uniform_faces([0, 0, 0]);
+        faces[2] = RgbImage::from_pixel(FACE_SIZE, FACE_SIZE, Rgb([255, 255, 255]));
+        let coefficients = sh_irradiance_from_faces(&faces, false);
+        let up = evaluate_sh_irradiance(&coefficients, &glm::vec3(0.0, 1.0, 0.0));
+        let side = evaluate_sh_irradiance(&coefficients, &glm::vec3(1.0, 0.0, 0.0));
+        let down = evaluate_sh_irradiance(&coefficients, &glm::vec3(0.0, -1.0, 0.0));
+        assert!(up.x > side.x && side.x > down.x, "up: {:?}, side: {:?}, down: {:?}", up, side, down);
+        // the light is symmetric around the y axis
+        let other_side = evaluate_sh_irradiance(&coefficients, &glm::vec3(0.0, 0.0, -1.0));
+        assert!((side.x - other_side.x).abs() < 1e-3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn compute_sh_irradiance_test_wrong_face_count() {
+        sh_irradiance_from_faces(&uniform_faces([0, 0, 0])[0..5], false);
+    }
+
+    #[test]
+    fn compute_sh_irradiance_test_cubemap() {
+        let Some(_context) = hidden_gl_context_version(4, 4, 3, 3) else {
+            return;
+        };
+        // a cubemap with a red +X face, everything else black
+        let mut faces = uniform_faces([0, 0, 0]);
+        faces[0] = RgbImage::from_pixel(FACE_SIZE, FACE_SIZE, Rgb([255, 0, 0]));
+        let mut cubemap = 0;
+        unsafe {
+            gl::GenTextures(1, &mut cubemap);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap);
+            for (i, face) in faces.iter().enumerate() {
+                gl::TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                    0,
+                    gl::RGB8 as GLint,
+                    FACE_SIZE as GLsizei,
+                    FACE_SIZE as GLsizei,
+                    0,
+                    gl::RGB,
+                    gl::UNSIGNED_BYTE,
+                    face.as_ptr() as *const _
+                );
+            }
+        }
+        let coefficients = compute_sh_irradiance(cubemap, false);
+        unsafe {
+            gl::DeleteTextures(1, &cubemap);
+        }
+
+        // reading the faces back is lossless
+        let expected = sh_irradiance_from_faces(&faces, false);
+        for (coefficient, expected) in coefficients.iter().zip(expected.iter()) {
+            assert_vec3_near(coefficient, expected, 1e-5);
+        }
+        let right = evaluate_sh_irradiance(&coefficients, &glm::vec3(1.0, 0.0, 0.0));
+        assert!(right.x > 0.0 && right.y.abs() < 1e-5 && right.z.abs() < 1e-5, "{:?}", right);
+    }
+
+    #[test]
+    fn pixel_transfer_format_test() {
+        assert_eq!(pixel_transfer_format(gl::RGBA8), Some((gl::RGBA, gl::UNSIGNED_BYTE, 4, 1)));
+        assert_eq!(pixel_transfer_format(gl::RGB16F), Some((gl::RGB, gl::FLOAT, 3, 4)));
+        assert_eq!(pixel_transfer_format(gl::DEPTH_COMPONENT24), None);
+    }
+
+    #[test]
+    fn texture_test_upload_mip() {
+        let Some(_context) = hidden_gl_context_version(4, 4, 4, 2) else {
+            return;
+        };
+        let levels = util::image::mip_level_count(4, 2);
+        let texture = Texture::with_mip_levels(4, 2, gl::RGBA8, levels);
+        let texels: [u8; 8] = [255, 0, 0, 255, 0, 0, 255, 255];
+        texture.upload_mip(1, 2, 1, &texels);
+
+        let mut immutable_levels = 0 as GLint;
+        let mut level = [0u8; 8];
+        unsafe {
+            gl::GetTexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_IMMUTABLE_LEVELS, &mut immutable_levels);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::GetTexImage(gl::TEXTURE_2D, 1, gl::RGBA, gl::UNSIGNED_BYTE, level.as_mut_ptr() as *mut _);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+        }
+        assert_eq!(immutable_levels as u32, levels);
+        assert_eq!(level, texels);
+    }
+}
\ No newline at end of file
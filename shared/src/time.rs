@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use glfw::Glfw;
+
+// Default timing values
+const FPS_SMOOTHING: f32 = 0.1;
+
+// Measures the time between frames without any global state, create one before the render loop
+// and hand it to whatever needs the delta time.
+pub struct FrameTimer {
+    delta: f32,
+    last_frame: Option<f32>,
+    // exponential moving average of the frame deltas, None until the second tick
+    average_delta: Option<f32>,
+    // weight of the newest delta in the average, higher values react faster but jitter more
+    fps_smoothing: f32
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self {
+            delta: 0.0,
+            last_frame: None,
+            average_delta: None,
+            fps_smoothing: FPS_SMOOTHING
+        }
+    }
+
+    // per-frame time logic, `now` is the current time in seconds (e.g. `glfw.get_time()`).
+    // Returns the time passed since the previous tick, which is zero for the very first one.
+    pub fn tick(&mut self, now: f32) -> f32 {
+        self.delta = match self.last_frame {
+            // a clock going backwards is treated the same as no time passing at all
+            Some(last_frame) => (now - last_frame).max(0.0),
+            None => 0.0
+        };
+        if self.last_frame.is_some() {
+            self.average_delta = Some(match self.average_delta {
+                Some(average) => average + (self.delta - average) * self.fps_smoothing,
+                None => self.delta
+            });
+        }
+        self.last_frame = Some(now);
+        self.delta
+    }
+
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    // frames per second averaged over the recent frames, zero until there is a measurement
+    pub fn fps(&self) -> f32 {
+        match self.average_delta {
+            Some(average) if average > 0.0 => 1.0 / average,
+            _ => 0.0
+        }
+    }
+
+    pub fn fps_smoothing(&self) -> f32 {
+        self.fps_smoothing
+    }
+
+    pub fn set_fps_smoothing(&mut self, fps_smoothing: f32) {
+        self.fps_smoothing = fps_smoothing;
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Animation time that can be paused and run faster or slower than the raw time source, so a moving
+// scene can be frozen to inspect a frame. Animations read `elapsed()` instead of `glfw.get_time()`, the
+// frame delta for camera movement should keep using the raw time.
+pub struct Clock {
+    source: Box<dyn Fn() -> f64>,
+    // the raw time and the elapsed time at the last pause, resume or scale change
+    anchor_raw: f64,
+    anchor_elapsed: f64,
+    scale: f32,
+    paused: bool
+}
+
+impl Clock {
+    // a clock reading `source` (in seconds), starting from its current value
+    pub fn new(source: impl Fn() -> f64 + 'static) -> Self {
+        let anchor_raw = source();
+        Self {
+            source: Box::new(source),
+            anchor_raw,
+            anchor_elapsed: anchor_raw,
+            scale: 1.0,
+            paused: false
+        }
+    }
+
+    // a clock running on GLFW's timer, elapsed() matches glfw.get_time() until paused or scaled
+    pub fn from_glfw(glfw: &Glfw) -> Self {
+        let glfw = glfw.clone();
+        Self::new(move || glfw.get_time())
+    }
+
+    // the scaled time in seconds, frozen while paused
+    pub fn elapsed(&self) -> f64 {
+        if self.paused {
+            self.anchor_elapsed
+        } else {
+            self.anchor_elapsed + ((self.source)() - self.anchor_raw) * self.scale as f64
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if !self.paused {
+            self.anchor_elapsed = self.elapsed();
+            self.paused = true;
+        }
+    }
+
+    // continues from the time the clock was paused at
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.anchor_raw = (self.source)();
+            self.paused = false;
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    // how fast the clock runs compared to its source, e.g. 0.5 for slow motion. The elapsed time
+    // doesn't jump when the scale changes.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.anchor_elapsed = self.elapsed();
+        self.anchor_raw = (self.source)();
+        self.scale = scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use super::*;
+
+    #[test]
+    fn tick_test_delta() {
+        let mut timer = FrameTimer::new();
+        assert_eq!(timer.tick(1.0), 0.0); // there is no previous frame yet
+        assert_eq!(timer.fps(), 0.0);
+        assert!((timer.tick(1.25) - 0.25).abs() < 1e-6);
+        assert!((timer.delta() - 0.25).abs() < 1e-6);
+        assert_eq!(timer.tick(1.0), 0.0);
+    }
+
+    #[test]
+    fn fps_test_steady() {
+        let mut timer = FrameTimer::new();
+        for frame in 0..100 {
+            timer.tick(frame as f32 * 0.02);
+        }
+        assert!((timer.fps() - 50.0).abs() < 1e-2, "Expected 50 fps, got {}.", timer.fps());
+    }
+
+    #[test]
+    fn fps_test_smoothed() {
+        let mut timer = FrameTimer::new();
+        // deltas of 0.1, 0.05 and 0.05 seconds
+        for now in [0.0, 0.1, 0.15, 0.2] {
+            timer.tick(now);
+        }
+        // 0.1 -> 0.1 + (0.05 - 0.1) * 0.1 = 0.095 -> 0.095 + (0.05 - 0.095) * 0.1 = 0.0905
+        assert!((timer.fps() - 1.0 / 0.0905).abs() < 1e-2, "Expected {} fps, got {}.", 1.0 / 0.0905, timer.fps());
+    }
+    #[test]
+    fn clock_test_pause_and_resume() {
+        let now = Rc::new(Cell::new(1.0f64));
+        let source = now.clone();
+        let mut clock = Clock::new(move || source.get());
+        assert_eq!(clock.elapsed(), 1.0);
+        now.set(2.0);
+        assert_eq!(clock.elapsed(), 2.0);
+
+        // frozen while paused
+        clock.pause();
+        now.set(5.0);
+        assert!(clock.is_paused());
+        assert_eq!(clock.elapsed(), 2.0);
+
+        // continues from the frozen value
+        clock.resume();
+        assert_eq!(clock.elapsed(), 2.0);
+        now.set(5.5);
+        assert_eq!(clock.elapsed(), 2.5);
+    }
+
+    #[test]
+    fn clock_test_scale() {
+        let now = Rc::new(Cell::new(0.0f64));
+        let source = now.clone();
+        let mut clock = Clock::new(move || source.get());
+        now.set(1.0);
+        clock.set_scale(0.5);
+        assert_eq!(clock.elapsed(), 1.0);
+        now.set(3.0);
+        assert_eq!(clock.elapsed(), 2.0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+extern crate nalgebra_glm as glm;
+
+use std::mem;
+use gl::types::*;
+
+// Uniform buffer holding the projection and view matrices shared by all shaders declaring
+//
+//     layout (std140) uniform Matrices
+//     {
+//         mat4 projection;
+//         mat4 view;
+//     };
+//
+// so they are uploaded once per frame instead of once per shader. The block of each shader has to be
+// bound to the same binding point with `Shader::bind_uniform_block("Matrices", MatricesUbo::BINDING)`.
+pub struct MatricesUbo {
+    ubo: u32
+}
+
+impl MatricesUbo {
+    pub const BLOCK_NAME: &'static str = "Matrices";
+    pub const BINDING: u32 = 0;
+    const MATRIX_SIZE: usize = mem::size_of::<glm::TMat4<f32>>();
+
+    // creates the buffer (both matrices zeroed) and binds it to binding point `BINDING`
+    pub fn new() -> Self {
+        let mut ubo = 0u32;
+        unsafe {
+            gl::GenBuffers(1, &mut ubo);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, ubo);
+            let zeroed = [0f32; 32];
+            gl::BufferData(gl::UNIFORM_BUFFER, (2 * Self::MATRIX_SIZE) as GLsizeiptr, zeroed.as_ptr() as *const _, gl::DYNAMIC_DRAW);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+            // link the whole buffer to the binding point
+            gl::BindBufferRange(gl::UNIFORM_BUFFER, Self::BINDING, ubo, 0, (2 * Self::MATRIX_SIZE) as GLsizeiptr);
+        }
+        Self { ubo }
+    }
+
+    pub fn set_projection(&self, projection: &glm::TMat4<f32>) {
+        self.store(0, projection);
+    }
+
+    pub fn set_view(&self, view: &glm::TMat4<f32>) {
+        self.store(Self::MATRIX_SIZE, view);
+    }
+
+    fn store(&self, offset: usize, matrix: &glm::TMat4<f32>) {
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.ubo);
+            gl::BufferSubData(gl::UNIFORM_BUFFER, offset as GLintptr, Self::MATRIX_SIZE as GLsizeiptr, glm::value_ptr(matrix).as_ptr() as *const _);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.ubo
+    }
+}
+
+impl Default for MatricesUbo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MatricesUbo {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.ubo);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+
+    fn read_matrix(ubo: &MatricesUbo, offset: usize) -> glm::TMat4<f32> {
+        let mut data = [0f32; 16];
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, ubo.id());
+            gl::GetBufferSubData(gl::UNIFORM_BUFFER, offset as GLintptr, MatricesUbo::MATRIX_SIZE as GLsizeiptr, data.as_mut_ptr() as *mut _);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        }
+        glm::make_mat4(&data)
+    }
+
+    #[test]
+    fn matrices_ubo_test_read_back() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let ubo = MatricesUbo::new();
+        let projection = glm::perspective(4.0 / 3.0, 45f32.to_radians(), 0.1, 100.0);
+        let view = glm::look_at(&glm::vec3(1.0, 2.0, 3.0), &glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 1.0, 0.0));
+        ubo.set_projection(&projection);
+        ubo.set_view(&view);
+        assert_eq!(read_matrix(&ubo, 0), projection);
+        assert_eq!(read_matrix(&ubo, MatricesUbo::MATRIX_SIZE), view);
+
+        // the buffer is what's bound to the binding point
+        let mut bound = 0 as GLint;
+        unsafe {
+            gl::GetIntegeri_v(gl::UNIFORM_BUFFER_BINDING, MatricesUbo::BINDING, &mut bound);
+        }
+        assert_eq!(bound as u32, ubo.id());
+    }
+}
\ No newline at end of file
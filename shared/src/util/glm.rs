@@ -136,6 +136,10 @@ pub fn mat2_from_mat3(src: &glm::TMat3<f32>) -> glm::TMat2<f32> {
     )
 }
 
+pub fn normal_matrix(model: &glm::TMat4<f32>) -> glm::TMat3<f32> {
+    glm::transpose(&glm::inverse(&mat3_from_mat4(model)))
+}
+
 pub fn vec3_wrap_vec2(target: &glm::TVec2<f32>, z: f32) -> glm::TVec3<f32> {
     glm::vec3(target.x, target.y, z)
 }
@@ -185,6 +189,22 @@ pub fn clamp(x: f32, min_val: f32, max_val: f32) -> f32 {
     max_val.min(min_val.max(x))
 }
 
+// linear interpolation from `a` (t = 0) to `b` (t = 1), `t` is not clamped
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
+
+pub fn lerp_vec3(a: &glm::TVec3<f32>, b: &glm::TVec3<f32>, t: f32) -> glm::TVec3<f32> {
+    a + (b - a) * t
+}
+
+// Hermite interpolation like GLSL's smoothstep: 0 up to `edge0`, 1 from `edge1` on and a smooth
+// curve in between
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = clamp((x - edge0) / (edge1 - edge0), 0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
 pub fn ortho(left: f32, right: f32, bottom: f32, top: f32) -> glm::TMat4<f32> {
     let mut result = diag_mat4(1.0);
     result[(0, 0)] = 2f32 / (right - left);
@@ -195,6 +215,50 @@ pub fn ortho(left: f32, right: f32, bottom: f32, top: f32) -> glm::TMat4<f32> {
     result
 }
 
+// view-projection matrices of the six faces of a point light's shadow cubemap, in GL face order
+// (+X, -X, +Y, -Y, +Z, -Z) with the up vectors cubemap lookups expect. The faces are square, so the
+// 90 degree projection always has an aspect of 1.
+pub fn cubemap_light_space_matrices(light_pos: &glm::TVec3<f32>, near: f32, far: f32) -> [glm::TMat4<f32>; 6] {
+    let shadow_proj = glm::perspective(1.0, 90f32.to_radians(), near, far);
+    let faces = [
+        (glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, -1.0, 0.0)),
+        (glm::vec3(-1.0, 0.0, 0.0), glm::vec3(0.0, -1.0, 0.0)),
+        (glm::vec3(0.0, 1.0, 0.0), glm::vec3(0.0, 0.0, 1.0)),
+        (glm::vec3(0.0, -1.0, 0.0), glm::vec3(0.0, 0.0, -1.0)),
+        (glm::vec3(0.0, 0.0, 1.0), glm::vec3(0.0, -1.0, 0.0)),
+        (glm::vec3(0.0, 0.0, -1.0), glm::vec3(0.0, -1.0, 0.0))
+    ];
+    faces.map(|(direction, up)| shadow_proj * glm::look_at(light_pos, &(light_pos + direction), &up))
+}
+
+// turns a [0, 1] depth buffer value of a perspective projection back into the linear distance along
+// the view direction: `near` for 0 and `far` for 1 (LinearizeDepth in resources/shaders/include/depth.glsl)
+pub fn linearize_depth(depth: f32, near: f32, far: f32) -> f32 {
+    let z = depth * 2.0 - 1.0; // back to NDC
+    (2.0 * near * far) / (far + near - z * (far - near))
+}
+
+// the view space position of the point at `ndc` (all components in [-1, 1], depth included), undoing
+// the projection the inverse of which is `inverse_projection`
+pub fn reconstruct_view_pos(ndc: &glm::TVec3<f32>, inverse_projection: &glm::TMat4<f32>) -> glm::TVec3<f32> {
+    let view_pos = inverse_projection * vec4_wrap_vec3(ndc, 1.0);
+    vec3_from_vec4(&view_pos) / view_pos.w
+}
+
+// the distance along the ray from `origin` in the normalized direction `dir` to where it first enters the
+// sphere, or where it leaves it when starting inside; None if the sphere is missed or behind the ray
+pub fn ray_sphere_intersect(origin: &glm::TVec3<f32>, dir: &glm::TVec3<f32>, center: &glm::TVec3<f32>, radius: f32) -> Option<f32> {
+    let to_origin = origin - center;
+    let b = glm::dot(&to_origin, dir);
+    let c = glm::dot(&to_origin, &to_origin) - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    [-b - root, -b + root].into_iter().find(|t| *t >= 0.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -382,6 +446,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mat3_value_ptr_round_trip_test() {
+        // set_mat3 hands glm::value_ptr to glUniformMatrix3fv with transpose off,
+        // so the column-major layout has to rebuild the very same matrix
+        let mat3 = glm::mat3(
+            11.0, 12.0, 13.0,
+            21.0, 22.0, 23.0,
+            31.0, 32.0, 33.0
+        );
+        let uploaded = glm::value_ptr(&mat3);
+        assert_eq!(&uploaded[..3], &[11.0, 21.0, 31.0]);
+        assert_eq!(glm::make_mat3(uploaded), mat3);
+    }
+
+    #[test]
+    fn normal_matrix_test() {
+        let mut model = diag_mat4(1.0);
+        model = glm::rotate(&model, 30f32.to_radians(), &glm::vec3(0.0, 1.0, 0.0));
+        model = glm::scale(&model, &glm::vec3(1.0, 4.0, 1.0));
+        let tangent = glm::vec3(1.0, 1.0, 0.0);
+        let normal = glm::vec3(1.0, -1.0, 0.0);
+        let world_tangent = mat3_from_mat4(&model) * tangent;
+        let world_normal = normal_matrix(&model) * normal;
+        // normals transformed by the normal matrix stay perpendicular to the surface
+        assert!(glm::dot(&world_tangent, &world_normal).abs() < 1e-5);
+    }
+
+    #[test]
+    fn normal_matrix_test_matches_hand_computed_inverse_transpose() {
+        // the bloom floor: translation drops out and each scale factor gets inverted
+        let mut model = diag_mat4(1.0);
+        model = glm::translate(&model, &glm::vec3(0.0, -1.0, 0.0));
+        model = glm::scale(&model, &glm::vec3(12.5, 0.5, 12.5));
+        let close = |a: &glm::TMat3<f32>, b: &glm::TMat3<f32>| a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-6);
+        assert!(close(&normal_matrix(&model), &diag_mat3_nums(0.08, 2.0, 0.08)));
+
+        // a shear along x: [[1, 2, 0], [0, 1, 0], [0, 0, 1]] inverts to [[1, -2, 0], [0, 1, 0], [0, 0, 1]]
+        let shear = mat4_wrap_mat3(&glm::mat3(
+            1.0, 2.0, 0.0,
+            0.0, 1.0, 0.0,
+            0.0, 0.0, 1.0
+        ));
+        let expected = glm::mat3(
+            1.0, 0.0, 0.0,
+            -2.0, 1.0, 0.0,
+            0.0, 0.0, 1.0
+        );
+        assert!(close(&normal_matrix(&shear), &expected), "Got {:?}.", normal_matrix(&shear));
+    }
+
     #[test]
     fn mat2_from_mat4_test() {
         let mat4 = glm::mat4(
@@ -533,4 +647,87 @@ mod tests {
     fn ortho_test() {
         //TODO
     }
+
+    #[test]
+    fn lerp_test() {
+        assert_eq!(lerp(0.1, 1.0, 0.0), 0.1);
+        assert!((lerp(0.1, 1.0, 1.0) - 1.0).abs() < 1e-6);
+        assert!((lerp(0.1, 1.0, 0.5) - 0.55).abs() < 1e-6);
+        assert_eq!(lerp(2.0, 4.0, 1.5), 5.0);
+    }
+
+    #[test]
+    fn lerp_vec3_test() {
+        let a = glm::vec3(0.0, 1.0, -2.0);
+        let b = glm::vec3(4.0, 1.0, 2.0);
+        assert_eq!(lerp_vec3(&a, &b, 0.0), a);
+        assert_eq!(lerp_vec3(&a, &b, 1.0), b);
+        assert_eq!(lerp_vec3(&a, &b, 0.5), glm::vec3(2.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn smoothstep_test() {
+        assert_eq!(smoothstep(1.0, 3.0, 1.0), 0.0);
+        assert_eq!(smoothstep(1.0, 3.0, 3.0), 1.0);
+        assert_eq!(smoothstep(1.0, 3.0, 2.0), 0.5);
+        // clamped outside of the edges
+        assert_eq!(smoothstep(1.0, 3.0, -5.0), 0.0);
+        assert_eq!(smoothstep(1.0, 3.0, 10.0), 1.0);
+        // eased, not linear
+        assert!(smoothstep(1.0, 3.0, 1.5) < 0.25);
+    }
+
+    #[test]
+    fn cubemap_light_space_matrices_test_positive_x() {
+        let light_pos = glm::vec3(1.0, 2.0, 3.0);
+        let matrices = cubemap_light_space_matrices(&light_pos, 0.5, 25.0);
+        let to_ndc = |matrix: &glm::TMat4<f32>, point: glm::TVec3<f32>| {
+            let clip = matrix * vec4_wrap_vec3(&point, 1.0);
+            assert!(clip.w > 0.0, "{:?} is behind the face.", point);
+            vec3_from_vec4(&clip) / clip.w
+        };
+        // one unit along +X from the light lands in the center of the +X face, inside the depth range
+        let center = to_ndc(&matrices[0], light_pos + glm::vec3(1.0, 0.0, 0.0));
+        assert!(center.x.abs() < 1e-5 && center.y.abs() < 1e-5, "Got {:?}.", center);
+        assert!(center.z > -1.0 && center.z < 1.0, "Got {:?}.", center);
+        // the face covers a 90 degree cone, with +Y pointing down like the cubemap convention wants
+        let upper = to_ndc(&matrices[0], light_pos + glm::vec3(1.0, 0.5, 0.0));
+        assert!((upper.y + 0.5).abs() < 1e-5, "Got {:?}.", upper);
+        // the same point is not in front of the -X face
+        let clip = matrices[1] * vec4_wrap_vec3(&(light_pos + glm::vec3(1.0, 0.0, 0.0)), 1.0);
+        assert!(clip.w < 0.0);
+    }
+
+    #[test]
+    fn linearize_depth_test_planes() {
+        // the near plane is written as 0 and the far plane as 1 into the depth buffer
+        assert!((linearize_depth(0.0, 0.1, 100.0) - 0.1).abs() < 1e-5);
+        assert!((linearize_depth(1.0, 0.1, 100.0) - 100.0).abs() < 1e-2);
+        // half way in the depth buffer is much closer than half way in view space
+        assert!(linearize_depth(0.5, 0.1, 100.0) < 1.0);
+    }
+
+    #[test]
+    fn reconstruct_view_pos_test_round_trip() {
+        let projection = glm::perspective(800.0 / 600.0, 45f32.to_radians(), 0.1, 100.0);
+        let view_pos = glm::vec3(1.0, -2.0, -7.5);
+        let clip = projection * vec4_wrap_vec3(&view_pos, 1.0);
+        let ndc = vec3_from_vec4(&clip) / clip.w;
+        let reconstructed = reconstruct_view_pos(&ndc, &glm::inverse(&projection));
+        assert!((reconstructed - view_pos).norm() < 1e-3, "Got {:?}.", reconstructed);
+        // the linearized depth agrees with the reconstructed position
+        assert!((linearize_depth(ndc.z * 0.5 + 0.5, 0.1, 100.0) + reconstructed.z).abs() < 1e-3);
+    }
+
+    #[test]
+    fn ray_sphere_intersect_test() {
+        let center = empty_vec3();
+        let forward = glm::vec3(0.0, 0.0, -1.0);
+        // straight at it, from inside and from behind it
+        assert_eq!(ray_sphere_intersect(&glm::vec3(0.0, 0.0, 3.0), &forward, &center, 1.0), Some(2.0));
+        assert_eq!(ray_sphere_intersect(&center, &forward, &center, 1.0), Some(1.0));
+        assert_eq!(ray_sphere_intersect(&glm::vec3(0.0, 0.0, -3.0), &forward, &center, 1.0), None);
+        // passing beside it
+        assert_eq!(ray_sphere_intersect(&glm::vec3(1.5, 0.0, 3.0), &forward, &center, 1.0), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+extern crate nalgebra_glm as glm;
+
+use std::ptr;
+use gl::types::*;
+use crate::{filesystem, primitives};
+use crate::shader::Shader;
+
+// image based lighting helpers: baking environment maps into cubemaps for the PBR examples
+// ------------------------------------------------------------------------
+
+// projection and view matrices of a 90 degree camera at the origin looking at the six cubemap faces
+// in GL order (+X, -X, +Y, -Y, +Z, -Z), the up vectors match the cubemap face orientation
+pub fn capture_matrices() -> (glm::TMat4<f32>, [glm::TMat4<f32>; 6]) {
+    let projection = glm::perspective(1.0, 90f32.to_radians(), 0.1, 10.0);
+    let origin = glm::vec3(0.0, 0.0, 0.0);
+    let views = [
+        glm::look_at(&origin, &glm::vec3(1.0, 0.0, 0.0), &glm::vec3(0.0, -1.0, 0.0)),
+        glm::look_at(&origin, &glm::vec3(-1.0, 0.0, 0.0), &glm::vec3(0.0, -1.0, 0.0)),
+        glm::look_at(&origin, &glm::vec3(0.0, 1.0, 0.0), &glm::vec3(0.0, 0.0, 1.0)),
+        glm::look_at(&origin, &glm::vec3(0.0, -1.0, 0.0), &glm::vec3(0.0, 0.0, -1.0)),
+        glm::look_at(&origin, &glm::vec3(0.0, 0.0, 1.0), &glm::vec3(0.0, -1.0, 0.0)),
+        glm::look_at(&origin, &glm::vec3(0.0, 0.0, -1.0), &glm::vec3(0.0, -1.0, 0.0))
+    ];
+    (projection, views)
+}
+
+// converts an equirectangular environment map (e.g. uploaded by `util::image::upload_hdr_texture`)
+// into a size x size GL_RGB16F cubemap by rendering a cube around a camera at the origin once per
+// face. The bound framebuffer and the viewport are restored afterwards. Panics if the capture
+// framebuffer isn't complete (e.g. if the driver can't render to RGB16F).
+pub fn equirect_to_cubemap(equirect_tex: u32, size: u32) -> u32 {
+    let shader = Shader::new(
+        filesystem::get_path("resources/shaders/ibl/cubemap_capture.vs".to_string()),
+        filesystem::get_path("resources/shaders/ibl/equirectangular_to_cubemap.fs".to_string()),
+        None
+    );
+    let (projection, views) = capture_matrices();
+
+    let mut cubemap = 0u32;
+    unsafe {
+        let mut previous_fbo = 0 as GLint;
+        let mut previous_viewport = [0 as GLint; 4];
+        gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_fbo);
+        gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+
+        // setup cubemap to render to
+        gl::GenTextures(1, &mut cubemap);
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap);
+        for i in 0..6 {
+            gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + i, 0, gl::RGB16F as _, size as _, size as _, 0, gl::RGB, gl::FLOAT, ptr::null());
+        }
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+
+        // setup capture framebuffer
+        let (mut capture_fbo, mut capture_rbo) = (0u32, 0u32);
+        gl::GenFramebuffers(1, &mut capture_fbo);
+        gl::GenRenderbuffers(1, &mut capture_rbo);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, capture_fbo);
+        gl::BindRenderbuffer(gl::RENDERBUFFER, capture_rbo);
+        gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, size as _, size as _);
+        gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, capture_rbo);
+        gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_CUBE_MAP_POSITIVE_X, cubemap, 0);
+        let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+        assert_eq!(status, gl::FRAMEBUFFER_COMPLETE, "The cubemap capture framebuffer is not complete.");
+
+        // convert HDR equirectangular environment map to cubemap equivalent
+        shader.use_shader();
+        shader.set_int("equirectangularMap".to_string(), 0);
+        shader.set_mat4("projection".to_string(), &projection);
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, equirect_tex);
+
+        gl::Viewport(0, 0, size as _, size as _); // don't forget to configure the viewport to the capture dimensions.
+        for (i, view) in views.iter().enumerate() {
+            shader.set_mat4("view".to_string(), view);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32, cubemap, 0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            primitives::render_cube();
+        }
+
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous_fbo as _);
+        gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        gl::DeleteRenderbuffers(1, &capture_rbo);
+        gl::DeleteFramebuffers(1, &capture_fbo);
+    }
+
+    cubemap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+
+    #[test]
+    fn capture_matrices_test_face_directions() {
+        // the center of each face ends up straight in front of the camera (-Z in view space)
+        let (_, views) = capture_matrices();
+        let directions = [
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(-1.0, 0.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(0.0, -1.0, 0.0),
+            glm::vec3(0.0, 0.0, 1.0),
+            glm::vec3(0.0, 0.0, -1.0)
+        ];
+        for (view, direction) in views.iter().zip(directions.iter()) {
+            let in_view = view * glm::vec4(direction.x, direction.y, direction.z, 0.0);
+            assert!((in_view - glm::vec4(0.0, 0.0, -1.0, 0.0)).abs().max() < 1e-6, "{:?} is at {:?}.", direction, in_view);
+        }
+    }
+
+    #[test]
+    fn equirect_to_cubemap_test_uniform_color() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        primitives::reset();
+        let color = [0.5f32, 1.0, 2.0];
+        let texels: Vec<f32> = color.iter().cycle().take(4 * 2 * 3).copied().collect();
+        let mut equirect = 0u32;
+        unsafe {
+            gl::GenTextures(1, &mut equirect);
+            gl::BindTexture(gl::TEXTURE_2D, equirect);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB16F as _, 4, 2, 0, gl::RGB, gl::FLOAT, texels.as_ptr() as *const _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+            gl::Viewport(0, 0, 4, 4);
+        }
+
+        let cubemap = equirect_to_cubemap(equirect, 8);
+
+        let mut viewport = [0 as GLint; 4];
+        let mut face = vec![0f32; 8 * 8 * 3];
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap);
+            gl::GetTexImage(gl::TEXTURE_CUBE_MAP_NEGATIVE_Z, 0, gl::RGB, gl::FLOAT, face.as_mut_ptr() as *mut _);
+            gl::DeleteTextures(1, &cubemap);
+            gl::DeleteTextures(1, &equirect);
+        }
+        assert_eq!(viewport, [0, 0, 4, 4]);
+        for texel in face.chunks(3) {
+            assert!(texel.iter().zip(color.iter()).all(|(a, b)| (a - b).abs() < 1e-3), "{:?}", texel);
+        }
+        primitives::reset();
+    }
+}
\ No newline at end of file
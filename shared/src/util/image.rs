@@ -15,33 +15,465 @@
 // limitations under the License.
 
 use std::error::Error;
+use std::ffi::CStr;
+use std::fs;
+use gl::types::*;
 use image::{RgbaImage, RgbImage};
 use image::io::Reader as ImageReader;
+use crate::filesystem;
+
+// from GL_EXT_texture_filter_anisotropic (core since 4.6, the bindings only go up to 4.5)
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+const ANISOTROPY_EXTENSIONS: [&str; 2] = ["GL_EXT_texture_filter_anisotropic", "GL_ARB_texture_filter_anisotropic"];
+// from GL_EXT_texture_compression_s3tc (never made core)
+const COMPRESSED_RGBA_S3TC_DXT1: GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT5: GLenum = 0x83F3;
+// their sRGB variants, from GL_EXT_texture_sRGB
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: GLenum = 0x8C4F;
+// DDS files start with this magic number followed by a 124 byte header, the header of a "DX10" file
+// is followed by another 20 bytes
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+const DDS_HEADER_END: usize = 128;
+const DDS_DX10_HEADER_END: usize = DDS_HEADER_END + 20;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
 
 pub fn load_image_data_rgb(path: String) -> Result<RgbImage, Box<dyn Error>> {
-    let img = ImageReader::open(path)?.with_guessed_format()?.decode()?.flipv();
+    let img = ImageReader::open(filesystem::check_exists(&path)?)?.with_guessed_format()?.decode()?.flipv();
     Ok(img.to_rgb8())
 }
 
 pub fn load_image_data_rgba(path: String) -> Result<RgbaImage, Box<dyn Error>> {
-    let img = ImageReader::open(path)?.with_guessed_format()?.decode()?.flipv();
+    let img = ImageReader::open(filesystem::check_exists(&path)?)?.with_guessed_format()?.decode()?.flipv();
     Ok(img.to_rgba8())
 }
 
+// high dynamic range image with tightly packed RGB float texels, bottom row first
+#[derive(Clone, Debug)]
+pub struct HdrImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<f32>
+}
+
+// loads a radiance (.hdr) or other floating point image, e.g. an equirectangular environment map
+pub fn load_hdr(path: String) -> Result<HdrImage, Box<dyn Error>> {
+    let img = ImageReader::open(filesystem::check_exists(&path)?)?.with_guessed_format()?.decode()?.flipv().to_rgb32f();
+    Ok(HdrImage {
+        width: img.width(),
+        height: img.height(),
+        data: img.into_raw()
+    })
+}
+
 pub fn load_image_data_rgb_without_flip(path: String) -> Result<RgbImage, Box<dyn Error>> {
-    let img = ImageReader::open(path)?.with_guessed_format()?.decode()?;
+    let img = ImageReader::open(filesystem::check_exists(&path)?)?.with_guessed_format()?.decode()?;
     Ok(img.to_rgb8())
 }
 
 pub fn load_image_data_rgba_without_flip(path: String) -> Result<RgbaImage, Box<dyn Error>> {
-    let img = ImageReader::open(path)?.with_guessed_format()?.decode()?;
+    let img = ImageReader::open(filesystem::check_exists(&path)?)?.with_guessed_format()?.decode()?;
     Ok(img.to_rgba8())
 }
 
+// sampling parameters applied by load_texture_2d_with_options; the defaults match what most
+// of the examples use: repeating, trilinear filtered and flipped to OpenGL's bottom-left origin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureOptions {
+    pub wrap_s: GLenum,
+    pub wrap_t: GLenum,
+    pub min_filter: GLenum,
+    pub mag_filter: GLenum,
+    pub flip_vertically: bool,
+    // use the highest anisotropic filtering the hardware supports (see `set_anisotropic_filtering`),
+    // sharpens textures looked at from grazing angles like floors
+    pub anisotropic: bool
+}
+
+impl TextureOptions {
+    // for this tutorial: use GL_CLAMP_TO_EDGE to prevent semi-transparent borders. Due to interpolation it takes texels from next repeat
+    pub fn clamp_to_edge() -> Self {
+        Self {
+            wrap_s: gl::CLAMP_TO_EDGE,
+            wrap_t: gl::CLAMP_TO_EDGE,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            wrap_s: gl::REPEAT,
+            wrap_t: gl::REPEAT,
+            min_filter: gl::LINEAR_MIPMAP_LINEAR,
+            mag_filter: gl::LINEAR,
+            flip_vertically: true,
+            anisotropic: false
+        }
+    }
+}
+
+// loads a 2D texture from file with mipmaps and the default sampling parameters, returning the GL texture id.
+// With gamma set the texels are stored as sRGB so they get linearized when sampled.
+// -------------------------------------------------------
+pub fn load_texture_2d(path: String, gamma: bool) -> u32 {
+    load_texture_2d_with_options(path, gamma, &TextureOptions::default())
+}
+
+pub fn load_texture_2d_with_options(path: String, gamma: bool, options: &TextureOptions) -> u32 {
+    let img = if options.flip_vertically {
+        load_image_data_rgba(path)
+    } else {
+        load_image_data_rgba_without_flip(path)
+    }.unwrap_or_else(|err| panic!("Failed to load texture data: {}", err));
+    let width = img.width();
+    let height = img.height();
+    let data = img.as_raw();
+
+    let mut texture_id = 0u32;
+    unsafe {
+        gl::GenTextures(1, &mut texture_id);
+        gl::BindTexture(gl::TEXTURE_2D, texture_id);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            if gamma { gl::SRGB_ALPHA } else { gl::RGBA } as _,
+            width as _,
+            height as _,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data.as_ptr() as _
+        );
+        gl::GenerateMipmap(gl::TEXTURE_2D);
+
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, options.wrap_s as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, options.wrap_t as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, options.min_filter as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, options.mag_filter as _);
+    }
+    if options.anisotropic {
+        set_anisotropic_filtering(gl::TEXTURE_2D, true);
+    }
+
+    texture_id
+}
+
+// whether one of the anisotropic filtering extensions is in the given extension names
+pub fn supports_anisotropic_filtering<'a>(mut extensions: impl Iterator<Item = &'a str>) -> bool {
+    extensions.any(|extension| ANISOTROPY_EXTENSIONS.contains(&extension))
+}
+
+// the highest anisotropy level the hardware supports, None if the current context doesn't support
+// anisotropic filtering at all
+pub fn max_anisotropy() -> Option<f32> {
+    if !supports_anisotropic_filtering(extension_names().iter().map(String::as_str)) {
+        return None;
+    }
+    unsafe {
+        let mut max = 1f32;
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+        Some(max)
+    }
+}
+
+// the names of all extensions of the current context
+fn extension_names() -> Vec<String> {
+    unsafe {
+        let mut count = 0 as GLint;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        (0..count as GLuint)
+            .map(|i| CStr::from_ptr(gl::GetStringi(gl::EXTENSIONS, i) as *const _).to_string_lossy().into_owned())
+            .collect()
+    }
+}
+
+// turns anisotropic filtering of the texture bound to `target` up to the hardware maximum, or back to
+// plain trilinear filtering. Does nothing if anisotropic filtering isn't supported.
+pub fn set_anisotropic_filtering(target: GLenum, enabled: bool) {
+    if let Some(max) = max_anisotropy() {
+        unsafe {
+            gl::TexParameterf(target, TEXTURE_MAX_ANISOTROPY, if enabled { max } else { 1.0 });
+        }
+    }
+}
+
+// uploads an HDR image as a GL_RGB16F texture so values above 1.0 survive, returning the GL texture id
+// -------------------------------------------------------
+pub fn upload_hdr_texture(img: &HdrImage) -> u32 {
+    let mut texture_id = 0u32;
+    unsafe {
+        gl::GenTextures(1, &mut texture_id);
+        gl::BindTexture(gl::TEXTURE_2D, texture_id);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGB16F as _,
+            img.width as _,
+            img.height as _,
+            0,
+            gl::RGB,
+            gl::FLOAT,
+            img.data.as_ptr() as _
+        );
+
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+    }
+
+    texture_id
+}
+
+// number of levels of a full mip chain for a width x height texture: floor(log2(max(width, height))) + 1
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).leading_zeros()
+}
+
+// loads a cubemap texture from 6 individual texture faces
+// order:
+// +X (right)
+// -X (left)
+// +Y (top)
+// -Y (bottom)
+// +Z (front)
+// -Z (back)
+// -------------------------------------------------------
+pub fn load_cubemap(faces: &[String]) -> u32 {
+    let mut texture_id = 0u32;
+    unsafe {
+        gl::GenTextures(1, &mut texture_id);
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
+
+        for (i, face) in faces.iter().enumerate() {
+            let img = load_image_data_rgb_without_flip(face.clone())
+                .unwrap_or_else(|err| panic!("Failed to load texture data: {}", err));
+            let width = img.width();
+            let height = img.height();
+            let data = img.as_raw();
+
+            gl::TexImage2D(
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
+                0,
+                gl::RGB as _,
+                width as _,
+                height as _,
+                0,
+                gl::RGB,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as _
+            );
+        }
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+        gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as _);
+    }
+
+    texture_id
+}
+
+// reads the RGBA pixels of the lower left width x height area of the bound read framebuffer,
+// returned top row first like regular images (GL's origin is the bottom left corner)
+pub fn read_pixels_rgba(width: u32, height: u32) -> RgbaImage {
+    let mut img = RgbaImage::new(width, height);
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(0, 0, width as _, height as _, gl::RGBA, gl::UNSIGNED_BYTE, img.as_mut_ptr() as _);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+    }
+    image::imageops::flip_vertical_in_place(&mut img);
+    img
+}
+
+// writes the current contents of the bound read framebuffer to an image file, the format is
+// picked from the extension of `path` (e.g. ".png")
+pub fn capture_screenshot(width: u32, height: u32, path: &str) -> Result<(), Box<dyn Error>> {
+    read_pixels_rgba(width, height).save(path)?;
+    Ok(())
+}
+
+// 64-bit FNV-1a hash of the pixel data. Unlike the hashers of the standard library its values are
+// stable across Rust versions and platforms, so they can be kept as golden values in tests.
+pub fn hash_pixels(img: &RgbaImage) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in img.as_raw() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// block compressed formats the GPU samples directly, each block encodes 4x4 texels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedFormat {
+    // RGB with 1 bit alpha in 8 bytes per block (DXT1)
+    Bc1,
+    // RGB as in BC1 plus interpolated alpha, 16 bytes per block (DXT5)
+    Bc3,
+    // high quality RGBA in 16 bytes per block
+    Bc7
+}
+
+impl CompressedFormat {
+    pub fn block_size(self) -> usize {
+        match self {
+            CompressedFormat::Bc1 => 8,
+            CompressedFormat::Bc3 | CompressedFormat::Bc7 => 16
+        }
+    }
+
+    // the GL internal format, with `srgb` the one that decodes the texels from sRGB when sampling
+    pub fn gl_format(self, srgb: bool) -> GLenum {
+        match (self, srgb) {
+            (CompressedFormat::Bc1, false) => COMPRESSED_RGBA_S3TC_DXT1,
+            (CompressedFormat::Bc1, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+            (CompressedFormat::Bc3, false) => COMPRESSED_RGBA_S3TC_DXT5,
+            (CompressedFormat::Bc3, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+            (CompressedFormat::Bc7, false) => gl::COMPRESSED_RGBA_BPTC_UNORM,
+            (CompressedFormat::Bc7, true) => gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM
+        }
+    }
+
+    // the extensions that each make the format available
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            CompressedFormat::Bc1 | CompressedFormat::Bc3 => &["GL_EXT_texture_compression_s3tc"],
+            CompressedFormat::Bc7 => &["GL_ARB_texture_compression_bptc", "GL_EXT_texture_compression_bptc"]
+        }
+    }
+
+    // whether one of the format's extensions is in the given extension names
+    pub fn is_supported<'a>(self, mut extensions: impl Iterator<Item = &'a str>) -> bool {
+        extensions.any(|extension| self.extensions().contains(&extension))
+    }
+
+    // the number of bytes of a width x height mip level, None if that doesn't fit in a usize
+    pub fn level_size(self, width: u32, height: u32) -> Option<usize> {
+        (width.div_ceil(4).max(1) as usize)
+            .checked_mul(height.div_ceil(4).max(1) as usize)?
+            .checked_mul(self.block_size())
+    }
+}
+
+// the edge length of mip level `level` of a texture that's `size` texels long at level 0
+fn level_extent(size: u32, level: u32) -> u32 {
+    size.checked_shr(level).unwrap_or(0).max(1)
+}
+
+// a block compressed texture with its whole mip chain as stored in the file, largest level first
+pub struct CompressedImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: CompressedFormat,
+    // whether the texels are sRGB encoded (the *_SRGB DXGI formats)
+    pub srgb: bool,
+    pub levels: Vec<Vec<u8>>
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+// parses a DDS file with BC1, BC3 or BC7 data, either with a legacy "DXT1"/"DXT5" FourCC or a DX10 header
+pub fn parse_dds(bytes: &[u8]) -> Result<CompressedImage, Box<dyn Error>> {
+    if bytes.len() < DDS_HEADER_END || &bytes[0..4] != DDS_MAGIC {
+        return Err("Not a DDS file.".into());
+    }
+    let flags = read_u32(bytes, 8);
+    let height = read_u32(bytes, 12);
+    let width = read_u32(bytes, 16);
+    if width == 0 || height == 0 {
+        return Err("DDS file has an empty image.".into());
+    }
+    let level_count = if flags & DDSD_MIPMAPCOUNT != 0 { read_u32(bytes, 28).max(1) } else { 1 };
+    // the header comes from the file, a chain longer than the full one down to 1x1 is corrupt
+    if level_count > mip_level_count(width, height) {
+        return Err(format!("DDS file has {} mip levels, a {}x{} image has at most {}.",
+            level_count, width, height, mip_level_count(width, height)).into());
+    }
+    let four_cc = &bytes[84..88];
+    let (format, srgb, mut offset) = match four_cc {
+        b"DXT1" => (CompressedFormat::Bc1, false, DDS_HEADER_END),
+        b"DXT5" => (CompressedFormat::Bc3, false, DDS_HEADER_END),
+        b"DX10" if bytes.len() >= DDS_DX10_HEADER_END => {
+            // DXGI_FORMAT values, the UNORM variants followed by the UNORM_SRGB ones
+            let (format, srgb) = match read_u32(bytes, DDS_HEADER_END) {
+                71 => (CompressedFormat::Bc1, false),
+                72 => (CompressedFormat::Bc1, true),
+                77 => (CompressedFormat::Bc3, false),
+                78 => (CompressedFormat::Bc3, true),
+                98 => (CompressedFormat::Bc7, false),
+                99 => (CompressedFormat::Bc7, true),
+                dxgi_format => return Err(format!("Unsupported DXGI format {} in DDS file.", dxgi_format).into())
+            };
+            (format, srgb, DDS_DX10_HEADER_END)
+        }
+        _ => return Err(format!("Unsupported DDS pixel format {:?}.", String::from_utf8_lossy(four_cc)).into())
+    };
+
+    let mut levels = Vec::with_capacity(level_count as usize);
+    for level in 0..level_count {
+        let size = format.level_size(level_extent(width, level), level_extent(height, level))
+            .ok_or("DDS file is too large.")?;
+        let end = offset.checked_add(size).ok_or("DDS file is too large.")?;
+        let data = bytes.get(offset..end).ok_or("DDS file is truncated.")?;
+        levels.push(data.to_vec());
+        offset = end;
+    }
+    Ok(CompressedImage { width, height, format, srgb, levels })
+}
+
+// Loads a block compressed DDS texture with all the mip levels stored in the file, returning the GL
+// texture id. The data is uploaded as is, so the rows are not flipped: DDS stores the top row first,
+// which means the texture coordinates have to be flipped vertically instead. Fails if the driver lacks
+// the format's extension.
+pub fn load_compressed(path: String) -> Result<u32, Box<dyn Error>> {
+    let img = parse_dds(&fs::read(filesystem::check_exists(&path)?)?)?;
+    if !img.format.is_supported(extension_names().iter().map(String::as_str)) {
+        return Err(format!(
+            "{}: {:?} compressed textures aren't supported by the driver (needs {}).",
+            path, img.format, img.format.extensions().join(" or ")
+        ).into());
+    }
+
+    let mut texture_id = 0u32;
+    unsafe {
+        gl::GenTextures(1, &mut texture_id);
+        gl::BindTexture(gl::TEXTURE_2D, texture_id);
+        for (level, data) in img.levels.iter().enumerate() {
+            gl::CompressedTexImage2D(
+                gl::TEXTURE_2D,
+                level as _,
+                img.format.gl_format(img.srgb),
+                level_extent(img.width, level as u32) as _,
+                level_extent(img.height, level as u32) as _,
+                0,
+                data.len() as _,
+                data.as_ptr() as *const _
+            );
+        }
+        // files with a partial mip chain are still complete textures
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, (img.levels.len() - 1) as _);
+
+        let min_filter = if img.levels.len() > 1 { gl::LINEAR_MIPMAP_LINEAR } else { gl::LINEAR };
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+    }
+
+    Ok(texture_id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::filesystem;
+    use crate::gl_test::hidden_gl_context;
 
     #[test]
     fn load_image_data_rgb_test_existing() {
@@ -75,6 +507,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn load_image_data_rgba_test_names_missing_resource() {
+        let err = load_image_data_rgba(filesystem::get_path("resources/textures/i_am_not_existing.png".to_string()))
+            .expect_err("The file shouldn't exist.");
+        assert!(err.to_string().contains("resources/textures/i_am_not_existing.png"), "Got {}.", err);
+    }
+
     #[test]
     fn load_image_data_rgb_without_flip_test_existing() {
         let img = load_image_data_rgb_without_flip(
@@ -106,4 +545,223 @@ mod tests {
             panic!("The file shouldn't exist.");
         }
     }
+
+    #[test]
+    fn texture_options_default_test() {
+        let options = TextureOptions::default();
+        assert_eq!(options.wrap_s, gl::REPEAT);
+        assert_eq!(options.wrap_t, gl::REPEAT);
+        assert_eq!(options.min_filter, gl::LINEAR_MIPMAP_LINEAR);
+        assert_eq!(options.mag_filter, gl::LINEAR);
+        assert!(options.flip_vertically);
+        assert!(!options.anisotropic);
+    }
+
+    #[test]
+    fn mip_level_count_test() {
+        assert_eq!(mip_level_count(256, 128), 9);
+        assert_eq!(mip_level_count(300, 5), 9);
+        assert_eq!(mip_level_count(1, 1), 1);
+    }
+
+    #[test]
+    fn supports_anisotropic_filtering_test() {
+        assert!(supports_anisotropic_filtering(["GL_ARB_debug_output", "GL_EXT_texture_filter_anisotropic"].into_iter()));
+        assert!(supports_anisotropic_filtering(["GL_ARB_texture_filter_anisotropic"].into_iter()));
+        assert!(!supports_anisotropic_filtering(["GL_ARB_debug_output"].into_iter()));
+        assert!(!supports_anisotropic_filtering(std::iter::empty()));
+    }
+
+    #[test]
+    fn texture_options_clamp_to_edge_test() {
+        let options = TextureOptions::clamp_to_edge();
+        assert_eq!(options.wrap_s, gl::CLAMP_TO_EDGE);
+        assert_eq!(options.wrap_t, gl::CLAMP_TO_EDGE);
+        assert_eq!(options.min_filter, TextureOptions::default().min_filter);
+    }
+
+    #[test]
+    fn load_hdr_test_existing() {
+        let img = load_hdr(
+            filesystem::get_path("resources/textures/hdr/newport_loft.hdr".to_string()))
+            .expect("The file should exist.");
+        assert_ne!(img.width, 0);
+        assert_ne!(img.height, 0);
+        assert_eq!(img.data.len(), (img.width * img.height * 3) as usize);
+        // radiance maps keep the bright spots above the displayable range
+        assert!(img.data.iter().any(|it| *it > 1.0), "The image should contain HDR values.");
+    }
+
+    #[test]
+    fn load_hdr_test_not_existing() {
+        let img = load_hdr("I_AM_NOT_EXISTING".to_string());
+        if let Ok(_) = img {
+            panic!("The file shouldn't exist.");
+        }
+    }
+
+    #[test]
+    fn capture_screenshot_test_top_bottom_split() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let (width, height) = (16u32, 8u32);
+        let path = std::env::temp_dir().join(format!("learnopengl_capture_screenshot_test_{}.png", std::process::id()));
+        let (mut fbo, mut color_buffer) = (0u32, 0u32);
+        unsafe {
+            // render orange off-screen, with a blue bottom half (GL's y axis points up)
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::GenRenderbuffers(1, &mut color_buffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, color_buffer);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::RGBA8, width as _, height as _);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, color_buffer);
+            assert_eq!(gl::CheckFramebufferStatus(gl::FRAMEBUFFER), gl::FRAMEBUFFER_COMPLETE);
+            gl::Viewport(0, 0, width as _, height as _);
+            gl::ClearColor(1.0, 0.5, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(0, 0, width as _, (height / 2) as _);
+            gl::ClearColor(0.0, 0.0, 1.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::Disable(gl::SCISSOR_TEST);
+
+            capture_screenshot(width, height, path.to_str().unwrap()).expect("The screenshot should be written.");
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::DeleteRenderbuffers(1, &color_buffer);
+            gl::DeleteFramebuffers(1, &fbo);
+        }
+
+        let img = load_image_data_rgba_without_flip(path.to_str().unwrap().to_string())
+            .expect("The screenshot should be readable.");
+        std::fs::remove_file(&path).ok();
+        assert_eq!((img.width(), img.height()), (width, height));
+        // the image's first row is the top of the framebuffer
+        let top = img.get_pixel(width / 2, 0);
+        assert_eq!(top[0], 255);
+        assert!((top[1] as i32 - 128).abs() <= 1, "Got {:?}.", top);
+        assert_eq!(top[2], 0);
+        assert_eq!(top[3], 255);
+        assert_eq!(*img.get_pixel(width / 2, height - 1), image::Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn hash_pixels_test_known_values() {
+        assert_eq!(hash_pixels(&RgbaImage::new(0, 0)), 0xcbf29ce484222325);
+        let red = RgbaImage::from_pixel(1, 1, image::Rgba([255, 0, 0, 255]));
+        assert_eq!(hash_pixels(&red), 0x6960db6491cbfed3);
+        let blue = RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 255, 255]));
+        assert_ne!(hash_pixels(&red), hash_pixels(&blue));
+    }
+
+    // a DDS header with a DX10 extension header for a width x height texture of the DXGI format
+    fn dx10_header(width: u32, height: u32, dxgi_format: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; DDS_DX10_HEADER_END];
+        bytes[0..4].copy_from_slice(DDS_MAGIC);
+        bytes[4..8].copy_from_slice(&124u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[84..88].copy_from_slice(b"DX10");
+        bytes[DDS_HEADER_END..DDS_HEADER_END + 4].copy_from_slice(&dxgi_format.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn parse_dds_test_bc3_fixture() {
+        let bytes = fs::read(filesystem::get_path("resources/textures/dds/tiny_bc3.dds".to_string())).unwrap();
+        let img = parse_dds(&bytes).unwrap();
+        assert_eq!((img.width, img.height), (8, 8));
+        assert_eq!(img.format, CompressedFormat::Bc3);
+        assert!(!img.srgb);
+        // 8x8, 4x4, 2x2 and 1x1, the levels below 4x4 still take a whole block
+        let sizes: Vec<usize> = img.levels.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![64, 16, 16, 16]);
+    }
+
+    #[test]
+    fn parse_dds_test_dx10_bc7() {
+        let mut bytes = dx10_header(8, 4, 98);
+        bytes.extend_from_slice(&[0u8; 32]);
+        let img = parse_dds(&bytes).unwrap();
+        assert_eq!(img.format, CompressedFormat::Bc7);
+        assert!(!img.srgb);
+        assert_eq!(img.levels.len(), 1);
+        assert_eq!(img.levels[0].len(), 32);
+    }
+
+    #[test]
+    fn parse_dds_test_dx10_srgb() {
+        for (dxgi_format, format, block_size) in [
+            (72, CompressedFormat::Bc1, 8),
+            (78, CompressedFormat::Bc3, 16),
+            (99, CompressedFormat::Bc7, 16)
+        ] {
+            let mut bytes = dx10_header(4, 4, dxgi_format);
+            bytes.extend_from_slice(&vec![0u8; block_size]);
+            let img = parse_dds(&bytes).unwrap();
+            assert_eq!(img.format, format);
+            assert!(img.srgb, "DXGI format {} is sRGB.", dxgi_format);
+        }
+        assert_eq!(CompressedFormat::Bc7.gl_format(true), gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM);
+    }
+
+    #[test]
+    fn parse_dds_test_corrupt_header() {
+        // a mip count that a 4x4 image can't have (it has 3 levels) must not allocate or shift that far
+        for level_count in [4, 40, u32::MAX] {
+            let mut bytes = dx10_header(4, 4, 98);
+            bytes[8..12].copy_from_slice(&DDSD_MIPMAPCOUNT.to_le_bytes());
+            bytes[28..32].copy_from_slice(&level_count.to_le_bytes());
+            bytes.extend_from_slice(&[0u8; 48]);
+            assert!(parse_dds(&bytes).is_err(), "{} levels should be rejected.", level_count);
+        }
+        // sizes that overflow the offsets are reported as errors too
+        assert!(parse_dds(&dx10_header(u32::MAX, u32::MAX, 98)).is_err());
+        assert!(parse_dds(&dx10_header(0, 4, 98)).is_err());
+    }
+
+    #[test]
+    fn parse_dds_test_invalid() {
+        assert!(parse_dds(b"PNG not a dds file").is_err());
+        // BC5 isn't supported
+        let mut bytes = dx10_header(4, 4, 83);
+        bytes.extend_from_slice(&[0u8; 16]);
+        assert!(parse_dds(&bytes).is_err());
+        // the data of the only level is missing
+        assert!(parse_dds(&dx10_header(4, 4, 98)).is_err());
+    }
+
+    #[test]
+    fn compressed_format_is_supported_test() {
+        assert!(CompressedFormat::Bc3.is_supported(["GL_ARB_debug_output", "GL_EXT_texture_compression_s3tc"].into_iter()));
+        assert!(!CompressedFormat::Bc7.is_supported(["GL_EXT_texture_compression_s3tc"].into_iter()));
+        assert!(CompressedFormat::Bc7.is_supported(["GL_ARB_texture_compression_bptc"].into_iter()));
+    }
+
+    #[test]
+    fn load_compressed_test_uploads_all_levels() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let texture = match load_compressed(filesystem::get_path("resources/textures/dds/tiny_bc3.dds".to_string())) {
+            Ok(texture) => texture,
+            // drivers without S3TC can't run this test
+            Err(err) => {
+                assert!(err.to_string().contains("aren't supported"), "Unexpected error: {}", err);
+                return;
+            }
+        };
+        let (mut compressed, mut width, mut max_level) = (0, 0, 0);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_COMPRESSED, &mut compressed);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 3, gl::TEXTURE_WIDTH, &mut width);
+            gl::GetTexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, &mut max_level);
+            assert_eq!(gl::GetError(), gl::NO_ERROR);
+            gl::DeleteTextures(1, &texture);
+        }
+        assert_eq!(compressed, gl::TRUE as GLint);
+        assert_eq!((width, max_level), (1, 3));
+    }
 }
\ No newline at end of file
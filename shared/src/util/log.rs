@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+use std::sync::OnceLock;
+use ::log::{LevelFilter, Log, Metadata, Record};
+
+// minimal logger for the `log` crate facade, so examples can emit per-frame diagnostics with
+// `log::debug!` without flooding the terminal by default
+// ------------------------------------------------------------------------
+
+// installs a logger writing to stderr, configured through the RUST_LOG environment variable:
+// a comma separated list of `level` (applies to every target) and `target=level` directives, e.g.
+// `RUST_LOG=debug` or `RUST_LOG=learnopengl_5_5_1_parallax_mapping=debug,learnopengl_shared=warn`.
+// The longest matching target prefix wins. If RUST_LOG isn't set (or holds no valid directive)
+// nothing is printed at all. Calling it again after the first time has no effect.
+pub fn init() {
+    static LOGGER: OnceLock<StderrLogger> = OnceLock::new();
+    let logger = LOGGER.get_or_init(|| StderrLogger {
+        filter: parse_filter(&std::env::var("RUST_LOG").unwrap_or_default())
+    });
+    if ::log::set_logger(logger).is_ok() {
+        ::log::set_max_level(logger.filter.max_level());
+    }
+}
+
+struct StderrLogger {
+    filter: Filter
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let _ = writeln!(std::io::stderr(), "[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Filter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>
+}
+
+impl Filter {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets.iter()
+            .filter(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets.iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+// parses RUST_LOG style directives, invalid ones are skipped
+fn parse_filter(spec: &str) -> Filter {
+    let mut filter = Filter {
+        default: LevelFilter::Off,
+        targets: Vec::new()
+    };
+    for directive in spec.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+        match directive.split_once('=') {
+            Some((target, level)) => {
+                if let Ok(level) = level.trim().parse() {
+                    filter.targets.push((target.trim().to_string(), level));
+                }
+            }
+            None => {
+                if let Ok(level) = directive.parse() {
+                    filter.default = level;
+                }
+            }
+        }
+    }
+    filter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_filter_test_empty_is_silent() {
+        let filter = parse_filter("");
+        assert_eq!(filter.max_level(), LevelFilter::Off);
+        assert_eq!(filter.level_for("learnopengl_shared"), LevelFilter::Off);
+    }
+
+    #[test]
+    fn parse_filter_test_directives() {
+        let filter = parse_filter("warn, learnopengl_shared=debug,learnopengl_shared::util=error,bogus=loud");
+        assert_eq!(filter.targets.len(), 2);
+        assert_eq!(filter.level_for("learnopengl_5_6_hdr"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("learnopengl_shared::shader"), LevelFilter::Debug);
+        // the longest matching prefix wins
+        assert_eq!(filter.level_for("learnopengl_shared::util::image"), LevelFilter::Error);
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+    }
+}
\ No newline at end of file
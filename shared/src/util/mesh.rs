@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::mem;
+use gl::types::*;
+
+// Describes the attribute layout of an interleaved vertex buffer and builds a vertex array object from
+// it. Attributes are packed in the order they're added, so the stride and the offset of every attribute
+// follow from their sizes instead of being spelled out (and copy-pasted) by hand:
+//
+//     let cube = VaoBuilder::new()
+//         .attrib(0, 3, gl::FLOAT) // position
+//         .attrib(1, 2, gl::FLOAT) // texture coords
+//         .build(&cube_vertices);
+pub struct VaoBuilder {
+    attribs: Vec<Attrib>
+}
+
+struct Attrib {
+    location: u32,
+    size: i32,
+    gl_type: GLenum
+}
+
+impl VaoBuilder {
+    pub fn new() -> Self {
+        Self { attribs: Vec::new() }
+    }
+
+    // appends an attribute of `size` components of `gl_type` that the shader reads at `location`
+    pub fn attrib(mut self, location: u32, size: i32, gl_type: GLenum) -> Self {
+        self.attribs.push(Attrib { location, size, gl_type });
+        self
+    }
+
+    // size in bytes of one vertex
+    pub fn stride(&self) -> usize {
+        self.attribs.iter().map(Attrib::byte_size).sum()
+    }
+
+    // byte offset of every attribute within a vertex, in the order they were added
+    pub fn offsets(&self) -> Vec<usize> {
+        self.attribs.iter()
+            .scan(0, |offset, attrib| {
+                let current = *offset;
+                *offset += attrib.byte_size();
+                Some(current)
+            })
+            .collect()
+    }
+
+    // uploads `data` into a new vertex buffer and records the attribute layout in a new vertex array
+    pub fn build(&self, data: &[f32]) -> Vao {
+        let stride = self.stride();
+        assert!(stride > 0, "A vertex array needs at least one attribute.");
+        let (mut vao, mut vbo) = (0u32, 0u32);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(data) as GLsizeiptr, data.as_ptr() as *const _, gl::STATIC_DRAW);
+            for (attrib, offset) in self.attribs.iter().zip(self.offsets()) {
+                gl::EnableVertexAttribArray(attrib.location);
+                gl::VertexAttribPointer(attrib.location, attrib.size, attrib.gl_type, gl::FALSE, stride as GLsizei, offset as *const _);
+            }
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+        Vao {
+            vao,
+            vbo,
+            vertex_count: (mem::size_of_val(data) / stride) as i32
+        }
+    }
+}
+
+impl Default for VaoBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Attrib {
+    fn byte_size(&self) -> usize {
+        let component_size = match self.gl_type {
+            gl::FLOAT | gl::INT | gl::UNSIGNED_INT => 4,
+            gl::HALF_FLOAT | gl::SHORT | gl::UNSIGNED_SHORT => 2,
+            gl::BYTE | gl::UNSIGNED_BYTE => 1,
+            gl::DOUBLE => 8,
+            other => panic!("Unsupported vertex attribute type 0x{:X}.", other)
+        };
+        self.size as usize * component_size
+    }
+}
+
+// A vertex array together with the vertex buffer backing it, both deleted on drop.
+pub struct Vao {
+    vao: u32,
+    vbo: u32,
+    vertex_count: i32
+}
+
+impl Vao {
+    pub fn id(&self) -> u32 {
+        self.vao
+    }
+
+    // number of whole vertices in the buffer
+    pub fn vertex_count(&self) -> i32 {
+        self.vertex_count
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vao);
+        }
+    }
+
+    // draws every vertex in the buffer as `mode` primitives
+    pub fn draw(&self, mode: GLenum) {
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(mode, 0, self.vertex_count);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for Vao {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+    use crate::gl_test::hidden_gl_context;
+
+    #[test]
+    fn vao_builder_test_position_normal_uv_layout() {
+        let builder = VaoBuilder::new()
+            .attrib(0, 3, gl::FLOAT)
+            .attrib(1, 3, gl::FLOAT)
+            .attrib(2, 2, gl::FLOAT);
+        let float_size = mem::size_of::<f32>();
+        assert_eq!(builder.stride(), 8 * float_size);
+        assert_eq!(builder.offsets(), vec![0, 3 * float_size, 6 * float_size]);
+    }
+
+    #[test]
+    fn vao_builder_test_build() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let data = [0f32; 5 * 4];
+        let vao = VaoBuilder::new()
+            .attrib(0, 3, gl::FLOAT)
+            .attrib(1, 2, gl::FLOAT)
+            .build(&data);
+        assert_eq!(vao.vertex_count(), 4);
+
+        vao.bind();
+        for (location, expected_offset) in [(0, 0usize), (1, 3 * mem::size_of::<f32>())] {
+            let (mut enabled, mut stride) = (0 as GLint, 0 as GLint);
+            let mut offset: *mut std::ffi::c_void = ptr::null_mut();
+            unsafe {
+                gl::GetVertexAttribiv(location, gl::VERTEX_ATTRIB_ARRAY_ENABLED, &mut enabled);
+                gl::GetVertexAttribiv(location, gl::VERTEX_ATTRIB_ARRAY_STRIDE, &mut stride);
+                gl::GetVertexAttribPointerv(location, gl::VERTEX_ATTRIB_ARRAY_POINTER, &mut offset);
+            }
+            assert_eq!(enabled, gl::TRUE as GLint);
+            assert_eq!(stride as usize, 5 * mem::size_of::<f32>());
+            assert_eq!(offset as usize, expected_offset);
+        }
+        unsafe {
+            gl::BindVertexArray(0);
+        }
+    }
+}
\ No newline at end of file
@@ -15,4 +15,10 @@
 // limitations under the License.
 
 pub mod glm;
-pub mod image;
\ No newline at end of file
+pub mod ibl;
+pub mod image;
+pub mod log;
+pub mod mesh;
+pub mod picking;
+pub mod post;
+pub mod render;
\ No newline at end of file
@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::ptr;
+use gl::types::*;
+use crate::filesystem;
+use crate::shader::Shader;
+
+// object picking: every object is rendered with its own id into an offscreen GL_R32UI buffer, so
+// finding out what is under the cursor is a single pixel read
+// ------------------------------------------------------------------------
+pub struct IdPicker {
+    fbo: u32,
+    id_texture: u32,
+    depth_buffer: u32,
+    width: u32,
+    height: u32,
+    shader: Shader
+}
+
+impl IdPicker {
+    // id 0 is written wherever no object was rendered, so it is reserved for "nothing"
+    pub const BACKGROUND: u32 = 0;
+
+    pub fn new(width: u32, height: u32) -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/picking/object_id.vs".to_string()),
+            filesystem::get_path("resources/shaders/picking/object_id.fs".to_string()),
+            None
+        );
+        let (mut fbo, mut id_texture, mut depth_buffer) = (0u32, 0u32, 0u32);
+        unsafe {
+            let mut previous_fbo = 0 as GLint;
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_fbo);
+
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            // integer textures can't be filtered, so sampling has to be GL_NEAREST
+            gl::GenTextures(1, &mut id_texture);
+            gl::BindTexture(gl::TEXTURE_2D, id_texture);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::R32UI as _, width as _, height as _, 0, gl::RED_INTEGER, gl::UNSIGNED_INT, ptr::null());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, id_texture, 0);
+            // objects hide each other the same way they do on screen
+            gl::GenRenderbuffers(1, &mut depth_buffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_buffer);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, width as _, height as _);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, depth_buffer);
+            assert_eq!(gl::CheckFramebufferStatus(gl::FRAMEBUFFER), gl::FRAMEBUFFER_COMPLETE, "The picking framebuffer is not complete.");
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_fbo as _);
+        }
+
+        Self {
+            fbo,
+            id_texture,
+            depth_buffer,
+            width,
+            height,
+            shader
+        }
+    }
+
+    // clears the id buffer and calls `draw_objects` with it bound and the picking shader in use.
+    // Every object has to be preceded by a `set_object` call. The bound framebuffer and the viewport
+    // are restored afterwards.
+    pub fn render<F: FnOnce(&IdPicker)>(&self, projection: &glm::TMat4<f32>, view: &glm::TMat4<f32>, draw_objects: F) {
+        unsafe {
+            let mut previous_fbo = 0 as GLint;
+            let mut previous_viewport = [0 as GLint; 4];
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_fbo);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST);
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, self.width as _, self.height as _);
+            gl::Enable(gl::DEPTH_TEST);
+            let background = [Self::BACKGROUND; 4];
+            gl::ClearBufferuiv(gl::COLOR, 0, background.as_ptr());
+            let depth = 1f32;
+            gl::ClearBufferfv(gl::DEPTH, 0, &depth);
+
+            self.shader.use_shader();
+            self.shader.set_mat4("projection".to_string(), projection);
+            self.shader.set_mat4("view".to_string(), view);
+            draw_objects(self);
+
+            if depth_test == gl::FALSE {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_fbo as _);
+            gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        }
+    }
+
+    // sets the id and the model matrix of the object drawn next inside `render`
+    pub fn set_object(&self, id: u32, model: &glm::TMat4<f32>) {
+        debug_assert_ne!(id, Self::BACKGROUND, "Object id 0 is reserved for the background.");
+        self.shader.set_uint("objectId".to_string(), id);
+        self.shader.set_mat4("model".to_string(), model);
+    }
+
+    // returns the id of the object at (x, y), given in framebuffer pixels with the origin at the top
+    // left corner like cursor positions are. None if nothing was rendered there or (x, y) is outside.
+    pub fn pick(&self, x: i32, y: i32) -> Option<u32> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        let mut id = Self::BACKGROUND;
+        unsafe {
+            let mut previous_read_fbo = 0 as GLint;
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read_fbo);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            // OpenGL counts rows from the bottom
+            let gl_y = self.height as i32 - 1 - y;
+            gl::ReadPixels(x, gl_y, 1, 1, gl::RED_INTEGER, gl::UNSIGNED_INT, &mut id as *mut u32 as *mut _);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read_fbo as _);
+        }
+        if id == Self::BACKGROUND {
+            None
+        } else {
+            Some(id)
+        }
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drop for IdPicker {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteTextures(1, &self.id_texture);
+            gl::DeleteRenderbuffers(1, &self.depth_buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+    use crate::{primitives, util};
+
+    #[test]
+    fn id_picker_test_two_quads() {
+        let Some(_context) = hidden_gl_context(8, 8) else {
+            return;
+        };
+        primitives::reset();
+        let picker = IdPicker::new(8, 8);
+        let identity = util::glm::diag_mat4(1.0);
+        // quad 1 covers the top half of the buffer, quad 2 the bottom half
+        let top = glm::scale(&glm::translation(&glm::vec3(0.0, 0.5, 0.0)), &glm::vec3(1.0, 0.5, 1.0));
+        let bottom = glm::scale(&glm::translation(&glm::vec3(0.0, -0.5, 0.0)), &glm::vec3(1.0, 0.5, 1.0));
+        picker.render(&identity, &identity, |picker| {
+            picker.set_object(1, &top);
+            primitives::render_quad();
+            picker.set_object(2, &bottom);
+            primitives::render_quad();
+        });
+
+        // y runs downwards like cursor positions
+        assert_eq!(picker.pick(4, 1), Some(1));
+        assert_eq!(picker.pick(4, 6), Some(2));
+        assert_eq!(picker.pick(8, 1), None);
+        assert_eq!(picker.pick(4, -1), None);
+        primitives::reset();
+    }
+}
\ No newline at end of file
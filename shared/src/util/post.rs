@@ -0,0 +1,374 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::RefCell;
+use gl::types::*;
+use crate::{filesystem, primitives};
+use crate::framebuffer::{Framebuffer, FramebufferBuilder, PingPong};
+use crate::shader::Shader;
+
+// post-processing passes drawn as fullscreen quads
+// ------------------------------------------------------------------------
+
+const GAMMA: f32 = 2.2;
+// default FXAA settings
+const FXAA_SPAN_MAX: f32 = 8.0;
+const FXAA_REDUCE_MUL: f32 = 1.0 / 8.0;
+const FXAA_REDUCE_MIN: f32 = 1.0 / 128.0;
+// blur passes (alternating horizontal and vertical) of the out of focus image
+const DOF_BLUR_ITERATIONS: u32 = 6;
+
+// the curve that maps HDR colors into the displayable [0, 1] range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    // c / (c + 1), never quite reaches white
+    Reinhard,
+    // 1 - exp(-c * exposure), the operator of the hdr chapter
+    Exposure,
+    // Narkowicz's fit of the ACES filmic curve, more contrast and saturated highlights
+    Aces,
+    // Hable's filmic curve from Uncharted 2
+    Uncharted2
+}
+
+impl Operator {
+    pub const ALL: [Operator; 4] = [Operator::Reinhard, Operator::Exposure, Operator::Aces, Operator::Uncharted2];
+
+    // the value of the `tonemapOperator` uniform of tonemap.fs
+    fn id(self) -> i32 {
+        match self {
+            Operator::Reinhard => 0,
+            Operator::Exposure => 1,
+            Operator::Aces => 2,
+            Operator::Uncharted2 => 3
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Operator::Reinhard => "Reinhard",
+            Operator::Exposure => "exposure",
+            Operator::Aces => "ACES",
+            Operator::Uncharted2 => "Uncharted 2"
+        }
+    }
+
+    // the following operator, wrapping around, for cycling through them with a key
+    pub fn next(self) -> Self {
+        Self::ALL[(self.id() as usize + 1) % Self::ALL.len()]
+    }
+}
+
+// Tone maps and gamma corrects an HDR color buffer into the currently bound framebuffer.
+pub struct Tonemapper {
+    shader: Shader,
+    operator: Operator
+}
+
+impl Tonemapper {
+    pub fn new(operator: Operator) -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/effects/fullscreen.vs".to_string()),
+            filesystem::get_path("resources/shaders/post/tonemap.fs".to_string()),
+            None
+        );
+        shader.use_shader();
+        shader.set_int("hdrBuffer".to_string(), 0);
+        shader.set_float("gamma".to_string(), GAMMA);
+        Self { shader, operator }
+    }
+
+    // renders `hdr_texture` as a fullscreen quad, scaling its colors by `exposure` before the operator's curve
+    pub fn apply(&self, hdr_texture: u32, exposure: f32) {
+        self.shader.use_shader();
+        self.shader.set_int("tonemapOperator".to_string(), self.operator.id());
+        self.shader.set_float("exposure".to_string(), exposure);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, hdr_texture);
+        }
+        primitives::render_quad();
+    }
+
+    pub fn operator(&self) -> Operator {
+        self.operator
+    }
+
+    pub fn set_operator(&mut self, operator: Operator) {
+        self.operator = operator;
+    }
+}
+
+// Fast approximate anti-aliasing: finds edges by their luma contrast and blurs along them. Runs on the
+// tone mapped LDR image as the luma has to be in the displayed range.
+pub struct Fxaa {
+    shader: Shader,
+    span_max: f32
+}
+
+impl Fxaa {
+    pub fn new() -> Self {
+        let shader = Shader::new(
+            filesystem::get_path("resources/shaders/effects/fullscreen.vs".to_string()),
+            filesystem::get_path("resources/shaders/post/fxaa.fs".to_string()),
+            None
+        );
+        shader.use_shader();
+        shader.set_int("screenTexture".to_string(), 0);
+        shader.set_float("reduceMul".to_string(), FXAA_REDUCE_MUL);
+        shader.set_float("reduceMin".to_string(), FXAA_REDUCE_MIN);
+        Self {
+            shader,
+            span_max: FXAA_SPAN_MAX
+        }
+    }
+
+    // renders the anti-aliased `ldr_texture` as a fullscreen quad. The texture has to be filtered linearly.
+    pub fn apply(&self, ldr_texture: u32) {
+        self.shader.use_shader();
+        self.shader.set_float("spanMax".to_string(), self.span_max);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, ldr_texture);
+        }
+        primitives::render_quad();
+    }
+
+    pub fn span_max(&self) -> f32 {
+        self.span_max
+    }
+
+    // how many texels the filter may reach along an edge, larger values smooth longer (flatter) staircases
+    // at the cost of more blur
+    pub fn set_span_max(&mut self, span_max: f32) {
+        self.span_max = span_max;
+    }
+}
+
+impl Default for Fxaa {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Depth of field: blends the scene with a blurred copy of itself by each pixel's circle of confusion,
+// which grows with the aperture and the distance to the focal plane.
+pub struct DepthOfField {
+    blur_shader: Shader,
+    composite_shader: Shader,
+    // `PingPong::blur` wants it mutably, `apply` only borrows it for the blur passes
+    ping_pong: RefCell<PingPong>,
+    target: Framebuffer
+}
+
+impl DepthOfField {
+    // the size of the color textures passed to `apply`
+    pub fn new(width: u32, height: u32) -> Self {
+        let blur_shader = Shader::new(
+            filesystem::get_path("resources/shaders/effects/fullscreen.vs".to_string()),
+            filesystem::get_path("resources/shaders/post/blur.fs".to_string()),
+            None
+        );
+        blur_shader.use_shader();
+        blur_shader.set_int("image".to_string(), 0);
+        let composite_shader = Shader::new(
+            filesystem::get_path("resources/shaders/effects/fullscreen.vs".to_string()),
+            filesystem::get_path("resources/shaders/post/depth_of_field.fs".to_string()),
+            None
+        );
+        composite_shader.use_shader();
+        composite_shader.set_int("sharpImage".to_string(), 0);
+        composite_shader.set_int("blurredImage".to_string(), 1);
+        composite_shader.set_int("depthMap".to_string(), 2);
+        Self {
+            blur_shader,
+            composite_shader,
+            ping_pong: RefCell::new(PingPong::new(width, height, gl::RGBA16F)),
+            target: FramebufferBuilder::new(width, height).color_format(gl::RGBA16F).depth(false).build()
+        }
+    }
+
+    // Renders the depth of field of `color_tex` into an internal HDR texture and returns it. `depth_tex`
+    // holds the linear view space depth in its red channel, `focus` is the depth that stays sharp and
+    // `aperture` scales the blur of everything in front of or behind it. Leaves the default framebuffer
+    // and the previous viewport bound.
+    pub fn apply(&self, color_tex: u32, depth_tex: u32, focus: f32, aperture: f32) -> u32 {
+        let blurred = self.ping_pong.borrow_mut().blur(&self.blur_shader, color_tex, DOF_BLUR_ITERATIONS);
+        unsafe {
+            let mut viewport = [0 as GLint; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            self.target.bind();
+
+            self.composite_shader.use_shader();
+            self.composite_shader.set_float("focus".to_string(), focus);
+            self.composite_shader.set_float("aperture".to_string(), aperture);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, color_tex);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, blurred);
+            gl::ActiveTexture(gl::TEXTURE2);
+            gl::BindTexture(gl::TEXTURE_2D, depth_tex);
+            primitives::render_quad();
+            gl::ActiveTexture(gl::TEXTURE0);
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+        self.target.color_texture().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl_test::hidden_gl_context;
+
+    #[test]
+    fn operator_next_cycles_through_all() {
+        let mut operator = Operator::Reinhard;
+        for expected in Operator::ALL.iter().skip(1).chain(Operator::ALL.iter().take(1)) {
+            operator = operator.next();
+            assert_eq!(operator, *expected);
+        }
+    }
+
+    #[test]
+    fn tonemapper_test_reinhard_keeps_white_below_one() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        primitives::reset();
+        let white = [1.0f32; 3];
+        let mut hdr_texture = 0u32;
+        unsafe {
+            gl::GenTextures(1, &mut hdr_texture);
+            gl::BindTexture(gl::TEXTURE_2D, hdr_texture);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB16F as _, 1, 1, 0, gl::RGB, gl::FLOAT, white.as_ptr() as *const _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl::Viewport(0, 0, 4, 4);
+        }
+
+        Tonemapper::new(Operator::Reinhard).apply(hdr_texture, 1.0);
+
+        let mut pixel = [0u8; 4];
+        unsafe {
+            gl::ReadPixels(2, 2, 1, 1, gl::RGBA, gl::UNSIGNED_BYTE, pixel.as_mut_ptr() as *mut _);
+            gl::DeleteTextures(1, &hdr_texture);
+        }
+        primitives::reset();
+        // 1 / (1 + 1) = 0.5 before gamma correction, 0.5^(1 / 2.2) after
+        let expected = (0.5f32.powf(1.0 / GAMMA) * 255.0).round() as i32;
+        for channel in &pixel[..3] {
+            assert!(*channel < 255, "Got {:?}.", pixel);
+            assert!((*channel as i32 - expected).abs() <= 1, "Got {:?}.", pixel);
+        }
+    }
+
+    // sum of the squared luma steps between horizontal neighbors, large for hard (aliased) edges
+    fn edge_energy(lumas: &[f32], size: usize) -> f32 {
+        lumas.chunks(size)
+            .flat_map(|row| row.windows(2).map(|pair| (pair[1] - pair[0]).powi(2)))
+            .sum()
+    }
+
+    #[test]
+    fn fxaa_test_softens_diagonal_edge() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        primitives::reset();
+        const SIZE: usize = 16;
+        // white above the diagonal, black below it
+        let texels: Vec<u8> = (0..SIZE * SIZE)
+            .flat_map(|i| {
+                let value = if i % SIZE > i / SIZE { 255u8 } else { 0 };
+                [value, value, value, 255]
+            })
+            .collect();
+        let mut ldr_texture = 0u32;
+        unsafe {
+            gl::GenTextures(1, &mut ldr_texture);
+            gl::BindTexture(gl::TEXTURE_2D, ldr_texture);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA8 as _, SIZE as _, SIZE as _, 0, gl::RGBA, gl::UNSIGNED_BYTE, texels.as_ptr() as *const _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+        }
+
+        let target = FramebufferBuilder::new(SIZE as u32, SIZE as u32).depth(false).build();
+        target.bind();
+        unsafe {
+            gl::Viewport(0, 0, SIZE as _, SIZE as _);
+        }
+        Fxaa::new().apply(ldr_texture);
+        let mut filtered = vec![0u8; SIZE * SIZE * 4];
+        unsafe {
+            gl::ReadPixels(0, 0, SIZE as _, SIZE as _, gl::RGBA, gl::UNSIGNED_BYTE, filtered.as_mut_ptr() as *mut _);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::DeleteTextures(1, &ldr_texture);
+        }
+        primitives::reset();
+
+        // the image is gray, so the red channel is its luma
+        let luma = |pixels: &[u8]| pixels.chunks(4).map(|pixel| pixel[0] as f32 / 255.0).collect::<Vec<f32>>();
+        let before = edge_energy(&luma(&texels), SIZE);
+        let after = edge_energy(&luma(&filtered), SIZE);
+        assert!(after < before, "FXAA didn't soften the edge: {} before, {} after.", before, after);
+    }
+
+    fn float_texture(internal_format: GLenum, format: GLenum, width: usize, height: usize, texels: &[f32]) -> u32 {
+        let mut texture = 0u32;
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, internal_format as _, width as _, height as _, 0, format, gl::FLOAT, texels.as_ptr() as *const _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+        }
+        texture
+    }
+
+    #[test]
+    fn depth_of_field_test_output_matches_input_size() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        primitives::reset();
+        const WIDTH: usize = 16;
+        const HEIGHT: usize = 8;
+        let color = float_texture(gl::RGBA16F, gl::RGBA, WIDTH, HEIGHT, &[0.5; WIDTH * HEIGHT * 4]);
+        // the left half is in focus, the right half far behind it
+        let depths: Vec<f32> = (0..WIDTH * HEIGHT).map(|i| if i % WIDTH < WIDTH / 2 { 2.0 } else { 20.0 }).collect();
+        let depth = float_texture(gl::R16F, gl::RED, WIDTH, HEIGHT, &depths);
+
+        let depth_of_field = DepthOfField::new(WIDTH as u32, HEIGHT as u32);
+        let output = depth_of_field.apply(color, depth, 2.0, 1.0);
+        let (mut width, mut height) = (0, 0);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, output);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_WIDTH, &mut width);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_HEIGHT, &mut height);
+            assert_eq!(gl::GetError(), gl::NO_ERROR);
+            gl::DeleteTextures(1, &color);
+            gl::DeleteTextures(1, &depth);
+        }
+        primitives::reset();
+
+        assert_ne!(output, color);
+        assert_eq!((width as usize, height as usize), (WIDTH, HEIGHT));
+    }
+}
\ No newline at end of file
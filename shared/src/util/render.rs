@@ -0,0 +1,1006 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Copyright 2024 src_resources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate nalgebra_glm as glm;
+
+use std::ptr;
+use gl::types::*;
+use crate::primitives;
+use crate::shader::Shader;
+use crate::util::ibl;
+
+// render state helpers shared by the debug overlays and the examples
+// ------------------------------------------------------------------------
+
+// enables (or disables) polygon offset for filled polygons. A positive factor/units pair pushes
+// the filled surface slightly away from the viewer so that lines drawn on top of it win the depth test.
+pub fn set_polygon_offset(enabled: bool, factor: f32, units: f32) {
+    unsafe {
+        if enabled {
+            gl::Enable(gl::POLYGON_OFFSET_FILL);
+            gl::PolygonOffset(factor, units);
+        } else {
+            gl::Disable(gl::POLYGON_OFFSET_FILL);
+            gl::PolygonOffset(0.0, 0.0);
+        }
+    }
+}
+
+// sets the rasterized width of lines. Note that core profile contexts only guarantee a width of 1.0.
+pub fn set_line_width(width: f32) {
+    unsafe {
+        gl::LineWidth(width);
+    }
+}
+
+// switches between wireframe (outlines only) and filled rasterization of polygons. Disabling it
+// restores GL_FILL, so passes that rely on filled polygons (e.g. screen quads) are unaffected.
+pub fn set_wireframe(enabled: bool) {
+    unsafe {
+        gl::PolygonMode(gl::FRONT_AND_BACK, if enabled { gl::LINE } else { gl::FILL });
+    }
+}
+
+// which faces get discarded before rasterization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    // the usual setting for closed meshes, faces pointing away from the camera are hidden anyway
+    Back,
+    // used by shadow depth passes: only the back faces end up in the depth map, which keeps the shadow
+    // acne off lit surfaces and reduces peter panning
+    Front
+}
+
+// enables face culling for `mode` (faces wound counter-clockwise are front faces) or disables it for `CullMode::None`
+pub fn set_cull(mode: CullMode) {
+    unsafe {
+        match mode {
+            CullMode::None => gl::Disable(gl::CULL_FACE),
+            CullMode::Back => {
+                gl::Enable(gl::CULL_FACE);
+                gl::CullFace(gl::BACK);
+            }
+            CullMode::Front => {
+                gl::Enable(gl::CULL_FACE);
+                gl::CullFace(gl::FRONT);
+            }
+        }
+    }
+}
+
+// how the fragments of the following draw calls are combined with what's already in the framebuffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    // no blending, fragments replace the framebuffer contents (GL's default state)
+    Opaque,
+    // classic transparency: src * alpha + dst * (1 - alpha)
+    AlphaBlend,
+    // src + dst, overlapping fragments add up (glowing lights, particles)
+    Additive
+}
+
+impl BlendMode {
+    pub fn apply(self) {
+        unsafe {
+            match self {
+                BlendMode::Opaque => {
+                    gl::Disable(gl::BLEND);
+                    gl::BlendFunc(gl::ONE, gl::ZERO);
+                }
+                BlendMode::AlphaBlend => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                }
+                BlendMode::Additive => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::ONE, gl::ONE);
+                }
+            }
+        }
+    }
+}
+
+// the technique a parallax mapping shader offsets the texture coordinates with, uploaded as the
+// `parallaxMode` int uniform (resources of 5.1.parallax_mapping)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParallaxMode {
+    // a single height sample, cheap but breaks down at steep viewing angles
+    Simple,
+    // marches through depth layers until it's below the height map
+    Steep,
+    // steep parallax mapping interpolating between the last two layers, which hides the layer steps
+    Occlusion
+}
+
+impl ParallaxMode {
+    // the value of the `parallaxMode` uniform selecting this technique
+    pub fn uniform_value(self) -> i32 {
+        match self {
+            ParallaxMode::Simple => 0,
+            ParallaxMode::Steep => 1,
+            ParallaxMode::Occlusion => 2
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ParallaxMode::Simple => "parallax mapping",
+            ParallaxMode::Steep => "steep parallax mapping",
+            ParallaxMode::Occlusion => "parallax occlusion mapping"
+        }
+    }
+
+    // sets the `parallaxMode` uniform of `shader`, which has to be in use
+    pub fn upload(self, shader: &Shader) {
+        shader.set_int("parallaxMode".to_string(), self.uniform_value());
+    }
+}
+
+// turns the fragment alpha into a coverage mask when rendering to a multisampled framebuffer, which
+// gives alpha tested geometry (foliage, fences) smooth edges without sorting
+pub fn set_alpha_to_coverage(enabled: bool) {
+    unsafe {
+        if enabled {
+            gl::Enable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+        } else {
+            gl::Disable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+        }
+    }
+}
+
+// the left and right halves (x, y, width, height) of the viewport rectangle, the right one gets the
+// extra pixel of odd widths
+pub fn split_viewports(viewport: [i32; 4]) -> [[i32; 4]; 2] {
+    let [x, y, width, height] = viewport;
+    let left_width = width / 2;
+    [
+        [x, y, left_width, height],
+        [x + left_width, y, width - left_width, height]
+    ]
+}
+
+// draws `left` into the left half of the current viewport and `right` into the right half, each with
+// viewport and scissor rectangle set to its half (so clears stay in it too). The projection should use
+// the aspect ratio of a half. Viewport, scissor rectangle and scissor test are restored afterwards.
+pub fn split_screen(left: impl Fn(), right: impl Fn()) {
+    unsafe {
+        let mut previous_viewport = [0 as GLint; 4];
+        let mut previous_scissor = [0 as GLint; 4];
+        gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+        gl::GetIntegerv(gl::SCISSOR_BOX, previous_scissor.as_mut_ptr());
+        let scissor_test = gl::IsEnabled(gl::SCISSOR_TEST) == gl::TRUE;
+
+        gl::Enable(gl::SCISSOR_TEST);
+        let halves = split_viewports(previous_viewport);
+        for (half, draw) in halves.iter().zip([&left as &dyn Fn(), &right]) {
+            gl::Viewport(half[0], half[1], half[2], half[3]);
+            gl::Scissor(half[0], half[1], half[2], half[3]);
+            draw();
+        }
+
+        gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        gl::Scissor(previous_scissor[0], previous_scissor[1], previous_scissor[2], previous_scissor[3]);
+        if !scissor_test {
+            gl::Disable(gl::SCISSOR_TEST);
+        }
+    }
+}
+
+// Object outlining with the stencil buffer: the object is drawn with `begin_write` active, marking its
+// pixels with 1, then `draw_outline` draws a slightly larger copy in a solid color only where the
+// stencil isn't 1, which leaves just the border. Clear the stencil buffer (to 0) every frame.
+pub struct OutlinePass {
+    color: glm::TVec3<f32>
+}
+
+impl OutlinePass {
+    // scale of the outline copy, relative to the object
+    pub const SCALE: f32 = 1.1;
+
+    pub fn new(color: glm::TVec3<f32>) -> Self {
+        Self { color }
+    }
+
+    // everything drawn from here on writes 1 into the stencil buffer where it passes the depth test
+    pub fn begin_write(&self) {
+        unsafe {
+            gl::Enable(gl::STENCIL_TEST);
+            gl::StencilOp(gl::KEEP, gl::KEEP, gl::REPLACE);
+            gl::StencilFunc(gl::ALWAYS, 1, 0xFF);
+            gl::StencilMask(0xFF);
+        }
+    }
+
+    // stops writing, objects drawn before `draw_outline` keep the stencil buffer unchanged
+    pub fn end_write(&self) {
+        unsafe {
+            gl::StencilMask(0x00);
+        }
+    }
+
+    // draws the outline with `shader`: sets its `model` uniform to `scaled_model` (see `scaled`) and
+    // `outlineColor` to the color, then `draw_geometry` issues the object's draw calls. The outline is
+    // drawn on top of everything (without depth testing); stencil test and mask are reset afterwards,
+    // depth testing is left as it was.
+    pub fn draw_outline<F: FnOnce()>(&self, scaled_model: &glm::TMat4<f32>, shader: &Shader, draw_geometry: F) {
+        let depth_test;
+        unsafe {
+            depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            gl::Enable(gl::STENCIL_TEST);
+            gl::StencilFunc(gl::NOTEQUAL, 1, 0xFF);
+            gl::StencilMask(0x00);
+            gl::Disable(gl::DEPTH_TEST);
+        }
+        shader.use_shader();
+        shader.set_mat4("model".to_string(), scaled_model);
+        shader.set_vec3("outlineColor".to_string(), &self.color);
+        draw_geometry();
+        unsafe {
+            gl::StencilMask(0xFF); // glClear only clears the stencil bits the mask lets through
+            gl::StencilFunc(gl::ALWAYS, 0, 0xFF);
+            gl::Disable(gl::STENCIL_TEST);
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    // `model` enlarged by SCALE around the object's origin, for `draw_outline`
+    pub fn scaled(model: &glm::TMat4<f32>) -> glm::TMat4<f32> {
+        glm::scale(model, &glm::vec3(Self::SCALE, Self::SCALE, Self::SCALE))
+    }
+
+    pub fn color(&self) -> glm::TVec3<f32> {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: glm::TVec3<f32>) {
+        self.color = color;
+    }
+}
+
+// Depth prepass against overdraw: the scene is drawn twice, first into the depth buffer only and then
+// with GL_EQUAL depth testing, so the (expensive) color pass shades every pixel once, for the surface
+// that's actually visible. Both passes have to produce bit identical depths, e.g. the same vertex
+// transform with `invariant gl_Position` in each vertex shader. Works with multi-sampled targets too.
+//
+//     if prepass.begin_depth() { /* draw the scene with a cheap shader */ }
+//     prepass.begin_color();
+//     /* draw the scene with the real shader */
+//     prepass.end();
+//
+// Disabled, the three calls do nothing and the scene is shaded in a single regular pass.
+pub struct DepthPrepass {
+    enabled: bool,
+    // state replaced by the passes, restored by `begin_color` and `end`
+    color_mask: [GLboolean; 4],
+    depth_mask: GLboolean,
+    depth_func: GLint
+}
+
+impl DepthPrepass {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            color_mask: [gl::TRUE; 4],
+            depth_mask: gl::TRUE,
+            depth_func: gl::LESS as GLint
+        }
+    }
+
+    // masks color writes and lets the geometry drawn from here on fill the depth buffer. Returns
+    // whether the depth pass should be drawn, i.e. false if the prepass is disabled.
+    pub fn begin_depth(&mut self) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        unsafe {
+            gl::GetBooleanv(gl::COLOR_WRITEMASK, self.color_mask.as_mut_ptr());
+            gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut self.depth_mask);
+            gl::GetIntegerv(gl::DEPTH_FUNC, &mut self.depth_func);
+            gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            gl::DepthMask(gl::TRUE);
+            gl::DepthFunc(gl::LESS);
+        }
+        true
+    }
+
+    // restores the color writes and only lets fragments at the depth of the prepass through. The depth
+    // buffer is complete already, so it's no longer written to.
+    pub fn begin_color(&self) {
+        if !self.enabled {
+            return;
+        }
+        let [r, g, b, a] = self.color_mask;
+        unsafe {
+            gl::ColorMask(r, g, b, a);
+            gl::DepthMask(gl::FALSE);
+            gl::DepthFunc(gl::EQUAL);
+        }
+    }
+
+    // restores the depth function and writes from before `begin_depth`
+    pub fn end(&self) {
+        if !self.enabled {
+            return;
+        }
+        unsafe {
+            gl::DepthMask(self.depth_mask);
+            gl::DepthFunc(self.depth_func as GLenum);
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+}
+
+impl Default for DepthPrepass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Occlusion query: counts the samples that pass the depth test while it's active. Drawing a cheap proxy
+// (e.g. the bounding box, see `test_bounding_box`) against the depth buffer tells whether the real
+// object would be visible at all; `draw_conditional` then lets the GPU skip it without waiting for
+// the result on the CPU.
+pub struct OcclusionQuery {
+    id: u32,
+    target: GLenum
+}
+
+impl OcclusionQuery {
+    // a query that only tells whether any sample passed (GL_ANY_SAMPLES_PASSED)
+    pub fn new() -> Self {
+        Self::with_target(gl::ANY_SAMPLES_PASSED)
+    }
+
+    // a query that counts the passed samples (GL_SAMPLES_PASSED)
+    pub fn counting() -> Self {
+        Self::with_target(gl::SAMPLES_PASSED)
+    }
+
+    fn with_target(target: GLenum) -> Self {
+        let mut id = 0u32;
+        unsafe {
+            gl::GenQueries(1, &mut id);
+        }
+        Self { id, target }
+    }
+
+    pub fn begin(&self) {
+        unsafe {
+            gl::BeginQuery(self.target, self.id);
+        }
+    }
+
+    pub fn end(&self) {
+        unsafe {
+            gl::EndQuery(self.target);
+        }
+    }
+
+    // whether the result can be read without stalling
+    pub fn result_available(&self) -> bool {
+        let mut available = 0 as GLuint;
+        unsafe {
+            gl::GetQueryObjectuiv(self.id, gl::QUERY_RESULT_AVAILABLE, &mut available);
+        }
+        available != 0
+    }
+
+    // the number of passed samples (0 or 1 for an any-samples query), waits for the GPU if necessary
+    pub fn samples_passed(&self) -> u32 {
+        let mut samples = 0 as GLuint;
+        unsafe {
+            gl::GetQueryObjectuiv(self.id, gl::QUERY_RESULT, &mut samples);
+        }
+        samples
+    }
+
+    // waits for the GPU if necessary
+    pub fn any_samples_passed(&self) -> bool {
+        self.samples_passed() > 0
+    }
+
+    // runs the query over `draw_proxy` with color and depth writes off, so the proxy is only tested
+    // against the depth buffer and leaves no trace. The previous write masks are restored afterwards.
+    pub fn test_proxy(&self, draw_proxy: impl FnOnce()) {
+        let mut color_mask = [gl::TRUE; 4];
+        let mut depth_mask = gl::TRUE;
+        unsafe {
+            gl::GetBooleanv(gl::COLOR_WRITEMASK, color_mask.as_mut_ptr());
+            gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut depth_mask);
+            gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            gl::DepthMask(gl::FALSE);
+        }
+        self.begin();
+        draw_proxy();
+        self.end();
+        unsafe {
+            gl::ColorMask(color_mask[0], color_mask[1], color_mask[2], color_mask[3]);
+            gl::DepthMask(depth_mask);
+        }
+    }
+
+    // tests the axis-aligned bounding box (min, max) of an object transformed by `model`, drawn as a
+    // cube with `shader`, which has to be in use with its view and projection set. Its `model`
+    // uniform is overwritten.
+    pub fn test_bounding_box(&self, shader: &Shader, model: &glm::TMat4<f32>, bounds: (glm::TVec3<f32>, glm::TVec3<f32>)) {
+        shader.set_mat4("model".to_string(), &bounding_box_proxy_model(model, bounds));
+        self.test_proxy(primitives::render_cube);
+    }
+
+    // draws with `draw` only if the last test saw any samples pass. The GPU decides, the CPU doesn't
+    // wait for the result.
+    pub fn draw_conditional(&self, draw: impl FnOnce()) {
+        unsafe {
+            gl::BeginConditionalRender(self.id, gl::QUERY_WAIT);
+        }
+        draw();
+        unsafe {
+            gl::EndConditionalRender();
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Default for OcclusionQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for OcclusionQuery {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteQueries(1, &self.id);
+        }
+    }
+}
+
+// the model matrix that turns primitives::render_cube (spanning [-1, 1]) into the bounding box (min, max)
+// of an object transformed by `model`
+pub fn bounding_box_proxy_model(model: &glm::TMat4<f32>, (min, max): (glm::TVec3<f32>, glm::TVec3<f32>)) -> glm::TMat4<f32> {
+    let proxy = glm::translate(model, &((min + max) * 0.5));
+    glm::scale(&proxy, &((max - min) * 0.5))
+}
+
+// indices of `positions` ordered from the farthest to the nearest to `camera_pos`, the order transparent
+// objects have to be drawn in to blend correctly. Positions at the same distance keep their relative order.
+pub fn sort_by_distance(positions: &[glm::TVec3<f32>], camera_pos: &glm::TVec3<f32>) -> Vec<usize> {
+    let distances: Vec<f32> = positions.iter()
+        .map(|position| glm::distance2(position, camera_pos))
+        .collect();
+    let mut indices: Vec<usize> = (0..positions.len()).collect();
+    indices.sort_by(|a, b| distances[*b].total_cmp(&distances[*a]));
+    indices
+}
+
+// A cubemap the scene gets rendered into from a given position, so reflective objects can reflect the
+// live scene instead of a static skybox. Owns the cubemap color texture, a depth renderbuffer shared by
+// all six faces and the framebuffer they're attached to.
+pub struct DynamicCubemap {
+    fbo: u32,
+    cubemap: u32,
+    depth_rbo: u32,
+    size: u32,
+    near: f32,
+    far: f32
+}
+
+impl DynamicCubemap {
+    pub const NEAR_PLANE: f32 = 0.1;
+    pub const FAR_PLANE: f32 = 100.0;
+
+    // creates a GL_RGBA8 cubemap with size x size faces
+    pub fn new(size: u32) -> Self {
+        let (mut fbo, mut cubemap, mut depth_rbo) = (0u32, 0u32, 0u32);
+        unsafe {
+            let mut previous_fbo = 0 as GLint;
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_fbo);
+
+            gl::GenTextures(1, &mut cubemap);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap);
+            for i in 0..6 {
+                gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + i, 0, gl::RGBA8 as _, size as _, size as _, 0, gl::RGBA, gl::UNSIGNED_BYTE, ptr::null());
+            }
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as _);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+
+            gl::GenRenderbuffers(1, &mut depth_rbo);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_rbo);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, size as _, size as _);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, depth_rbo);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_CUBE_MAP_POSITIVE_X, cubemap, 0);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_fbo as _);
+        }
+        Self {
+            fbo,
+            cubemap,
+            depth_rbo,
+            size,
+            near: Self::NEAR_PLANE,
+            far: Self::FAR_PLANE
+        }
+    }
+
+    // sets the clip planes of the 90 degree capture projection
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.near = near;
+        self.far = far;
+    }
+
+    // renders the scene into all six faces as seen from `position`. `draw_scene` is called once per face
+    // with the projection and view matrix to draw with, after the face has been cleared. The bound
+    // framebuffer and the viewport are restored afterwards.
+    pub fn capture(&self, position: glm::TVec3<f32>, draw_scene: impl Fn(&glm::TMat4<f32>, &glm::TMat4<f32>)) {
+        let projection = glm::perspective(1.0, 90f32.to_radians(), self.near, self.far);
+        let (_, views) = ibl::capture_matrices();
+        let to_origin = glm::translation(&-position);
+        unsafe {
+            let mut previous_fbo = 0 as GLint;
+            let mut previous_viewport = [0 as GLint; 4];
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_fbo);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, self.size as _, self.size as _);
+            for (i, view) in views.iter().enumerate() {
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32, self.cubemap, 0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                draw_scene(&projection, &(view * to_origin));
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_fbo as _);
+            gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        }
+    }
+
+    // the cubemap texture to sample the captured scene from
+    pub fn texture(&self) -> u32 {
+        self.cubemap
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+
+impl Drop for DynamicCubemap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteRenderbuffers(1, &self.depth_rbo);
+            gl::DeleteTextures(1, &self.cubemap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::raw::c_void;
+    use std::sync::Mutex;
+    use crate::gl_test::{gl_test_lock, hidden_gl_context};
+    use crate::primitives;
+    use crate::shader::Shader;
+
+    static POLYGON_MODE_CALLS: Mutex<Vec<(GLenum, GLenum)>> = Mutex::new(Vec::new());
+
+    extern "system" fn record_polygon_mode(face: GLenum, mode: GLenum) {
+        POLYGON_MODE_CALLS.lock().unwrap().push((face, mode));
+    }
+
+    static CULL_CALLS: Mutex<Vec<(&'static str, GLenum)>> = Mutex::new(Vec::new());
+
+    extern "system" fn record_enable(cap: GLenum) {
+        CULL_CALLS.lock().unwrap().push(("Enable", cap));
+    }
+
+    extern "system" fn record_disable(cap: GLenum) {
+        CULL_CALLS.lock().unwrap().push(("Disable", cap));
+    }
+
+    extern "system" fn record_cull_face(mode: GLenum) {
+        CULL_CALLS.lock().unwrap().push(("CullFace", mode));
+    }
+
+    #[test]
+    fn set_wireframe_test_polygon_mode() {
+        // the stub stays loaded until the next context loads the real functions, which only happens
+        // while holding the same lock
+        let _lock = gl_test_lock();
+        gl::PolygonMode::load_with(|_| record_polygon_mode as *const c_void);
+        set_wireframe(true);
+        set_wireframe(false);
+        assert_eq!(*POLYGON_MODE_CALLS.lock().unwrap(), vec![
+            (gl::FRONT_AND_BACK, gl::LINE),
+            (gl::FRONT_AND_BACK, gl::FILL)
+        ]);
+    }
+
+    #[test]
+    fn set_cull_test_modes() {
+        // see set_wireframe_test_polygon_mode for why the stubs don't leak into other tests
+        let _lock = gl_test_lock();
+        gl::Enable::load_with(|_| record_enable as *const c_void);
+        gl::Disable::load_with(|_| record_disable as *const c_void);
+        gl::CullFace::load_with(|_| record_cull_face as *const c_void);
+        set_cull(CullMode::Front);
+        set_cull(CullMode::Back);
+        set_cull(CullMode::None);
+        assert_eq!(*CULL_CALLS.lock().unwrap(), vec![
+            ("Enable", gl::CULL_FACE),
+            ("CullFace", gl::FRONT),
+            ("Enable", gl::CULL_FACE),
+            ("CullFace", gl::BACK),
+            ("Disable", gl::CULL_FACE)
+        ]);
+    }
+
+    #[test]
+    fn dynamic_cubemap_test_complete() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let cubemap = DynamicCubemap::new(16);
+        let status = unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, cubemap.fbo);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_CUBE_MAP_NEGATIVE_Z, cubemap.texture(), 0);
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+        assert_eq!(status, gl::FRAMEBUFFER_COMPLETE);
+    }
+
+    #[test]
+    fn dynamic_cubemap_test_capture_restores_state() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let cubemap = DynamicCubemap::new(8);
+        let faces = std::cell::Cell::new(0);
+        unsafe {
+            gl::Viewport(1, 1, 2, 3);
+        }
+        cubemap.capture(glm::vec3(1.0, 2.0, 3.0), |_, view| {
+            // every face looks away from the capture position
+            let position = view * glm::vec4(1.0, 2.0, 3.0, 1.0);
+            assert!(glm::vec3(position.x, position.y, position.z).norm() < 1e-5);
+            faces.set(faces.get() + 1);
+        });
+        assert_eq!(faces.get(), 6);
+
+        let mut fbo = 0 as GLint;
+        let mut viewport = [0 as GLint; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut fbo);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+        assert_eq!(fbo, 0);
+        assert_eq!(viewport, [1, 1, 2, 3]);
+    }
+
+    #[test]
+    fn blend_mode_test_overlapping_half_transparent_quads() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        const VS: &str = "#version 330 core
+layout (location = 0) in vec3 aPos;
+void main() {
+    gl_Position = vec4(aPos, 1.0);
+}";
+        const FS: &str = "#version 330 core
+out vec4 FragColor;
+uniform vec4 color;
+void main() {
+    FragColor = color;
+}";
+        primitives::reset();
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        shader.use_shader();
+        let mut pixel = [0u8; 4];
+        unsafe {
+            gl::Viewport(0, 0, 4, 4);
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            BlendMode::AlphaBlend.apply();
+            shader.set_vec4("color".to_string(), &glm::vec4(1.0, 0.0, 0.0, 0.5));
+            primitives::render_quad();
+            shader.set_vec4("color".to_string(), &glm::vec4(0.0, 0.0, 1.0, 0.5));
+            primitives::render_quad();
+            BlendMode::Opaque.apply();
+            gl::ReadPixels(2, 2, 1, 1, gl::RGBA, gl::UNSIGNED_BYTE, pixel.as_mut_ptr() as *mut _);
+        }
+        primitives::reset();
+
+        // the red quad covers half of the black background, the blue one half of the result
+        let expected = [64i32, 0, 128];
+        for (channel, value) in expected.iter().enumerate() {
+            assert!((pixel[channel] as i32 - value).abs() <= 1, "Got {:?}.", pixel);
+        }
+        assert_eq!(unsafe { gl::IsEnabled(gl::BLEND) }, gl::FALSE);
+    }
+
+    #[test]
+    fn depth_prepass_test_masks_color_during_depth_pass() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        let color_mask = || {
+            let mut mask = [gl::FALSE; 4];
+            unsafe {
+                gl::GetBooleanv(gl::COLOR_WRITEMASK, mask.as_mut_ptr());
+            }
+            mask
+        };
+        let depth_state = || {
+            let (mut mask, mut func) = (gl::FALSE, 0 as GLint);
+            unsafe {
+                gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut mask);
+                gl::GetIntegerv(gl::DEPTH_FUNC, &mut func);
+            }
+            (mask, func as GLenum)
+        };
+        unsafe {
+            // alpha writes off, which has to survive the prepass
+            gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::FALSE);
+            gl::DepthFunc(gl::LEQUAL);
+        }
+
+        let mut prepass = DepthPrepass::new();
+        assert!(prepass.begin_depth());
+        assert_eq!(color_mask(), [gl::FALSE; 4]);
+        assert_eq!(depth_state(), (gl::TRUE, gl::LESS));
+        prepass.begin_color();
+        assert_eq!(color_mask(), [gl::TRUE, gl::TRUE, gl::TRUE, gl::FALSE]);
+        assert_eq!(depth_state(), (gl::FALSE, gl::EQUAL));
+        prepass.end();
+        assert_eq!(depth_state(), (gl::TRUE, gl::LEQUAL));
+
+        // disabled it leaves everything alone
+        prepass.set_enabled(false);
+        assert!(!prepass.begin_depth());
+        prepass.begin_color();
+        assert_eq!(color_mask(), [gl::TRUE, gl::TRUE, gl::TRUE, gl::FALSE]);
+        assert_eq!(depth_state(), (gl::TRUE, gl::LEQUAL));
+        unsafe {
+            gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+            gl::DepthFunc(gl::LESS);
+        }
+    }
+
+    #[test]
+    fn outline_pass_test_marks_stencil_under_quad() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        const VS: &str = "#version 330 core
+layout (location = 0) in vec3 aPos;
+uniform mat4 model;
+void main() {
+    gl_Position = model * vec4(aPos, 1.0);
+}";
+        const FS: &str = "#version 330 core
+out vec4 FragColor;
+void main() {
+    FragColor = vec4(1.0);
+}";
+        primitives::reset();
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        shader.use_shader();
+        // the quad covers the center 2x2 pixels
+        shader.set_mat4("model".to_string(), &glm::scaling(&glm::vec3(0.5, 0.5, 1.0)));
+        let outline = OutlinePass::new(glm::vec3(1.0, 0.5, 0.0));
+        let mut stencil = [0u8; 4 * 4];
+        unsafe {
+            gl::Viewport(0, 0, 4, 4);
+            gl::ClearStencil(0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+            outline.begin_write();
+            primitives::render_quad();
+            outline.end_write();
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(0, 0, 4, 4, gl::STENCIL_INDEX, gl::UNSIGNED_BYTE, stencil.as_mut_ptr() as *mut _);
+            gl::Disable(gl::STENCIL_TEST);
+            gl::StencilMask(0xFF);
+        }
+        primitives::reset();
+        assert_eq!(stencil, [
+            0, 0, 0, 0,
+            0, 1, 1, 0,
+            0, 1, 1, 0,
+            0, 0, 0, 0
+        ]);
+    }
+
+    #[test]
+    fn outline_pass_test_keeps_depth_test_state() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        const VS: &str = "#version 330 core
+layout (location = 0) in vec3 aPos;
+uniform mat4 model;
+void main() {
+    gl_Position = model * vec4(aPos, 1.0);
+}";
+        const FS: &str = "#version 330 core
+out vec4 FragColor;
+uniform vec3 outlineColor;
+void main() {
+    FragColor = vec4(outlineColor, 1.0);
+}";
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        let outline = OutlinePass::new(glm::vec3(1.0, 0.5, 0.0));
+        for depth_test in [false, true] {
+            unsafe {
+                if depth_test {
+                    gl::Enable(gl::DEPTH_TEST);
+                } else {
+                    gl::Disable(gl::DEPTH_TEST);
+                }
+            }
+            outline.draw_outline(&crate::util::glm::diag_mat4(1.0), &shader, || {});
+            assert_eq!(unsafe { gl::IsEnabled(gl::DEPTH_TEST) } == gl::TRUE, depth_test);
+        }
+    }
+
+    #[test]
+    fn occlusion_query_test_hidden_proxy_passes_no_samples() {
+        let Some(_context) = hidden_gl_context(4, 4) else {
+            return;
+        };
+        const VS: &str = "#version 330 core
+layout (location = 0) in vec3 aPos;
+uniform mat4 model;
+void main() {
+    gl_Position = model * vec4(aPos, 1.0);
+}";
+        const FS: &str = "#version 330 core
+out vec4 FragColor;
+void main() {
+    FragColor = vec4(1.0);
+}";
+        primitives::reset();
+        let shader = Shader::from_sources(VS, FS, None).expect("The test shader should build.");
+        shader.use_shader();
+        unsafe {
+            gl::Viewport(0, 0, 4, 4);
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthFunc(gl::LESS);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        // a fullscreen occluder halfway into the depth range
+        shader.set_mat4("model".to_string(), &crate::util::glm::diag_mat4(1.0));
+        primitives::render_quad();
+
+        let query = OcclusionQuery::counting();
+        // a proxy behind the occluder
+        shader.set_mat4("model".to_string(), &glm::translation(&glm::vec3(0.0, 0.0, 0.5)));
+        query.test_proxy(primitives::render_quad);
+        let hidden = query.samples_passed();
+        // and one in front of it, which doesn't write depth either
+        shader.set_mat4("model".to_string(), &glm::translation(&glm::vec3(0.0, 0.0, -0.5)));
+        query.test_proxy(primitives::render_quad);
+        let visible = query.samples_passed();
+        query.test_proxy(primitives::render_quad);
+        let visible_again = query.samples_passed();
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+        }
+        primitives::reset();
+        assert_eq!(hidden, 0);
+        assert_eq!(visible, 4 * 4);
+        assert_eq!(visible_again, 4 * 4);
+    }
+
+    #[test]
+    fn bounding_box_proxy_model_test_maps_unit_cube() {
+        let model = glm::translation(&glm::vec3(1.0, 0.0, 0.0));
+        let proxy = bounding_box_proxy_model(&model, (glm::vec3(-1.0, 0.0, 0.0), glm::vec3(3.0, 2.0, 1.0)));
+        let corner = |x: f32, y: f32, z: f32| crate::util::glm::vec3_from_vec4(&(proxy * glm::vec4(x, y, z, 1.0)));
+        assert_eq!(corner(-1.0, -1.0, -1.0), glm::vec3(0.0, 0.0, 0.0));
+        assert_eq!(corner(1.0, 1.0, 1.0), glm::vec3(4.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn parallax_mode_test_uniform_values() {
+        assert_eq!(ParallaxMode::Simple.uniform_value(), 0);
+        assert_eq!(ParallaxMode::Steep.uniform_value(), 1);
+        assert_eq!(ParallaxMode::Occlusion.uniform_value(), 2);
+    }
+
+    #[test]
+    fn split_viewports_test_halves() {
+        assert_eq!(split_viewports([0, 0, 800, 600]), [[0, 0, 400, 600], [400, 0, 400, 600]]);
+        assert_eq!(split_viewports([10, 20, 801, 600]), [[10, 20, 400, 600], [410, 20, 401, 600]]);
+    }
+
+    #[test]
+    fn split_screen_test_restores_state() {
+        let Some(_context) = hidden_gl_context(8, 4) else {
+            return;
+        };
+        unsafe {
+            gl::Viewport(0, 0, 8, 4);
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        let clear = |red: f32| unsafe {
+            gl::ClearColor(red, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        };
+        split_screen(|| clear(1.0), || clear(0.0));
+
+        let mut viewport = [0 as GLint; 4];
+        let mut pixels = [0u8; 8 * 4];
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            assert_eq!(gl::IsEnabled(gl::SCISSOR_TEST), gl::FALSE);
+            gl::ReadPixels(0, 0, 8, 1, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _);
+        }
+        assert_eq!(viewport, [0, 0, 8, 4]);
+        // only the left half has been cleared to red
+        let reds: Vec<u8> = pixels.chunks(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(reds, vec![255, 255, 255, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn sort_by_distance_test_farthest_first() {
+        let positions = [
+            glm::vec3(0.0, 0.0, -1.0),
+            glm::vec3(0.0, 0.0, -5.0),
+            glm::vec3(1.0, 0.0, -2.0)
+        ];
+        assert_eq!(sort_by_distance(&positions, &glm::vec3(0.0, 0.0, 1.0)), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn sort_by_distance_test_ties_keep_order() {
+        let positions = [
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(0.0, 3.0, 0.0),
+            glm::vec3(-1.0, 0.0, 0.0),
+            glm::vec3(0.0, 0.0, 1.0)
+        ];
+        assert_eq!(sort_by_distance(&positions, &glm::vec3(0.0, 0.0, 0.0)), vec![1, 0, 2, 3]);
+    }
+}
\ No newline at end of file